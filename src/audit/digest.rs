@@ -0,0 +1,261 @@
+//! Markdown digest of audit activity
+//!
+//! Summarizes a window of audit entries (sessions, decisions, rules triggered,
+//! allowlist usage, anomalies) into a report suitable for a team channel.
+
+use chrono::{DateTime, Utc};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fmt::Write;
+
+use super::{AuditEntry, LogLevel};
+
+/// Sessions with at least this many blocks are reported as anomalies
+const SESSION_BLOCK_ANOMALY_THRESHOLD: usize = 10;
+
+/// Maximum rows shown in ranked tables
+const TOP_N: usize = 10;
+
+/// Aggregated statistics for a digest window
+#[derive(Debug, Default)]
+pub struct Digest {
+    /// Start of the window
+    pub since: Option<DateTime<Utc>>,
+    /// End of the window
+    pub until: Option<DateTime<Utc>>,
+    /// Total entries in the window
+    pub total: usize,
+    /// Distinct session IDs seen
+    pub sessions: usize,
+    /// Counts per log level
+    pub allowed: usize,
+    pub blocked: usize,
+    pub warned: usize,
+    pub asked: usize,
+    pub disabled: usize,
+    /// Block counts per rule ID
+    pub blocks_by_rule: BTreeMap<String, usize>,
+    /// Rules that fired in the window but never before it
+    pub new_rules: BTreeSet<String>,
+    /// Allowlist reason -> number of uses
+    pub allowlist_usage: BTreeMap<String, usize>,
+    /// Human-readable anomaly descriptions
+    pub anomalies: Vec<String>,
+}
+
+impl Digest {
+    /// Build a digest from all known entries, restricted to those at or after `since`
+    ///
+    /// Entries before `since` are only used to decide which rules are new.
+    pub fn build(entries: &[AuditEntry], since: Option<DateTime<Utc>>) -> Self {
+        let in_window = |e: &AuditEntry| since.is_none_or(|s| e.timestamp >= s);
+
+        let previously_seen: HashSet<&str> = entries
+            .iter()
+            .filter(|e| !in_window(e))
+            .filter_map(|e| e.rule_id.as_deref())
+            .collect();
+
+        let mut digest = Digest {
+            since,
+            ..Default::default()
+        };
+        let mut sessions = HashSet::new();
+        let mut blocks_by_session: BTreeMap<&str, usize> = BTreeMap::new();
+
+        for entry in entries.iter().filter(|e| in_window(e)) {
+            digest.total += 1;
+            digest.until = Some(digest.until.map_or(entry.timestamp, |u| u.max(entry.timestamp)));
+
+            if let Some(ref session) = entry.session_id {
+                sessions.insert(session.as_str());
+            }
+
+            match entry.level {
+                LogLevel::Allowed => digest.allowed += 1,
+                LogLevel::Blocked => digest.blocked += 1,
+                LogLevel::Warn => digest.warned += 1,
+                LogLevel::Ask => digest.asked += 1,
+                LogLevel::Disabled => digest.disabled += 1,
+                LogLevel::Error | LogLevel::Snapshot | LogLevel::Policy => {}
+            }
+
+            if let Some(ref rule_id) = entry.rule_id {
+                if !previously_seen.contains(rule_id.as_str()) {
+                    digest.new_rules.insert(rule_id.clone());
+                }
+                if matches!(entry.level, LogLevel::Blocked) {
+                    *digest.blocks_by_rule.entry(rule_id.clone()).or_default() += 1;
+                    if let Some(ref session) = entry.session_id {
+                        *blocks_by_session.entry(session.as_str()).or_default() += 1;
+                    }
+                }
+            }
+
+            if let Some(reason) = entry.reason.strip_prefix("allowlisted: ") {
+                *digest.allowlist_usage.entry(reason.to_string()).or_default() += 1;
+            }
+        }
+
+        digest.sessions = sessions.len();
+
+        for (session, count) in blocks_by_session {
+            if count >= SESSION_BLOCK_ANOMALY_THRESHOLD {
+                digest
+                    .anomalies
+                    .push(format!("Session `{}` was blocked {} times", session, count));
+            }
+        }
+        if digest.disabled > 0 {
+            digest.anomalies.push(format!(
+                "Guardrails were disabled for {} decision(s) via GUARDRAILS_DISABLED",
+                digest.disabled
+            ));
+        }
+        if let Some(count) = digest.blocks_by_rule.get("parse-error") {
+            digest
+                .anomalies
+                .push(format!("{} hook input(s) failed to parse", count));
+        }
+
+        digest
+    }
+
+    /// Render the digest as Markdown
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::new();
+        let fmt_ts = |ts: Option<DateTime<Utc>>| {
+            ts.map(|t| t.format("%Y-%m-%d %H:%M UTC").to_string())
+                .unwrap_or_else(|| "-".to_string())
+        };
+
+        let _ = writeln!(out, "# Guardrails digest\n");
+        let _ = writeln!(
+            out,
+            "Period: {} → {}\n",
+            fmt_ts(self.since),
+            fmt_ts(self.until)
+        );
+
+        let _ = writeln!(out, "## Summary\n");
+        let _ = writeln!(out, "| Metric | Count |");
+        let _ = writeln!(out, "|--------|-------|");
+        let _ = writeln!(out, "| Decisions | {} |", self.total);
+        let _ = writeln!(out, "| Sessions | {} |", self.sessions);
+        let _ = writeln!(out, "| Allowed | {} |", self.allowed);
+        let _ = writeln!(out, "| Blocked | {} |", self.blocked);
+        let _ = writeln!(out, "| Warned | {} |", self.warned);
+        let _ = writeln!(out, "| Asked | {} |", self.asked);
+        let _ = writeln!(out, "| Disabled | {} |", self.disabled);
+
+        let _ = writeln!(out, "\n## Top blocked rules\n");
+        if self.blocks_by_rule.is_empty() {
+            let _ = writeln!(out, "_No blocks in this period._");
+        } else {
+            let mut ranked: Vec<_> = self.blocks_by_rule.iter().collect();
+            ranked.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+            let _ = writeln!(out, "| Rule | Blocks |");
+            let _ = writeln!(out, "|------|--------|");
+            for (rule, count) in ranked.into_iter().take(TOP_N) {
+                let _ = writeln!(out, "| `{}` | {} |", rule, count);
+            }
+        }
+
+        let _ = writeln!(out, "\n## New rules triggered\n");
+        if self.new_rules.is_empty() {
+            let _ = writeln!(out, "_None._");
+        } else {
+            for rule in &self.new_rules {
+                let _ = writeln!(out, "- `{}`", rule);
+            }
+        }
+
+        let _ = writeln!(out, "\n## Allowlist usage\n");
+        if self.allowlist_usage.is_empty() {
+            let _ = writeln!(out, "_No allowlist matches._");
+        } else {
+            let mut ranked: Vec<_> = self.allowlist_usage.iter().collect();
+            ranked.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+            for (reason, count) in ranked.into_iter().take(TOP_N) {
+                let _ = writeln!(out, "- {} ({}×)", reason, count);
+            }
+        }
+
+        let _ = writeln!(out, "\n## Anomalies\n");
+        if self.anomalies.is_empty() {
+            let _ = writeln!(out, "_None detected._");
+        } else {
+            for anomaly in &self.anomalies {
+                let _ = writeln!(out, "- {}", anomaly);
+            }
+        }
+
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn entry(
+        ts: DateTime<Utc>,
+        level: LogLevel,
+        rule_id: Option<&str>,
+        reason: &str,
+        session: &str,
+    ) -> AuditEntry {
+        AuditEntry {
+            timestamp: ts,
+            level,
+            tool: "Bash".to_string(),
+            rule_id: rule_id.map(String::from),
+            input_summary: "Bash: test".to_string(),
+            reason: reason.to_string(),
+            session_id: Some(session.to_string()),
+            cwd: None,
+            input: None,
+            provenance: None,
+            allowed_by: None,
+        }
+    }
+
+    #[test]
+    fn test_digest_counts_and_new_rules() {
+        let now = Utc::now();
+        let old = now - Duration::days(30);
+        let entries = vec![
+            entry(old, LogLevel::Blocked, Some("rm-root"), "root", "s0"),
+            entry(now, LogLevel::Blocked, Some("rm-root"), "root", "s1"),
+            entry(now, LogLevel::Blocked, Some("git-reset-hard"), "reset", "s1"),
+            entry(now, LogLevel::Allowed, None, "allowlisted: node_modules", "s2"),
+            entry(now, LogLevel::Allowed, None, "passed all checks", "s2"),
+        ];
+
+        let digest = Digest::build(&entries, Some(now - Duration::days(7)));
+        assert_eq!(digest.total, 4);
+        assert_eq!(digest.sessions, 2);
+        assert_eq!(digest.blocked, 2);
+        assert_eq!(digest.allowed, 2);
+        assert!(digest.new_rules.contains("git-reset-hard"));
+        assert!(!digest.new_rules.contains("rm-root"));
+        assert_eq!(digest.allowlist_usage.get("node_modules"), Some(&1));
+    }
+
+    #[test]
+    fn test_digest_anomalies() {
+        let now = Utc::now();
+        let mut entries: Vec<_> = (0..SESSION_BLOCK_ANOMALY_THRESHOLD)
+            .map(|_| entry(now, LogLevel::Blocked, Some("rm-root"), "root", "noisy"))
+            .collect();
+        entries.push(entry(now, LogLevel::Disabled, None, "GUARDRAILS_DISABLED", "s2"));
+
+        let digest = Digest::build(&entries, None);
+        assert_eq!(digest.anomalies.len(), 2);
+
+        let markdown = digest.to_markdown();
+        assert!(markdown.contains("# Guardrails digest"));
+        assert!(markdown.contains("`rm-root`"));
+        assert!(markdown.contains("noisy"));
+    }
+}
@@ -0,0 +1,539 @@
+//! JSONL audit logging for claude-guardrails
+//!
+//! Records all security decisions to a JSONL file for later analysis.
+
+pub mod digest;
+pub mod reader;
+pub mod replay;
+pub mod sinks;
+pub mod spool;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::config::{Config, SinkKind};
+use crate::engine::common::redact_secrets;
+use crate::engine::provenance::Provenance;
+use crate::input::{HookInput, PromptInput};
+use crate::output::{AllowSource, Decision};
+use crate::providers::{Clock, SystemClock};
+use crate::signing::Verification;
+
+/// Log level for audit entries
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum LogLevel {
+    Allowed,
+    Blocked,
+    Warn,
+    Ask,
+    Disabled,
+    Error,
+    Snapshot,
+    Policy,
+}
+
+impl LogLevel {
+    /// Name as written in the log ("BLOCKED", ...)
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LogLevel::Allowed => "ALLOWED",
+            LogLevel::Blocked => "BLOCKED",
+            LogLevel::Warn => "WARN",
+            LogLevel::Ask => "ASK",
+            LogLevel::Disabled => "DISABLED",
+            LogLevel::Error => "ERROR",
+            LogLevel::Snapshot => "SNAPSHOT",
+            LogLevel::Policy => "POLICY",
+        }
+    }
+
+    /// Parse a level name, case-insensitively
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s.to_ascii_uppercase().as_str() {
+            "ALLOWED" => Some(LogLevel::Allowed),
+            "BLOCKED" => Some(LogLevel::Blocked),
+            "WARN" => Some(LogLevel::Warn),
+            "ASK" => Some(LogLevel::Ask),
+            "DISABLED" => Some(LogLevel::Disabled),
+            "ERROR" => Some(LogLevel::Error),
+            "SNAPSHOT" => Some(LogLevel::Snapshot),
+            "POLICY" => Some(LogLevel::Policy),
+            _ => None,
+        }
+    }
+}
+
+/// An audit log entry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    /// Timestamp of the decision
+    pub timestamp: DateTime<Utc>,
+
+    /// Log level (ALLOWED, BLOCKED, WARN, ASK, DISABLED, SNAPSHOT, POLICY)
+    pub level: LogLevel,
+
+    /// Tool that was invoked
+    pub tool: String,
+
+    /// Rule ID that matched (if any)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rule_id: Option<String>,
+
+    /// Summary of the input
+    pub input_summary: String,
+
+    /// Reason for the decision
+    pub reason: String,
+
+    /// Session ID (if provided)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+
+    /// Working directory of the session (if provided)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<String>,
+
+    /// Full tool input (or `{"prompt": ...}`) with secrets redacted, so the
+    /// decision can be replayed; missing in entries from older versions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input: Option<serde_json::Value>,
+
+    /// Analysis tier that produced the decision (`ast`, `regex-fallback`,
+    /// `allowlist`, ...); missing in entries from older versions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<Provenance>,
+
+    /// What let an allowed input through (`checked`, `allowlisted`,
+    /// `approved`, ...); missing in other entries and older versions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allowed_by: Option<AllowSource>,
+}
+
+impl AuditEntry {
+    /// Create a new audit entry from input and decision
+    pub fn new(input: &HookInput, decision: &Decision, disabled: bool) -> Self {
+        let (level, rule_id, reason) = Self::outcome(decision, disabled);
+        Self {
+            timestamp: Utc::now(),
+            level,
+            tool: input.tool_name.clone(),
+            rule_id,
+            input_summary: input.summary(),
+            reason,
+            session_id: input.session_id.clone(),
+            cwd: input.cwd.clone(),
+            input: Some(redact(input.tool_input.to_value())),
+            provenance: None,
+            allowed_by: decision.allow_source().cloned(),
+        }
+    }
+
+    /// Create an entry for a UserPromptSubmit check
+    pub fn prompt(input: &PromptInput, decision: &Decision) -> Self {
+        let (level, rule_id, reason) = Self::outcome(decision, false);
+        Self {
+            timestamp: Utc::now(),
+            level,
+            tool: "UserPromptSubmit".to_string(),
+            rule_id,
+            input_summary: input.summary(),
+            reason,
+            session_id: input.session_id.clone(),
+            cwd: input.cwd.clone(),
+            input: Some(serde_json::json!({ "prompt": redact_secrets(&input.prompt) })),
+            provenance: Some(Provenance::Rules),
+            allowed_by: decision.allow_source().cloned(),
+        }
+    }
+
+    /// Log level, rule ID and reason recorded for a decision
+    fn outcome(decision: &Decision, disabled: bool) -> (LogLevel, Option<String>, String) {
+        if disabled {
+            (LogLevel::Disabled, None, "GUARDRAILS_DISABLED".to_string())
+        } else {
+            match decision {
+                Decision::Allow { reason, .. } => (LogLevel::Allowed, None, reason.clone()),
+                Decision::Deny { rule_id, reason } => {
+                    (LogLevel::Blocked, Some(rule_id.clone()), reason.clone())
+                }
+                Decision::Warn { rule_id, reason } => {
+                    (LogLevel::Warn, Some(rule_id.clone()), reason.clone())
+                }
+                Decision::Ask { rule_id, reason } => {
+                    (LogLevel::Ask, Some(rule_id.clone()), reason.clone())
+                }
+            }
+        }
+    }
+}
+
+impl AuditEntry {
+    /// Create an entry recording a session snapshot ref
+    pub fn snapshot(input: &HookInput, ref_name: &str, commit: &str) -> Self {
+        Self {
+            timestamp: Utc::now(),
+            level: LogLevel::Snapshot,
+            tool: input.tool_name.clone(),
+            rule_id: None,
+            input_summary: input.summary(),
+            reason: format!("snapshot {} at {}", ref_name, commit),
+            session_id: input.session_id.clone(),
+            cwd: input.cwd.clone(),
+            input: None,
+            provenance: None,
+            allowed_by: None,
+        }
+    }
+}
+
+impl AuditEntry {
+    /// Create an entry recording a policy file fetched from `source`, with
+    /// its signature status (`installed` is false when it was refused)
+    pub fn policy(source: &str, path: &Path, verification: &Verification, installed: bool) -> Self {
+        let outcome = if installed { "installed" } else { "refused" };
+        Self {
+            timestamp: Utc::now(),
+            level: LogLevel::Policy,
+            tool: "policy".to_string(),
+            rule_id: None,
+            input_summary: source.to_string(),
+            reason: format!("{} {}: {}", outcome, path.display(), verification.describe()),
+            session_id: None,
+            cwd: None,
+            input: Some(serde_json::json!({ "source": source, "signature": verification })),
+            provenance: None,
+            allowed_by: None,
+        }
+    }
+}
+
+/// `value` with secrets redacted from every string in it
+fn redact(value: serde_json::Value) -> serde_json::Value {
+    use serde_json::Value;
+    match value {
+        Value::String(s) => Value::String(redact_secrets(&s)),
+        Value::Array(items) => Value::Array(items.into_iter().map(redact).collect()),
+        Value::Object(map) => Value::Object(map.into_iter().map(|(k, v)| (k, redact(v))).collect()),
+        other => other,
+    }
+}
+
+/// A destination for audit entries
+///
+/// Implement this to forward decisions somewhere other than the default
+/// JSONL file (syslog, a webhook, a database, ...). Sinks are registered on
+/// an `AuditLogger`, which fans every entry out to all of them.
+pub trait AuditSink: Send {
+    /// Record a single audit entry
+    fn log(&mut self, entry: &AuditEntry) -> Result<(), std::io::Error>;
+
+    /// Short name used in error messages
+    fn name(&self) -> &str;
+}
+
+/// Audit logger
+pub struct AuditLogger {
+    sinks: Vec<Box<dyn AuditSink>>,
+    /// Timestamps entries built by `log_decision`
+    clock: Arc<dyn Clock>,
+}
+
+impl AuditLogger {
+    /// Create a new audit logger writing JSONL to `path` (if given)
+    pub fn new(path: Option<&Path>) -> Self {
+        let mut logger = Self::default();
+        if let Some(sink) = path.and_then(sinks::JsonlFileSink::open) {
+            logger.add_sink(Box::new(sink));
+        }
+        logger
+    }
+
+    /// Create a logger with the sinks listed in `[audit] sinks`
+    ///
+    /// Disabled when `general.audit_log` is off. Sinks that can't be set up
+    /// (missing URL, feature not compiled in, no syslog socket) are skipped
+    /// with a warning; audit logging must never prevent the hook from
+    /// answering.
+    pub fn from_config(config: &Config) -> Self {
+        let mut logger = Self::default();
+        if !config.general.audit_log {
+            return logger;
+        }
+        for kind in &config.audit.sinks {
+            // No path (e.g. a `~` path under --no-home, already warned about)
+            if *kind == SinkKind::File && config.audit_path().is_none() {
+                continue;
+            }
+            match Self::open_sink(*kind, config) {
+                Ok(sink) => logger.add_sink(sink),
+                Err(e) => eprintln!("Warning: audit sink {:?} unavailable: {}", kind, e),
+            }
+        }
+        if config.audit.remote_endpoint.is_some() {
+            #[cfg(feature = "remote")]
+            match Self::remote_sink(config) {
+                Ok(sink) => logger.add_sink(Box::new(sink)),
+                Err(e) => eprintln!("Warning: remote audit shipping unavailable: {}", e),
+            }
+            #[cfg(not(feature = "remote"))]
+            eprintln!("Warning: audit.remote_endpoint is set but this build lacks the remote feature");
+        }
+        logger
+    }
+
+    /// The sink shipping entries to `audit.remote_endpoint`
+    #[cfg(feature = "remote")]
+    pub fn remote_sink(config: &Config) -> Result<sinks::RemoteSink, String> {
+        let audit = &config.audit;
+        let endpoint = audit.remote_endpoint.as_ref().ok_or("no remote_endpoint")?;
+        let path = config.remote_spool_path().ok_or("no remote_spool")?;
+        let token = match &audit.remote_token_env {
+            Some(var) => Some(std::env::var(var).map_err(|_| format!("{} is not set", var))?),
+            None => None,
+        };
+        let spool = spool::Spool::new(path, audit.remote_batch_size, audit.remote_flush_secs, audit.remote_max_spool);
+        Ok(sinks::RemoteSink::new(endpoint.as_str(), token, spool))
+    }
+
+    fn open_sink(kind: SinkKind, config: &Config) -> Result<Box<dyn AuditSink>, String> {
+        match kind {
+            SinkKind::File => {
+                let path = config.audit_path().ok_or("no audit_path")?;
+                sinks::JsonlFileSink::try_open(&path)
+                    .map(|sink| Box::new(sink) as Box<dyn AuditSink>)
+                    .map_err(|e| format!("{}: {}", path.display(), e))
+            }
+            SinkKind::Stderr => Ok(Box::new(sinks::StderrSink)),
+            SinkKind::Null => Ok(Box::new(sinks::NullSink)),
+            #[cfg(unix)]
+            SinkKind::Syslog => sinks::SyslogSink::connect()
+                .map(|sink| Box::new(sink) as Box<dyn AuditSink>)
+                .ok_or_else(|| "no syslog socket".to_string()),
+            #[cfg(not(unix))]
+            SinkKind::Syslog => Err("syslog is only supported on Unix".to_string()),
+            #[cfg(feature = "webhook")]
+            SinkKind::Webhook => {
+                let url = config.audit.webhook_url.as_ref().ok_or("no webhook_url")?;
+                Ok(Box::new(sinks::WebhookSink::new(url.as_str())))
+            }
+            #[cfg(not(feature = "webhook"))]
+            SinkKind::Webhook => Err("built without the webhook feature".to_string()),
+            #[cfg(feature = "sqlite")]
+            SinkKind::Sqlite => {
+                let path = config.sqlite_audit_path().ok_or("no sqlite_path")?;
+                sinks::SqliteSink::open(&path)
+                    .map(|sink| Box::new(sink) as Box<dyn AuditSink>)
+                    .map_err(|e| format!("{}: {}", path.display(), e))
+            }
+            #[cfg(not(feature = "sqlite"))]
+            SinkKind::Sqlite => Err("built without the sqlite feature".to_string()),
+        }
+    }
+
+    /// Register an additional sink
+    pub fn add_sink(&mut self, sink: Box<dyn AuditSink>) {
+        self.sinks.push(sink);
+    }
+
+    /// Builder-style variant of `add_sink`
+    pub fn with_sink(mut self, sink: Box<dyn AuditSink>) -> Self {
+        self.add_sink(sink);
+        self
+    }
+
+    /// Timestamp decisions with `clock` instead of the system clock
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Log an audit entry to every registered sink
+    ///
+    /// All sinks are attempted even if one fails; the first error is returned.
+    pub fn log(&mut self, entry: &AuditEntry) -> Result<(), std::io::Error> {
+        let mut first_error = None;
+        for sink in &mut self.sinks {
+            if let Err(e) = sink.log(entry) {
+                let e = std::io::Error::new(e.kind(), format!("{}: {}", sink.name(), e));
+                first_error.get_or_insert(e);
+            }
+        }
+        match first_error {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+
+    /// Log a decision
+    pub fn log_decision(
+        &mut self,
+        input: &HookInput,
+        decision: &Decision,
+        disabled: bool,
+        provenance: Option<Provenance>,
+    ) -> Result<(), std::io::Error> {
+        let mut entry = AuditEntry::new(input, decision, disabled);
+        entry.timestamp = self.clock.now();
+        entry.provenance = provenance;
+        self.log(&entry)
+    }
+
+    /// Check if logging is enabled
+    pub fn is_enabled(&self) -> bool {
+        !self.sinks.is_empty()
+    }
+
+    /// Number of registered sinks
+    pub fn sink_count(&self) -> usize {
+        self.sinks.len()
+    }
+}
+
+/// Create a disabled logger (for when audit logging is off)
+impl Default for AuditLogger {
+    fn default() -> Self {
+        Self {
+            sinks: Vec::new(),
+            clock: Arc::new(SystemClock),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::ToolInput;
+    use tempfile::NamedTempFile;
+
+    fn test_input() -> HookInput {
+        HookInput {
+            tool_name: "Bash".to_string(),
+            tool_input: ToolInput::Bash {
+                command: "rm -rf /".to_string(),
+                description: None,
+                timeout: None,
+            },
+            session_id: Some("test-session".to_string()),
+            hook_event_name: Some("PreToolUse".to_string()),
+            cwd: None,
+            previous_decision: None,
+            tool_response: None,
+        }
+    }
+
+    #[test]
+    fn test_audit_entry_allow() {
+        let input = test_input();
+        let decision = Decision::allow("passed checks");
+        let entry = AuditEntry::new(&input, &decision, false);
+
+        assert!(matches!(entry.level, LogLevel::Allowed));
+        assert!(entry.rule_id.is_none());
+    }
+
+    #[test]
+    fn test_audit_entry_deny() {
+        let input = test_input();
+        let decision = Decision::deny("rm-root", "Attempting to delete root");
+        let entry = AuditEntry::new(&input, &decision, false);
+
+        assert!(matches!(entry.level, LogLevel::Blocked));
+        assert_eq!(entry.rule_id, Some("rm-root".to_string()));
+    }
+
+    #[test]
+    fn test_audit_entry_disabled() {
+        let input = test_input();
+        let decision = Decision::allow("disabled");
+        let entry = AuditEntry::new(&input, &decision, true);
+
+        assert!(matches!(entry.level, LogLevel::Disabled));
+    }
+
+    #[test]
+    fn test_audit_logger_write() {
+        let temp = NamedTempFile::new().unwrap();
+        let path = temp.path();
+
+        let mut logger = AuditLogger::new(Some(path));
+        assert!(logger.is_enabled());
+
+        let input = test_input();
+        let decision = Decision::deny("test-rule", "test reason");
+        logger.log_decision(&input, &decision, false, None).unwrap();
+
+        // Read back and verify
+        let content = std::fs::read_to_string(path).unwrap();
+        assert!(content.contains("test-rule"));
+        assert!(content.contains("BLOCKED"));
+    }
+
+    #[test]
+    fn test_audit_logger_clock() {
+        use crate::providers::FixedClock;
+
+        let temp = NamedTempFile::new().unwrap();
+        let now: DateTime<Utc> = "2025-03-01T08:30:00Z".parse().unwrap();
+        let mut logger = AuditLogger::new(Some(temp.path())).with_clock(Arc::new(FixedClock::new(now)));
+        logger.log_decision(&test_input(), &Decision::allow("ok"), false, None).unwrap();
+
+        let content = std::fs::read_to_string(temp.path()).unwrap();
+        let entry: AuditEntry = serde_json::from_str(content.lines().next().unwrap()).unwrap();
+        assert_eq!(entry.timestamp, now);
+    }
+
+    struct CountingSink(std::sync::Arc<std::sync::atomic::AtomicUsize>);
+
+    impl AuditSink for CountingSink {
+        fn log(&mut self, _entry: &AuditEntry) -> Result<(), std::io::Error> {
+            self.0.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Ok(())
+        }
+
+        fn name(&self) -> &str {
+            "counting"
+        }
+    }
+
+    #[test]
+    fn test_audit_logger_fans_out_to_all_sinks() {
+        let count = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let mut logger = AuditLogger::default()
+            .with_sink(Box::new(CountingSink(count.clone())))
+            .with_sink(Box::new(CountingSink(count.clone())));
+        assert_eq!(logger.sink_count(), 2);
+
+        let input = test_input();
+        logger
+            .log_decision(&input, &Decision::allow("test"), false, None)
+            .unwrap();
+        assert_eq!(count.load(std::sync::atomic::Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_audit_logger_from_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = Config::default();
+        config.general.audit_path = Some(dir.path().join("audit.jsonl").display().to_string());
+        config.audit.sinks = vec![SinkKind::File, SinkKind::Null, SinkKind::Stderr];
+        assert_eq!(AuditLogger::from_config(&config).sink_count(), 3);
+
+        config.general.audit_log = false;
+        assert!(!AuditLogger::from_config(&config).is_enabled());
+    }
+
+    #[test]
+    fn test_audit_logger_disabled() {
+        let mut logger = AuditLogger::default();
+        assert!(!logger.is_enabled());
+
+        let input = test_input();
+        let decision = Decision::allow("test");
+        // Should not error even when disabled
+        logger.log_decision(&input, &decision, false, None).unwrap();
+    }
+}
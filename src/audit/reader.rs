@@ -0,0 +1,269 @@
+//! Reading back the JSONL audit log
+//!
+//! Parses entries written by `AuditLogger` so they can be summarized or
+//! queried (`audit tail`, `audit stats`, `audit grep`).
+
+use chrono::{DateTime, Duration, Utc};
+use regex::Regex;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+use super::{AuditEntry, LogLevel};
+
+/// Read all entries from an audit log file
+///
+/// Malformed lines are skipped rather than failing the whole read, since the
+/// log may contain entries from older versions or partially-written lines.
+pub fn read_entries(path: &Path) -> Result<Vec<AuditEntry>, std::io::Error> {
+    let file = File::open(path)?;
+    let reader = BufReader::new(file);
+    let mut entries = Vec::new();
+
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Ok(entry) = serde_json::from_str::<AuditEntry>(&line) {
+            entries.push(entry);
+        }
+    }
+
+    Ok(entries)
+}
+
+/// Parse a relative duration like "7d", "24h", "30m" or "2w"
+///
+/// Returns `None` for anything else, including values too large to represent.
+pub fn parse_duration(s: &str) -> Option<Duration> {
+    let s = s.trim();
+    let (split, unit) = s.char_indices().last()?;
+    let value: i64 = s[..split].parse().ok()?;
+    if value < 0 {
+        return None;
+    }
+
+    match unit {
+        'm' => Duration::try_minutes(value),
+        'h' => Duration::try_hours(value),
+        'd' => Duration::try_days(value),
+        'w' => Duration::try_weeks(value),
+        _ => None,
+    }
+}
+
+/// Resolve a `--since` value relative to `now`
+pub fn since_timestamp(since: &str, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    parse_duration(since).and_then(|d| now.checked_sub_signed(d))
+}
+
+/// Which entries a query selects; unset fields match everything
+#[derive(Debug, Clone, Default)]
+pub struct Filter {
+    /// Entries at or after this time
+    pub since: Option<DateTime<Utc>>,
+    pub session_id: Option<String>,
+    pub level: Option<LogLevel>,
+    pub rule_id: Option<String>,
+    pub tool: Option<String>,
+    /// Matched against the input summary, reason and rule ID
+    pub pattern: Option<Regex>,
+}
+
+impl Filter {
+    /// Whether `entry` is selected
+    pub fn matches(&self, entry: &AuditEntry) -> bool {
+        self.since.is_none_or(|s| entry.timestamp >= s)
+            && self
+                .session_id
+                .as_ref()
+                .is_none_or(|id| entry.session_id.as_ref() == Some(id))
+            && self.level.is_none_or(|l| entry.level == l)
+            && self
+                .rule_id
+                .as_ref()
+                .is_none_or(|id| entry.rule_id.as_ref() == Some(id))
+            && self
+                .tool
+                .as_ref()
+                .is_none_or(|t| entry.tool.eq_ignore_ascii_case(t))
+            && self.pattern.as_ref().is_none_or(|re| {
+                re.is_match(&entry.input_summary)
+                    || re.is_match(&entry.reason)
+                    || entry.rule_id.as_deref().is_some_and(|id| re.is_match(id))
+            })
+    }
+
+    /// The selected entries, in log order
+    pub fn apply<'a>(&self, entries: &'a [AuditEntry]) -> Vec<&'a AuditEntry> {
+        entries.iter().filter(|e| self.matches(e)).collect()
+    }
+}
+
+/// One line per entry: time, level, tool, rule, summary
+pub fn format_entry(entry: &AuditEntry) -> String {
+    let mut line = format!(
+        "{}  {:<8}  {:<12}  {:<24}  {}",
+        entry.timestamp.format("%Y-%m-%d %H:%M:%S"),
+        entry.level.as_str(),
+        entry.tool,
+        entry.rule_id.as_deref().unwrap_or("-"),
+        entry.input_summary
+    );
+    if let Some(ref session) = entry.session_id {
+        let _ = write!(line, "  [{}]", session);
+    }
+    line
+}
+
+/// Entry counts by rule, tool, level and analysis tier
+#[derive(Debug, Default)]
+pub struct Stats {
+    pub total: usize,
+    pub by_rule: BTreeMap<String, usize>,
+    pub by_tool: BTreeMap<String, usize>,
+    pub by_level: BTreeMap<&'static str, usize>,
+    /// Entries that record their tier (older ones don't)
+    pub by_provenance: BTreeMap<&'static str, usize>,
+}
+
+impl Stats {
+    /// Count the given entries
+    pub fn build<'a>(entries: impl IntoIterator<Item = &'a AuditEntry>) -> Self {
+        let mut stats = Stats::default();
+        for entry in entries {
+            stats.total += 1;
+            if let Some(ref rule_id) = entry.rule_id {
+                *stats.by_rule.entry(rule_id.clone()).or_default() += 1;
+            }
+            *stats.by_tool.entry(entry.tool.clone()).or_default() += 1;
+            *stats.by_level.entry(entry.level.as_str()).or_default() += 1;
+            if let Some(provenance) = entry.provenance {
+                *stats.by_provenance.entry(provenance.as_str()).or_default() += 1;
+            }
+        }
+        stats
+    }
+
+    /// Render as plain text, each section sorted by count
+    pub fn to_text(&self) -> String {
+        let mut out = format!("{} entries\n", self.total);
+        let sections: [(&str, Vec<(&str, usize)>); 4] = [
+            ("Level", self.by_level.iter().map(|(k, v)| (*k, *v)).collect()),
+            ("Analysis", self.by_provenance.iter().map(|(k, v)| (*k, *v)).collect()),
+            ("Tool", self.by_tool.iter().map(|(k, v)| (k.as_str(), *v)).collect()),
+            ("Rule", self.by_rule.iter().map(|(k, v)| (k.as_str(), *v)).collect()),
+        ];
+        for (title, mut rows) in sections {
+            if rows.is_empty() {
+                continue;
+            }
+            rows.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+            let _ = writeln!(out, "\n{}:", title);
+            for (name, count) in rows {
+                let _ = writeln!(out, "  {:>6}  {}", count, name);
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::provenance::Provenance;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("7d"), Some(Duration::days(7)));
+        assert_eq!(parse_duration("24h"), Some(Duration::hours(24)));
+        assert_eq!(parse_duration("30m"), Some(Duration::minutes(30)));
+        assert_eq!(parse_duration("2w"), Some(Duration::weeks(2)));
+        assert_eq!(parse_duration("7"), None);
+        assert_eq!(parse_duration("xd"), None);
+        assert_eq!(parse_duration("5y"), None);
+        assert_eq!(parse_duration("1é"), None);
+        assert_eq!(parse_duration("é"), None);
+        assert_eq!(parse_duration("999999999999999d"), None);
+    }
+
+    #[test]
+    fn test_since_timestamp_out_of_range() {
+        let now = Utc::now();
+        assert_eq!(since_timestamp("1h", now), Some(now - Duration::hours(1)));
+        assert_eq!(since_timestamp("99999999999d", now), None);
+        assert_eq!(since_timestamp("1é", now), None);
+    }
+
+    #[test]
+    fn test_read_entries_skips_malformed() {
+        let mut temp = NamedTempFile::new().unwrap();
+        writeln!(
+            temp,
+            r#"{{"timestamp":"2025-02-04T10:30:00Z","level":"BLOCKED","tool":"Bash","rule_id":"rm-root","input_summary":"Bash: rm -rf /","reason":"root"}}"#
+        )
+        .unwrap();
+        writeln!(temp, "not json").unwrap();
+        writeln!(
+            temp,
+            r#"{{"timestamp":"2025-02-04T10:30:05Z","level":"ALLOWED","tool":"Bash","input_summary":"Bash: ls","reason":"passed all checks"}}"#
+        )
+        .unwrap();
+
+        let entries = read_entries(temp.path()).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].rule_id.as_deref(), Some("rm-root"));
+        assert!(entries[1].rule_id.is_none());
+    }
+
+    #[test]
+    fn test_filter_and_stats() {
+        let entry = |level, rule: Option<&str>, summary: &str, session: &str| AuditEntry {
+            timestamp: "2025-02-04T10:30:00Z".parse().unwrap(),
+            level,
+            tool: "Bash".to_string(),
+            rule_id: rule.map(String::from),
+            input_summary: summary.to_string(),
+            reason: "r".to_string(),
+            session_id: Some(session.to_string()),
+            cwd: None,
+            input: None,
+            provenance: None,
+            allowed_by: None,
+        };
+        let mut entries = vec![
+            entry(LogLevel::Blocked, Some("rm-root"), "Bash: rm -rf /", "a"),
+            entry(LogLevel::Allowed, None, "Bash: ls", "a"),
+            entry(LogLevel::Blocked, Some("rm-root"), "Bash: sudo rm -rf /", "b"),
+        ];
+
+        let filter = Filter {
+            session_id: Some("a".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(filter.apply(&entries).len(), 2);
+
+        let filter = Filter {
+            pattern: Some(Regex::new("sudo").unwrap()),
+            level: LogLevel::from_str("blocked"),
+            ..Default::default()
+        };
+        let selected = filter.apply(&entries);
+        assert_eq!(selected.len(), 1);
+        assert!(format_entry(selected[0]).contains("BLOCKED   Bash"));
+
+        entries[0].provenance = Some(Provenance::Ast);
+        entries[1].provenance = Some(Provenance::RegexFallback);
+        let stats = Stats::build(&entries);
+        assert_eq!(stats.by_provenance.get("regex-fallback"), Some(&1));
+        assert!(stats.to_text().contains("\nAnalysis:\n       1  ast\n"));
+        assert_eq!(stats.by_rule.get("rm-root"), Some(&2));
+        assert_eq!(stats.by_level.get("ALLOWED"), Some(&1));
+        assert!(stats.to_text().starts_with("3 entries\n"));
+    }
+}
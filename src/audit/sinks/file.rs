@@ -0,0 +1,49 @@
+//! JSONL file sink (the default audit destination)
+
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use crate::audit::{AuditEntry, AuditSink};
+
+/// Appends one JSON object per line to a file
+pub struct JsonlFileSink {
+    writer: BufWriter<File>,
+}
+
+impl JsonlFileSink {
+    /// Open (or create) the log file, creating parent directories as needed
+    ///
+    /// Returns `None` if the file cannot be opened; audit logging must never
+    /// prevent the hook from answering.
+    pub fn open(path: &Path) -> Option<Self> {
+        Self::try_open(path).ok()
+    }
+
+    /// Like `open`, but reports why the file could not be opened
+    pub fn try_open(path: &Path) -> std::io::Result<Self> {
+        if let Some(parent) = path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map(|file| Self {
+                writer: BufWriter::new(file),
+            })
+    }
+}
+
+impl AuditSink for JsonlFileSink {
+    fn log(&mut self, entry: &AuditEntry) -> Result<(), std::io::Error> {
+        let json = serde_json::to_string(entry)?;
+        writeln!(self.writer, "{}", json)?;
+        self.writer.flush()
+    }
+
+    fn name(&self) -> &str {
+        "file"
+    }
+}
@@ -0,0 +1,27 @@
+//! Built-in audit sink backends
+//!
+//! The JSONL file, stderr, null and syslog sinks are always available. The webhook,
+//! SQLite and remote sinks pull in extra dependencies and are behind the
+//! `webhook`, `sqlite` and `remote` cargo features.
+
+mod file;
+#[cfg(feature = "remote")]
+mod remote;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+mod stderr;
+#[cfg(unix)]
+mod syslog;
+#[cfg(feature = "webhook")]
+mod webhook;
+
+pub use file::JsonlFileSink;
+#[cfg(feature = "remote")]
+pub use remote::RemoteSink;
+#[cfg(feature = "sqlite")]
+pub use sqlite::SqliteSink;
+pub use stderr::{NullSink, StderrSink};
+#[cfg(unix)]
+pub use syslog::SyslogSink;
+#[cfg(feature = "webhook")]
+pub use webhook::WebhookSink;
@@ -0,0 +1,79 @@
+//! Remote sink: spools entries and POSTs them in batches to a collector
+
+use chrono::Utc;
+use std::thread;
+use std::time::Duration;
+
+use crate::audit::spool::Spool;
+use crate::audit::{AuditEntry, AuditSink};
+
+/// Keep well under the hook timeout so a slow endpoint can't stall Claude Code
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Attempts per batch within one flush (later flushes retry again)
+const ATTEMPTS: u32 = 2;
+
+/// Pause between attempts
+const BACKOFF: Duration = Duration::from_millis(250);
+
+/// Ships entries to `audit.remote_endpoint` through an on-disk spool
+pub struct RemoteSink {
+    endpoint: String,
+    token: Option<String>,
+    spool: Spool,
+    agent: ureq::Agent,
+}
+
+impl RemoteSink {
+    /// Create a sink posting batches from `spool` to `endpoint`, with
+    /// `token` (if any) as a bearer token
+    pub fn new(endpoint: impl Into<String>, token: Option<String>, spool: Spool) -> Self {
+        Self {
+            endpoint: endpoint.into(),
+            token,
+            spool,
+            agent: ureq::AgentBuilder::new().timeout(REQUEST_TIMEOUT).build(),
+        }
+    }
+
+    /// POST one batch as a JSON array, retrying once on failure
+    fn post(&self, batch: &[AuditEntry]) -> Result<(), String> {
+        let body = serde_json::to_string(batch).map_err(|e| e.to_string())?;
+        let mut last_error = String::new();
+        for attempt in 0..ATTEMPTS {
+            if attempt > 0 {
+                thread::sleep(BACKOFF);
+            }
+            let mut request = self.agent.post(&self.endpoint).set("Content-Type", "application/json");
+            if let Some(token) = &self.token {
+                request = request.set("Authorization", &format!("Bearer {}", token));
+            }
+            match request.send_string(&body) {
+                Ok(_) => return Ok(()),
+                Err(e) => last_error = e.to_string(),
+            }
+        }
+        Err(last_error)
+    }
+
+    /// Send everything spooled now; returns the number of entries delivered
+    pub fn flush(&self) -> Result<usize, std::io::Error> {
+        self.spool.flush(|batch| self.post(batch))
+    }
+}
+
+impl AuditSink for RemoteSink {
+    fn log(&mut self, entry: &AuditEntry) -> Result<(), std::io::Error> {
+        self.spool.push(entry)?;
+        // A failed send leaves the entry spooled for a later retry, so it
+        // isn't an error for this call
+        if self.spool.due(Utc::now()) {
+            let _ = self.flush();
+        }
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        "remote"
+    }
+}
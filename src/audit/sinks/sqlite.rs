@@ -0,0 +1,97 @@
+//! SQLite sink: one row per entry, queryable with plain SQL
+
+use std::path::Path;
+
+use crate::audit::{AuditEntry, AuditSink};
+
+const SCHEMA: &str = "CREATE TABLE IF NOT EXISTS audit (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    timestamp TEXT NOT NULL,
+    level TEXT NOT NULL,
+    tool TEXT NOT NULL,
+    rule_id TEXT,
+    input_summary TEXT NOT NULL,
+    reason TEXT NOT NULL,
+    session_id TEXT,
+    entry_json TEXT NOT NULL
+)";
+
+/// Inserts entries into an `audit` table
+pub struct SqliteSink {
+    conn: rusqlite::Connection,
+}
+
+impl SqliteSink {
+    /// Open (or create) the database and ensure the schema exists
+    pub fn open(path: &Path) -> Result<Self, std::io::Error> {
+        let conn = rusqlite::Connection::open(path).map_err(to_io)?;
+        conn.execute(SCHEMA, []).map_err(to_io)?;
+        Ok(Self { conn })
+    }
+}
+
+fn to_io(e: rusqlite::Error) -> std::io::Error {
+    std::io::Error::other(e.to_string())
+}
+
+impl AuditSink for SqliteSink {
+    fn log(&mut self, entry: &AuditEntry) -> Result<(), std::io::Error> {
+        let level = serde_json::to_value(entry.level)?;
+        self.conn
+            .execute(
+                "INSERT INTO audit (timestamp, level, tool, rule_id, input_summary, reason, session_id, entry_json)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                rusqlite::params![
+                    entry.timestamp.to_rfc3339(),
+                    level.as_str().unwrap_or_default(),
+                    entry.tool,
+                    entry.rule_id,
+                    entry.input_summary,
+                    entry.reason,
+                    entry.session_id,
+                    serde_json::to_string(entry)?,
+                ],
+            )
+            .map(|_| ())
+            .map_err(to_io)
+    }
+
+    fn name(&self) -> &str {
+        "sqlite"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::LogLevel;
+    use chrono::Utc;
+
+    #[test]
+    fn test_sqlite_insert() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audit.db");
+        let mut sink = SqliteSink::open(&path).unwrap();
+
+        let entry = AuditEntry {
+            timestamp: Utc::now(),
+            level: LogLevel::Blocked,
+            tool: "Bash".to_string(),
+            rule_id: Some("rm-root".to_string()),
+            input_summary: "Bash: rm -rf /".to_string(),
+            reason: "root".to_string(),
+            session_id: None,
+            cwd: None,
+            input: None,
+            provenance: None,
+            allowed_by: None,
+        };
+        sink.log(&entry).unwrap();
+
+        let count: i64 = sink
+            .conn
+            .query_row("SELECT COUNT(*) FROM audit WHERE level = 'BLOCKED'", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(count, 1);
+    }
+}
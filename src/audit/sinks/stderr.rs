@@ -0,0 +1,35 @@
+//! Stderr and null sinks
+
+use std::io::Write;
+
+use crate::audit::{AuditEntry, AuditSink};
+
+/// Writes one JSON object per line to stderr (for containers whose log
+/// collector reads the process output)
+#[derive(Debug, Default)]
+pub struct StderrSink;
+
+impl AuditSink for StderrSink {
+    fn log(&mut self, entry: &AuditEntry) -> Result<(), std::io::Error> {
+        let json = serde_json::to_string(entry)?;
+        writeln!(std::io::stderr().lock(), "{}", json)
+    }
+
+    fn name(&self) -> &str {
+        "stderr"
+    }
+}
+
+/// Discards every entry
+#[derive(Debug, Default)]
+pub struct NullSink;
+
+impl AuditSink for NullSink {
+    fn log(&mut self, _entry: &AuditEntry) -> Result<(), std::io::Error> {
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        "null"
+    }
+}
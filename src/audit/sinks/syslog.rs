@@ -0,0 +1,103 @@
+//! Syslog sink over the local datagram socket
+
+use std::os::unix::net::UnixDatagram;
+use std::path::Path;
+
+use crate::audit::{AuditEntry, AuditSink, LogLevel};
+
+/// Candidate syslog socket paths (Linux, macOS)
+const SYSLOG_SOCKETS: &[&str] = &["/dev/log", "/var/run/syslog", "/var/run/log"];
+
+/// Facility `user` (1) shifted into the PRI field
+const FACILITY_USER: u8 = 1 << 3;
+
+/// Sends RFC 3164 style messages to the local syslog daemon
+pub struct SyslogSink {
+    socket: UnixDatagram,
+    ident: String,
+}
+
+impl SyslogSink {
+    /// Connect to the first available local syslog socket
+    pub fn connect() -> Option<Self> {
+        SYSLOG_SOCKETS
+            .iter()
+            .find_map(|path| Self::connect_to(Path::new(path)))
+    }
+
+    /// Connect to a specific syslog socket path
+    pub fn connect_to(path: &Path) -> Option<Self> {
+        let socket = UnixDatagram::unbound().ok()?;
+        socket.connect(path).ok()?;
+        Some(Self {
+            socket,
+            ident: "claude-guardrails".to_string(),
+        })
+    }
+
+    /// Map an audit level to a syslog severity
+    fn severity(level: LogLevel) -> u8 {
+        match level {
+            LogLevel::Blocked | LogLevel::Error => 4, // warning
+            LogLevel::Warn | LogLevel::Ask | LogLevel::Disabled => 5, // notice
+            LogLevel::Allowed | LogLevel::Snapshot | LogLevel::Policy => 6, // info
+        }
+    }
+
+    /// Format an entry as a syslog datagram
+    fn format(&self, entry: &AuditEntry) -> Result<String, std::io::Error> {
+        let json = serde_json::to_string(entry)?;
+        Ok(format!(
+            "<{}>{}: {}",
+            FACILITY_USER | Self::severity(entry.level),
+            self.ident,
+            json
+        ))
+    }
+}
+
+impl AuditSink for SyslogSink {
+    fn log(&mut self, entry: &AuditEntry) -> Result<(), std::io::Error> {
+        let message = self.format(entry)?;
+        self.socket.send(message.as_bytes()).map(|_| ())
+    }
+
+    fn name(&self) -> &str {
+        "syslog"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    #[test]
+    fn test_syslog_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("log.sock");
+        let server = UnixDatagram::bind(&path).unwrap();
+
+        let mut sink = SyslogSink::connect_to(&path).unwrap();
+        let entry = AuditEntry {
+            timestamp: Utc::now(),
+            level: LogLevel::Blocked,
+            tool: "Bash".to_string(),
+            rule_id: Some("rm-root".to_string()),
+            input_summary: "Bash: rm -rf /".to_string(),
+            reason: "root".to_string(),
+            session_id: None,
+            cwd: None,
+            input: None,
+            provenance: None,
+            allowed_by: None,
+        };
+        sink.log(&entry).unwrap();
+
+        let mut buf = [0u8; 1024];
+        let n = server.recv(&mut buf).unwrap();
+        let message = String::from_utf8_lossy(&buf[..n]);
+        assert!(message.starts_with("<12>claude-guardrails: "));
+        assert!(message.contains("rm-root"));
+    }
+}
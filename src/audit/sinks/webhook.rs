@@ -0,0 +1,40 @@
+//! Webhook sink: POSTs each entry as JSON to an HTTP(S) endpoint
+
+use std::time::Duration;
+
+use crate::audit::{AuditEntry, AuditSink};
+
+/// Keep well under the hook timeout so a slow endpoint can't stall Claude Code
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Sends every entry to a URL with a JSON body
+pub struct WebhookSink {
+    url: String,
+    agent: ureq::Agent,
+}
+
+impl WebhookSink {
+    /// Create a sink posting to `url`
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            agent: ureq::AgentBuilder::new().timeout(REQUEST_TIMEOUT).build(),
+        }
+    }
+}
+
+impl AuditSink for WebhookSink {
+    fn log(&mut self, entry: &AuditEntry) -> Result<(), std::io::Error> {
+        let body = serde_json::to_string(entry)?;
+        self.agent
+            .post(&self.url)
+            .set("Content-Type", "application/json")
+            .send_string(&body)
+            .map(|_| ())
+            .map_err(|e| std::io::Error::other(e.to_string()))
+    }
+
+    fn name(&self) -> &str {
+        "webhook"
+    }
+}
@@ -0,0 +1,267 @@
+//! On-disk spool for shipping audit entries in batches
+//!
+//! Each hook call is its own process, so entries bound for a remote
+//! collector are appended to a JSONL spool and sent together once a batch
+//! has filled up or its oldest entry has waited long enough. Sending moves
+//! the spool aside (`<spool>.sending`) under a lock file; a batch that fails
+//! stays there and is retried after the same interval, so entries logged
+//! while offline are delivered once the endpoint is reachable again. The
+//! backlog is capped, dropping the oldest entries first.
+
+use chrono::{DateTime, Duration, Utc};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use super::AuditEntry;
+
+/// A lock older than this was left by a process that died mid-flush
+const STALE_LOCK_SECS: u64 = 60;
+
+/// Spooled entries awaiting delivery
+#[derive(Debug, Clone)]
+pub struct Spool {
+    path: PathBuf,
+    batch_size: usize,
+    max_age: Duration,
+    max_entries: usize,
+}
+
+/// Removes the lock file when a flush ends
+struct LockGuard(PathBuf);
+
+impl Drop for LockGuard {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+impl Spool {
+    /// Spool at `path`, sending `batch_size` entries at a time and at most
+    /// `max_age_secs` after an entry was spooled
+    pub fn new(path: impl Into<PathBuf>, batch_size: usize, max_age_secs: u64, max_entries: usize) -> Self {
+        Self {
+            path: path.into(),
+            batch_size: batch_size.max(1),
+            max_age: Duration::seconds(max_age_secs as i64),
+            max_entries: max_entries.max(1),
+        }
+    }
+
+    fn sibling(&self, suffix: &str) -> PathBuf {
+        let mut name = self.path.clone().into_os_string();
+        name.push(suffix);
+        PathBuf::from(name)
+    }
+
+    /// Append an entry
+    pub fn push(&self, entry: &AuditEntry) -> io::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let line = serde_json::to_string(entry)?;
+        let mut file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        writeln!(file, "{}", line)
+    }
+
+    /// Entries waiting to be sent (including a batch that failed)
+    pub fn pending(&self) -> usize {
+        count_lines(&self.path) + count_lines(&self.sibling(".sending"))
+    }
+
+    /// Whether a flush should be attempted at `now`: a full batch is
+    /// waiting, the oldest entry is older than the max age, or a failed
+    /// batch is due for a retry
+    pub fn due(&self, now: DateTime<Utc>) -> bool {
+        let sending = self.sibling(".sending");
+        if let Ok(modified) = fs::metadata(&sending).and_then(|m| m.modified()) {
+            return DateTime::<Utc>::from(modified) + self.max_age <= now;
+        }
+        let Ok(file) = File::open(&self.path) else {
+            return false;
+        };
+        let mut lines = BufReader::new(file).lines().map_while(Result::ok);
+        let Some(first) = lines.next() else {
+            return false;
+        };
+        let oldest = serde_json::from_str::<AuditEntry>(&first).map(|e| e.timestamp).unwrap_or(now);
+        oldest + self.max_age <= now || 1 + lines.count() >= self.batch_size
+    }
+
+    /// Send everything spooled, `batch_size` entries per `send` call
+    ///
+    /// Returns the number of entries delivered. If another process is
+    /// already flushing, nothing is sent. On a failed send the unsent
+    /// entries stay spooled and the error is returned.
+    pub fn flush(&self, mut send: impl FnMut(&[AuditEntry]) -> Result<(), String>) -> io::Result<usize> {
+        let Some(_lock) = self.lock()? else {
+            return Ok(0);
+        };
+        let sending = self.sibling(".sending");
+
+        // Move new entries over to the batch being sent; appenders after the
+        // rename start a fresh spool
+        let incoming = self.sibling(".incoming");
+        match fs::rename(&self.path, &incoming) {
+            Ok(()) => {
+                let content = fs::read(&incoming)?;
+                OpenOptions::new().create(true).append(true).open(&sending)?.write_all(&content)?;
+                fs::remove_file(&incoming)?;
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e),
+        }
+
+        let mut entries = read_entries(&sending)?;
+        if entries.len() > self.max_entries {
+            entries.drain(..entries.len() - self.max_entries);
+        }
+
+        let mut sent = 0;
+        for batch in entries.chunks(self.batch_size) {
+            if let Err(e) = send(batch) {
+                write_entries(&sending, &entries[sent..])?;
+                return Err(io::Error::other(e));
+            }
+            sent += batch.len();
+        }
+        match fs::remove_file(&sending) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(sent),
+        }
+    }
+
+    /// Take the flush lock, or `None` if another process holds it
+    fn lock(&self) -> io::Result<Option<LockGuard>> {
+        let path = self.sibling(".lock");
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        for _ in 0..2 {
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(_) => return Ok(Some(LockGuard(path))),
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+                    let stale = fs::metadata(&path)
+                        .and_then(|m| m.modified())
+                        .ok()
+                        .and_then(|t| SystemTime::now().duration_since(t).ok())
+                        .is_some_and(|age| age.as_secs() >= STALE_LOCK_SECS);
+                    if !stale {
+                        return Ok(None);
+                    }
+                    let _ = fs::remove_file(&path);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(None)
+    }
+}
+
+fn count_lines(path: &Path) -> usize {
+    File::open(path)
+        .map(|f| BufReader::new(f).lines().map_while(Result::ok).filter(|l| !l.trim().is_empty()).count())
+        .unwrap_or(0)
+}
+
+/// Entries in a spool file, skipping lines that don't parse
+fn read_entries(path: &Path) -> io::Result<Vec<AuditEntry>> {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    Ok(BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_str(&line).ok())
+        .collect())
+}
+
+/// Replace a spool file's contents (tmp file + rename)
+fn write_entries(path: &Path, entries: &[AuditEntry]) -> io::Result<()> {
+    let mut content = String::new();
+    for entry in entries {
+        content.push_str(&serde_json::to_string(entry)?);
+        content.push('\n');
+    }
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, content)?;
+    fs::rename(&tmp, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::HookInput;
+    use crate::output::Decision;
+
+    fn entry(command: &str) -> AuditEntry {
+        let json = format!(r#"{{"tool_name":"Bash","tool_input":{{"command":"{}"}}}}"#, command);
+        AuditEntry::new(&HookInput::from_json(&json).unwrap(), &Decision::deny("rm-root", "root"), false)
+    }
+
+    #[test]
+    fn test_batches_and_retries() {
+        let dir = tempfile::tempdir().unwrap();
+        let spool = Spool::new(dir.path().join("spool.jsonl"), 2, 3600, 100);
+        let now = Utc::now();
+
+        spool.push(&entry("rm -rf /a")).unwrap();
+        assert!(!spool.due(now));
+        assert!(spool.due(now + Duration::hours(2)));
+        for c in ["rm -rf /b", "rm -rf /c"] {
+            spool.push(&entry(c)).unwrap();
+        }
+        assert!(spool.due(now));
+
+        // Offline: the second batch fails and stays spooled
+        let mut batches = Vec::new();
+        let err = spool
+            .flush(|batch| {
+                if batches.len() == 1 {
+                    return Err("connection refused".to_string());
+                }
+                batches.push(batch.len());
+                Ok(())
+            })
+            .unwrap_err();
+        assert!(err.to_string().contains("connection refused"));
+        assert_eq!(batches, [2]);
+        assert_eq!(spool.pending(), 1);
+        // Retried after the interval, together with anything spooled since
+        assert!(!spool.due(Utc::now()));
+        assert!(spool.due(Utc::now() + Duration::hours(2)));
+        spool.push(&entry("rm -rf /d")).unwrap();
+
+        let mut delivered = Vec::new();
+        let sent = spool
+            .flush(|batch| {
+                delivered.extend(batch.iter().map(|e| e.input_summary.clone()));
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(sent, 2);
+        assert_eq!(delivered, ["Bash: rm -rf /c", "Bash: rm -rf /d"]);
+        assert_eq!(spool.pending(), 0);
+        assert!(!dir.path().join("spool.jsonl.lock").exists());
+    }
+
+    #[test]
+    fn test_backlog_capped() {
+        let dir = tempfile::tempdir().unwrap();
+        let spool = Spool::new(dir.path().join("spool.jsonl"), 10, 60, 3);
+        for i in 0..5 {
+            spool.push(&entry(&format!("rm -rf /{}", i))).unwrap();
+        }
+        let mut delivered = Vec::new();
+        spool
+            .flush(|batch| {
+                delivered.extend(batch.iter().map(|e| e.input_summary.clone()));
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(delivered, ["Bash: rm -rf /2", "Bash: rm -rf /3", "Bash: rm -rf /4"]);
+    }
+}
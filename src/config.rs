@@ -1,9 +1,14 @@
 //! Configuration loading for claude-guardrails
 //!
-//! Supports TOML configuration with embedded defaults.
+//! Supports TOML configuration with embedded defaults. A project can add a
+//! `.guardrails.toml` at its root, which is merged over the user config.
 
 use serde::Deserialize;
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Project-local config file name, discovered by walking up from the session cwd
+pub const PROJECT_CONFIG_FILE: &str = ".guardrails.toml";
 
 /// Safety level determines which rules are active
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Default)]
@@ -32,7 +37,35 @@ impl SafetyLevel {
         }
     }
 
+    /// Lowercase name as used in configuration files
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SafetyLevel::Critical => "critical",
+            SafetyLevel::High => "high",
+            SafetyLevel::Strict => "strict",
+        }
+    }
+
+    /// Ordering from least to most strict
+    fn rank(&self) -> u8 {
+        match self {
+            SafetyLevel::Critical => 0,
+            SafetyLevel::High => 1,
+            SafetyLevel::Strict => 2,
+        }
+    }
+
+    /// The stricter of two levels
+    pub fn max(self, other: SafetyLevel) -> SafetyLevel {
+        if other.rank() > self.rank() {
+            other
+        } else {
+            self
+        }
+    }
+
     /// Parse from string
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> Option<Self> {
         match s.to_lowercase().as_str() {
             "critical" => Some(SafetyLevel::Critical),
@@ -46,6 +79,7 @@ impl SafetyLevel {
 /// General configuration section
 #[derive(Debug, Clone, Deserialize)]
 #[serde(default)]
+#[non_exhaustive]
 pub struct GeneralConfig {
     /// Safety level for rule filtering
     pub safety_level: SafetyLevel,
@@ -55,6 +89,11 @@ pub struct GeneralConfig {
 
     /// Path to audit log file
     pub audit_path: Option<String>,
+
+    /// Never consult the home directory: the user config is not loaded and
+    /// `~` paths are ignored, so audit/allowlist paths must be explicit
+    /// (containers and static deployments without `HOME`)
+    pub no_home: bool,
 }
 
 impl Default for GeneralConfig {
@@ -63,21 +102,141 @@ impl Default for GeneralConfig {
             safety_level: SafetyLevel::High,
             audit_log: true,
             audit_path: Some("~/.claude/guardrails/audit.jsonl".to_string()),
+            no_home: false,
         }
     }
 }
 
+/// Audit destinations (`[audit]`)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+#[non_exhaustive]
+pub struct AuditConfig {
+    /// Sinks every entry is sent to, in order
+    pub sinks: Vec<SinkKind>,
+
+    /// Endpoint for the `webhook` sink
+    pub webhook_url: Option<String>,
+
+    /// Database for the `sqlite` sink
+    pub sqlite_path: Option<String>,
+
+    /// Collector that spooled entries are POSTed to in batches, in
+    /// addition to `sinks` (cargo feature `remote`)
+    pub remote_endpoint: Option<String>,
+
+    /// Environment variable holding a bearer token for `remote_endpoint`
+    pub remote_token_env: Option<String>,
+
+    /// Entries per POST
+    pub remote_batch_size: usize,
+
+    /// Seconds an entry may wait in the spool (and between retries while
+    /// the endpoint is unreachable)
+    pub remote_flush_secs: u64,
+
+    /// Spool of entries not yet delivered
+    pub remote_spool: String,
+
+    /// Most entries kept while offline; the oldest are dropped beyond this
+    pub remote_max_spool: usize,
+}
+
+impl Default for AuditConfig {
+    fn default() -> Self {
+        Self {
+            sinks: vec![SinkKind::File],
+            webhook_url: None,
+            sqlite_path: None,
+            remote_endpoint: None,
+            remote_token_env: None,
+            remote_batch_size: 50,
+            remote_flush_secs: 60,
+            remote_spool: "~/.claude/guardrails/audit-spool.jsonl".to_string(),
+            remote_max_spool: 10_000,
+        }
+    }
+}
+
+/// An audit destination
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SinkKind {
+    /// JSONL at `general.audit_path`
+    File,
+    /// JSONL on stderr
+    Stderr,
+    /// The local syslog daemon / journal (Unix)
+    Syslog,
+    /// POST to `audit.webhook_url` (cargo feature `webhook`)
+    Webhook,
+    /// Rows in `audit.sqlite_path` (cargo feature `sqlite`)
+    Sqlite,
+    /// Discard
+    Null,
+}
+
 /// Override configuration section
 #[derive(Debug, Clone, Deserialize, Default)]
 #[serde(default)]
+#[non_exhaustive]
 pub struct OverrideConfig {
-    /// Path to allowlist file
-    pub allowlist_file: Option<String>,
+    /// Allowlist file(s): a single path or a list merged in order
+    /// (e.g. user-level, team-shared, project-level)
+    #[serde(deserialize_with = "one_or_many")]
+    pub allowlist_file: Vec<String>,
+
+    /// Path to the allow-once approval token store
+    pub approvals_file: Option<String>,
+}
+
+/// Append the entries of `extra` that `list` doesn't have
+fn add_missing(list: &mut Vec<String>, extra: &[String]) {
+    for item in extra {
+        if !list.contains(item) {
+            list.push(item.clone());
+        }
+    }
 }
 
+/// Accept either a single string or a list of strings
+fn one_or_many<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(String),
+        Many(Vec<String>),
+    }
+
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(path) => vec![path],
+        OneOrMany::Many(paths) => paths,
+    })
+}
+
+/// Processes protected from `kill`/`pkill`/`killall` by default: the agent
+/// itself, IDEs, remote access, container runtimes and session managers
+const DEFAULT_PROTECTED_PROCESSES: &[&str] = &[
+    "claude", "code", "cursor", "windsurf", "zed", "idea", "pycharm", "webstorm", "goland", "clion", "rider",
+    "sshd", "dockerd", "containerd", "systemd", "launchd", "init", "tmux", "screen",
+];
+
+/// Hosts load generators may target by default: this machine and reserved
+/// local names
+const DEFAULT_LOAD_TEST_TARGETS: &[&str] =
+    &["localhost", "127.0.0.1", "::1", "0.0.0.0", "*.localhost", "*.local", "*.test"];
+
+/// Words marking an environment variable as secret by default
+const DEFAULT_SECRET_ENV_NAMES: &[&str] =
+    &["SECRET", "TOKEN", "PASSWORD", "PASSWD", "API_KEY", "APIKEY", "PRIVATE_KEY", "CREDENTIAL", "ACCESS_KEY"];
+
 /// Bash-specific configuration
 #[derive(Debug, Clone, Deserialize)]
 #[serde(default)]
+#[non_exhaustive]
 pub struct BashConfig {
     /// Commands that wrap other commands (to scan recursively)
     pub wrappers: Vec<String>,
@@ -87,6 +246,38 @@ pub struct BashConfig {
 
     /// Block dangerous pipe targets (| sh, | bash)
     pub block_pipe_to_shell: bool,
+
+    /// Read and check project scripts the command runs (`./x.sh`, `bash x.sh`)
+    pub scan_scripts: bool,
+
+    /// Bytes of each script read when `scan_scripts` is on
+    pub script_max_bytes: usize,
+
+    /// Remember each session's downloads, so running one in a later call
+    /// is flagged like `curl -o x && ./x`
+    pub track_downloads: bool,
+
+    /// Where session downloads are remembered
+    pub downloads_file: String,
+
+    /// Processes `kill`, `pkill` and `killall` may not target
+    pub protected_processes: Vec<String>,
+
+    /// Hosts `ab`, `wrk`, `hey`, `siege` and `locust` may target at the
+    /// Strict level (`host`, `host:port`, `*.domain`)
+    pub load_test_targets: Vec<String>,
+
+    /// Shell syntax commands are written in: "bash" (the default, parsed
+    /// as written), "zsh", "fish", or "auto" for the shell in `$SHELL`
+    pub shell: String,
+
+    /// Words that mark an environment variable as secret, matched within
+    /// the name ignoring case (`GITHUB_TOKEN`, `db_password`)
+    pub secret_env_names: Vec<String>,
+
+    /// Check commands fed an automatic "yes" (`yes | cmd`, `echo y | cmd`)
+    /// one safety level stricter
+    pub escalate_auto_confirm: bool,
 }
 
 impl Default for BashConfig {
@@ -106,6 +297,15 @@ impl Default for BashConfig {
             ],
             block_variable_commands: true,
             block_pipe_to_shell: true,
+            scan_scripts: false,
+            script_max_bytes: 64 * 1024,
+            track_downloads: false,
+            downloads_file: "~/.claude/guardrails/downloads.json".to_string(),
+            protected_processes: DEFAULT_PROTECTED_PROCESSES.iter().map(|p| p.to_string()).collect(),
+            load_test_targets: DEFAULT_LOAD_TEST_TARGETS.iter().map(|t| t.to_string()).collect(),
+            shell: "bash".to_string(),
+            secret_env_names: DEFAULT_SECRET_ENV_NAMES.iter().map(|n| n.to_string()).collect(),
+            escalate_auto_confirm: true,
         }
     }
 }
@@ -113,9 +313,28 @@ impl Default for BashConfig {
 /// File operation configuration
 #[derive(Debug, Clone, Deserialize)]
 #[serde(default)]
+#[non_exhaustive]
 pub struct FilesConfig {
     /// Patterns to protect from Read/Edit/Write
     pub protected_patterns: Vec<String>,
+
+    /// Paths that are never checked (vendored code, build output, fixtures)
+    pub ignore_patterns: Vec<String>,
+
+    /// Downgrade file denies to warnings for paths that look like test fixtures
+    pub fixture_aware: bool,
+
+    /// Patterns recognizing test/fixture paths when `fixture_aware` is on
+    pub fixture_patterns: Vec<String>,
+
+    /// Also match paths with `~`, `.`/`..` and symlinks resolved
+    pub canonicalize: bool,
+
+    /// Deny Read/Edit/Write outside the session's working directory
+    pub restrict_to_project: bool,
+
+    /// Directories besides the project that `restrict_to_project` allows
+    pub allowed_roots: Vec<String>,
 }
 
 impl Default for FilesConfig {
@@ -136,46 +355,593 @@ impl Default for FilesConfig {
                 r"\.npmrc$".to_string(),
                 r"\.pypirc$".to_string(),
             ],
+            ignore_patterns: Vec::new(),
+            fixture_aware: false,
+            fixture_patterns: vec![
+                r"(^|/)(tests?|spec|__tests__|testdata|fixtures?|__fixtures__|__mocks__)/".to_string(),
+                r"(?i)(example|sample|fixture|mock)[^/]*$".to_string(),
+            ],
+            canonicalize: false,
+            restrict_to_project: false,
+            allowed_roots: vec!["/tmp".to_string()],
+        }
+    }
+}
+
+/// Hook output configuration
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+#[non_exhaustive]
+pub struct OutputConfig {
+    /// Append a machine-readable retry hint to deny messages
+    pub hints: bool,
+
+    /// Rule IDs that never get a hint (retrying is undesirable)
+    pub suppress_hints: Vec<String>,
+
+    /// System message template ({tier}, {rule_id}, {action}, {reason})
+    pub message_format: String,
+
+    /// Suggest a Claude Code permission rule for allowed read-only commands
+    pub suggest_permissions: bool,
+}
+
+impl Default for OutputConfig {
+    fn default() -> Self {
+        Self {
+            hints: false,
+            suppress_hints: vec!["rm-root".to_string(), "rm-home".to_string()],
+            message_format: crate::output::DEFAULT_MESSAGE_FORMAT.to_string(),
+            suggest_permissions: false,
         }
     }
 }
 
+/// Command rewrite configuration
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+#[non_exhaustive]
+pub struct RewriteConfig {
+    /// Return a sanitized command via updatedInput instead of blocking
+    pub enabled: bool,
+
+    /// Rewrite IDs to never apply
+    pub skip: Vec<String>,
+
+    /// Timeout (ms) to inject into long-running Bash calls that set none (0 disables)
+    pub timeout_ms: Option<u64>,
+
+    /// Move allowed `rm -r` targets to the trash instead of deleting them
+    pub trash: bool,
+
+    /// Trash command to use (default: first of trash-put, gio trash, trash on PATH)
+    pub trash_command: Option<String>,
+
+    /// Rule IDs whose commands get `git stash push -u` prepended instead of a block
+    pub stash_rules: Vec<String>,
+
+    /// Run the allowed prefix of a denied compound command (`npm test` from
+    /// `npm test && rm -rf /`) instead of blocking the whole line
+    pub safe_prefix: bool,
+}
+
+impl Default for RewriteConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            skip: Vec::new(),
+            timeout_ms: Some(300_000),
+            trash: false,
+            trash_command: None,
+            stash_rules: Vec::new(),
+            safe_prefix: false,
+        }
+    }
+}
+
+/// Shadow evaluation configuration
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+#[non_exhaustive]
+pub struct ShadowConfig {
+    /// Enumerate paths affected by rm/find deletions before allowing them
+    pub enabled: bool,
+
+    /// Deny if a deletion affects more paths than this
+    pub max_paths: usize,
+
+    /// Time limit for the enumerating subprocess
+    pub timeout_ms: u64,
+}
+
+impl Default for ShadowConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_paths: 10_000,
+            timeout_ms: 2_000,
+        }
+    }
+}
+
+/// Breadth limits for bulk rm/chmod -R/chown -R commands
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+#[non_exhaustive]
+pub struct BulkConfig {
+    /// Expand the targets of rm/chmod -R/chown -R/chgrp -R and check their breadth
+    pub enabled: bool,
+
+    /// Deny if the expanded targets number more than this
+    pub max_targets: usize,
+
+    /// Deny a recursive command whose targets span more top-level directories than this
+    pub max_top_level: usize,
+}
+
+impl Default for BulkConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_targets: 250,
+            max_top_level: 4,
+        }
+    }
+}
+
+/// Session snapshot configuration
+#[derive(Debug, Clone, Deserialize, Default)]
+#[serde(default)]
+#[non_exhaustive]
+pub struct SnapshotConfig {
+    /// Create refs/guardrails/session-<id> before a session's first mutating command
+    pub enabled: bool,
+}
+
+/// Daemon mode (`--daemon` / `--client`)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+#[non_exhaustive]
+pub struct DaemonConfig {
+    /// Unix socket the daemon listens on and the client connects to
+    pub socket: String,
+}
+
+impl Default for DaemonConfig {
+    fn default() -> Self {
+        Self {
+            socket: "~/.claude/guardrails/daemon.sock".to_string(),
+        }
+    }
+}
+
+/// Opt-in anonymous rule hit counters (see `telemetry`)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+#[non_exhaustive]
+pub struct TelemetryConfig {
+    /// Count rule hits (rule IDs and counts only) in `path`
+    pub enabled: bool,
+
+    /// Local counters file
+    pub path: String,
+
+    /// Org endpoint `telemetry submit` POSTs the counters to
+    pub endpoint: Option<String>,
+
+    /// OpenTelemetry collector (OTLP/HTTP base URL, e.g.
+    /// `http://localhost:4318`) each decision is exported to (cargo
+    /// feature `otlp`)
+    pub otlp_endpoint: Option<String>,
+
+    /// `service.name` of the exported metrics and traces
+    pub otlp_service_name: String,
+
+    /// Fraction of traced rule-set matches that also time every rule on its
+    /// own and export the slowest as `rule-cost` spans (0 turns it off)
+    pub rule_cost_sample_rate: f64,
+}
+
+impl Default for TelemetryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            path: "~/.claude/guardrails/telemetry.json".to_string(),
+            endpoint: None,
+            otlp_endpoint: None,
+            otlp_service_name: "claude-guardrails".to_string(),
+            rule_cost_sample_rate: 0.05,
+        }
+    }
+}
+
+/// Remote approval endpoint for `remote-ask` rules (see `remote_ask`)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+#[non_exhaustive]
+pub struct ApprovalConfig {
+    /// URL the decision context is POSTed to (cargo feature `webhook`)
+    pub endpoint: Option<String>,
+
+    /// How long to wait for a verdict before denying; keep it under the
+    /// hook's own timeout
+    pub timeout_ms: u64,
+
+    /// Delay between polls while the verdict is pending
+    pub poll_interval_ms: u64,
+}
+
+impl Default for ApprovalConfig {
+    fn default() -> Self {
+        Self {
+            endpoint: None,
+            timeout_ms: 4_000,
+            poll_interval_ms: 500,
+        }
+    }
+}
+
+/// Outbound connection policy for network commands (see `engine::egress`)
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+#[non_exhaustive]
+pub struct EgressConfig {
+    /// Ports no command may connect to (`[4444, 1337]`)
+    pub deny_ports: Vec<u16>,
+
+    /// Block connections to literal IP addresses (loopback excepted)
+    pub deny_raw_ips: bool,
+
+    /// When non-empty, the only destinations allowed: `host`, `host:port`,
+    /// `*.domain:port` or `*:port`
+    pub allow: Vec<String>,
+}
+
+impl EgressConfig {
+    /// Whether any egress policy is configured
+    pub fn is_enabled(&self) -> bool {
+        !self.deny_ports.is_empty() || self.deny_raw_ips || !self.allow.is_empty()
+    }
+
+    /// This policy with `other`'s restrictions added: denied ports are
+    /// combined, and `other`'s allowlist only applies if this one has none
+    fn tightened_by(&self, other: &EgressConfig) -> EgressConfig {
+        let mut deny_ports = self.deny_ports.clone();
+        deny_ports.extend(other.deny_ports.iter().filter(|p| !self.deny_ports.contains(p)));
+        EgressConfig {
+            deny_ports,
+            deny_raw_ips: self.deny_raw_ips || other.deny_raw_ips,
+            allow: if self.allow.is_empty() { other.allow.clone() } else { self.allow.clone() },
+        }
+    }
+}
+
+/// Content scanning for Write/Edit/MultiEdit
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+#[non_exhaustive]
+pub struct ContentConfig {
+    /// Block quoted high-entropy strings (likely tokens) in written content
+    pub entropy_scan: bool,
+
+    /// Minimum entropy in bits per character to flag a string
+    pub entropy_threshold: f64,
+
+    /// Minimum length of a quoted string to consider
+    pub entropy_min_length: usize,
+
+    /// Prefixes of strings that are never flagged (integrity hashes, data URIs)
+    pub entropy_allow_prefixes: Vec<String>,
+
+    /// Files whose content is never scanned (lockfiles full of hashes)
+    pub entropy_skip_files: Vec<String>,
+}
+
+impl Default for ContentConfig {
+    fn default() -> Self {
+        Self {
+            entropy_scan: false,
+            entropy_threshold: 4.3,
+            entropy_min_length: 20,
+            entropy_allow_prefixes: ["sha1-", "sha256-", "sha384-", "sha512-", "data:", "http://", "https://"]
+                .iter()
+                .map(|p| p.to_string())
+                .collect(),
+            entropy_skip_files: vec![
+                r"(^|/)(Cargo|Gemfile|composer|poetry|flake|Pipfile)\.lock$".to_string(),
+                r"(^|/)(package-lock\.json|npm-shrinkwrap\.json|yarn\.lock|pnpm-lock\.yaml|bun\.lock|go\.sum)$".to_string(),
+            ],
+        }
+    }
+}
+
+impl ContentConfig {
+    /// This scan with `other`'s settings applied only where they flag more
+    fn tightened_by(&self, other: &ContentConfig) -> ContentConfig {
+        ContentConfig {
+            entropy_scan: self.entropy_scan || other.entropy_scan,
+            entropy_threshold: self.entropy_threshold.min(other.entropy_threshold),
+            entropy_min_length: self.entropy_min_length.min(other.entropy_min_length),
+            entropy_allow_prefixes: self.entropy_allow_prefixes.clone(),
+            entropy_skip_files: self.entropy_skip_files.clone(),
+        }
+    }
+}
+
+/// Known token format detection
+#[derive(Debug, Clone, Deserialize, Default)]
+#[serde(default)]
+#[non_exhaustive]
+pub struct TokensConfig {
+    /// Block Bash commands and Write/Edit content containing a known token format
+    pub enabled: bool,
+
+    /// Extra token packs merged over the built-in formats
+    pub files: Vec<String>,
+}
+
+/// Installed rule packs (see `rules::packs`)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+#[non_exhaustive]
+pub struct PacksConfig {
+    /// Directory holding `<name>/pack.toml` and the `index.toml` of
+    /// enabled packs
+    pub dir: String,
+}
+
+impl Default for PacksConfig {
+    fn default() -> Self {
+        Self {
+            dir: "~/.claude/guardrails/packs".to_string(),
+        }
+    }
+}
+
+/// Signatures required on distributed policy files (see `signing`)
+#[derive(Debug, Clone, Deserialize, Default)]
+#[serde(default)]
+#[non_exhaustive]
+pub struct SigningConfig {
+    /// Minisign public keys (`RW...`, or the path of a `.pub` file); when
+    /// set, token packs and fetched policy files must be signed by one
+    pub trusted_keys: Vec<String>,
+}
+
+/// What to do when a rule matches, overriding its built-in behavior
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleAction {
+    /// Never match
+    Off,
+    /// Allow, but report a warning
+    Warn,
+    /// Block (the default for built-in rules)
+    Deny,
+    /// Ask the user to confirm instead of blocking
+    Ask,
+    /// Block unless a teammate approves through `[approval]` (`remote-ask`)
+    #[serde(rename = "remote-ask")]
+    RemoteAsk,
+}
+
+/// Rule configuration
+#[derive(Debug, Clone, Deserialize, Default)]
+#[serde(default)]
+#[non_exhaustive]
+pub struct RulesConfig {
+    /// Per-rule overrides keyed by rule ID (`git-reset-hard = "off"`)
+    pub overrides: HashMap<String, RuleAction>,
+
+    /// Rule severities that ask the user to confirm instead of blocking
+    pub ask_levels: Vec<SafetyLevel>,
+}
+
+/// Per-tool configuration (`[tools.Bash]`, `[tools.Read]`, ...)
+#[derive(Debug, Clone, Deserialize, Default)]
+#[serde(default)]
+#[non_exhaustive]
+pub struct ToolConfig {
+    /// Safety level for this tool, overriding `general.safety_level`
+    pub safety_level: Option<SafetyLevel>,
+}
+
 /// Main configuration structure
 #[derive(Debug, Clone, Deserialize, Default)]
 #[serde(default)]
+#[non_exhaustive]
 pub struct Config {
     pub general: GeneralConfig,
+    pub audit: AuditConfig,
     pub overrides: OverrideConfig,
     pub bash: BashConfig,
+    pub egress: EgressConfig,
     pub files: FilesConfig,
+    pub content: ContentConfig,
+    pub tokens: TokensConfig,
+    pub packs: PacksConfig,
+    pub signing: SigningConfig,
+    pub rules: RulesConfig,
+    pub output: OutputConfig,
+    pub rewrite: RewriteConfig,
+    pub shadow: ShadowConfig,
+    pub bulk: BulkConfig,
+    pub snapshot: SnapshotConfig,
+    pub daemon: DaemonConfig,
+    pub telemetry: TelemetryConfig,
+    pub approval: ApprovalConfig,
+    /// Per-tool overrides keyed by tool name
+    pub tools: HashMap<String, ToolConfig>,
+    /// Hash of the merged TOML this config was loaded from (set by the
+    /// layered loaders; `None` for configs built in code)
+    #[serde(skip)]
+    pub source_hash: Option<String>,
 }
 
 impl Config {
-    /// Load configuration from file or use defaults
+    /// Load configuration from the standard locations, merged over defaults
     pub fn load() -> Self {
-        // Try to load from standard locations
-        let config_paths = [
-            // User-specific config
-            dirs::home_dir().map(|p| p.join(".claude/guardrails/config.toml")),
-            // System-wide config
-            Some(PathBuf::from("/etc/claude-guardrails/config.toml")),
-        ];
-
-        for path in config_paths.into_iter().flatten() {
-            if path.exists() {
-                if let Ok(content) = std::fs::read_to_string(&path) {
-                    match toml::from_str(&content) {
-                        Ok(config) => return config,
-                        Err(e) => {
-                            eprintln!("Warning: Failed to parse {}: {}", path.display(), e);
-                        }
-                    }
-                }
+        Self::load_layered(None, None)
+    }
+
+    /// Load configuration in layers: embedded defaults, then `path` (or
+    /// /etc/claude-guardrails/config.toml and ~/.claude/guardrails/config.toml
+    /// when no path is given), then the project's `.guardrails.toml` found
+    /// from `cwd`
+    ///
+    /// Later layers win key by key; `protected_patterns` and `wrappers` are
+    /// appended to instead of replaced. The project file can tighten policy
+    /// but not lower the general safety level set by the layers below it.
+    pub fn load_layered(path: Option<&Path>, cwd: Option<&Path>) -> Self {
+        Self::load_from_layers(path, cwd, dirs::home_dir())
+    }
+
+    /// Like `load_layered`, but without the home directory (`--no-home`):
+    /// the user config is skipped and `~` paths are ignored
+    pub fn load_without_home(path: Option<&Path>, cwd: Option<&Path>) -> Self {
+        let mut config = Self::load_from_layers(path, cwd, None);
+        config.general.no_home = true;
+        config
+    }
+
+    fn load_from_layers(path: Option<&Path>, cwd: Option<&Path>, home: Option<PathBuf>) -> Self {
+        let layers: Vec<PathBuf> = match path {
+            Some(path) => vec![path.to_path_buf()],
+            None => [
+                // System-wide config
+                Some(PathBuf::from("/etc/claude-guardrails/config.toml")),
+                // User-specific config
+                home.map(|p| p.join(".claude/guardrails/config.toml")),
+            ]
+            .into_iter()
+            .flatten()
+            .filter(|p| p.exists())
+            .collect(),
+        };
+        let project = cwd.and_then(Self::find_project_config);
+        Self::merge_layers(&layers, project.as_deref())
+    }
+
+    /// Merge config files over the embedded defaults, skipping unreadable ones
+    fn merge_layers(layers: &[PathBuf], project: Option<&Path>) -> Self {
+        let (mut config, value) = Self::merge_layer_values(layers, project);
+        let hash = crate::rules::manifest::fnv1a(value.to_string().as_bytes());
+        config.source_hash = Some(format!("{:016x}", hash));
+        config
+    }
+
+    /// The merged config along with the TOML value it was built from
+    fn merge_layer_values(layers: &[PathBuf], project: Option<&Path>) -> (Self, toml::Value) {
+        let mut value: toml::Value =
+            toml::from_str(DEFAULT_CONFIG_TOML).expect("embedded default config is valid");
+
+        for path in layers {
+            match Self::read_value(path) {
+                Ok(layer) => merge_values(&mut value, layer),
+                Err(e) => eprintln!("Warning: Failed to load config from {}: {}", path.display(), e),
+            }
+        }
+        let base: Config = value.clone().try_into().unwrap_or_default();
+
+        let Some(project) = project else {
+            return (base, value);
+        };
+        let base_value = value.clone();
+        match Self::read_value(project) {
+            Ok(layer) => merge_values(&mut value, layer),
+            Err(e) => {
+                eprintln!("Warning: Ignoring {}: {}", project.display(), e);
+                return (base, base_value);
             }
         }
 
-        // Return defaults
-        Config::default()
+        let merged: Config = value.clone().try_into().unwrap_or_else(|_| base.clone());
+        (base.tightened_by(&merged), value)
+    }
+
+    /// This config with the settings a repository's `.guardrails.toml` may
+    /// change taken from `project`, each only in the stricter direction
+    ///
+    /// Everything else (allowlists, audit, rewrites, packs, endpoints,
+    /// ignore and fixture paths, the shell dialect, ...) stays as the layers
+    /// below set it: a cloned repo must not be able to loosen the policy it
+    /// is checked under, so new fields are base-only until added here.
+    fn tightened_by(&self, project: &Config) -> Config {
+        let mut config = self.clone();
+        config.general.safety_level = self.general.safety_level.max(project.general.safety_level);
+        for (name, tool) in &project.tools {
+            if let Some(level) = tool.safety_level {
+                let level = level.max(self.safety_level_for(name));
+                config.tools.entry(name.clone()).or_default().safety_level = Some(level);
+            }
+        }
+        // `deny` overrides only
+        for (rule_id, action) in &project.rules.overrides {
+            if *action == RuleAction::Deny {
+                config.rules.overrides.insert(rule_id.clone(), RuleAction::Deny);
+            }
+        }
+
+        let (bash, theirs) = (&mut config.bash, &project.bash);
+        bash.block_variable_commands |= theirs.block_variable_commands;
+        bash.block_pipe_to_shell |= theirs.block_pipe_to_shell;
+        bash.escalate_auto_confirm |= theirs.escalate_auto_confirm;
+        bash.scan_scripts |= theirs.scan_scripts;
+        bash.track_downloads |= theirs.track_downloads;
+        add_missing(&mut bash.wrappers, &theirs.wrappers);
+        add_missing(&mut bash.protected_processes, &theirs.protected_processes);
+        add_missing(&mut bash.secret_env_names, &theirs.secret_env_names);
+
+        add_missing(&mut config.files.protected_patterns, &project.files.protected_patterns);
+        config.files.restrict_to_project |= project.files.restrict_to_project;
+        config.files.fixture_aware &= project.files.fixture_aware;
+        config.egress = self.egress.tightened_by(&project.egress);
+        config.content = self.content.tightened_by(&project.content);
+        config.tokens.enabled |= project.tokens.enabled;
+
+        config.bulk.enabled |= project.bulk.enabled;
+        config.bulk.max_targets = self.bulk.max_targets.min(project.bulk.max_targets);
+        config.bulk.max_top_level = self.bulk.max_top_level.min(project.bulk.max_top_level);
+        config
+    }
+
+    /// Read a TOML file, checking that it is a valid config
+    fn read_value(path: &Path) -> Result<toml::Value, Box<dyn std::error::Error>> {
+        let content = std::fs::read_to_string(path)?;
+        let value: toml::Value = toml::from_str(&content)?;
+        // Surface type errors against the file they come from
+        let _: Config = value.clone().try_into()?;
+        Ok(value)
+    }
+
+    /// Find `.guardrails.toml` in `cwd` or the nearest ancestor that has one
+    pub fn find_project_config(cwd: &Path) -> Option<PathBuf> {
+        cwd.ancestors()
+            .map(|dir| dir.join(PROJECT_CONFIG_FILE))
+            .find(|path| path.is_file())
+    }
+
+    /// Load the config at `path` (or the standard locations) with the
+    /// project's `.guardrails.toml`, if any, merged over it
+    pub fn load_for_project(path: Option<&Path>, cwd: &Path) -> Self {
+        Self::load_layered(path, Some(cwd))
+    }
+
+    /// Parse a config from TOML text, merged over the embedded defaults
+    /// like a config file layer (keys it omits keep their defaults, and
+    /// `protected_patterns`/`wrappers` append)
+    ///
+    /// Nothing is read from disk, so embedders and tests can build a config
+    /// hermetically; set `general.no_home` as well to keep `~` paths (audit
+    /// log, allowlist, approvals) from resolving into the real home.
+    pub fn from_toml_str(toml: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut value: toml::Value =
+            toml::from_str(DEFAULT_CONFIG_TOML).expect("embedded default config is valid");
+        merge_values(&mut value, toml::from_str(toml)?);
+        let hash = crate::rules::manifest::fnv1a(value.to_string().as_bytes());
+        let mut config: Config = value.try_into()?;
+        config.source_hash = Some(format!("{:016x}", hash));
+        Ok(config)
     }
 
     /// Load from a specific path
@@ -187,25 +953,168 @@ impl Config {
 
     /// Expand ~ in path strings
     pub fn expand_path(path: &str) -> PathBuf {
-        if path.starts_with("~/") {
+        if let Some(rest) = path.strip_prefix("~/") {
             if let Some(home) = dirs::home_dir() {
-                return home.join(&path[2..]);
+                return home.join(rest);
             }
         }
         PathBuf::from(path)
     }
 
+    /// Resolve a configured path, expanding `~`
+    ///
+    /// Returns `None` for `~` paths when there is no usable home directory
+    /// (`general.no_home`, or `HOME` unset in a container) instead of
+    /// writing to a literal `~` directory under the working directory.
+    pub fn resolve_path(&self, path: &str) -> Option<PathBuf> {
+        let rest = match path.strip_prefix('~') {
+            Some(rest) if rest.is_empty() || rest.starts_with('/') => rest.trim_start_matches('/'),
+            _ => return Some(PathBuf::from(path)),
+        };
+        if self.general.no_home {
+            return None;
+        }
+        dirs::home_dir().map(|home| home.join(rest))
+    }
+
+    /// Configured paths that `--no-home` ignores because they start with `~`
+    pub fn home_relative_paths(&self) -> Vec<&str> {
+        self.general
+            .audit_path
+            .iter()
+            .chain(&self.audit.sqlite_path)
+            .chain(self.audit.remote_endpoint.is_some().then_some(&self.audit.remote_spool))
+            .chain(&self.overrides.allowlist_file)
+            .chain(&self.overrides.approvals_file)
+            .chain(&self.tokens.files)
+            .chain(self.telemetry.enabled.then_some(&self.telemetry.path))
+            .chain(self.bash.track_downloads.then_some(&self.bash.downloads_file))
+            .map(String::as_str)
+            .filter(|p| p.starts_with('~'))
+            .collect()
+    }
+
+    /// Safety level for a tool: its `[tools.<name>]` override, else the general level
+    pub fn safety_level_for(&self, tool: &str) -> SafetyLevel {
+        self.tools
+            .get(tool)
+            .and_then(|t| t.safety_level)
+            .unwrap_or(self.general.safety_level)
+    }
+
+    /// Configured override for a rule ID, if any
+    ///
+    /// The `guardrails-*` self-protection rules can't be overridden, so a
+    /// project config can't switch off the rules that guard it.
+    pub fn rule_action(&self, rule_id: &str) -> Option<RuleAction> {
+        if rule_id.starts_with("guardrails-") {
+            return None;
+        }
+        self.rules.overrides.get(rule_id).copied()
+    }
+
+    /// Whether a denial from this rule should ask for confirmation instead,
+    /// because its severity is listed in `rules.ask_levels`
+    pub fn asks_at_level(&self, rule_id: &str) -> bool {
+        !rule_id.starts_with("guardrails-")
+            && self.rules.ask_levels.contains(&crate::rules::severity_of(rule_id))
+    }
+
     /// Get the audit log path (expanded)
     pub fn audit_path(&self) -> Option<PathBuf> {
-        self.general.audit_path.as_ref().map(|p| Self::expand_path(p))
+        self.general.audit_path.as_ref().and_then(|p| self.resolve_path(p))
+    }
+
+    /// Get the SQLite audit database path (expanded)
+    pub fn sqlite_audit_path(&self) -> Option<PathBuf> {
+        self.audit.sqlite_path.as_ref().and_then(|p| self.resolve_path(p))
+    }
+
+    /// Get the remote audit spool path (expanded)
+    pub fn remote_spool_path(&self) -> Option<PathBuf> {
+        self.resolve_path(&self.audit.remote_spool)
     }
 
-    /// Get the allowlist file path (expanded)
-    pub fn allowlist_path(&self) -> Option<PathBuf> {
+    /// Get the allowlist file paths (expanded), in merge order
+    pub fn allowlist_paths(&self) -> Vec<PathBuf> {
         self.overrides
             .allowlist_file
+            .iter()
+            .filter_map(|p| self.resolve_path(p))
+            .collect()
+    }
+
+    /// Get the extra token pack paths (expanded)
+    pub fn token_pack_paths(&self) -> Vec<PathBuf> {
+        self.tokens.files.iter().filter_map(|p| self.resolve_path(p)).collect()
+    }
+
+    /// Get the rule packs directory (expanded)
+    pub fn packs_dir(&self) -> Option<PathBuf> {
+        self.resolve_path(&self.packs.dir)
+    }
+
+    /// Get the telemetry counters path (expanded)
+    pub fn telemetry_path(&self) -> Option<PathBuf> {
+        self.resolve_path(&self.telemetry.path)
+    }
+
+    /// Get the session downloads path (expanded), if tracking is on
+    pub fn downloads_path(&self) -> Option<PathBuf> {
+        self.bash
+            .track_downloads
+            .then(|| self.resolve_path(&self.bash.downloads_file))
+            .flatten()
+    }
+
+    /// Get the approval token store path (expanded)
+    pub fn approvals_path(&self) -> Option<PathBuf> {
+        self.overrides
+            .approvals_file
             .as_ref()
-            .map(|p| Self::expand_path(p))
+            .and_then(|p| self.resolve_path(p))
+    }
+}
+
+/// Arrays that accumulate across config layers instead of being replaced
+const APPEND_KEYS: &[&str] = &[
+    "files.protected_patterns",
+    "bash.wrappers",
+    "bash.protected_processes",
+    "bash.secret_env_names",
+];
+
+/// Merge `overlay` into `base`: tables merge key by key, the arrays in
+/// `APPEND_KEYS` gain the overlay's new entries, other values are replaced
+pub fn merge_values(base: &mut toml::Value, overlay: toml::Value) {
+    merge_at(base, overlay, "");
+}
+
+fn merge_at(base: &mut toml::Value, overlay: toml::Value, key_path: &str) {
+    match (base, overlay) {
+        (toml::Value::Table(base), toml::Value::Table(overlay)) => {
+            for (key, value) in overlay {
+                let child_path = if key_path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", key_path, key)
+                };
+                match base.get_mut(&key) {
+                    Some(existing) => merge_at(existing, value, &child_path),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (toml::Value::Array(base), toml::Value::Array(overlay)) if APPEND_KEYS.contains(&key_path) => {
+            for item in overlay {
+                if !base.contains(&item) {
+                    base.push(item);
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
     }
 }
 
@@ -216,13 +1125,39 @@ safety_level = "high"
 audit_log = true
 audit_path = "~/.claude/guardrails/audit.jsonl"
 
+[audit]
+sinks = ["file"]
+remote_batch_size = 50
+remote_flush_secs = 60
+remote_spool = "~/.claude/guardrails/audit-spool.jsonl"
+remote_max_spool = 10000
+
 [overrides]
 allowlist_file = "~/.claude/guardrails/allow.toml"
+approvals_file = "~/.claude/guardrails/approvals.json"
 
 [bash]
 wrappers = ["sudo", "timeout", "xargs", "env", "nice", "nohup", "ionice", "strace", "time"]
 block_variable_commands = true
 block_pipe_to_shell = true
+scan_scripts = false
+script_max_bytes = 65536
+track_downloads = false
+downloads_file = "~/.claude/guardrails/downloads.json"
+protected_processes = [
+    "claude", "code", "cursor", "windsurf", "zed", "idea", "pycharm", "webstorm", "goland", "clion", "rider",
+    "sshd", "dockerd", "containerd", "systemd", "launchd", "init", "tmux", "screen",
+]
+load_test_targets = ["localhost", "127.0.0.1", "::1", "0.0.0.0", "*.localhost", "*.local", "*.test"]
+shell = "bash"
+secret_env_names = [
+    "SECRET", "TOKEN", "PASSWORD", "PASSWD", "API_KEY", "APIKEY", "PRIVATE_KEY", "CREDENTIAL", "ACCESS_KEY",
+]
+
+[egress]
+deny_ports = []
+deny_raw_ips = false
+allow = []
 
 [files]
 protected_patterns = [
@@ -240,12 +1175,480 @@ protected_patterns = [
     "\\.npmrc$",
     "\\.pypirc$",
 ]
+
+[output]
+hints = false
+suppress_hints = ["rm-root", "rm-home"]
+message_format = "[guardrails][{tier}] {action} ({rule_id}): {reason}"
+suggest_permissions = false
+
+[rewrite]
+enabled = false
+skip = []
+timeout_ms = 300000
+trash = false
+stash_rules = []
+
+[shadow]
+enabled = false
+max_paths = 10000
+timeout_ms = 2000
+
+[bulk]
+enabled = true
+max_targets = 250
+max_top_level = 4
+
+[snapshot]
+enabled = false
+
+[daemon]
+socket = "~/.claude/guardrails/daemon.sock"
+
+[telemetry]
+enabled = false
+path = "~/.claude/guardrails/telemetry.json"
+otlp_service_name = "claude-guardrails"
+rule_cost_sample_rate = 0.05
+
+[approval]
+timeout_ms = 4000
+poll_interval_ms = 500
 "#;
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_per_tool_safety_level() {
+        let config: Config = toml::from_str(
+            r#"
+[general]
+safety_level = "high"
+
+[tools.Bash]
+safety_level = "strict"
+"#,
+        )
+        .unwrap();
+        assert_eq!(config.safety_level_for("Bash"), SafetyLevel::Strict);
+        assert_eq!(config.safety_level_for("Read"), SafetyLevel::High);
+    }
+
+    #[test]
+    fn test_allowlist_file_one_or_many() {
+        let config: Config = toml::from_str(
+            r#"
+            [overrides]
+            allowlist_file = "/etc/guardrails/allow.toml"
+        "#,
+        )
+        .unwrap();
+        assert_eq!(config.allowlist_paths(), vec![PathBuf::from("/etc/guardrails/allow.toml")]);
+
+        let config: Config = toml::from_str(
+            r#"
+            [overrides]
+            allowlist_file = ["/etc/guardrails/team.toml", ".claude/guardrails-allow.toml"]
+        "#,
+        )
+        .unwrap();
+        assert_eq!(config.allowlist_paths().len(), 2);
+        assert!(Config::default().allowlist_paths().is_empty());
+    }
+
+    #[test]
+    fn test_rule_overrides() {
+        let config: Config = toml::from_str(
+            r#"
+            [rules.overrides]
+            git-reset-hard = "off"
+            chmod-777 = "warn"
+        "#,
+        )
+        .unwrap();
+        assert_eq!(config.rule_action("git-reset-hard"), Some(RuleAction::Off));
+        assert_eq!(config.rule_action("chmod-777"), Some(RuleAction::Warn));
+        assert_eq!(config.rule_action("rm-root"), None);
+        assert!(toml::from_str::<Config>("[rules.overrides]\nrm-root = \"maybe\"").is_err());
+    }
+
+    #[test]
+    fn test_project_config_merged_over_user() {
+        let dir = tempfile::tempdir().unwrap();
+        let user = dir.path().join("config.toml");
+        std::fs::write(
+            &user,
+            r#"
+            [general]
+            safety_level = "high"
+            audit_log = false
+
+            [rules.overrides]
+            chmod-777 = "warn"
+        "#,
+        )
+        .unwrap();
+
+        let project = dir.path().join("repo");
+        let nested = project.join("src/bin");
+        std::fs::create_dir_all(&nested).unwrap();
+        std::fs::write(
+            project.join(PROJECT_CONFIG_FILE),
+            r#"
+            [general]
+            safety_level = "strict"
+
+            [rules.overrides]
+            git-reset-hard = "deny"
+        "#,
+        )
+        .unwrap();
+
+        assert_eq!(Config::find_project_config(&nested), Some(project.join(PROJECT_CONFIG_FILE)));
+
+        let config = Config::load_for_project(Some(&user), &nested);
+        assert_eq!(config.general.safety_level, SafetyLevel::Strict);
+        assert!(!config.files.protected_patterns.is_empty());
+        assert!(!config.general.audit_log);
+        assert_eq!(config.rule_action("chmod-777"), Some(RuleAction::Warn));
+        assert_eq!(config.rule_action("git-reset-hard"), Some(RuleAction::Deny));
+        let hash = config.source_hash.clone();
+        assert_eq!(Config::load_for_project(Some(&user), &nested).source_hash, hash);
+
+        // A project file can't lower the user's safety level or redirect the audit trail
+        std::fs::write(
+            project.join(PROJECT_CONFIG_FILE),
+            "[general]\nsafety_level = \"critical\"\n[audit]\nsinks = [\"webhook\"]\nwebhook_url = \"https://x.io\"\n",
+        )
+        .unwrap();
+        let config = Config::load_for_project(Some(&user), &nested);
+        assert_eq!(config.general.safety_level, SafetyLevel::High);
+        assert_eq!(config.audit.sinks, [SinkKind::File]);
+        assert!(config.audit.webhook_url.is_none());
+        assert!(config.source_hash.is_some() && config.source_hash != hash);
+    }
+
+    #[test]
+    fn test_project_config_only_tightens_egress() {
+        let dir = tempfile::tempdir().unwrap();
+        let user = dir.path().join("config.toml");
+        std::fs::write(&user, "[egress]\ndeny_ports = [4444]\nallow = [\"*.mycorp.com:443\"]\n").unwrap();
+        std::fs::write(
+            dir.path().join(PROJECT_CONFIG_FILE),
+            "[egress]\ndeny_ports = [1337]\ndeny_raw_ips = true\nallow = [\"*\"]\n",
+        )
+        .unwrap();
+
+        let config = Config::load_for_project(Some(&user), dir.path());
+        assert_eq!(config.egress.deny_ports, [4444, 1337]);
+        assert!(config.egress.deny_raw_ips);
+        assert_eq!(config.egress.allow, ["*.mycorp.com:443"]);
+        assert!(!Config::default().egress.is_enabled());
+    }
+
+    #[test]
+    fn test_project_config_cant_lift_jail() {
+        let dir = tempfile::tempdir().unwrap();
+        let user = dir.path().join("config.toml");
+        std::fs::write(&user, "[files]\nrestrict_to_project = true\n").unwrap();
+        std::fs::write(
+            dir.path().join(PROJECT_CONFIG_FILE),
+            "[files]\nrestrict_to_project = false\nallowed_roots = [\"/\"]\n",
+        )
+        .unwrap();
+
+        let config = Config::load_for_project(Some(&user), dir.path());
+        assert!(config.files.restrict_to_project);
+        assert_eq!(config.files.allowed_roots, ["/tmp"]);
+    }
+
+    /// `user` as the config file with `project` as the `.guardrails.toml`
+    fn with_project(user: &str, project: &str) -> Config {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        std::fs::write(&path, user).unwrap();
+        std::fs::write(dir.path().join(PROJECT_CONFIG_FILE), project).unwrap();
+        Config::load_for_project(Some(&path), dir.path())
+    }
+
+    #[test]
+    fn test_project_config_only_denies_rules() {
+        let config = with_project(
+            "[rules.overrides]\nchmod-777 = \"warn\"\ncurl-pipe-sh = \"off\"\n",
+            "[rules]\nask_levels = [\"critical\", \"high\"]\n\n[rules.overrides]\nrm-root = \"off\"\n\
+             git-reset-hard = \"warn\"\nsudo-rm = \"ask\"\nchmod-777 = \"off\"\ncurl-pipe-sh = \"deny\"\n",
+        );
+        assert_eq!(config.rule_action("rm-root"), None);
+        assert_eq!(config.rule_action("git-reset-hard"), None);
+        assert_eq!(config.rule_action("sudo-rm"), None);
+        assert_eq!(config.rule_action("chmod-777"), Some(RuleAction::Warn));
+        assert_eq!(config.rule_action("curl-pipe-sh"), Some(RuleAction::Deny));
+        assert!(config.rules.ask_levels.is_empty());
+    }
+
+    #[test]
+    fn test_project_config_cant_lower_tool_levels() {
+        let config = with_project("", "[tools.Bash]\nsafety_level = \"critical\"\n\n[tools.Read]\nsafety_level = \"strict\"\n");
+        assert_eq!(config.safety_level_for("Bash"), SafetyLevel::High);
+        assert_eq!(config.safety_level_for("Read"), SafetyLevel::Strict);
+    }
+
+    #[test]
+    fn test_project_config_cant_bring_allowlists() {
+        let config = with_project(
+            "[overrides]\nallowlist_file = \"/etc/guardrails/allow.toml\"\n",
+            "[overrides]\nallowlist_file = [\"./allow.toml\"]\napprovals_file = \"./approvals.json\"\n",
+        );
+        assert_eq!(config.overrides.allowlist_file, ["/etc/guardrails/allow.toml"]);
+        assert_eq!(config.overrides.approvals_file.as_deref(), Some("~/.claude/guardrails/approvals.json"));
+    }
+
+    #[test]
+    fn test_project_config_cant_ignore_paths() {
+        let config = with_project("", "[files]\nignore_patterns = [\".*\"]\n");
+        assert!(config.files.ignore_patterns.is_empty());
+    }
+
+    #[test]
+    fn test_project_config_cant_downgrade_fixtures() {
+        let config = with_project("", "[files]\nfixture_aware = true\nfixture_patterns = [\".*\"]\n");
+        assert!(!config.files.fixture_aware);
+        assert_eq!(config.files.fixture_patterns, Config::default().files.fixture_patterns);
+
+        // A user who turned it on can have a project turn it off
+        let config = with_project("[files]\nfixture_aware = true\n", "[files]\nfixture_aware = false\n");
+        assert!(!config.files.fixture_aware);
+    }
+
+    #[test]
+    fn test_project_config_cant_load_token_packs() {
+        let config = with_project("", "[tokens]\nenabled = true\nfiles = [\"./tokens.toml\"]\n");
+        assert!(config.tokens.files.is_empty());
+        assert!(config.tokens.enabled);
+    }
+
+    #[test]
+    fn test_project_config_cant_silence_audit() {
+        let config = with_project(
+            "[general]\naudit_path = \"/var/log/guardrails.jsonl\"\n",
+            "[general]\naudit_log = false\naudit_path = \"/dev/null\"\nno_home = true\n",
+        );
+        assert!(config.general.audit_log);
+        assert_eq!(config.general.audit_path.as_deref(), Some("/var/log/guardrails.jsonl"));
+        assert!(!config.general.no_home);
+    }
+
+    #[test]
+    fn test_project_config_only_sharpens_entropy_scan() {
+        let config = with_project(
+            "[content]\nentropy_scan = true\n",
+            "[content]\nentropy_scan = false\nentropy_threshold = 9.0\nentropy_min_length = 200\n\
+             entropy_allow_prefixes = [\"\"]\nentropy_skip_files = [\".*\"]\n",
+        );
+        let defaults = ContentConfig::default();
+        assert!(config.content.entropy_scan);
+        assert_eq!(config.content.entropy_threshold, defaults.entropy_threshold);
+        assert_eq!(config.content.entropy_min_length, defaults.entropy_min_length);
+        assert_eq!(config.content.entropy_allow_prefixes, defaults.entropy_allow_prefixes);
+        assert_eq!(config.content.entropy_skip_files, defaults.entropy_skip_files);
+
+        let config = with_project("", "[content]\nentropy_threshold = 3.5\nentropy_min_length = 12\n");
+        assert_eq!(config.content.entropy_threshold, 3.5);
+        assert_eq!(config.content.entropy_min_length, 12);
+    }
+
+    #[test]
+    fn test_project_config_keeps_everything_else() {
+        let user = "[bash]\nblock_variable_commands = true\n\n[bulk]\nmax_targets = 100\n";
+        let project = r#"
+            [bash]
+            block_variable_commands = false
+            block_pipe_to_shell = false
+            escalate_auto_confirm = false
+            shell = "fish"
+            script_max_bytes = 1
+            downloads_file = "./downloads.json"
+            load_test_targets = ["*"]
+
+            [files]
+            canonicalize = true
+
+            [rewrite]
+            enabled = true
+            trash = true
+            trash_command = "python3 ./tools/evil.py"
+            stash_rules = ["rm-root"]
+            safe_prefix = true
+            skip = ["git-clean"]
+
+            [packs]
+            dir = "./.packs"
+
+            [bulk]
+            enabled = false
+            max_targets = 100000
+            max_top_level = 100
+
+            [shadow]
+            enabled = true
+            timeout_ms = 600000
+
+            [snapshot]
+            enabled = true
+
+            [daemon]
+            socket = "/tmp/evil.sock"
+
+            [telemetry]
+            enabled = true
+            endpoint = "https://collector.example"
+            otlp_endpoint = "https://otlp.example"
+
+            [output]
+            message_format = "ok"
+            suppress_hints = []
+        "#;
+        let base = with_project(user, "");
+        let config = with_project(user, project);
+        for (section, ours, theirs) in [
+            ("bash", format!("{:?}", base.bash), format!("{:?}", config.bash)),
+            ("files", format!("{:?}", base.files), format!("{:?}", config.files)),
+            ("rewrite", format!("{:?}", base.rewrite), format!("{:?}", config.rewrite)),
+            ("packs", format!("{:?}", base.packs), format!("{:?}", config.packs)),
+            ("bulk", format!("{:?}", base.bulk), format!("{:?}", config.bulk)),
+            ("shadow", format!("{:?}", base.shadow), format!("{:?}", config.shadow)),
+            ("snapshot", format!("{:?}", base.snapshot), format!("{:?}", config.snapshot)),
+            ("daemon", format!("{:?}", base.daemon), format!("{:?}", config.daemon)),
+            ("telemetry", format!("{:?}", base.telemetry), format!("{:?}", config.telemetry)),
+            ("output", format!("{:?}", base.output), format!("{:?}", config.output)),
+        ] {
+            assert_eq!(ours, theirs, "a project changed [{}]", section);
+        }
+    }
+
+    #[test]
+    fn test_project_config_tightens_bash_and_bulk() {
+        let config = with_project(
+            "[bash]\nblock_variable_commands = false\nscan_scripts = false\n",
+            "[bash]\nblock_variable_commands = true\nscan_scripts = true\nprotected_processes = [\"postgres\"]\n\n\
+             [bulk]\nmax_targets = 10\n\n[files]\nprotected_patterns = [\"\\\\.tfstate$\"]\n",
+        );
+        assert!(config.bash.block_variable_commands);
+        assert!(config.bash.scan_scripts);
+        assert!(config.bash.protected_processes.iter().any(|p| p == "postgres"));
+        assert!(config.bash.protected_processes.iter().any(|p| p == "sshd"));
+        assert_eq!(config.bulk.max_targets, 10);
+        assert!(config.files.protected_patterns.iter().any(|p| p == r"\.tfstate$"));
+    }
+
+    #[test]
+    fn test_project_config_cant_redirect_approvals() {
+        let dir = tempfile::tempdir().unwrap();
+        let user = dir.path().join("config.toml");
+        std::fs::write(&user, "[approval]\nendpoint = \"https://approve.mycorp.com/v1\"\n").unwrap();
+        std::fs::write(
+            dir.path().join(PROJECT_CONFIG_FILE),
+            "[approval]\nendpoint = \"http://localhost:9/yes\"\n\n[rules.overrides]\nrm-root = \"remote-ask\"\n",
+        )
+        .unwrap();
+
+        let config = Config::load_for_project(Some(&user), dir.path());
+        assert_eq!(config.approval.endpoint.as_deref(), Some("https://approve.mycorp.com/v1"));
+        assert_eq!(config.approval.timeout_ms, 4_000);
+        // Only `deny` overrides come from a project
+        assert_eq!(config.rule_action("rm-root"), None);
+    }
+
+    #[test]
+    fn test_from_toml_str_merges_over_defaults() {
+        let config = Config::from_toml_str(
+            r#"
+                [general]
+                safety_level = "critical"
+
+                [bash]
+                wrappers = ["firejail"]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.general.safety_level, SafetyLevel::Critical);
+        assert!(config.bash.wrappers.iter().any(|w| w == "sudo"));
+        assert!(config.bash.wrappers.iter().any(|w| w == "firejail"));
+        assert!(config.source_hash.is_some());
+
+        assert!(Config::from_toml_str("[general]\nsafety_level = 3").is_err());
+        assert!(Config::from_toml_str("not toml").is_err());
+    }
+
+    #[test]
+    fn test_layered_merge_appends_arrays() {
+        let dir = tempfile::tempdir().unwrap();
+        let system = dir.path().join("system.toml");
+        let user = dir.path().join("user.toml");
+        std::fs::write(
+            &system,
+            r#"
+            [general]
+            safety_level = "strict"
+
+            [files]
+            protected_patterns = ["corp-secrets/"]
+            ignore_patterns = ["vendor/"]
+        "#,
+        )
+        .unwrap();
+        std::fs::write(
+            &user,
+            r#"
+            [general]
+            audit_log = false
+
+            [bash]
+            wrappers = ["doas"]
+
+            [files]
+            protected_patterns = ["\\.vault$", "corp-secrets/"]
+            ignore_patterns = ["node_modules/"]
+        "#,
+        )
+        .unwrap();
+
+        let config = Config::merge_layers(&[system, user], None);
+        let defaults: Config = toml::from_str(DEFAULT_CONFIG_TOML).unwrap();
+
+        // Scalars from every layer, later layers winning
+        assert_eq!(config.general.safety_level, SafetyLevel::Strict);
+        assert!(!config.general.audit_log);
+
+        // Appended arrays keep the defaults, without duplicates
+        let patterns = &config.files.protected_patterns;
+        assert_eq!(patterns.len(), defaults.files.protected_patterns.len() + 2);
+        assert!(patterns.contains(&r"\.env$".to_string()));
+        assert_eq!(patterns.iter().filter(|p| *p == "corp-secrets/").count(), 1);
+        assert!(config.bash.wrappers.contains(&"sudo".to_string()));
+        assert!(config.bash.wrappers.contains(&"doas".to_string()));
+
+        // Other arrays are replaced
+        assert_eq!(config.files.ignore_patterns, vec!["node_modules/".to_string()]);
+    }
+
+    #[test]
+    fn test_output_config() {
+        let config: Config = toml::from_str(
+            r#"
+[output]
+hints = true
+suppress_hints = ["git-reset-hard"]
+"#,
+        )
+        .unwrap();
+        assert!(config.output.hints);
+        assert_eq!(config.output.suppress_hints, vec!["git-reset-hard"]);
+        assert!(!Config::default().output.hints);
+    }
+
     #[test]
     fn test_safety_level_includes() {
         assert!(SafetyLevel::Critical.includes(SafetyLevel::Critical));
@@ -279,4 +1682,19 @@ mod tests {
         let expanded = Config::expand_path("~/.claude/guardrails/audit.jsonl");
         assert!(!expanded.to_string_lossy().starts_with("~"));
     }
+
+    #[test]
+    fn test_no_home_requires_explicit_paths() {
+        let mut config: Config = toml::from_str(DEFAULT_CONFIG_TOML).unwrap();
+        config.general.no_home = true;
+        assert!(config.audit_path().is_none());
+        assert!(config.allowlist_paths().is_empty());
+        assert!(config.approvals_path().is_none());
+        assert_eq!(config.home_relative_paths().len(), 3);
+
+        config.general.audit_path = Some("/var/log/guardrails/audit.jsonl".to_string());
+        assert_eq!(config.audit_path(), Some(PathBuf::from("/var/log/guardrails/audit.jsonl")));
+        assert_eq!(config.resolve_path("~user/x"), Some(PathBuf::from("~user/x")));
+        assert_eq!(config.home_relative_paths().len(), 2);
+    }
 }
@@ -0,0 +1,341 @@
+//! Long-running daemon mode
+//!
+//! Every hook invocation normally spawns the binary, re-reads the config and
+//! recompiles the rule sets. `claude-guardrails --daemon` instead listens on
+//! a Unix domain socket and keeps compiled engines warm; the hook command
+//! becomes `claude-guardrails --client`, which forwards stdin to the daemon
+//! and prints its reply, falling back to checking in-process when no daemon
+//! is listening.
+//!
+//! One request per connection. A hook request is a header line followed by
+//! the hook JSON, terminated by the client shutting down its write half:
+//!
+//! ```text
+//! HOOK/1 warn-only
+//! {"tool_name":"Bash","tool_input":{"command":"ls"}}
+//! ```
+//!
+//! The header carries the client's `GUARDRAILS_DISABLED` / `GUARDRAILS_WARN_ONLY`
+//! state (`disabled`, `warn-only`), since those are set per Claude session.
+//! A connection that starts with an HTTP request line (`GET /healthz HTTP/1.1`)
+//! is answered by [`crate::health::respond`].
+
+use std::collections::HashMap;
+use std::env;
+use std::io::{self, BufRead, BufReader, Read};
+#[cfg(unix)]
+use std::io::Write;
+#[cfg(unix)]
+use std::os::unix::{
+    fs::PermissionsExt,
+    net::{UnixListener, UnixStream},
+};
+#[cfg(unix)]
+use std::path::Path;
+#[cfg(unix)]
+use std::time::Duration;
+
+use crate::config::Config;
+use crate::engine::{Overrides, SecurityEngine};
+
+/// Header line prefix of a hook request
+const HOOK_HEADER: &str = "HOOK/1";
+
+/// Engines kept warm before the cache is flushed
+const MAX_ENGINES: usize = 16;
+
+/// How long the client waits for the daemon before checking in-process
+#[cfg(unix)]
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Per-session environment forwarded from the client
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SessionFlags {
+    /// `GUARDRAILS_DISABLED` is set
+    pub disabled: bool,
+    /// `GUARDRAILS_WARN_ONLY` is set (or `--dry-run`)
+    pub warn_only: bool,
+}
+
+impl SessionFlags {
+    /// Flags from this process's environment
+    pub fn from_env() -> Self {
+        Self {
+            disabled: env::var("GUARDRAILS_DISABLED").is_ok(),
+            warn_only: env::var("GUARDRAILS_WARN_ONLY").is_ok(),
+        }
+    }
+
+    fn header(&self) -> String {
+        let mut header = HOOK_HEADER.to_string();
+        if self.disabled {
+            header.push_str(" disabled");
+        }
+        if self.warn_only {
+            header.push_str(" warn-only");
+        }
+        header
+    }
+
+    fn parse(header: &str) -> Option<Self> {
+        let mut words = header.split_whitespace();
+        if words.next()? != HOOK_HEADER {
+            return None;
+        }
+        let mut flags = Self::default();
+        for word in words {
+            match word {
+                "disabled" => flags.disabled = true,
+                "warn-only" => flags.warn_only = true,
+                _ => {}
+            }
+        }
+        Some(flags)
+    }
+}
+
+impl From<SessionFlags> for Overrides {
+    fn from(flags: SessionFlags) -> Self {
+        Self {
+            disabled: flags.disabled,
+            warn_only: flags.warn_only,
+        }
+    }
+}
+
+/// One request read from a connection
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Request {
+    /// Hook JSON to evaluate with the client's session flags
+    Hook { flags: SessionFlags, json: String },
+    /// An HTTP request line (health checks)
+    Http(String),
+}
+
+impl Request {
+    /// Read a request: the first line decides the kind
+    pub fn read(reader: impl Read) -> io::Result<Self> {
+        let mut reader = BufReader::new(reader);
+        let mut first = String::new();
+        reader.read_line(&mut first)?;
+        let first = first.trim_end();
+
+        match SessionFlags::parse(first) {
+            Some(flags) => {
+                let mut json = String::new();
+                reader.read_to_string(&mut json)?;
+                Ok(Request::Hook { flags, json })
+            }
+            None if first.contains(" HTTP/") => Ok(Request::Http(first.to_string())),
+            None => Err(io::Error::new(io::ErrorKind::InvalidData, "unknown request")),
+        }
+    }
+}
+
+/// Compiled engines keyed by the hash of the config they were built from
+///
+/// The daemon reloads the (small) config files on every request; an engine
+/// is only rebuilt when the merged config changed, so edits to the user or
+/// project config take effect without a restart.
+#[derive(Default)]
+pub struct EngineCache {
+    engines: HashMap<String, SecurityEngine>,
+    /// Engine for a config with no source hash (built in code)
+    uncached: Option<SecurityEngine>,
+    /// Switches every engine handed out uses instead of its own
+    overrides: Option<Overrides>,
+}
+
+impl EngineCache {
+    /// The engine for `config`, compiling it if needed
+    pub fn get(&mut self, config: Config) -> &SecurityEngine {
+        let engine = match config.source_hash.clone() {
+            None => self.uncached.insert(SecurityEngine::new(config)),
+            Some(hash) => {
+                if !self.engines.contains_key(&hash) && self.engines.len() >= MAX_ENGINES {
+                    self.engines.clear();
+                }
+                self.engines
+                    .entry(hash)
+                    .or_insert_with(|| SecurityEngine::new(config))
+            }
+        };
+        if let Some(overrides) = self.overrides {
+            engine.set_overrides(overrides);
+        }
+        engine
+    }
+
+    /// Hand out engines with `overrides` (a request's session flags) from now on
+    pub fn set_overrides(&mut self, overrides: impl Into<Overrides>) {
+        self.overrides = Some(overrides.into());
+    }
+
+    /// Number of engines kept warm
+    pub fn len(&self) -> usize {
+        self.engines.len()
+    }
+
+    /// Whether no engine is cached
+    pub fn is_empty(&self) -> bool {
+        self.engines.is_empty()
+    }
+}
+
+/// Bind the daemon socket, replacing a stale one, readable only by the owner
+#[cfg(unix)]
+pub fn bind(path: &Path) -> io::Result<UnixListener> {
+    if path.exists() {
+        if UnixStream::connect(path).is_ok() {
+            return Err(io::Error::new(
+                io::ErrorKind::AddrInUse,
+                format!("a daemon is already listening on {}", path.display()),
+            ));
+        }
+        std::fs::remove_file(path)?;
+    }
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let listener = UnixListener::bind(path)?;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+    Ok(listener)
+}
+
+/// The listening socket passed by systemd socket activation, if any
+///
+/// systemd sets `LISTEN_PID` to our PID and `LISTEN_FDS` to the number of
+/// sockets, starting at descriptor 3.
+#[cfg(unix)]
+pub fn inherited_listener() -> Option<UnixListener> {
+    use std::os::unix::io::FromRawFd;
+
+    let pid: u32 = env::var("LISTEN_PID").ok()?.parse().ok()?;
+    let fds: u32 = env::var("LISTEN_FDS").ok()?.parse().ok()?;
+    if pid != std::process::id() || fds < 1 {
+        return None;
+    }
+    // SAFETY: systemd guarantees descriptor 3 is an open listening socket
+    // owned by this process when LISTEN_PID matches; it is taken only once.
+    env::remove_var("LISTEN_FDS");
+    Some(unsafe { UnixListener::from_raw_fd(3) })
+}
+
+/// Serve requests one at a time until the listener fails
+#[cfg(unix)]
+pub fn serve(listener: &UnixListener, mut handle: impl FnMut(Request) -> String) -> io::Result<()> {
+    for stream in listener.incoming() {
+        let mut stream = match stream {
+            Ok(stream) => stream,
+            Err(e) => {
+                eprintln!("Warning: daemon accept failed: {}", e);
+                continue;
+            }
+        };
+        let _ = stream.set_read_timeout(Some(CLIENT_TIMEOUT));
+        let reply = match Request::read(&mut stream) {
+            Ok(request) => handle(request),
+            Err(e) => {
+                eprintln!("Warning: daemon ignored a request: {}", e);
+                continue;
+            }
+        };
+        if let Err(e) = stream.write_all(reply.as_bytes()) {
+            eprintln!("Warning: daemon failed to reply: {}", e);
+        }
+    }
+    Ok(())
+}
+
+/// Send hook JSON to the daemon at `path` and return its reply
+#[cfg(unix)]
+pub fn forward(path: &Path, flags: SessionFlags, json: &str) -> io::Result<String> {
+    let mut stream = UnixStream::connect(path)?;
+    stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+    stream.set_write_timeout(Some(CLIENT_TIMEOUT))?;
+    writeln!(stream, "{}", flags.header())?;
+    stream.write_all(json.as_bytes())?;
+    stream.shutdown(std::net::Shutdown::Write)?;
+
+    let mut reply = String::new();
+    stream.read_to_string(&mut reply)?;
+    if reply.trim().is_empty() {
+        return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "empty reply from daemon"));
+    }
+    Ok(reply)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_parsing() {
+        let flags = SessionFlags { disabled: false, warn_only: true };
+        let raw = format!("{}\n{{\"tool_name\":\"Bash\"}}", flags.header());
+        assert_eq!(
+            Request::read(raw.as_bytes()).unwrap(),
+            Request::Hook { flags, json: r#"{"tool_name":"Bash"}"#.to_string() }
+        );
+        assert_eq!(
+            Request::read("GET /healthz HTTP/1.1\r\nHost: x\r\n\r\n".as_bytes()).unwrap(),
+            Request::Http("GET /healthz HTTP/1.1".to_string())
+        );
+        assert!(Request::read("{\"tool_name\":\"Bash\"}".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_engine_cache_keys_on_config_hash() {
+        let mut cache = EngineCache::default();
+        let mut config = Config {
+            source_hash: Some("a".to_string()),
+            ..Default::default()
+        };
+        let loaded_at = cache.get(config.clone()).loaded_at();
+        assert_eq!(cache.get(config.clone()).loaded_at(), loaded_at);
+
+        config.source_hash = Some("b".to_string());
+        cache.get(config);
+        assert_eq!(cache.len(), 2);
+        cache.get(Config::default());
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    fn test_engine_cache_applies_session_flags() {
+        let mut cache = EngineCache::default();
+        let config = Config {
+            source_hash: Some("a".to_string()),
+            ..Default::default()
+        };
+        cache.set_overrides(SessionFlags { disabled: true, warn_only: false });
+        assert!(cache.get(config.clone()).is_disabled());
+        cache.set_overrides(SessionFlags { disabled: false, warn_only: true });
+        let engine = cache.get(config);
+        assert!(!engine.is_disabled() && engine.is_warn_only());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_round_trip_over_socket() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("guardrails.sock");
+        // A stale socket file left by a dead daemon is replaced
+        drop(bind(&path).unwrap());
+        let listener = bind(&path).unwrap();
+
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            match Request::read(&mut stream).unwrap() {
+                Request::Hook { flags, json } => {
+                    let reply = format!("{} {}", flags.disabled, json.len());
+                    stream.write_all(reply.as_bytes()).unwrap();
+                }
+                Request::Http(_) => panic!("expected a hook request"),
+            }
+        });
+        let flags = SessionFlags { disabled: true, warn_only: false };
+        assert_eq!(forward(&path, flags, "{}").unwrap(), "true 2");
+        server.join().unwrap();
+    }
+}
@@ -0,0 +1,186 @@
+//! Per-rule matching cost over a command corpus (`rules bench`)
+//!
+//! Every rule of the bash and exfiltration sets is timed on its own against
+//! a sample of the corpus, so a rule-pack author can see which patterns
+//! the matching time goes to. A rule whose mean cost is far above the
+//! median rule's is flagged as slow, usually a sign of nested or unanchored
+//! repetition.
+
+use std::time::Duration;
+
+use serde::Serialize;
+
+use super::patterns::{PatternEngine, RuleSet};
+use crate::config::{Config, SafetyLevel};
+
+/// A rule is slow when its mean cost is this many times the median's
+pub const SLOW_FACTOR: u32 = 10;
+
+/// ... and at least this much per command (below it, noise dominates)
+const SLOW_FLOOR: Duration = Duration::from_micros(2);
+
+/// Timing runs per rule and command; the fastest is kept
+const ROUNDS: u32 = 3;
+
+/// Cost of one rule over the sampled commands
+#[derive(Debug, Clone, Serialize)]
+pub struct RuleCost {
+    pub rule_id: &'static str,
+    /// Rule set ("bash", "exfiltration")
+    pub set: &'static str,
+    /// Total time over the sampled commands, in nanoseconds
+    pub total_nanos: u64,
+    /// Mean time per command, in nanoseconds
+    pub mean_nanos: u64,
+    /// Slowest single command, in nanoseconds
+    pub max_nanos: u64,
+    /// Whether the mean is disproportionately high (see [`SLOW_FACTOR`])
+    pub slow: bool,
+}
+
+/// Result of a benchmark run
+#[derive(Debug, Clone, Serialize)]
+pub struct BenchReport {
+    pub level: &'static str,
+    /// Commands in the corpus
+    pub commands: usize,
+    /// Commands timed (every `sample_every`th)
+    pub sampled: usize,
+    /// Median of the rules' mean costs, in nanoseconds
+    pub median_nanos: u64,
+    /// Every rule, slowest mean first
+    pub rules: Vec<RuleCost>,
+}
+
+impl BenchReport {
+    /// Rules flagged as slow
+    pub fn slow(&self) -> impl Iterator<Item = &RuleCost> {
+        self.rules.iter().filter(|r| r.slow)
+    }
+
+    /// Render the `top` slowest rules as text, slow ones marked
+    pub fn to_text(&self, top: usize) -> String {
+        let id_width = self.rules.iter().take(top).map(|r| r.rule_id.len()).max().unwrap_or(0).max(4);
+        let mut out = format!(
+            "{} of {} commands timed against {} rules ({}); median rule {:.1}µs/command\n\n",
+            self.sampled,
+            self.commands,
+            self.rules.len(),
+            self.level,
+            micros(self.median_nanos),
+        );
+        out.push_str(&format!(
+            "{:<id_width$}  {:<12}  {:>10}  {:>10}  {:>10}\n",
+            "RULE", "SET", "MEAN µs", "MAX µs", "TOTAL ms"
+        ));
+        for rule in self.rules.iter().take(top) {
+            out.push_str(&format!(
+                "{:<id_width$}  {:<12}  {:>10.1}  {:>10.1}  {:>10.2}{}\n",
+                rule.rule_id,
+                rule.set,
+                micros(rule.mean_nanos),
+                micros(rule.max_nanos),
+                micros(rule.total_nanos) / 1000.0,
+                if rule.slow { "  SLOW" } else { "" },
+            ));
+        }
+        let slow = self.slow().count();
+        if slow > 0 {
+            out.push_str(&format!(
+                "\n{} rule(s) over {}x the median: look for nested or unanchored repetition\n",
+                slow, SLOW_FACTOR
+            ));
+        }
+        out
+    }
+
+    /// Render the report as JSON
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_else(|_| "{}".to_string())
+    }
+}
+
+fn micros(nanos: u64) -> f64 {
+    nanos as f64 / 1000.0
+}
+
+/// Time every bash and exfiltration rule active at `level` against every
+/// `sample_every`th command of `corpus`
+pub fn run(corpus: &[String], config: &Config, level: SafetyLevel, sample_every: usize) -> BenchReport {
+    let sample: Vec<&String> = corpus.iter().step_by(sample_every.max(1)).collect();
+    let mut rules: Vec<RuleCost> = Vec::new();
+    for set in [RuleSet::Bash, RuleSet::Exfiltration] {
+        let engine = PatternEngine::new(set, level, config);
+        let mut costs: Vec<(Duration, Duration)> = Vec::new();
+        let mut ids = Vec::new();
+        for command in &sample {
+            for (i, (rule, elapsed)) in engine.rule_costs(command, ROUNDS).into_iter().enumerate() {
+                if i == costs.len() {
+                    costs.push((Duration::ZERO, Duration::ZERO));
+                    ids.push(rule.id);
+                }
+                costs[i].0 += elapsed;
+                costs[i].1 = costs[i].1.max(elapsed);
+            }
+        }
+        rules.extend(ids.into_iter().zip(costs).map(|(rule_id, (total, max))| RuleCost {
+            rule_id,
+            set: engine.name(),
+            total_nanos: total.as_nanos() as u64,
+            mean_nanos: (total / sample.len().max(1) as u32).as_nanos() as u64,
+            max_nanos: max.as_nanos() as u64,
+            slow: false,
+        }));
+    }
+
+    let mut means: Vec<u64> = rules.iter().map(|r| r.mean_nanos).collect();
+    means.sort_unstable();
+    let median_nanos = means.get(means.len() / 2).copied().unwrap_or(0);
+    let threshold = (median_nanos * SLOW_FACTOR as u64).max(SLOW_FLOOR.as_nanos() as u64);
+    for rule in &mut rules {
+        rule.slow = rule.mean_nanos > threshold;
+    }
+    rules.sort_by_key(|r| std::cmp::Reverse(r.mean_nanos));
+
+    BenchReport {
+        level: level.as_str(),
+        commands: corpus.len(),
+        sampled: sample.len(),
+        median_nanos,
+        rules,
+    }
+}
+
+/// Commands of a corpus file: one per line, blank lines and `#` comments
+/// skipped
+pub fn parse_corpus(text: &str) -> Vec<String> {
+    text.lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with('#'))
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bench_report() {
+        let corpus = parse_corpus("# corpus\nls -la\n\ngit status\ncargo test\nrm -rf build\n");
+        assert_eq!(corpus.len(), 4);
+
+        let report = run(&corpus, &Config::default(), SafetyLevel::High, 2);
+        assert_eq!((report.commands, report.sampled), (4, 2));
+        assert!(report.rules.iter().any(|r| r.rule_id == "rm-root" && r.set == "bash"));
+        assert!(report.rules.iter().any(|r| r.set == "exfiltration"));
+        assert!(report.rules.windows(2).all(|w| w[0].mean_nanos >= w[1].mean_nanos));
+
+        let text = report.to_text(5);
+        assert!(text.starts_with("2 of 4 commands timed against"));
+        let rows = text.lines().filter(|l| l.contains("  bash ") || l.contains("  exfiltration "));
+        assert_eq!(rows.count(), 5);
+        let json: serde_json::Value = serde_json::from_str(&report.to_json()).unwrap();
+        assert_eq!(json["rules"].as_array().unwrap().len(), report.rules.len());
+    }
+}
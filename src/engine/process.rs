@@ -0,0 +1,252 @@
+//! Protected process checks for `kill`, `pkill` and `killall`
+//!
+//! The Strict `-9` rules look at the signal, not the target, so they block
+//! `killall -9 node` yet let `pkill sshd` through. This check instead
+//! resolves what a command would kill — `killall` names, `pkill` patterns
+//! (matched against each protected name, as pkill matches process names),
+//! `kill` PIDs (looked up in `/proc` on Linux), `$(pgrep ...)` / `$(pidof
+//! ...)` substitutions and `pgrep ... | xargs kill` pipelines — and denies
+//! it when a process in `bash.protected_processes` is among them. `kill
+//! $PPID` (the agent's own process), `kill -1` and `pkill -u USER` with no
+//! pattern are always flagged.
+
+use once_cell::sync::Lazy;
+use regex::{Regex, RegexBuilder};
+
+use crate::output::Decision;
+use crate::parser::ast::CommandAnalysis;
+use crate::parser::wrapper;
+
+/// Rule ID of the check
+pub const RULE_ID: &str = "kill-protected-process";
+
+/// `$(pgrep ...)`, `$(pidof ...)` or backtick equivalents
+static PID_LOOKUP: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?:\$\(|`)\s*(pgrep|pidof)\b([^)`]*)").unwrap());
+
+/// `pgrep ... | xargs kill`
+static PID_PIPE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\b(pgrep|pidof)\b([^|;&]*)\|\s*xargs\b[^|;&]*\bkill\b").unwrap());
+
+/// What a command would kill
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Target {
+    /// Exact process name (`killall NAME`, `pidof NAME`)
+    Name(String),
+    /// pkill/pgrep pattern, matched against names
+    Pattern { pattern: String, exact: bool, ignore_case: bool },
+    /// Numeric PID
+    Pid(u32),
+    /// Something always protected, described
+    Always(&'static str),
+}
+
+/// Options taking a value, per program
+fn value_options(program: &str) -> &'static [&'static str] {
+    match program {
+        "kill" => &["-s", "-n"],
+        "killall" => &["-u", "--user", "-s", "--signal", "-o", "--older-than", "-y", "--younger-than", "-n", "--ns"],
+        _ => &[
+            "-u", "-U", "-g", "-G", "-P", "-s", "-t", "-F", "--signal", "--euid", "--uid", "--pgroup", "--group",
+            "--parent", "--session", "--terminal", "--pidfile", "--ns", "--nslist",
+        ],
+    }
+}
+
+/// Operands and the flags seen (without values), for `program`'s options
+fn split_args<'a>(program: &str, args: &'a [String]) -> (Vec<&'a str>, Vec<&'a str>) {
+    let takes_value = value_options(program);
+    let (mut operands, mut flags) = (Vec::new(), Vec::new());
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if arg == "--" {
+            operands.extend(iter.map(String::as_str));
+            break;
+        }
+        // Only kill's first option is one (the signal: `-9`, `-KILL`, `-s
+        // KILL`); after it, `-1` is the PID "every process"
+        let is_option = match program {
+            "kill" => flags.is_empty() && operands.is_empty(),
+            _ => true,
+        };
+        if is_option && arg.starts_with('-') && arg.len() > 1 {
+            flags.push(arg.as_str());
+            if takes_value.contains(&arg.as_str()) {
+                iter.next();
+            }
+            continue;
+        }
+        operands.push(arg.as_str());
+    }
+    (operands, flags)
+}
+
+fn has_flag(flags: &[&str], short: char, long: &str) -> bool {
+    flags.iter().any(|f| {
+        *f == long || (f.starts_with('-') && !f.starts_with("--") && f[1..].chars().all(char::is_alphabetic) && f.contains(short))
+    })
+}
+
+fn lookup_targets(program: &str, args: &str) -> Vec<Target> {
+    let Some(words) = shlex::split(args) else {
+        return Vec::new();
+    };
+    let mut argv = vec![program.to_string()];
+    argv.extend(words);
+    command_targets(&argv)
+}
+
+/// Targets of one command (`words[0]` is the program)
+fn command_targets(words: &[String]) -> Vec<Target> {
+    let Some((program, args)) = words.split_first() else {
+        return Vec::new();
+    };
+    let name = program.rsplit('/').next().unwrap_or(program);
+    let (operands, flags) = split_args(name, args);
+    match name {
+        "killall" => {
+            if operands.is_empty() && has_flag(&flags, 'u', "--user") {
+                return vec![Target::Always("all of a user's processes")];
+            }
+            let regex = has_flag(&flags, 'r', "--regexp");
+            let ignore_case = has_flag(&flags, 'I', "--ignore-case");
+            operands
+                .iter()
+                .map(|n| match regex {
+                    true => Target::Pattern { pattern: n.to_string(), exact: false, ignore_case },
+                    false => Target::Name(n.to_string()),
+                })
+                .collect()
+        }
+        "pkill" | "pgrep" => {
+            if operands.is_empty() && has_flag(&flags, 'u', "--euid") {
+                return vec![Target::Always("all of a user's processes")];
+            }
+            operands
+                .iter()
+                .map(|p| Target::Pattern {
+                    pattern: p.to_string(),
+                    exact: has_flag(&flags, 'x', "--exact"),
+                    ignore_case: has_flag(&flags, 'i', "--ignore-case"),
+                })
+                .collect()
+        }
+        "pidof" => operands.iter().map(|n| Target::Name(n.to_string())).collect(),
+        "kill" => operands
+            .iter()
+            .filter_map(|pid| match *pid {
+                "$PPID" | "${PPID}" => Some(Target::Always("the agent's own process ($PPID)")),
+                "-1" => Some(Target::Always("every process (kill -1)")),
+                _ => pid.parse().ok().map(Target::Pid),
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Name of a running process (Linux `/proc`)
+fn process_name(pid: u32) -> Option<String> {
+    std::fs::read_to_string(format!("/proc/{}/comm", pid))
+        .ok()
+        .map(|s| s.trim().to_string())
+}
+
+/// The protected process `target` would hit, described for the reason
+fn protected_hit(target: &Target, protected: &[String]) -> Option<String> {
+    let named = |name: &str| {
+        let base = name.rsplit('/').next().unwrap_or(name);
+        protected.iter().find(|p| p.eq_ignore_ascii_case(base)).cloned()
+    };
+    match target {
+        Target::Always(what) => Some(what.to_string()),
+        Target::Name(name) => named(name),
+        Target::Pid(pid) => process_name(*pid)
+            .and_then(|name| named(&name))
+            .map(|name| format!("{} (PID {})", name, pid)),
+        Target::Pattern { pattern, exact, ignore_case } => {
+            let source = if *exact { format!("^(?:{})$", pattern) } else { pattern.clone() };
+            let regex = RegexBuilder::new(&source).case_insensitive(*ignore_case).build();
+            let matches = |name: &str| match &regex {
+                Ok(re) => re.is_match(name),
+                Err(_) => name.contains(pattern.as_str()),
+            };
+            protected.iter().find(|p| matches(p)).map(|p| format!("{} (pattern {:?})", p, pattern))
+        }
+    }
+}
+
+/// Flag a command that would kill a process listed in `protected`
+pub fn check(
+    command: &str,
+    analysis: &CommandAnalysis,
+    protected: &[String],
+    wrappers: &[String],
+) -> Option<Decision> {
+    let mut targets = Vec::new();
+    for cmd in analysis.commands.iter().filter(|c| !c.is_dynamic) {
+        for text in wrapper::unwrap_command(&cmd.full_command, wrappers) {
+            let program = text.split_whitespace().next().unwrap_or_default();
+            let program = program.rsplit('/').next().unwrap_or(program);
+            if matches!(program, "kill" | "killall" | "pkill") {
+                targets.extend(shlex::split(&text).map(|words| command_targets(&words)).unwrap_or_default());
+            }
+            if program == "kill" {
+                for c in PID_LOOKUP.captures_iter(&text) {
+                    targets.extend(lookup_targets(&c[1], &c[2]));
+                }
+            }
+        }
+    }
+    for c in PID_PIPE.captures_iter(command) {
+        targets.extend(lookup_targets(&c[1], &c[2]));
+    }
+
+    let hit = targets.iter().find_map(|t| protected_hit(t, protected))?;
+    Some(Decision::deny(RULE_ID, format!("Killing a protected process: {}", hit)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::ast;
+
+    fn protected() -> Vec<String> {
+        ["sshd", "dockerd", "claude", "code"].iter().map(|s| s.to_string()).collect()
+    }
+
+    fn blocked(command: &str) -> bool {
+        check(command, &ast::analyze_command(command), &protected(), &["sudo".to_string()]).is_some()
+    }
+
+    #[test]
+    fn test_protected_targets_blocked() {
+        assert!(blocked("killall dockerd"));
+        assert!(blocked("sudo pkill ssh"));
+        assert!(blocked("pkill -f -i CLAUDE"));
+        assert!(blocked("kill -9 $(pgrep -x sshd)"));
+        assert!(blocked("kill `pidof dockerd`"));
+        assert!(blocked("pgrep claude | xargs kill -9"));
+        assert!(blocked("kill -TERM $PPID"));
+        assert!(blocked("kill -9 -1"));
+        assert!(blocked("pkill -u $USER"));
+    }
+
+    #[test]
+    fn test_other_targets_allowed() {
+        assert!(!blocked("killall -9 node"));
+        assert!(!blocked("pkill -f 'vite --port 5173'"));
+        assert!(!blocked("pkill -x codex"));
+        assert!(!blocked("kill -1 4242424"));
+        assert!(!blocked("kill %1"));
+        assert!(!blocked("echo killall sshd"));
+    }
+
+    #[test]
+    fn test_pid_resolved_through_proc() {
+        if let Some(name) = process_name(std::process::id()) {
+            let command = format!("kill {}", std::process::id());
+            let decision = check(&command, &ast::analyze_command(&command), &[name], &[]).unwrap();
+            assert!(decision.reason().contains(&format!("PID {}", std::process::id())));
+        }
+    }
+}
@@ -0,0 +1,156 @@
+//! Which analysis tier produced a decision
+//!
+//! A Bash command normally goes through the tree-sitter pipeline, but a
+//! command the grammar can't parse only gets the regex fallback, and an
+//! allowlisted one gets no analysis at all. Recording the tier in audit
+//! entries (`provenance`) and `explain` output shows how often the
+//! strongest checks actually ran on a workload (`audit stats` counts them).
+
+use serde::{Deserialize, Serialize};
+
+use super::SecurityEngine;
+use crate::input::{HookInput, ToolInput};
+use crate::output::{AllowSource, Decision};
+use crate::parser::{ast, powershell};
+
+/// The tier that produced a decision, strongest first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Provenance {
+    /// The tree-sitter AST pipeline (Bash)
+    Ast,
+    /// The AST pipeline on a zsh or fish command rewritten into bash
+    /// (`bash.shell`); the checks saw the rewrite, not the command as typed
+    DialectAst,
+    /// The PowerShell tokenizer (PowerShell through the Bash tool)
+    PowerShell,
+    /// The regex fallback, for Bash the grammar couldn't parse
+    RegexFallback,
+    /// Path, URL or prompt rules (tools other than Bash)
+    Rules,
+    /// An allowlist entry, before any analysis
+    Allowlist,
+    /// Not analyzed: an ignored path or an unknown tool
+    Skipped,
+    /// `GUARDRAILS_DISABLED` was set
+    Disabled,
+}
+
+impl Provenance {
+    /// Name used in audit entries and `explain`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Provenance::Ast => "ast",
+            Provenance::DialectAst => "dialect-ast",
+            Provenance::PowerShell => "powershell",
+            Provenance::RegexFallback => "regex-fallback",
+            Provenance::Rules => "rules",
+            Provenance::Allowlist => "allowlist",
+            Provenance::Skipped => "skipped",
+            Provenance::Disabled => "disabled",
+        }
+    }
+}
+
+/// The tier that produced `decision` for a Bash `command`
+pub fn of_command(engine: &SecurityEngine, command: &str, decision: &Decision) -> Provenance {
+    if let Some(provenance) = before_analysis(decision) {
+        return provenance;
+    }
+    if powershell::is_powershell(command) {
+        return Provenance::PowerShell;
+    }
+    let syntax = engine.bash_syntax(command);
+    if !ast::analyze_command(&syntax).parsed {
+        Provenance::RegexFallback
+    } else if syntax != command {
+        Provenance::DialectAst
+    } else {
+        Provenance::Ast
+    }
+}
+
+/// The tier that produced `decision` (from `engine.check(input)`)
+pub fn of(engine: &SecurityEngine, input: &HookInput, decision: &Decision) -> Provenance {
+    match &input.tool_input {
+        ToolInput::Bash { command, .. } => of_command(engine, command, decision),
+        ToolInput::Unknown { .. } => before_analysis(decision).unwrap_or(Provenance::Skipped),
+        _ => before_analysis(decision).unwrap_or(Provenance::Rules),
+    }
+}
+
+/// Decisions made without analyzing the input
+fn before_analysis(decision: &Decision) -> Option<Provenance> {
+    match decision.allow_source()? {
+        AllowSource::Disabled => Some(Provenance::Disabled),
+        AllowSource::Allowlisted => Some(Provenance::Allowlist),
+        AllowSource::IgnoredPath => Some(Provenance::Skipped),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::rules::allowlist::CompiledAllowlist;
+
+    fn provenance(engine: &SecurityEngine, json: &str) -> Provenance {
+        let input = HookInput::from_json(json).unwrap();
+        of(engine, &input, &engine.check(&input))
+    }
+
+    #[test]
+    fn test_provenance() {
+        let mut config = Config::default();
+        config.files.ignore_patterns = vec!["^vendor/".to_string()];
+        let engine = SecurityEngine::new(config);
+        let bash = |command: &str| {
+            let json = serde_json::json!({"tool_name": "Bash", "tool_input": {"command": command}});
+            provenance(&engine, &json.to_string())
+        };
+
+        assert_eq!(bash("rm -rf /"), Provenance::Ast);
+        assert_eq!(bash("echo 'unterminated"), Provenance::RegexFallback);
+        assert_eq!(bash("Remove-Item -Recurse -Force C:\\"), Provenance::PowerShell);
+        assert_eq!(
+            provenance(&engine, r#"{"tool_name":"Read","tool_input":{"file_path":".env"}}"#),
+            Provenance::Rules
+        );
+        assert_eq!(
+            provenance(&engine, r#"{"tool_name":"Read","tool_input":{"file_path":"vendor/.env"}}"#),
+            Provenance::Skipped
+        );
+        assert_eq!(serde_json::to_string(&Provenance::RegexFallback).unwrap(), r#""regex-fallback""#);
+    }
+
+    #[test]
+    fn test_rewritten_dialect_provenance() {
+        let mut config = Config::default();
+        config.bash.shell = "fish".to_string();
+        let engine = SecurityEngine::new(config);
+        let command = "set -x LD_PRELOAD /tmp/evil.so; and ls";
+        assert_eq!(of_command(&engine, command, &engine.check_bash(command)), Provenance::DialectAst);
+        assert_eq!(of_command(&engine, "ls -la", &engine.check_bash("ls -la")), Provenance::Ast);
+
+        // Bash, the default, is parsed as written
+        let engine = SecurityEngine::new(Config::default());
+        assert_eq!(of_command(&engine, command, &engine.check_bash(command)), Provenance::Ast);
+    }
+
+    #[test]
+    fn test_allowlisted_provenance() {
+        let allowlist = CompiledAllowlist::from_toml_str(
+            r#"
+                [[allow]]
+                command = "git"
+                args_match = ["push", "--force", "origin", "feature-.*"]
+                reason = "feature branches"
+            "#,
+        )
+        .unwrap();
+        let engine = SecurityEngine::from_parts(Config::default(), allowlist);
+        let command = "git push --force origin feature-x";
+        assert_eq!(of_command(&engine, command, &engine.check_bash(command)), Provenance::Allowlist);
+    }
+}
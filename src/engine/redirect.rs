@@ -0,0 +1,151 @@
+//! Files a Bash command writes
+//!
+//! The file rules only see the paths given to Read/Write/Edit, so `echo key
+//! >> ~/.ssh/authorized_keys` or `cat payload | sudo tee -a ~/.bashrc`
+//! would write around them. This collects the files a command line writes
+//! (output redirects, including those in substitutions, and `tee` operands)
+//! and hands each one to the engine's path checks: the secret file rules,
+//! the shell-init rules and `files.protected_patterns`.
+
+use crate::output::Decision;
+use crate::parser::ast::CommandAnalysis;
+use crate::parser::wrapper;
+
+/// Rule ID for writes to a path in `files.protected_patterns`
+pub const RULE_ID: &str = "protected-path-write";
+
+/// Redirect operators that write their target
+const WRITE_OPERATORS: &[&str] = &[">", ">>", ">|", "&>", "&>>", "<>", ">&"];
+
+/// Whether a redirect target is a file (not `/dev/null`, a descriptor or `-`)
+fn is_file(target: &str) -> bool {
+    !target.is_empty()
+        && target != "-"
+        && !target.starts_with("/dev/")
+        && !target.chars().all(|c| c.is_ascii_digit() || c == '-')
+}
+
+/// The files a command line writes, in source order
+pub fn targets(analysis: &CommandAnalysis, wrappers: &[String]) -> Vec<String> {
+    let redirected = analysis
+        .redirects
+        .iter()
+        .filter(|r| WRITE_OPERATORS.contains(&r.operator.as_str()))
+        .map(|r| r.target.clone());
+
+    let teed = analysis
+        .commands
+        .iter()
+        .filter(|c| !c.is_dynamic)
+        .flat_map(|c| wrapper::unwrap_command(&c.full_command, wrappers))
+        .filter_map(|text| shlex::split(&text))
+        .filter(|words| words.first().is_some_and(|w| w.rsplit('/').next() == Some("tee")))
+        .flat_map(|words| words.into_iter().skip(1).filter(|w| !w.starts_with('-')));
+
+    let mut targets: Vec<String> = Vec::new();
+    for target in redirected.chain(teed).filter(|t| is_file(t)) {
+        if !targets.contains(&target) {
+            targets.push(target);
+        }
+    }
+    targets
+}
+
+/// Commands piped into `tee`, rewritten as redirects to the files it writes
+///
+/// `aws sts get-session-token | tee creds.json` saves the output just like
+/// `... > creds.json`, but the pipe splits the two halves apart before the
+/// rules see them. The rewritten text (`tee -a` becomes `>>`) lets a rule
+/// about where a command's output goes match either form.
+pub fn teed_outputs(command: &str, analysis: &CommandAnalysis, wrappers: &[String]) -> Vec<String> {
+    let mut outputs = Vec::new();
+    for pair in analysis.commands.windows(2) {
+        let (previous, cmd) = (&pair[0], &pair[1]);
+        let between = command.get(previous.span.end..cmd.span.start).unwrap_or_default();
+        if between.trim() != "|" || previous.is_dynamic || cmd.is_dynamic {
+            continue;
+        }
+        let Some(all_words) = shlex::split(&cmd.full_command) else {
+            continue;
+        };
+        for text in wrapper::unwrap_command(&cmd.full_command, wrappers) {
+            // Unwrapping re-joins words without their quotes, so the operands
+            // come from the original words
+            let Some(program) = text.split_whitespace().next() else {
+                continue;
+            };
+            if program.rsplit('/').next() != Some("tee") {
+                continue;
+            }
+            let Some(start) = all_words.iter().position(|w| w == program) else {
+                continue;
+            };
+            let args = &all_words[start + 1..];
+            let operator = if args.iter().any(|a| a == "-a" || a == "--append") { ">>" } else { ">" };
+            for file in args.iter().filter(|a| !a.starts_with('-') && is_file(a)) {
+                outputs.push(format!("{} {} {}", previous.full_command, operator, shlex::try_quote(file).unwrap_or_default()));
+            }
+        }
+    }
+    outputs
+}
+
+/// Check every file a command line writes with `check_path`, which returns
+/// the rule a path is denied by
+///
+/// The first denial is returned, its reason naming the file written.
+pub fn check(
+    analysis: &CommandAnalysis,
+    wrappers: &[String],
+    check_path: impl Fn(&str) -> Option<Decision>,
+) -> Option<Decision> {
+    targets(analysis, wrappers).iter().find_map(|target| {
+        let decision = check_path(target)?;
+        let rule_id = decision.rule_id()?.to_string();
+        Some(Decision::deny(rule_id, format!("{} (written to {})", decision.reason(), target)))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::ast;
+    use crate::parser::wrapper::DEFAULT_WRAPPERS;
+
+    fn written(command: &str) -> Vec<String> {
+        let wrappers: Vec<String> = DEFAULT_WRAPPERS.iter().map(|w| w.to_string()).collect();
+        targets(&ast::analyze_command(command), &wrappers)
+    }
+
+    #[test]
+    fn test_targets() {
+        assert_eq!(written("echo k >> ~/.ssh/authorized_keys"), ["~/.ssh/authorized_keys"]);
+        assert_eq!(written("cat payload | sudo tee -a ~/.bashrc /tmp/copy"), ["~/.bashrc", "/tmp/copy"]);
+        assert_eq!(written("make &> 'build log.txt' 2>&1"), ["build log.txt"]);
+        assert_eq!(written("x=$(date > stamp); echo $x >| out"), ["stamp", "out"]);
+
+        // Reads, descriptors and devices
+        assert!(written("sort < .env 2>/dev/null >&2").is_empty());
+        assert!(written("cat <<EOF\n~/.bashrc\nEOF").is_empty());
+        assert!(written("echo hi | tee").is_empty());
+    }
+
+    #[test]
+    fn test_teed_outputs() {
+        let teed = |command: &str| teed_outputs(command, &ast::analyze_command(command), &["sudo".to_string()]);
+        assert_eq!(teed("aws sts get-session-token | tee creds.json"), ["aws sts get-session-token > creds.json"]);
+        assert_eq!(teed("env | sudo tee -a /tmp/env 'my log'"), ["env >> /tmp/env", "env >> 'my log'"]);
+        assert!(teed("make | tee /dev/null").is_empty());
+        assert!(teed("make; tee out < in").is_empty());
+    }
+
+    #[test]
+    fn test_reason_names_the_target() {
+        let analysis = ast::analyze_command("echo x > a.txt; echo y > ~/.zshrc");
+        let decision = check(&analysis, &[], |path| {
+            path.ends_with(".zshrc").then(|| Decision::deny("shell-init-zsh", "Writing a zsh startup file"))
+        });
+        assert_eq!(decision.as_ref().and_then(|d| d.rule_id()), Some("shell-init-zsh"));
+        assert_eq!(decision.unwrap().reason(), "Writing a zsh startup file (written to ~/.zshrc)");
+    }
+}
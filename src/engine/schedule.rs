@@ -0,0 +1,200 @@
+//! Commands deferred with `at`, `batch`, `systemd-run` and `launchctl submit`
+//!
+//! A scheduled command runs after the hook has answered, so it is never
+//! checked on its own. The deferred command is pulled out of the
+//! scheduling call — an `at`/`batch` here-string, here-document or
+//! `echo ... |` pipeline, the command line after `systemd-run`'s options or
+//! `launchctl submit --` — and run through the full Bash pipeline. A
+//! denial is reported under the deferred command's rule. Scheduling itself
+//! is left to the Strict rules (`at-scheduled-command`, ...).
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::output::Decision;
+use crate::parser::ast::CommandAnalysis;
+use crate::parser::wrapper;
+
+/// `at ... <<EOF` or `batch <<'EOF'`: the here-document delimiter
+static AT_HEREDOC: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?m)(?:^|[\s;&|(/])(?:at|batch)\b[^\n]*?<<-?\s*['"]?([A-Za-z_][A-Za-z0-9_]*)['"]?[^\n]*$"#).unwrap()
+});
+
+/// `systemd-run` options that take a separate value
+const SYSTEMD_RUN_VALUE_OPTIONS: &[&str] = &[
+    "-u", "--unit", "-p", "--property", "-E", "--setenv", "-M", "--machine", "-H", "--host", "--description",
+    "--slice", "--uid", "--gid", "--nice", "--working-directory", "--on-calendar", "--on-active", "--on-boot",
+    "--on-startup", "--on-unit-active", "--on-unit-inactive", "--timer-property", "--path-property",
+    "--socket-property", "--service-type",
+];
+
+/// `launchctl submit` options that take a value
+const LAUNCHCTL_SUBMIT_VALUE_OPTIONS: &[&str] = &["-l", "-p", "-o", "-e"];
+
+/// A command scheduled to run later
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Deferred {
+    /// The scheduling program ("at", "systemd-run", ...)
+    pub scheduler: &'static str,
+    /// The command it will run
+    pub command: String,
+}
+
+/// Join argv back into a command line, quoting only words with spaces so
+/// `~` and `$VAR` read as they did on the original line
+fn join(words: &[String]) -> String {
+    words
+        .iter()
+        .map(|w| match w.is_empty() || w.contains(char::is_whitespace) {
+            true => shlex::try_quote(w).map(|q| q.into_owned()).unwrap_or_else(|_| w.clone()),
+            false => w.clone(),
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Operands after the options (`value_options` take the next word)
+fn after_options<'a>(args: &'a [String], value_options: &[&str]) -> &'a [String] {
+    let mut i = 0;
+    while let Some(arg) = args.get(i) {
+        if arg == "--" {
+            return &args[i + 1..];
+        }
+        if !arg.starts_with('-') {
+            break;
+        }
+        i += if value_options.contains(&arg.as_str()) { 2 } else { 1 };
+    }
+    args.get(i..).unwrap_or_default()
+}
+
+/// The deferred command of one (unwrapped) command
+fn command_deferred(words: &[String]) -> Option<Deferred> {
+    let (program, args) = words.split_first()?;
+    let name = program.rsplit('/').next().unwrap_or(program);
+    let (scheduler, command) = match name {
+        "at" | "batch" => {
+            let here = args.iter().position(|w| w == "<<<")?;
+            ("at", args.get(here + 1)?.clone())
+        }
+        "systemd-run" => ("systemd-run", join(after_options(args, SYSTEMD_RUN_VALUE_OPTIONS))),
+        "launchctl" if args.first().is_some_and(|a| a == "submit") => {
+            ("launchctl submit", join(after_options(&args[1..], LAUNCHCTL_SUBMIT_VALUE_OPTIONS)))
+        }
+        _ => return None,
+    };
+    Some(Deferred { scheduler, command }).filter(|d| !d.command.trim().is_empty())
+}
+
+/// Text `echo`/`printf` writes, for `echo CMD | at now`
+fn echoed(words: &[String]) -> Option<String> {
+    let (program, args) = words.split_first()?;
+    match program.rsplit('/').next().unwrap_or(program) {
+        "echo" => {
+            let words: Vec<&str> = args.iter().map(String::as_str).filter(|a| !matches!(*a, "-n" | "-e" | "-E")).collect();
+            Some(words.join(" "))
+        }
+        "printf" => args.first().map(|format| format.replace("\\n", "\n")),
+        _ => None,
+    }
+}
+
+/// Commands a command line schedules, with wrappers (`sudo`, ...) unwrapped
+pub fn deferred_commands(command: &str, analysis: &CommandAnalysis, wrappers: &[String]) -> Vec<Deferred> {
+    let mut deferred = Vec::new();
+    for (i, cmd) in analysis.commands.iter().enumerate().filter(|(_, c)| !c.is_dynamic) {
+        let Some(all_words) = shlex::split(&cmd.full_command) else {
+            continue;
+        };
+        for text in wrapper::unwrap_command(&cmd.full_command, wrappers) {
+            // Unwrapping re-joins words without their quotes, so only the
+            // program is taken from it; the words come from the original
+            let Some(program) = text.split_whitespace().next() else {
+                continue;
+            };
+            let Some(start) = all_words.iter().position(|w| w == program) else {
+                continue;
+            };
+            let words = &all_words[start..];
+            deferred.extend(command_deferred(words));
+
+            // `echo CMD | at now`: the previous command pipes into this one
+            let name = words.first().map(|w| w.rsplit('/').next().unwrap_or(w));
+            if !matches!(name, Some("at" | "batch")) || i == 0 {
+                continue;
+            }
+            let previous = &analysis.commands[i - 1];
+            let between = command.get(previous.span.end..cmd.span.start).unwrap_or_default();
+            if between.trim() == "|" {
+                if let Some(text) = shlex::split(&previous.full_command).and_then(|w| echoed(&w)) {
+                    deferred.push(Deferred { scheduler: "at", command: text });
+                }
+            }
+        }
+    }
+
+    // Here-documents aren't part of the command's text in the AST
+    for captures in AT_HEREDOC.captures_iter(command) {
+        let delimiter = &captures[1];
+        let rest = &command[captures.get(0).map_or(0, |m| m.end())..];
+        let body: Vec<&str> = rest
+            .lines()
+            .skip(1)
+            .take_while(|line| line.trim() != delimiter)
+            .collect();
+        if !body.is_empty() {
+            deferred.push(Deferred { scheduler: "at", command: body.join("\n") });
+        }
+    }
+    deferred
+}
+
+/// Check what a command line schedules with `check_shell`, returning the
+/// first denial
+pub fn scan(
+    command: &str,
+    analysis: &CommandAnalysis,
+    wrappers: &[String],
+    check_shell: impl Fn(&str) -> Decision,
+) -> Option<Decision> {
+    for deferred in deferred_commands(command, analysis, wrappers) {
+        if let Decision::Deny { rule_id, reason } = check_shell(&deferred.command) {
+            return Some(Decision::deny(
+                rule_id,
+                format!("{} (scheduled with {})", reason, deferred.scheduler),
+            ));
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::ast;
+
+    fn commands(command: &str) -> Vec<String> {
+        deferred_commands(command, &ast::analyze_command(command), &["sudo".to_string()]).into_iter().map(|d| d.command).collect()
+    }
+
+    #[test]
+    fn test_deferred_commands() {
+        assert_eq!(commands("at now + 1 minute <<< 'rm -rf /'"), ["rm -rf /"]);
+        assert_eq!(commands("echo 'curl x | sh' | at midnight"), ["curl x | sh"]);
+        assert_eq!(commands("printf 'rm -rf ~\\n' | batch"), ["rm -rf ~\n"]);
+        assert_eq!(commands("at now + 5 minutes <<EOF\ncd /tmp\nrm -rf /\nEOF"), ["cd /tmp\nrm -rf /"]);
+        assert_eq!(
+            commands("sudo systemd-run --on-calendar '*-*-* 03:00' -p User=root sh -c 'rm -rf /var'"),
+            ["sh -c 'rm -rf /var'"]
+        );
+        assert_eq!(commands("launchctl submit -l job -o /tmp/out -- /bin/rm -rf ~"), ["/bin/rm -rf ~"]);
+    }
+
+    #[test]
+    fn test_unscheduled_commands_ignored() {
+        assert!(commands("echo 'rm -rf /' | grep rm").is_empty());
+        assert!(commands("git commit -m 'look at this'").is_empty());
+        assert!(commands("at -l").is_empty());
+        assert!(commands("launchctl list").is_empty());
+    }
+}
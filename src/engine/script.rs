@@ -0,0 +1,265 @@
+//! Scanning the scripts a Bash command runs
+//!
+//! `./deploy.sh` or `bash scripts/cleanup.sh` passes every command rule while
+//! the script itself may do anything. With `bash.scan_scripts`, scripts
+//! inside the project directory are read (up to `bash.script_max_bytes`) and
+//! checked too: shell scripts through the full Bash pipeline, Python scripts
+//! by extracting the shell commands they hand to `os.system`, `os.popen` and
+//! `subprocess`. The interpreter named on the command line wins; otherwise
+//! the shebang, then the file extension, decides.
+
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::output::Decision;
+use crate::parser::ast::CommandAnalysis;
+
+/// Shell interpreters whose script argument is run as a shell script
+const SHELLS: &[&str] = &["sh", "bash", "zsh", "dash", "ksh"];
+
+/// Python interpreters
+const PYTHONS: &[&str] = &["python", "python2", "python3"];
+
+/// Shell commands passed to Python's process APIs as a string
+static PYTHON_SHELL_STRING: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r#"\b(?:os\.system|os\.popen|subprocess\.(?:run|call|check_call|check_output|Popen|getoutput|getstatusoutput))\(\s*[rbuf]?("(?:[^"\\]|\\.)*"|'(?:[^'\\]|\\.)*')"#,
+    )
+    .unwrap()
+});
+
+/// Argument lists passed to `subprocess` (`["rm", "-rf", "/"]`)
+static PYTHON_ARGV: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r#"\bsubprocess\.(?:run|call|check_call|check_output|Popen)\(\s*\[([^\]]*)\]"#,
+    )
+    .unwrap()
+});
+
+/// String literals inside an argument list
+static PYTHON_STRING: Lazy<Regex> = Lazy::new(|| Regex::new(r#""((?:[^"\\]|\\.)*)"|'((?:[^'\\]|\\.)*)'"#).unwrap());
+
+/// How a script's contents are analyzed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScriptKind {
+    Shell,
+    Python,
+}
+
+impl ScriptKind {
+    /// Kind for an interpreter name (`bash`, `/usr/bin/python3`, ...)
+    fn of_interpreter(name: &str) -> Option<Self> {
+        let name = name.rsplit('/').next().unwrap_or(name);
+        if SHELLS.contains(&name) {
+            Some(ScriptKind::Shell)
+        } else if PYTHONS.contains(&name) || name.starts_with("python3.") {
+            Some(ScriptKind::Python)
+        } else {
+            None
+        }
+    }
+
+    /// Kind named by a `#!` line (`#!/bin/bash`, `#!/usr/bin/env python3`)
+    fn of_shebang(content: &str) -> Option<Self> {
+        let line = content.lines().next()?.strip_prefix("#!")?;
+        let mut words = line.split_whitespace();
+        let program = words.next()?;
+        if program.ends_with("/env") {
+            words.find(|w| !w.starts_with('-')).and_then(Self::of_interpreter)
+        } else {
+            Self::of_interpreter(program)
+        }
+    }
+
+    fn of_extension(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "sh" | "bash" | "zsh" => Some(ScriptKind::Shell),
+            "py" => Some(ScriptKind::Python),
+            _ => None,
+        }
+    }
+}
+
+/// A script run by a command: the path as written and the interpreter
+/// named on the command line, if any
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Invocation {
+    pub path: String,
+    pub interpreter: Option<ScriptKind>,
+}
+
+/// Scripts a command line runs directly (`./x.sh`, `scripts/x`) or through
+/// a shell or Python interpreter (`bash x.sh`, `python3 tool.py`)
+pub fn invocations(analysis: &CommandAnalysis) -> Vec<Invocation> {
+    let mut found = Vec::new();
+    for cmd in &analysis.commands {
+        if cmd.is_dynamic {
+            continue;
+        }
+        if let Some(kind) = ScriptKind::of_interpreter(&cmd.name) {
+            // `bash -c '...'` and `python -m mod` don't run a script file
+            let mut args = cmd.arguments.iter();
+            let script = args.find(|a| !a.starts_with('-') || a.as_str() == "-c" || a.as_str() == "-m");
+            if let Some(path) = script.filter(|a| !a.starts_with('-')) {
+                found.push(Invocation {
+                    path: path.clone(),
+                    interpreter: Some(kind),
+                });
+            }
+        } else if cmd.name.contains('/') {
+            found.push(Invocation {
+                path: cmd.name.clone(),
+                interpreter: None,
+            });
+        }
+    }
+    found
+}
+
+/// `path` resolved against `project`, if it is a regular file inside it
+fn resolve(path: &str, project: &Path) -> Option<PathBuf> {
+    let project = project.canonicalize().ok()?;
+    let resolved = project.join(path).canonicalize().ok()?;
+    (resolved.starts_with(&project) && resolved.is_file()).then_some(resolved)
+}
+
+/// Read at most `max_bytes` of a file as (lossy) UTF-8
+fn read_capped(path: &Path, max_bytes: usize) -> Option<String> {
+    let mut buf = Vec::new();
+    File::open(path)
+        .ok()?
+        .take(max_bytes as u64)
+        .read_to_end(&mut buf)
+        .ok()?;
+    Some(String::from_utf8_lossy(&buf).into_owned())
+}
+
+/// Shell commands a Python script passes to `os.system` / `subprocess`
+pub fn python_shell_commands(source: &str) -> Vec<String> {
+    let unquote = |s: &str| s[1..s.len() - 1].replace("\\\"", "\"").replace("\\'", "'");
+    let mut commands: Vec<String> = PYTHON_SHELL_STRING
+        .captures_iter(source)
+        .map(|c| unquote(&c[1]))
+        .collect();
+    for list in PYTHON_ARGV.captures_iter(source) {
+        let argv: Vec<String> = PYTHON_STRING
+            .captures_iter(&list[1])
+            .filter_map(|c| c.get(1).or_else(|| c.get(2)))
+            .map(|m| shlex::try_quote(m.as_str()).map(|q| q.into_owned()).unwrap_or_default())
+            .collect();
+        if !argv.is_empty() {
+            commands.push(argv.join(" "));
+        }
+    }
+    commands
+}
+
+/// Check the project scripts an analyzed command line runs
+///
+/// `check_shell` runs a shell script body (or a command extracted from a
+/// Python script) through the Bash pipeline; the first denial is returned,
+/// with the script named in its reason.
+pub fn scan(
+    analysis: &CommandAnalysis,
+    project: &Path,
+    max_bytes: usize,
+    check_shell: impl Fn(&str) -> Decision,
+) -> Option<Decision> {
+    for invocation in invocations(analysis) {
+        let Some(path) = resolve(&invocation.path, project) else {
+            continue;
+        };
+        let Some(content) = read_capped(&path, max_bytes) else {
+            continue;
+        };
+        let kind = invocation
+            .interpreter
+            .or_else(|| ScriptKind::of_shebang(&content))
+            .or_else(|| ScriptKind::of_extension(&path))
+            .unwrap_or(ScriptKind::Shell);
+
+        let bodies = match kind {
+            ScriptKind::Shell => vec![content],
+            ScriptKind::Python => python_shell_commands(&content),
+        };
+        for body in bodies {
+            if let Decision::Deny { rule_id, reason } = check_shell(&body) {
+                return Some(Decision::deny(
+                    rule_id,
+                    format!("{} (in script {})", reason, invocation.path),
+                ));
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::ast;
+
+    fn invocations(command: &str) -> Vec<Invocation> {
+        super::invocations(&ast::analyze_command(command))
+    }
+
+    #[test]
+    fn test_invocations() {
+        let found = invocations("./deploy.sh --prod && bash scripts/clean.sh; python3 -u tool.py x; bash -c 'ls'");
+        let paths: Vec<&str> = found.iter().map(|i| i.path.as_str()).collect();
+        assert_eq!(paths, ["./deploy.sh", "scripts/clean.sh", "tool.py"]);
+        assert_eq!(found[0].interpreter, None);
+        assert_eq!(found[2].interpreter, Some(ScriptKind::Python));
+        assert!(invocations("git status").is_empty());
+    }
+
+    #[test]
+    fn test_kind_detection() {
+        assert_eq!(ScriptKind::of_shebang("#!/usr/bin/env python3\nimport os"), Some(ScriptKind::Python));
+        assert_eq!(ScriptKind::of_shebang("#!/bin/bash -e\n"), Some(ScriptKind::Shell));
+        assert_eq!(ScriptKind::of_shebang("echo hi"), None);
+        assert_eq!(ScriptKind::of_extension(Path::new("x.py")), Some(ScriptKind::Python));
+    }
+
+    #[test]
+    fn test_python_shell_commands() {
+        let source = r#"
+import os, subprocess
+os.system("rm -rf /")
+subprocess.run(["git", "push", "--force", "origin", "main"])
+subprocess.check_output('ls -la', shell=True)
+"#;
+        assert_eq!(
+            python_shell_commands(source),
+            ["rm -rf /", "ls -la", "git push --force origin main"]
+        );
+    }
+
+    #[test]
+    fn test_scan_only_reads_project_scripts() {
+        let project = tempfile::tempdir().unwrap();
+        std::fs::write(project.path().join("nuke.sh"), "#!/bin/sh\necho cleaning\nrm -rf /\n").unwrap();
+        let outside = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(outside.path(), "rm -rf /\n").unwrap();
+
+        let check = |body: &str| {
+            if body.contains("rm -rf /") {
+                Decision::deny("rm-root", "root")
+            } else {
+                Decision::allow("ok")
+            }
+        };
+        let scan = |command: &str, max_bytes| scan(&ast::analyze_command(command), project.path(), max_bytes, check);
+        let decision = scan("./nuke.sh", 4096).unwrap();
+        assert!(decision.reason().contains("in script ./nuke.sh"));
+
+        let command = format!("bash {}", outside.path().display());
+        assert!(scan(&command, 4096).is_none());
+        // Capped before the dangerous line
+        assert!(scan("./nuke.sh", 10).is_none());
+    }
+}
@@ -0,0 +1,345 @@
+//! Shadow evaluation of deletion commands
+//!
+//! Regex rules can only guess what `rm -r build/*` or `find . -name x -delete`
+//! will actually remove. Shadow evaluation runs a read-only equivalent
+//! (`find ... -print` instead of `-delete`, glob expansion plus `find -print`
+//! for `rm -r`) in a subprocess to enumerate the affected paths, then checks
+//! each one against the project root and the protected path rules.
+
+use std::io::{BufRead, BufReader};
+use std::path::{Component, Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+use crate::config::ShadowConfig;
+use crate::output::Decision;
+use crate::parser::shell;
+
+/// `find` actions that execute or write, which we refuse to shadow-run
+const UNSAFE_FIND_ACTIONS: &[&str] = &[
+    "-exec", "-execdir", "-ok", "-okdir", "-fprint", "-fprint0", "-fprintf", "-fls",
+];
+
+/// Characters allowed in an `rm` glob we hand to the shell for expansion
+fn is_plain_glob(arg: &str) -> bool {
+    arg.chars()
+        .all(|c| c.is_ascii_alphanumeric() || "._-/*?[]+@%,=".contains(c))
+}
+
+/// A deletion found in a command, reduced to a read-only enumeration
+#[derive(Debug, PartialEq)]
+enum Enumeration {
+    /// Run `find` with these arguments (`-delete` replaced by `-print`)
+    Find(Vec<String>),
+    /// Expand these `rm` targets, descending into directories if recursive
+    Remove { targets: Vec<String>, recursive: bool },
+}
+
+/// Turn one simple command into an enumeration, if it deletes anything
+fn plan(tokens: &[String]) -> Option<Enumeration> {
+    let tokens = match tokens.first().map(String::as_str) {
+        Some("sudo") | Some("command") => &tokens[1..],
+        _ => tokens,
+    };
+    let (name, args) = tokens.split_first()?;
+
+    match name.as_str() {
+        "find" if args.iter().any(|a| a == "-delete") => {
+            if args.iter().any(|a| UNSAFE_FIND_ACTIONS.contains(&a.as_str())) {
+                return None;
+            }
+            let args = args
+                .iter()
+                .map(|a| if a == "-delete" { "-print".to_string() } else { a.clone() })
+                .collect();
+            Some(Enumeration::Find(args))
+        }
+        "rm" => {
+            let mut recursive = false;
+            let mut targets = Vec::new();
+            let mut end_of_flags = false;
+            for arg in args {
+                if !end_of_flags && arg == "--" {
+                    end_of_flags = true;
+                } else if !end_of_flags && arg.starts_with("--") {
+                    recursive |= arg == "--recursive";
+                } else if !end_of_flags && arg.starts_with('-') && arg.len() > 1 {
+                    recursive |= arg.contains('r') || arg.contains('R');
+                } else {
+                    targets.push(arg.clone());
+                }
+            }
+            (!targets.is_empty()).then_some(Enumeration::Remove { targets, recursive })
+        }
+        _ => None,
+    }
+}
+
+/// Run a command and collect up to `limit` output lines, killing it on timeout
+///
+/// Returns the lines and whether the output was cut short.
+fn capture_lines(mut cmd: Command, limit: usize, timeout: Duration) -> Option<(Vec<String>, bool)> {
+    let mut child = cmd
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+
+    let stdout = child.stdout.take()?;
+    let reader = std::thread::spawn(move || {
+        let mut lines = Vec::new();
+        let mut truncated = false;
+        for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+            if lines.len() >= limit {
+                truncated = true;
+                break;
+            }
+            lines.push(line);
+        }
+        (lines, truncated)
+    });
+
+    let deadline = Instant::now() + timeout;
+    loop {
+        match child.try_wait() {
+            Ok(Some(_)) => break,
+            Ok(None) if Instant::now() >= deadline => {
+                let _ = child.kill();
+                let _ = child.wait();
+                let (lines, _) = reader.join().ok()?;
+                return Some((lines, true));
+            }
+            Ok(None) => std::thread::sleep(Duration::from_millis(5)),
+            Err(_) => return None,
+        }
+    }
+
+    reader.join().ok()
+}
+
+/// Enumerate the paths an enumeration would touch
+fn enumerate(plan: &Enumeration, cwd: &Path, config: &ShadowConfig) -> Option<(Vec<String>, bool)> {
+    let timeout = Duration::from_millis(config.timeout_ms);
+
+    match plan {
+        Enumeration::Find(args) => {
+            let mut cmd = Command::new("find");
+            cmd.args(args).current_dir(cwd);
+            capture_lines(cmd, config.max_paths, timeout)
+        }
+        Enumeration::Remove { targets, recursive } => {
+            let mut paths = Vec::new();
+            for target in targets {
+                let target = match target.strip_prefix("~/") {
+                    Some(rest) => dirs::home_dir()?.join(rest).to_string_lossy().into_owned(),
+                    None => target.clone(),
+                };
+
+                let expanded = if target.contains(['*', '?', '[']) {
+                    if !is_plain_glob(&target) {
+                        return None;
+                    }
+                    let mut cmd = Command::new("sh");
+                    cmd.arg("-c")
+                        .arg(format!(
+                            "for p in {}; do [ -e \"$p\" ] && printf '%s\\n' \"$p\"; done",
+                            target
+                        ))
+                        .current_dir(cwd);
+                    capture_lines(cmd, config.max_paths, timeout)?.0
+                } else {
+                    vec![target]
+                };
+
+                for path in expanded {
+                    if *recursive && cwd.join(&path).is_dir() {
+                        let mut cmd = Command::new("find");
+                        cmd.arg(&path).arg("-print").current_dir(cwd);
+                        let (found, truncated) = capture_lines(cmd, config.max_paths, timeout)?;
+                        paths.extend(found);
+                        if truncated {
+                            return Some((paths, true));
+                        }
+                    } else {
+                        paths.push(path);
+                    }
+                }
+            }
+            let truncated = paths.len() > config.max_paths;
+            paths.truncate(config.max_paths);
+            Some((paths, truncated))
+        }
+    }
+}
+
+/// Resolve `path` against `cwd` and remove `.`/`..` components lexically
+fn resolve(path: &str, cwd: &Path) -> PathBuf {
+    let mut resolved = PathBuf::new();
+    for component in cwd.join(path).components() {
+        match component {
+            Component::ParentDir => {
+                resolved.pop();
+            }
+            Component::CurDir => {}
+            other => resolved.push(other),
+        }
+    }
+    resolved
+}
+
+/// Shadow-evaluate a command that passed the regex checks
+///
+/// Returns a deny decision if any enumerated path lies outside `cwd` or is
+/// protected according to `is_protected` (which returns the matching rule
+/// ID). Returns `None` when the command deletes nothing we can enumerate or
+/// every affected path is fine.
+pub fn evaluate(
+    command: &str,
+    cwd: &Path,
+    config: &ShadowConfig,
+    is_protected: impl Fn(&str) -> Option<String>,
+) -> Option<Decision> {
+    // Never hand expansions or substitutions to a subprocess
+    if command.contains(['$', '`']) {
+        return None;
+    }
+
+    let root = resolve(".", cwd);
+
+    for part in shell::split_compound_command(command) {
+        let Some(tokens) = shell::tokenize(part.trim()) else {
+            continue;
+        };
+        let Some(plan) = plan(&tokens) else {
+            continue;
+        };
+        let Some((paths, truncated)) = enumerate(&plan, cwd, config) else {
+            continue;
+        };
+
+        for path in &paths {
+            let resolved = resolve(path, cwd);
+            if !resolved.starts_with(&root) {
+                return Some(Decision::deny(
+                    "shadow-outside-project",
+                    format!("Deletion reaches outside the project: {}", resolved.display()),
+                ));
+            }
+            if let Some(rule_id) = is_protected(&resolved.to_string_lossy()) {
+                return Some(Decision::deny(
+                    "shadow-protected-path",
+                    format!("Deletion would remove a protected path ({}): {}", rule_id, path),
+                ));
+            }
+        }
+
+        if truncated {
+            return Some(Decision::deny(
+                "shadow-too-many-paths",
+                format!(
+                    "Deletion affects more than {} paths (shadow evaluation limit)",
+                    config.max_paths
+                ),
+            ));
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn project() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("project/build")).unwrap();
+        fs::write(dir.path().join("project/build/a.tmp"), "").unwrap();
+        fs::write(dir.path().join("project/build/b.tmp"), "").unwrap();
+        fs::write(dir.path().join("project/.env"), "SECRET=1").unwrap();
+        fs::write(dir.path().join("outside.txt"), "").unwrap();
+        dir
+    }
+
+    fn protected(path: &str) -> Option<String> {
+        path.ends_with(".env").then(|| "env-file".to_string())
+    }
+
+    #[test]
+    fn test_plan_find_delete() {
+        let tokens: Vec<String> = ["find", ".", "-name", "*.tmp", "-delete"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert_eq!(
+            plan(&tokens),
+            Some(Enumeration::Find(
+                [".", "-name", "*.tmp", "-print"].iter().map(|s| s.to_string()).collect()
+            ))
+        );
+
+        let tokens: Vec<String> = ["find", ".", "-delete", "-exec", "sh", ";"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert_eq!(plan(&tokens), None);
+    }
+
+    #[test]
+    fn test_find_inside_project_allowed() {
+        let dir = project();
+        let cwd = dir.path().join("project");
+        let config = ShadowConfig::default();
+
+        let decision = evaluate("find build -name '*.tmp' -delete", &cwd, &config, protected);
+        assert!(decision.is_none());
+        // Nothing was actually deleted
+        assert!(cwd.join("build/a.tmp").exists());
+    }
+
+    #[test]
+    fn test_find_protected_denied() {
+        let dir = project();
+        let cwd = dir.path().join("project");
+        let decision = evaluate("find . -delete", &cwd, &ShadowConfig::default(), protected).unwrap();
+        assert_eq!(decision.rule_id(), Some("shadow-protected-path"));
+        assert!(cwd.join(".env").exists());
+    }
+
+    #[test]
+    fn test_rm_outside_project_denied() {
+        let dir = project();
+        let cwd = dir.path().join("project");
+        let decision =
+            evaluate("rm -rf ../outside.txt", &cwd, &ShadowConfig::default(), protected).unwrap();
+        assert_eq!(decision.rule_id(), Some("shadow-outside-project"));
+    }
+
+    #[test]
+    fn test_rm_glob_expanded() {
+        let dir = project();
+        let cwd = dir.path().join("project");
+        assert!(evaluate("rm -f build/*.tmp", &cwd, &ShadowConfig::default(), protected).is_none());
+    }
+
+    #[test]
+    fn test_path_limit() {
+        let dir = project();
+        let cwd = dir.path().join("project");
+        let config = ShadowConfig {
+            max_paths: 1,
+            ..ShadowConfig::default()
+        };
+        let decision = evaluate("rm -r build", &cwd, &config, protected).unwrap();
+        assert_eq!(decision.rule_id(), Some("shadow-too-many-paths"));
+    }
+
+    #[test]
+    fn test_dynamic_commands_skipped() {
+        let dir = project();
+        let cwd = dir.path().join("project");
+        assert!(evaluate("rm -rf $(echo ..)", &cwd, &ShadowConfig::default(), protected).is_none());
+    }
+}
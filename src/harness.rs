@@ -0,0 +1,187 @@
+//! End-to-end driver for the hook binary
+//!
+//! `Harness` runs a built `claude-guardrails` the way Claude Code does: hook
+//! JSON on stdin, one JSON object back on stdout. It is what the
+//! `tests/integration` end-to-end suite uses, and is public so wrappers and
+//! packaging tests can check a binary they built themselves.
+//!
+//! ```no_run
+//! use claude_guardrails::harness::{bash_input, Harness};
+//!
+//! let run = Harness::new("target/debug/claude-guardrails")
+//!     .no_home()
+//!     .run(&bash_input("rm -rf /"))?;
+//! assert_eq!(run.decision().as_deref(), Some("deny"));
+//! # Ok::<(), std::io::Error>(())
+//! ```
+//!
+//! The override variables (`GUARDRAILS_DISABLED`, `GUARDRAILS_WARN_ONLY`)
+//! are removed from the inherited environment so a developer's shell can't
+//! change a test's outcome; set them again with [`Harness::env`].
+
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus, Stdio};
+
+use serde_json::{json, Value};
+
+use crate::config::SafetyLevel;
+
+/// Environment variables the binary reads to relax enforcement
+const OVERRIDE_VARS: &[&str] = &["GUARDRAILS_DISABLED", "GUARDRAILS_WARN_ONLY"];
+
+/// Hook input for a `Bash` tool call
+pub fn bash_input(command: &str) -> String {
+    tool_input("Bash", json!({ "command": command }))
+}
+
+/// Hook input for any tool call
+pub fn tool_input(tool_name: &str, tool_input: Value) -> String {
+    json!({ "tool_name": tool_name, "tool_input": tool_input }).to_string()
+}
+
+/// A configured invocation of the hook binary
+#[derive(Debug, Clone)]
+pub struct Harness {
+    binary: PathBuf,
+    args: Vec<String>,
+    env: Vec<(String, String)>,
+    home: Option<PathBuf>,
+    cwd: Option<PathBuf>,
+}
+
+impl Harness {
+    /// Drive the binary at `binary`
+    ///
+    /// From an integration test of this crate, pass
+    /// `env!("CARGO_BIN_EXE_claude-guardrails")`.
+    pub fn new(binary: impl Into<PathBuf>) -> Self {
+        Self {
+            binary: binary.into(),
+            args: Vec::new(),
+            env: Vec::new(),
+            home: None,
+            cwd: None,
+        }
+    }
+
+    /// Pass a command-line argument
+    pub fn arg(mut self, arg: impl Into<String>) -> Self {
+        self.args.push(arg.into());
+        self
+    }
+
+    /// Set an environment variable for the run
+    pub fn env(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.env.push((key.into(), value.into()));
+        self
+    }
+
+    /// Run with `HOME` pointing at `dir` (user config, audit log, allowlist)
+    pub fn home(mut self, dir: impl AsRef<Path>) -> Self {
+        self.home = Some(dir.as_ref().to_path_buf());
+        self
+    }
+
+    /// Run from `dir`, the directory project config is discovered from
+    pub fn current_dir(mut self, dir: impl AsRef<Path>) -> Self {
+        self.cwd = Some(dir.as_ref().to_path_buf());
+        self
+    }
+
+    /// Use `path` as the config file (`--config`)
+    pub fn config(self, path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref().display().to_string();
+        self.arg("--config").arg(path)
+    }
+
+    /// Override the safety level (`--safety-level`)
+    pub fn safety_level(self, level: SafetyLevel) -> Self {
+        self.arg("--safety-level").arg(level.as_str())
+    }
+
+    /// Ignore the home directory entirely (`--no-home`)
+    pub fn no_home(self) -> Self {
+        self.arg("--no-home")
+    }
+
+    /// Run the binary with `stdin` as the hook input and wait for it
+    pub fn run(&self, stdin: &str) -> io::Result<HookRun> {
+        let mut command = Command::new(&self.binary);
+        command
+            .args(&self.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        for var in OVERRIDE_VARS {
+            command.env_remove(var);
+        }
+        if let Some(home) = &self.home {
+            command.env("HOME", home);
+        }
+        if let Some(cwd) = &self.cwd {
+            command.current_dir(cwd);
+        }
+        command.envs(self.env.iter().map(|(k, v)| (k, v)));
+
+        let mut child = command.spawn()?;
+        if let Some(mut pipe) = child.stdin.take() {
+            // The binary may exit before reading everything (e.g. `--help`)
+            match pipe.write_all(stdin.as_bytes()) {
+                Err(e) if e.kind() != io::ErrorKind::BrokenPipe => return Err(e),
+                _ => {}
+            }
+        }
+        let output = child.wait_with_output()?;
+        Ok(HookRun {
+            status: output.status,
+            stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+            stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+        })
+    }
+}
+
+/// What one run of the binary produced
+#[derive(Debug, Clone)]
+pub struct HookRun {
+    pub status: ExitStatus,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+impl HookRun {
+    /// stdout parsed as the hook's JSON output
+    pub fn output(&self) -> Result<Value, serde_json::Error> {
+        serde_json::from_str(self.stdout.trim())
+    }
+
+    /// `permissionDecision`, or `None` for a plain allow (`{}`)
+    pub fn decision(&self) -> Option<String> {
+        let output = self.output().ok()?;
+        output
+            .pointer("/hookSpecificOutput/permissionDecision")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+    }
+
+    /// `systemMessage`, if any
+    pub fn message(&self) -> Option<String> {
+        let output = self.output().ok()?;
+        output.get("systemMessage").and_then(Value::as_str).map(str::to_string)
+    }
+
+    /// Rule IDs reported as warnings (warn-only mode)
+    pub fn warnings(&self) -> Vec<String> {
+        let Ok(output) = self.output() else {
+            return Vec::new();
+        };
+        output
+            .pointer("/hookSpecificOutput/warnings")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(|w| w.get("ruleId").and_then(Value::as_str))
+            .map(str::to_string)
+            .collect()
+    }
+}
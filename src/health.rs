@@ -0,0 +1,326 @@
+//! Health and readiness reporting
+//!
+//! Lets orchestration (a systemd/launchd unit, a k8s sidecar probe) verify
+//! that guardrails is actually enforcing before agent sessions start.
+//! `/healthz` reports engine status; `/readyz` succeeds only when every rule
+//! set compiled and neither `GUARDRAILS_DISABLED` nor `GUARDRAILS_WARN_ONLY`
+//! is set. The same report is printed by `claude-guardrails healthz` for
+//! exec-style probes.
+//!
+//! [`Precheck`] runs the same checks when a Claude session starts (the
+//! SessionStart hook), plus whether the audit log can be written, so the
+//! user sees that protection is on before the first tool call. It also
+//! tells Claude which kinds of operation will be denied, so it steers clear
+//! of them instead of spending turns on blocked commands.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::audit::sinks::JsonlFileSink;
+use crate::config::RuleAction;
+use crate::engine::SecurityEngine;
+use crate::parser::grammar::{self, GrammarStatus};
+use crate::rules::{self, Category};
+
+/// Categories listed in the SessionStart context
+const CONTEXT_CATEGORIES: usize = 6;
+
+/// Examples shown per category in the SessionStart context
+const CONTEXT_EXAMPLES: usize = 2;
+
+/// Compiled rules of one rule set
+#[derive(Debug, Clone, Serialize)]
+pub struct RuleSetStatus {
+    pub name: &'static str,
+    pub rules: usize,
+    /// Whether every pattern compiled
+    pub compiled: bool,
+}
+
+/// Engine status as reported by `/healthz`
+#[derive(Debug, Clone, Serialize)]
+pub struct Health {
+    /// "enforcing", "warn-only", "disabled" or "degraded" (a rule set failed
+    /// to compile, or the bash grammar failed its check)
+    pub status: &'static str,
+    pub version: &'static str,
+    /// Pinned bash grammar, and why AST analysis is off if it is
+    pub grammar: GrammarStatus,
+    pub safety_level: &'static str,
+    /// Hash of the merged config, if it was loaded from files
+    pub config_hash: Option<String>,
+    pub rule_sets: Vec<RuleSetStatus>,
+    /// When the engine last (re)loaded its config
+    pub loaded_at: DateTime<Utc>,
+}
+
+impl Health {
+    /// Report on an engine
+    pub fn of(engine: &SecurityEngine) -> Self {
+        let rule_sets: Vec<RuleSetStatus> = engine
+            .rule_counts()
+            .into_iter()
+            .map(|(name, rules, compiled)| RuleSetStatus { name, rules, compiled })
+            .collect();
+
+        let status = if engine.is_disabled() {
+            "disabled"
+        } else if rule_sets.iter().any(|s| !s.compiled) || !grammar::status().is_usable() {
+            "degraded"
+        } else if engine.is_warn_only() {
+            "warn-only"
+        } else {
+            "enforcing"
+        };
+
+        Self {
+            status,
+            version: env!("CARGO_PKG_VERSION"),
+            grammar: grammar::status().clone(),
+            safety_level: engine.safety_level().as_str(),
+            config_hash: engine.config().source_hash.clone(),
+            rule_sets,
+            loaded_at: engine.loaded_at(),
+        }
+    }
+
+    /// Whether the engine blocks what its rules match
+    pub fn is_ready(&self) -> bool {
+        self.status == "enforcing"
+    }
+
+    /// Serialize to a JSON string
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_else(|_| "{}".to_string())
+    }
+}
+
+/// Session warm-up report for the SessionStart hook
+#[derive(Debug, Clone)]
+pub struct Precheck {
+    pub health: Health,
+    /// Entries in the merged allowlists
+    pub allowlist_entries: usize,
+    /// Why the audit log can't be written, if it is enabled and can't be
+    pub audit_error: Option<String>,
+    /// Categories with the most active blocking rules, most first
+    pub blocked_categories: Vec<BlockedCategory>,
+}
+
+/// Active blocking rules of one category
+#[derive(Debug, Clone)]
+pub struct BlockedCategory {
+    pub category: Category,
+    pub rules: usize,
+    /// A few inputs the category's rules deny
+    pub examples: Vec<&'static str>,
+}
+
+/// Built-in tool rules that deny (not warn or ask) at the engine's
+/// levels, grouped by category with the largest groups first
+fn blocked_categories(engine: &SecurityEngine) -> Vec<BlockedCategory> {
+    let config = engine.config();
+    let mut groups: Vec<BlockedCategory> = Vec::new();
+    for (set, rule) in rules::builtin_rules() {
+        let level = match set {
+            "bash" | "exfiltration" | "shell-init" | "sql" => config.safety_level_for("Bash"),
+            "prompt" => continue,
+            _ => engine.safety_level(),
+        };
+        let denies = matches!(config.rule_action(rule.id), None | Some(RuleAction::Deny));
+        if !level.includes(rule.level) || !denies || config.asks_at_level(rule.id) {
+            continue;
+        }
+        let group = match groups.iter_mut().position(|g| g.category == rule.category) {
+            Some(i) => &mut groups[i],
+            None => {
+                groups.push(BlockedCategory { category: rule.category, rules: 0, examples: Vec::new() });
+                groups.last_mut().unwrap()
+            }
+        };
+        group.rules += 1;
+        if group.examples.len() < CONTEXT_EXAMPLES {
+            group.examples.extend(rule.should_match.first());
+        }
+    }
+    groups.sort_by_key(|g| std::cmp::Reverse(g.rules));
+    groups.truncate(CONTEXT_CATEGORIES);
+    groups
+}
+
+impl Precheck {
+    /// Check an engine that was just compiled for the session's config
+    pub fn run(engine: &SecurityEngine) -> Self {
+        let config = engine.config();
+        let audit_error = match config.audit_path() {
+            Some(path) if config.general.audit_log => JsonlFileSink::try_open(&path)
+                .err()
+                .map(|e| format!("{}: {}", path.display(), e)),
+            _ => None,
+        };
+
+        Self {
+            health: Health::of(engine),
+            allowlist_entries: engine.allowlist().len(),
+            audit_error,
+            blocked_categories: blocked_categories(engine),
+        }
+    }
+
+    /// Context for Claude: the active level and what it will deny, or
+    /// `None` when nothing is checked
+    pub fn context(&self) -> Option<String> {
+        let lead = match self.health.status {
+            "disabled" => return None,
+            "warn-only" => "runs in warn-only mode: nothing is blocked, but operations like these are reported",
+            _ => "deny operations like these",
+        };
+        let mut context = format!(
+            "[guardrails] Safety hooks (level {}) {}:",
+            self.health.safety_level, lead
+        );
+        for group in &self.blocked_categories {
+            let examples: Vec<String> = group.examples.iter().map(|e| format!("`{}`", e)).collect();
+            context.push_str(&format!(
+                "\n- {} ({} rules), e.g. {}",
+                group.category.as_str(),
+                group.rules,
+                examples.join(", ")
+            ));
+        }
+        context.push_str(
+            "\nChoose a safer alternative up front, or ask the user to run such an operation themselves. \
+             Rewording, encoding or splitting a denied command is denied too.",
+        );
+        Some(context)
+    }
+
+    /// One-line summary, e.g. `guardrails active: level=high, 63 rules, allowlist 4 entries`
+    pub fn summary(&self) -> String {
+        let state = match self.health.status {
+            "enforcing" => "active",
+            "warn-only" => "active (warn-only, nothing is blocked)",
+            "disabled" => "DISABLED (GUARDRAILS_DISABLED is set)",
+            _ if !self.health.grammar.is_usable() => "DEGRADED (bash grammar unusable, regex fallback only)",
+            _ => "DEGRADED (a rule set failed to compile)",
+        };
+        let rules: usize = self.health.rule_sets.iter().map(|s| s.rules).sum();
+        let mut summary = format!(
+            "guardrails {}: level={}, {} rules, allowlist {} {}",
+            state,
+            self.health.safety_level,
+            rules,
+            self.allowlist_entries,
+            if self.allowlist_entries == 1 { "entry" } else { "entries" }
+        );
+        if let Some(ref error) = self.audit_error {
+            summary.push_str(&format!("; audit log not writable ({})", error));
+        }
+        summary
+    }
+}
+
+/// Answer an HTTP request line (`GET /healthz HTTP/1.1`) with a full
+/// HTTP/1.1 response
+///
+/// `/healthz` is 200 whenever the engine is up; `/readyz` is 503 unless it
+/// is enforcing. Returns `None` if the line is not an HTTP request.
+pub fn respond(request_line: &str, engine: &SecurityEngine) -> Option<String> {
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next()?;
+    let path = parts.next()?;
+    if !parts.next()?.starts_with("HTTP/") {
+        return None;
+    }
+
+    let (code, body) = match (method, path) {
+        ("GET" | "HEAD", "/healthz") => (200, Health::of(engine).to_json()),
+        ("GET" | "HEAD", "/readyz") => {
+            let health = Health::of(engine);
+            let code = if health.is_ready() { 200 } else { 503 };
+            (code, health.to_json())
+        }
+        ("GET" | "HEAD", _) => (404, r#"{"error":"not found"}"#.to_string()),
+        _ => (405, r#"{"error":"method not allowed"}"#.to_string()),
+    };
+    let reason = match code {
+        200 => "OK",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        _ => "Service Unavailable",
+    };
+    let body = if method == "HEAD" { "" } else { body.as_str() };
+    Some(format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        code,
+        reason,
+        body.len(),
+        body
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{Config, SafetyLevel};
+
+    #[test]
+    fn test_health_report() {
+        let engine = SecurityEngine::new(Config::default());
+        let health = Health::of(&engine);
+        assert!(health.rule_sets.iter().all(|s| s.compiled && s.rules > 0));
+        assert!(health.rule_sets.iter().any(|s| s.name == "bash"));
+        assert!(health.config_hash.is_none());
+        assert_eq!(health.status, "enforcing");
+        assert!(health.grammar.is_usable());
+
+        let value: serde_json::Value = serde_json::from_str(&health.to_json()).unwrap();
+        assert_eq!(value["safety_level"], "high");
+        assert!(value["loaded_at"].is_string());
+        assert_eq!(value["grammar"]["version"].as_str(), grammar::PINNED_VERSION);
+    }
+
+    #[test]
+    fn test_http_responses() {
+        let engine = SecurityEngine::new(Config::default());
+        let ok = respond("GET /healthz HTTP/1.1", &engine).unwrap();
+        assert!(ok.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(ok.contains(r#""rule_sets":"#));
+
+        assert!(respond("GET /nope HTTP/1.1", &engine).unwrap().starts_with("HTTP/1.1 404"));
+        assert!(respond("POST /healthz HTTP/1.1", &engine).unwrap().starts_with("HTTP/1.1 405"));
+        assert!(respond(r#"{"tool_name":"Bash"}"#, &engine).is_none());
+    }
+
+    #[test]
+    fn test_precheck_summary() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = Config::default();
+        config.general.audit_log = true;
+        config.general.audit_path = Some(dir.path().join("audit.jsonl").display().to_string());
+        let precheck = Precheck::run(&SecurityEngine::new(config.clone()));
+        assert!(precheck.audit_error.is_none());
+        assert!(precheck.summary().starts_with("guardrails active: level=high, "));
+
+        // A directory where the log file should be can't be opened for append
+        config.general.audit_path = Some(dir.path().display().to_string());
+        let precheck = Precheck::run(&SecurityEngine::new(config));
+        assert!(precheck.summary().contains("audit log not writable"));
+    }
+
+    #[test]
+    fn test_precheck_context() {
+        let mut config = Config::default();
+        let context = Precheck::run(&SecurityEngine::new(config.clone())).context().unwrap();
+        assert!(context.starts_with("[guardrails] Safety hooks (level high) deny"));
+        assert!(context.contains("\n- filesystem ("));
+        assert!(!context.contains("prompt-injection"));
+
+        // Rules that ask instead are left out; only `guardrails-*` rules
+        // always deny
+        config.rules.ask_levels = vec![SafetyLevel::Critical, SafetyLevel::High];
+        let precheck = Precheck::run(&SecurityEngine::new(config));
+        assert!(precheck.blocked_categories.iter().any(|g| g.category == Category::Guardrails));
+        assert!(precheck.blocked_categories.iter().all(|g| g.category != Category::Filesystem));
+    }
+}
@@ -4,8 +4,10 @@
 
 use serde::Deserialize;
 
+use crate::output::Decision;
+
 /// Main input structure from Claude Code hooks
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct HookInput {
     /// Name of the tool being invoked (e.g., "Bash", "Read", "Edit", "Write")
     pub tool_name: String,
@@ -20,6 +22,198 @@ pub struct HookInput {
     /// Hook event name (e.g., "PreToolUse")
     #[serde(default)]
     pub hook_event_name: Option<String>,
+
+    /// Working directory of the session (the project root)
+    #[serde(default)]
+    pub cwd: Option<String>,
+
+    /// Decision already reached by an earlier hook in the chain, if known
+    #[serde(default)]
+    pub previous_decision: Option<PreviousDecision>,
+
+    /// The tool's result (PostToolUse only); its shape varies by tool
+    #[serde(default)]
+    pub tool_response: Option<serde_json::Value>,
+}
+
+/// Which hook event an input is for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookEvent {
+    /// Before the tool runs (the default when the event name is missing)
+    PreToolUse,
+    /// After the tool ran, with `tool_response` filled in
+    PostToolUse,
+    /// A prompt submitted by the user (see [`PromptInput`])
+    UserPromptSubmit,
+    /// A session starting or resuming (see [`SessionInput`])
+    SessionStart,
+    /// Claude finishing its response (see [`StopInput`])
+    Stop,
+    /// A subagent finishing its task (see [`StopInput`])
+    SubagentStop,
+}
+
+impl HookEvent {
+    /// Event for a `hook_event_name`, defaulting to PreToolUse
+    pub fn from_name(name: Option<&str>) -> Self {
+        match name {
+            Some("PostToolUse") => HookEvent::PostToolUse,
+            Some("UserPromptSubmit") => HookEvent::UserPromptSubmit,
+            Some("SessionStart") => HookEvent::SessionStart,
+            Some("Stop") => HookEvent::Stop,
+            Some("SubagentStop") => HookEvent::SubagentStop,
+            _ => HookEvent::PreToolUse,
+        }
+    }
+
+    /// Event named in raw hook JSON, read before choosing an input type
+    pub fn from_json(json: &str) -> Self {
+        #[derive(Deserialize)]
+        struct Peek {
+            hook_event_name: Option<String>,
+        }
+        let name = serde_json::from_str::<Peek>(json).ok().and_then(|p| p.hook_event_name);
+        Self::from_name(name.as_deref())
+    }
+}
+
+/// Input for the UserPromptSubmit hook, which carries a prompt instead of a tool call
+#[derive(Debug, Clone, Deserialize)]
+pub struct PromptInput {
+    /// The prompt the user submitted
+    pub prompt: String,
+
+    /// Optional session identifier
+    #[serde(default)]
+    pub session_id: Option<String>,
+
+    /// Hook event name ("UserPromptSubmit")
+    #[serde(default)]
+    pub hook_event_name: Option<String>,
+
+    /// Working directory of the session
+    #[serde(default)]
+    pub cwd: Option<String>,
+}
+
+impl PromptInput {
+    /// Parse input from JSON string
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    /// Get a summary of the prompt for logging
+    pub fn summary(&self) -> String {
+        let mut chars = self.prompt.chars();
+        let truncated: String = chars.by_ref().take(100).collect();
+        if chars.next().is_some() {
+            format!("Prompt: {}...", truncated)
+        } else {
+            format!("Prompt: {}", truncated)
+        }
+    }
+}
+
+/// Input for the SessionStart hook
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SessionInput {
+    /// Optional session identifier
+    #[serde(default)]
+    pub session_id: Option<String>,
+
+    /// How the session started: "startup", "resume", "clear" or "compact"
+    #[serde(default)]
+    pub source: Option<String>,
+
+    /// Working directory of the session
+    #[serde(default)]
+    pub cwd: Option<String>,
+}
+
+impl SessionInput {
+    /// Parse input from JSON string
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+}
+
+/// Input Claude Code pipes to a `statusLine` command
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct StatusLineInput {
+    /// Optional session identifier
+    #[serde(default)]
+    pub session_id: Option<String>,
+
+    /// Working directory of the session
+    #[serde(default)]
+    pub cwd: Option<String>,
+}
+
+impl StatusLineInput {
+    /// Parse input from JSON string
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+}
+
+/// Input for the Stop and SubagentStop hooks
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct StopInput {
+    /// Optional session identifier
+    #[serde(default)]
+    pub session_id: Option<String>,
+
+    /// Path of the session's JSONL transcript
+    #[serde(default)]
+    pub transcript_path: Option<String>,
+
+    /// Whether Claude is already continuing because of a Stop hook
+    #[serde(default)]
+    pub stop_hook_active: bool,
+
+    /// Working directory of the session
+    #[serde(default)]
+    pub cwd: Option<String>,
+}
+
+impl StopInput {
+    /// Parse input from JSON string
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+}
+
+/// A decision made by an upstream hook when several PreToolUse hooks run
+#[derive(Debug, Clone, Deserialize)]
+pub struct PreviousDecision {
+    /// Permission decision: "allow", "deny" or "ask"
+    pub decision: String,
+
+    /// Which hook produced it (for logging)
+    #[serde(default)]
+    pub source: Option<String>,
+
+    /// Upstream reason, if provided
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+impl PreviousDecision {
+    /// Whether the upstream hook blocked the call
+    pub fn is_deny(&self) -> bool {
+        self.decision.eq_ignore_ascii_case("deny")
+    }
+
+    /// The upstream decision on the [`Decision::strictness`] scale; anything
+    /// unrecognized ranks as an allow, so it never suppresses ours
+    pub fn strictness(&self) -> u8 {
+        let decision = match self.decision.to_ascii_lowercase().as_str() {
+            "deny" => Decision::deny("", ""),
+            "ask" => Decision::ask("", ""),
+            _ => Decision::allow(""),
+        };
+        decision.strictness()
+    }
 }
 
 /// Tool-specific input variants
@@ -46,18 +240,45 @@ pub enum ToolInput {
         new_string: String,
     },
 
+    /// Several edits to one file, applied in order
+    MultiEdit {
+        file_path: String,
+        edits: Vec<FileEdit>,
+    },
+
     /// File write operation
     Write {
         file_path: String,
         content: String,
     },
 
+    /// Fetch a URL and process it with a prompt
+    WebFetch {
+        url: String,
+        #[allow(dead_code)]
+        prompt: Option<String>,
+    },
+
+    /// Web search query
+    WebSearch {
+        query: String,
+    },
+
     /// Unknown tool - pass through
     Unknown {
         raw: serde_json::Value,
     },
 }
 
+/// One replacement within a MultiEdit
+#[derive(Debug, Clone, Deserialize)]
+pub struct FileEdit {
+    pub old_string: String,
+    pub new_string: String,
+    #[serde(default)]
+    pub replace_all: bool,
+}
+
 impl<'de> Deserialize<'de> for ToolInput {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -91,6 +312,15 @@ impl<'de> Deserialize<'de> for ToolInput {
                     });
                 }
 
+                // MultiEdit has an edits array
+                if let Some(edits) = obj.get("edits").filter(|v| v.is_array()) {
+                    let edits = serde_json::from_value(edits.clone()).map_err(serde::de::Error::custom)?;
+                    return Ok(ToolInput::MultiEdit {
+                        file_path: file_path.to_string(),
+                        edits,
+                    });
+                }
+
                 // Write has content
                 if let Some(content) = obj.get("content").and_then(|v| v.as_str()) {
                     return Ok(ToolInput::Write {
@@ -104,6 +334,21 @@ impl<'de> Deserialize<'de> for ToolInput {
                     file_path: file_path.to_string(),
                 });
             }
+
+            // WebFetch has a url
+            if let Some(url) = obj.get("url").and_then(|v| v.as_str()) {
+                return Ok(ToolInput::WebFetch {
+                    url: url.to_string(),
+                    prompt: obj.get("prompt").and_then(|v| v.as_str()).map(String::from),
+                });
+            }
+
+            // WebSearch has a query
+            if let Some(query) = obj.get("query").and_then(|v| v.as_str()) {
+                return Ok(ToolInput::WebSearch {
+                    query: query.to_string(),
+                });
+            }
         }
 
         // Unknown tool format - preserve raw data
@@ -111,12 +356,113 @@ impl<'de> Deserialize<'de> for ToolInput {
     }
 }
 
+impl ToolInput {
+    /// Serialize back to the tool_input JSON shape Claude Code expects
+    pub fn to_value(&self) -> serde_json::Value {
+        match self {
+            ToolInput::Bash {
+                command,
+                description,
+                timeout,
+            } => {
+                let mut obj = serde_json::json!({ "command": command });
+                if let Some(description) = description {
+                    obj["description"] = description.clone().into();
+                }
+                if let Some(timeout) = timeout {
+                    obj["timeout"] = (*timeout).into();
+                }
+                obj
+            }
+            ToolInput::Read { file_path } => serde_json::json!({ "file_path": file_path }),
+            ToolInput::Edit {
+                file_path,
+                old_string,
+                new_string,
+            } => serde_json::json!({
+                "file_path": file_path,
+                "old_string": old_string,
+                "new_string": new_string,
+            }),
+            ToolInput::MultiEdit { file_path, edits } => {
+                let edits: Vec<_> = edits
+                    .iter()
+                    .map(|edit| {
+                        let mut obj = serde_json::json!({
+                            "old_string": edit.old_string,
+                            "new_string": edit.new_string,
+                        });
+                        if edit.replace_all {
+                            obj["replace_all"] = true.into();
+                        }
+                        obj
+                    })
+                    .collect();
+                serde_json::json!({ "file_path": file_path, "edits": edits })
+            }
+            ToolInput::Write { file_path, content } => {
+                serde_json::json!({ "file_path": file_path, "content": content })
+            }
+            ToolInput::WebFetch { url, prompt } => {
+                let mut obj = serde_json::json!({ "url": url });
+                if let Some(prompt) = prompt {
+                    obj["prompt"] = prompt.clone().into();
+                }
+                obj
+            }
+            ToolInput::WebSearch { query } => serde_json::json!({ "query": query }),
+            ToolInput::Unknown { raw } => raw.clone(),
+        }
+    }
+}
+
 impl HookInput {
     /// Parse input from JSON string
     pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
         serde_json::from_str(json)
     }
 
+    /// The hook event this input is for
+    pub fn event(&self) -> HookEvent {
+        HookEvent::from_name(self.hook_event_name.as_deref())
+    }
+
+    /// All text in `tool_response` (stdout, stderr, file content, ...),
+    /// one string value per line
+    pub fn response_text(&self) -> String {
+        fn collect(value: &serde_json::Value, out: &mut String) {
+            match value {
+                serde_json::Value::String(s) => {
+                    out.push_str(s);
+                    out.push('\n');
+                }
+                serde_json::Value::Array(items) => items.iter().for_each(|v| collect(v, out)),
+                serde_json::Value::Object(map) => map.values().for_each(|v| collect(v, out)),
+                _ => {}
+            }
+        }
+
+        let mut text = String::new();
+        if let Some(response) = &self.tool_response {
+            collect(response, &mut text);
+        }
+        text
+    }
+
+    /// The command or file path the tool operates on, if any
+    pub fn subject(&self) -> Option<&str> {
+        match &self.tool_input {
+            ToolInput::Bash { command, .. } => Some(command),
+            ToolInput::Read { file_path }
+            | ToolInput::Edit { file_path, .. }
+            | ToolInput::MultiEdit { file_path, .. }
+            | ToolInput::Write { file_path, .. } => Some(file_path),
+            ToolInput::WebFetch { url, .. } => Some(url),
+            ToolInput::WebSearch { query } => Some(query),
+            ToolInput::Unknown { .. } => None,
+        }
+    }
+
     /// Get a summary of the input for logging
     pub fn summary(&self) -> String {
         match &self.tool_input {
@@ -130,7 +476,12 @@ impl HookInput {
             }
             ToolInput::Read { file_path } => format!("Read: {}", file_path),
             ToolInput::Edit { file_path, .. } => format!("Edit: {}", file_path),
+            ToolInput::MultiEdit { file_path, edits } => {
+                format!("MultiEdit: {} ({} edits)", file_path, edits.len())
+            }
             ToolInput::Write { file_path, .. } => format!("Write: {}", file_path),
+            ToolInput::WebFetch { url, .. } => format!("WebFetch: {}", url),
+            ToolInput::WebSearch { query } => format!("WebSearch: {}", query),
             ToolInput::Unknown { .. } => format!("Unknown tool: {}", self.tool_name),
         }
     }
@@ -181,6 +532,22 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_multi_edit_input() {
+        let json = r#"{"tool_name":"MultiEdit","tool_input":{"file_path":".env","edits":[{"old_string":"a","new_string":"b"},{"old_string":"c","new_string":"d","replace_all":true}]}}"#;
+        let input = HookInput::from_json(json).unwrap();
+        assert_eq!(input.summary(), "MultiEdit: .env (2 edits)");
+        match &input.tool_input {
+            ToolInput::MultiEdit { file_path, edits } => {
+                assert_eq!(file_path, ".env");
+                assert_eq!(edits[1].new_string, "d");
+                assert!(!edits[0].replace_all && edits[1].replace_all);
+            }
+            _ => panic!("Expected MultiEdit input"),
+        }
+        assert_eq!(input.tool_input.to_value()["edits"][1]["replace_all"], true);
+    }
+
     #[test]
     fn test_parse_write_input() {
         let json = r#"{"tool_name":"Write","tool_input":{"file_path":"test.txt","content":"hello world"}}"#;
@@ -195,6 +562,76 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_web_inputs() {
+        let json = r#"{"tool_name":"WebFetch","tool_input":{"url":"https://example.com","prompt":"summarize"}}"#;
+        let input = HookInput::from_json(json).unwrap();
+        match &input.tool_input {
+            ToolInput::WebFetch { url, prompt } => {
+                assert_eq!(url, "https://example.com");
+                assert_eq!(prompt.as_deref(), Some("summarize"));
+            }
+            _ => panic!("Expected WebFetch input"),
+        }
+
+        let json = r#"{"tool_name":"WebSearch","tool_input":{"query":"rust regex"}}"#;
+        let input = HookInput::from_json(json).unwrap();
+        assert_eq!(input.subject(), Some("rust regex"));
+    }
+
+    #[test]
+    fn test_tool_input_roundtrip() {
+        let json = r#"{"tool_name":"Bash","tool_input":{"command":"ls","timeout":5000}}"#;
+        let input = HookInput::from_json(json).unwrap();
+        let value = input.tool_input.to_value();
+        assert_eq!(value["command"], "ls");
+        assert_eq!(value["timeout"], 5000);
+        assert!(value.get("description").is_none());
+    }
+
+    #[test]
+    fn test_parse_post_tool_use() {
+        let json = r#"{"tool_name":"Bash","hook_event_name":"PostToolUse","tool_input":{"command":"cat creds"},"tool_response":{"stdout":"key=abc","stderr":"","interrupted":false}}"#;
+        let input = HookInput::from_json(json).unwrap();
+        assert_eq!(input.event(), HookEvent::PostToolUse);
+        assert_eq!(input.response_text().trim(), "key=abc");
+
+        let json = r#"{"tool_name":"Bash","tool_input":{"command":"ls"}}"#;
+        let input = HookInput::from_json(json).unwrap();
+        assert_eq!(input.event(), HookEvent::PreToolUse);
+        assert!(input.response_text().is_empty());
+    }
+
+    #[test]
+    fn test_parse_user_prompt_submit() {
+        let json = r#"{"session_id":"s1","hook_event_name":"UserPromptSubmit","prompt":"fix the build"}"#;
+        assert_eq!(HookEvent::from_json(json), HookEvent::UserPromptSubmit);
+        let input = PromptInput::from_json(json).unwrap();
+        assert_eq!(input.prompt, "fix the build");
+        assert_eq!(input.summary(), "Prompt: fix the build");
+
+        assert_eq!(HookEvent::from_json(r#"{"tool_name":"Bash"}"#), HookEvent::PreToolUse);
+        assert_eq!(HookEvent::from_json("not json"), HookEvent::PreToolUse);
+    }
+
+    #[test]
+    fn test_parse_stop() {
+        let json = r#"{"session_id":"s1","hook_event_name":"SubagentStop","transcript_path":"/tmp/s1.jsonl","stop_hook_active":false}"#;
+        assert_eq!(HookEvent::from_json(json), HookEvent::SubagentStop);
+        let input = StopInput::from_json(json).unwrap();
+        assert_eq!(input.transcript_path.as_deref(), Some("/tmp/s1.jsonl"));
+        assert!(!input.stop_hook_active);
+    }
+
+    #[test]
+    fn test_parse_previous_decision() {
+        let json = r#"{"tool_name":"Bash","tool_input":{"command":"ls"},"previous_decision":{"decision":"deny","source":"corp-policy"}}"#;
+        let input = HookInput::from_json(json).unwrap();
+        let previous = input.previous_decision.unwrap();
+        assert!(previous.is_deny());
+        assert_eq!(previous.source.as_deref(), Some("corp-policy"));
+    }
+
     #[test]
     fn test_parse_with_session_id() {
         let json = r#"{"tool_name":"Bash","tool_input":{"command":"ls"},"session_id":"abc123"}"#;
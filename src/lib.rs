@@ -15,7 +15,7 @@
 //! # Example
 //!
 //! ```
-//! use claude_guardrails::{Config, SecurityEngine, HookInput};
+//! use claude_guardrails::prelude::*;
 //!
 //! let config = Config::default();
 //! let engine = SecurityEngine::new(config);
@@ -26,17 +26,56 @@
 //! let decision = engine.check(&hook_input);
 //! assert!(decision.is_deny());
 //! ```
+//!
+//! # Stability
+//!
+//! The [`prelude`] is the supported API for embedders, along with the
+//! `config`, `input` and `output` types it names. The other modules are
+//! public because the `claude-guardrails` binary and the tests use them;
+//! they are hidden from the docs and change between minor releases.
 
-pub mod audit;
 pub mod config;
-pub mod engine;
 pub mod input;
 pub mod output;
+pub mod prelude;
+
+#[doc(hidden)]
+pub mod audit;
+#[doc(hidden)]
+pub mod daemon;
+#[doc(hidden)]
+pub mod engine;
+#[doc(hidden)]
+pub mod harness;
+#[doc(hidden)]
+pub mod health;
+#[doc(hidden)]
+pub mod lint_script;
+#[doc(hidden)]
 pub mod parser;
+#[doc(hidden)]
+pub mod providers;
+#[doc(hidden)]
+pub mod remote_ask;
+#[doc(hidden)]
 pub mod rules;
+#[doc(hidden)]
+pub mod scan;
+#[doc(hidden)]
+pub mod service;
+#[doc(hidden)]
+pub mod signing;
+#[doc(hidden)]
+pub mod snapshot;
+#[doc(hidden)]
+pub mod statusline;
+#[doc(hidden)]
+pub mod telemetry;
+#[doc(hidden)]
+pub mod transcript;
 
 // Re-exports for convenience
 pub use config::{Config, SafetyLevel};
 pub use engine::SecurityEngine;
 pub use input::{HookInput, ToolInput};
-pub use output::{Decision, HookOutput};
+pub use output::{AllowSource, Decision, HookOutput};
@@ -0,0 +1,232 @@
+//! Checking human-authored shell scripts (`claude-guardrails lint-script`)
+//!
+//! Runs script files through the same parser and rules the agent's Bash
+//! commands go through, so a team can gate the scripts people commit (in a
+//! pre-commit hook or CI) with the policy it already has. Each top-level
+//! statement is checked on its own and reported with its line; a script
+//! the bash grammar can't parse is checked line by line (joining `\`
+//! continuations) instead. Denials from Critical and High rules are
+//! errors; anything else the policy flags is reported as a warning.
+
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::config::SafetyLevel;
+use crate::engine::SecurityEngine;
+use crate::output::Decision;
+use crate::parser::grammar;
+use crate::rules::severity_of;
+
+/// One statement the policy flags
+#[derive(Debug, Clone, Serialize)]
+pub struct ScriptFinding {
+    pub path: String,
+    /// 1-based line the statement starts on
+    pub line: usize,
+    /// "error" (fails the check) or "warning"
+    pub severity: &'static str,
+    /// Level of the rule that matched ("critical", "high", "strict")
+    pub level: &'static str,
+    pub rule_id: String,
+    pub reason: String,
+    /// The statement's first line
+    pub statement: String,
+}
+
+/// Result of checking a set of scripts
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ScriptReport {
+    /// Scripts checked
+    pub files: usize,
+    pub findings: Vec<ScriptFinding>,
+    /// Files that couldn't be read, with the error
+    pub unreadable: Vec<(String, String)>,
+}
+
+impl ScriptReport {
+    /// Whether anything fails the check (an error finding or an unreadable file)
+    pub fn failed(&self) -> bool {
+        !self.unreadable.is_empty() || self.findings.iter().any(|f| f.severity == "error")
+    }
+
+    /// `path:line: severity [LEVEL] rule: reason` per finding, then a summary
+    pub fn to_text(&self) -> String {
+        let mut out = String::new();
+        for (path, error) in &self.unreadable {
+            out.push_str(&format!("{}: error: {}\n", path, error));
+        }
+        for f in &self.findings {
+            out.push_str(&format!(
+                "{}:{}: {} [{}] {}: {}\n    {}\n",
+                f.path,
+                f.line,
+                f.severity,
+                f.level.to_uppercase(),
+                f.rule_id,
+                f.reason,
+                f.statement
+            ));
+        }
+        let errors = self.findings.iter().filter(|f| f.severity == "error").count();
+        out.push_str(&format!(
+            "{} {} checked: {} {}, {} {}\n",
+            self.files,
+            if self.files == 1 { "script" } else { "scripts" },
+            errors,
+            if errors == 1 { "error" } else { "errors" },
+            self.findings.len() - errors,
+            if self.findings.len() - errors == 1 { "warning" } else { "warnings" }
+        ));
+        out
+    }
+
+    /// Serialize to a JSON string
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_else(|_| "{}".to_string())
+    }
+}
+
+/// Top-level statements of a script, with the line each starts on
+///
+/// Falls back to logical lines when the script doesn't parse cleanly.
+pub fn statements(source: &str) -> Vec<(usize, String)> {
+    let tree = grammar::parser().ok().and_then(|mut parser| parser.parse(source, None));
+    let Some(tree) = tree.filter(|tree| !tree.root_node().has_error()) else {
+        return logical_lines(source);
+    };
+    let root = tree.root_node();
+    let mut cursor = root.walk();
+    root.named_children(&mut cursor)
+        .filter(|node| node.kind() != "comment")
+        .filter_map(|node| {
+            let text = source.get(node.byte_range())?;
+            Some((node.start_position().row + 1, text.to_string()))
+        })
+        .collect()
+}
+
+/// Non-blank, non-comment lines with `\` continuations joined
+fn logical_lines(source: &str) -> Vec<(usize, String)> {
+    let mut lines = Vec::new();
+    let mut pending: Option<(usize, String)> = None;
+    for (i, line) in source.lines().enumerate() {
+        let (start, mut text) = pending.take().unwrap_or((i + 1, String::new()));
+        if text.is_empty() && (line.trim().is_empty() || line.trim_start().starts_with('#')) {
+            continue;
+        }
+        match line.strip_suffix('\\') {
+            Some(rest) => {
+                text.push_str(rest);
+                pending = Some((start, text));
+            }
+            None => {
+                text.push_str(line);
+                lines.push((start, text));
+            }
+        }
+    }
+    lines.extend(pending);
+    lines
+}
+
+/// Check one script's source; findings are reported against `path`
+pub fn check_source(path: &str, source: &str, engine: &SecurityEngine) -> Vec<ScriptFinding> {
+    statements(source)
+        .into_iter()
+        .filter_map(|(line, statement)| {
+            let (rule_id, reason, denied) = match engine.check_bash_with_overrides(&statement) {
+                Decision::Allow { .. } => return None,
+                Decision::Deny { rule_id, reason } | Decision::Ask { rule_id, reason } => (rule_id, reason, true),
+                Decision::Warn { rule_id, reason } => (rule_id, reason, false),
+            };
+            let level = severity_of(&rule_id);
+            let error = denied && matches!(level, SafetyLevel::Critical | SafetyLevel::High);
+            Some(ScriptFinding {
+                path: path.to_string(),
+                line,
+                severity: if error { "error" } else { "warning" },
+                level: level.as_str(),
+                rule_id,
+                reason,
+                statement: statement.lines().next().unwrap_or_default().trim().to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Check script files
+pub fn check_files(paths: &[&Path], engine: &SecurityEngine) -> ScriptReport {
+    let mut report = ScriptReport::default();
+    for path in paths {
+        let name = path.display().to_string();
+        match std::fs::read(path) {
+            Ok(bytes) => {
+                report.files += 1;
+                let source = String::from_utf8_lossy(&bytes);
+                report.findings.extend(check_source(&name, &source, engine));
+            }
+            Err(e) => report.unreadable.push((name, e.to_string())),
+        }
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{Config, RuleAction};
+
+    const SCRIPT: &str = r#"#!/bin/bash
+set -euo pipefail
+# clean up old builds
+rm -rf build/
+
+if [ -n "$FORCE" ]; then
+    git push --force origin main
+fi
+
+chmod -R 777 \
+    /
+"#;
+
+    #[test]
+    fn test_statements() {
+        let lines: Vec<usize> = statements(SCRIPT).iter().map(|(line, _)| *line).collect();
+        assert_eq!(lines, [2, 4, 6, 10]);
+        assert_eq!(logical_lines("a \\\n  b\n\n# c\nd").iter().map(|(l, _)| *l).collect::<Vec<_>>(), [1, 5]);
+        assert_eq!(logical_lines("a \\\n  b")[0].1, "a   b");
+    }
+
+    #[test]
+    fn test_findings_by_line() {
+        let engine = SecurityEngine::new(Config::default());
+        let findings = check_source("deploy.sh", SCRIPT, &engine);
+        let found: Vec<(usize, &str)> = findings.iter().map(|f| (f.line, f.severity)).collect();
+        assert_eq!(found, [(6, "error"), (10, "error")]);
+        assert_eq!(findings[0].statement, r#"if [ -n "$FORCE" ]; then"#);
+
+        // Rules set to warn are reported without failing
+        let mut config = Config::default();
+        for f in &findings {
+            config.rules.overrides.insert(f.rule_id.clone(), RuleAction::Warn);
+        }
+        let report = ScriptReport {
+            files: 1,
+            findings: check_source("deploy.sh", SCRIPT, &SecurityEngine::new(config)),
+            ..Default::default()
+        };
+        assert_eq!(report.findings.len(), 2);
+        assert!(!report.failed());
+        assert!(report.to_text().ends_with("1 script checked: 0 errors, 2 warnings\n"));
+    }
+
+    #[test]
+    fn test_unparsable_script_checked_by_line() {
+        let engine = SecurityEngine::new(Config::default());
+        let findings = check_source("x.sh", "echo 'unterminated\nrm -rf /\n", &engine);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].line, 2);
+        assert_eq!(findings[0].level, "critical");
+    }
+}
@@ -13,22 +13,50 @@
 //!
 //! # Dry-run mode (show what would be blocked)
 //! claude-guardrails --dry-run
+//!
+//! # Weekly Markdown digest of the audit log
+//! claude-guardrails digest --since 7d --output digest.md
 //! ```
 
 use std::env;
-use std::io::{self, BufRead, Write};
+use std::io::{self, BufRead, Read, Write};
+use std::path::Path;
 
 use claude_guardrails::{
-    audit::AuditLogger,
+    audit::{digest::Digest, reader, replay, AuditEntry, AuditLogger, LogLevel},
     config::{Config, SafetyLevel},
-    engine::SecurityEngine,
-    input::HookInput,
-    output::HookOutput,
+    daemon::EngineCache,
+    engine::{bench, compare, download, provenance, SecurityEngine},
+    health::{self, Health, Precheck},
+    input::{HookEvent, HookInput, PromptInput, SessionInput, StatusLineInput, StopInput, ToolInput},
+    lint_script,
+    parser::grammar,
+    output::{format_message, AllowSource, Decision, Hint, HookOutput, HookSchemaVersion},
+    rules::{approvals, catalog, hints, lint, manifest::Manifest, packs, tokens::TokenPack, Category},
+    scan,
+    remote_ask,
+    service::{ServiceFiles, ServiceKind},
+    signing::TrustedKeys,
+    snapshot,
+    statusline::StatusLine,
+    telemetry::{self, trace::SpanRecord},
+    transcript,
 };
 
+#[cfg(unix)]
+use claude_guardrails::daemon::{self, Request, SessionFlags};
+
 /// Print version information
 fn print_version() {
     println!("claude-guardrails {}", env!("CARGO_PKG_VERSION"));
+    let grammar = grammar::status();
+    match (grammar.version, &grammar.error) {
+        (Some(version), None) => println!("tree-sitter-bash {}", version),
+        (_, error) => println!(
+            "tree-sitter-bash unusable, regex fallback only: {}",
+            error.as_deref().unwrap_or("not built in")
+        ),
+    }
 }
 
 /// Print help message
@@ -38,6 +66,62 @@ fn print_help() {
 
 USAGE:
     claude-guardrails [OPTIONS]
+    claude-guardrails <SUBCOMMAND> [ARGS]
+
+SUBCOMMANDS:
+    digest [--since 7d] [--output FILE] [--log FILE]
+                            Summarize audit activity as Markdown
+    audit tail [-n 20] | audit grep PATTERN | audit stats
+                            Query the audit log; filter with --since 7d,
+                            --session ID, --level BLOCKED, --rule ID, --tool NAME
+                            (--json prints matching entries as JSONL)
+    audit flush             Send the remote audit spool now (--features remote)
+    replay [--log FILE] [--json]
+                            Re-check logged inputs against the current config and
+                            list decisions that would change (takes audit filters)
+    telemetry <show | submit | reset>
+                            Opt-in rule hit counters ([telemetry] in config)
+    approve --command CMD [--ttl 15m]
+                            Issue a one-time token for CMD; append
+                            `# guardrails:allow <token>` to it
+    rules list [--level LEVEL] [--category CATEGORY] [--json]
+                            Built-in rules with level, category, pattern, reason
+    rules diff --from VERSION
+                            Built-in rules added/changed/removed since VERSION
+    rules manifest [--version VERSION]
+                            Print the regenerated rules manifest (maintainers)
+    rules lint [PACK...]    Check rule and token-pack examples (default: the
+                            built-in rules plus configured [tokens] files)
+    rules bench CORPUS [--level L] [--sample N] [--top N] [--json]
+                            Time each bash/exfiltration rule on its own over a
+                            command corpus (every Nth line) and flag slow rules
+    rules packs <list | enable NAME | disable NAME>
+                            Installed rule packs ([packs] dir), toggled in the
+                            packs index.toml
+    healthz                 Print engine status as JSON; exit 1 unless enforcing
+    policy fetch URL OUTPUT [--key KEY]
+                            Download a policy file or token pack and its .minisig,
+                            installing both only if signed by a trusted key
+                            ([signing] trusted_keys; --features webhook)
+    policy verify FILE... [--key KEY]
+                            Check files against their .minisig signatures
+    scan DIR [--content] [--exclude DIR,...] [--max-bytes N] [--json]
+                            Report which files in a repository the agent would be
+                            blocked from reading or writing (--content also runs
+                            the token and entropy scanners if they are off)
+    lint-script FILE... [--level L] [--json]
+                            Check shell scripts with the Bash rules, one finding
+                            per statement; exits 1 on a Critical or High denial
+                            (for pre-commit hooks and CI)
+    explain [--json] <command>
+                            Show every rule, AST finding, unwrapping step and
+                            allowlist entry involved in checking a Bash command
+    statusline              Print a one-line status (level, denies this session,
+                            warn-only) for Claude Code's statusLine command;
+                            reads the status line JSON from stdin
+    daemon install-service [--kind systemd|launchd] [--print]
+                            Install a systemd user unit (socket-activated) or
+                            launchd agent running --daemon
 
 OPTIONS:
     -h, --help              Print this help message
@@ -45,6 +129,15 @@ OPTIONS:
     -l, --safety-level      Safety level: critical, high, strict (default: high)
     -d, --dry-run           Dry-run mode (show what would be blocked but allow)
     -c, --config PATH       Path to config file
+        --no-home           Don't use the home directory: skip the user config
+                            and ignore `~` paths (audit/allowlist must be explicit)
+        --daemon            Serve hook requests on a Unix socket, keeping rules warm
+        --client            Forward the hook input to the daemon (checks in-process
+                            if no daemon is running)
+        --socket PATH       Daemon socket (default: [daemon] socket)
+        --hook-schema-version N
+                            Hook output shape: 2 (permissionDecision, default)
+                            or 1 (legacy decision/reason; ask and rewrites block)
 
 ENVIRONMENT:
     GUARDRAILS_DISABLED=1   Disable all checks (still logs)
@@ -58,7 +151,7 @@ USAGE AS HOOK:
           "type": "command",
           "command": "~/.claude/guardrails/claude-guardrails",
           "timeout": 5000,
-          "tools": ["Bash", "Read", "Edit", "Write"]
+          "tools": ["Bash", "Read", "Edit", "MultiEdit", "Write", "WebFetch", "WebSearch"]
         }}]
       }}
     }}
@@ -73,6 +166,19 @@ struct Args {
     safety_level: Option<SafetyLevel>,
     dry_run: bool,
     config_path: Option<String>,
+    no_home: bool,
+    /// Serve requests on the daemon socket
+    daemon: bool,
+    /// Forward to the daemon, checking in-process if it isn't running
+    client: bool,
+    socket: Option<String>,
+    /// Shape of the hook output (`--hook-schema-version`)
+    hook_schema_version: HookSchemaVersion,
+    /// Log Bash commands the AST pipeline and the regex fallback disagree
+    /// on (`--compare-engines`, for maintainers; not in `--help`)
+    compare_engines: bool,
+    /// Subcommand name and its remaining arguments (if any)
+    subcommand: Option<(String, Vec<String>)>,
 }
 
 impl Args {
@@ -84,25 +190,44 @@ impl Args {
             safety_level: None,
             dry_run: false,
             config_path: None,
+            no_home: false,
+            daemon: false,
+            client: false,
+            socket: None,
+            hook_schema_version: HookSchemaVersion::default(),
+            compare_engines: false,
+            subcommand: None,
         };
 
         let mut i = 1;
         while i < args.len() {
             match args[i].as_str() {
+                "digest" | "audit" | "approve" | "rules" | "healthz" | "daemon" | "explain" | "telemetry" | "replay"
+                | "policy" | "scan" | "statusline" | "lint-script" => {
+                    result.subcommand = Some((args[i].clone(), args[i + 1..].to_vec()));
+                    break;
+                }
                 "-h" | "--help" => result.help = true,
                 "-v" | "--version" => result.version = true,
                 "-d" | "--dry-run" => result.dry_run = true,
-                "-l" | "--safety-level" => {
-                    if i + 1 < args.len() {
-                        i += 1;
-                        result.safety_level = SafetyLevel::from_str(&args[i]);
-                    }
+                "--no-home" => result.no_home = true,
+                "--daemon" => result.daemon = true,
+                "--client" => result.client = true,
+                "--compare-engines" => result.compare_engines = true,
+                "--socket" if i + 1 < args.len() => {
+                    i += 1;
+                    result.socket = Some(args[i].clone());
                 }
-                "-c" | "--config" => {
-                    if i + 1 < args.len() {
-                        i += 1;
-                        result.config_path = Some(args[i].clone());
-                    }
+                arg if arg.starts_with("--socket=") => {
+                    result.socket = Some(arg.trim_start_matches("--socket=").to_string());
+                }
+                "-l" | "--safety-level" if i + 1 < args.len() => {
+                    i += 1;
+                    result.safety_level = SafetyLevel::from_str(&args[i]);
+                }
+                "-c" | "--config" if i + 1 < args.len() => {
+                    i += 1;
+                    result.config_path = Some(args[i].clone());
                 }
                 arg if arg.starts_with("--safety-level=") => {
                     let level = arg.trim_start_matches("--safety-level=");
@@ -112,6 +237,14 @@ impl Args {
                     let path = arg.trim_start_matches("--config=");
                     result.config_path = Some(path.to_string());
                 }
+                "--hook-schema-version" if i + 1 < args.len() => {
+                    i += 1;
+                    result.hook_schema_version = parse_schema_version(&args[i]);
+                }
+                arg if arg.starts_with("--hook-schema-version=") => {
+                    let version = arg.trim_start_matches("--hook-schema-version=");
+                    result.hook_schema_version = parse_schema_version(version);
+                }
                 _ => {}
             }
             i += 1;
@@ -121,102 +254,1431 @@ impl Args {
     }
 }
 
-fn main() {
-    let args = Args::parse();
+/// Parse `--hook-schema-version`, falling back to the current version
+fn parse_schema_version(version: &str) -> HookSchemaVersion {
+    HookSchemaVersion::parse(version).unwrap_or_else(|| {
+        let latest = HookSchemaVersion::default();
+        eprintln!("Warning: unknown hook schema version {}, using {}", version, latest.as_str());
+        latest
+    })
+}
 
-    // Handle help and version
-    if args.help {
-        print_help();
-        return;
+/// Get the value of `--name VALUE` or `--name=VALUE` from subcommand arguments
+fn flag_value(args: &[String], name: &str) -> Option<String> {
+    let prefix = format!("{}=", name);
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if arg == name {
+            return iter.next().cloned();
+        }
+        if let Some(value) = arg.strip_prefix(&prefix) {
+            return Some(value.to_string());
+        }
     }
+    None
+}
 
-    if args.version {
-        print_version();
+/// Resolve the audit log to read: `--log` flag, then the configured path
+fn audit_log_path(args: &[String], config: &Config) -> Option<std::path::PathBuf> {
+    flag_value(args, "--log")
+        .and_then(|p| config.resolve_path(&p))
+        .or_else(|| config.audit_path())
+}
+
+/// `digest` subcommand: summarize audit activity as Markdown
+fn run_digest(args: &[String], config: &Config) -> i32 {
+    let Some(log_path) = audit_log_path(args, config) else {
+        eprintln!("Error: no audit log configured (use --log PATH)");
+        return 1;
+    };
+
+    let since_arg = flag_value(args, "--since").unwrap_or_else(|| "7d".to_string());
+    let Some(since) = reader::since_timestamp(&since_arg, chrono::Utc::now()) else {
+        eprintln!("Error: invalid --since value: {} (expected e.g. 7d, 24h)", since_arg);
+        return 1;
+    };
+
+    let entries = match reader::read_entries(&log_path) {
+        Ok(entries) => entries,
+        Err(e) => {
+            eprintln!("Error: failed to read {}: {}", log_path.display(), e);
+            return 1;
+        }
+    };
+
+    let markdown = Digest::build(&entries, Some(since)).to_markdown();
+
+    match flag_value(args, "--output") {
+        Some(output) => {
+            if let Err(e) = std::fs::write(&output, markdown) {
+                eprintln!("Error: failed to write {}: {}", output, e);
+                return 1;
+            }
+        }
+        None => print!("{}", markdown),
+    }
+
+    0
+}
+
+/// `audit` subcommand: query the JSONL audit log
+///
+/// `tail` prints the last entries, `grep PATTERN` the entries whose summary,
+/// reason or rule matches a regex, and `stats` counts by rule, tool and
+/// level. All accept `--since`, `--session`, `--level`, `--rule`, `--tool`
+/// and `--log`; `tail` and `grep` print JSONL with `--json`.
+/// Send the remote audit spool now
+#[cfg(feature = "remote")]
+fn run_audit_flush(config: &Config) -> i32 {
+    let sink = match AuditLogger::remote_sink(config) {
+        Ok(sink) => sink,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            return 1;
+        }
+    };
+    match sink.flush() {
+        Ok(sent) => {
+            println!("{} entries sent", sent);
+            0
+        }
+        Err(e) => {
+            eprintln!("Error: flush failed, entries stay spooled: {}", e);
+            1
+        }
+    }
+}
+
+#[cfg(not(feature = "remote"))]
+fn run_audit_flush(_config: &Config) -> i32 {
+    eprintln!("Error: built without the remote feature (cargo build --features remote)");
+    1
+}
+
+fn run_audit(args: &[String], config: &Config) -> i32 {
+    let usage = "Usage: claude-guardrails audit <tail [-n N] | grep PATTERN | stats> \
+                 [--since 7d] [--session ID] [--level LEVEL] [--rule ID] [--tool NAME] [--log FILE] [--json]\n       \
+                 claude-guardrails audit flush";
+    let Some(command) = args.first().map(String::as_str) else {
+        eprintln!("{}", usage);
+        return 1;
+    };
+    if command == "flush" {
+        return run_audit_flush(config);
+    }
+
+    let Some(mut filter) = audit_filter(args) else {
+        return 1;
+    };
+    if command == "grep" {
+        let Some(pattern) = args.get(1).filter(|a| !a.starts_with("--")) else {
+            eprintln!("Error: audit grep requires a PATTERN");
+            return 1;
+        };
+        match regex::Regex::new(pattern) {
+            Ok(re) => filter.pattern = Some(re),
+            Err(e) => {
+                eprintln!("Error: invalid pattern: {}", e);
+                return 1;
+            }
+        }
+    }
+
+    let Some(log_path) = audit_log_path(args, config) else {
+        eprintln!("Error: no audit log configured (use --log PATH)");
+        return 1;
+    };
+    let entries = match reader::read_entries(&log_path) {
+        Ok(entries) => entries,
+        Err(e) => {
+            eprintln!("Error: failed to read {}: {}", log_path.display(), e);
+            return 1;
+        }
+    };
+    let selected = filter.apply(&entries);
+
+    let print = |entries: &[&AuditEntry]| {
+        let json = args.iter().any(|a| a == "--json");
+        for entry in entries {
+            if json {
+                println!("{}", serde_json::to_string(entry).unwrap_or_default());
+            } else {
+                println!("{}", reader::format_entry(entry));
+            }
+        }
+    };
+    match command {
+        "tail" => {
+            let count = match flag_value(args, "-n").map(|n| n.parse::<usize>()) {
+                None => 20,
+                Some(Ok(n)) => n,
+                Some(Err(_)) => {
+                    eprintln!("Error: -n expects a number");
+                    return 1;
+                }
+            };
+            print(&selected[selected.len().saturating_sub(count)..]);
+        }
+        "grep" => print(&selected),
+        "stats" => print!("{}", reader::Stats::build(selected).to_text()),
+        _ => {
+            eprintln!("{}", usage);
+            return 1;
+        }
+    }
+    0
+}
+
+/// Entry filter from `--since`, `--session`, `--level`, `--rule` and
+/// `--tool` (errors are printed)
+fn audit_filter(args: &[String]) -> Option<reader::Filter> {
+    let mut filter = reader::Filter {
+        session_id: flag_value(args, "--session"),
+        rule_id: flag_value(args, "--rule"),
+        tool: flag_value(args, "--tool"),
+        ..Default::default()
+    };
+    if let Some(since) = flag_value(args, "--since") {
+        match reader::since_timestamp(&since, chrono::Utc::now()) {
+            Some(since) => filter.since = Some(since),
+            None => {
+                eprintln!("Error: invalid --since value: {} (expected e.g. 7d, 24h)", since);
+                return None;
+            }
+        }
+    }
+    if let Some(level) = flag_value(args, "--level") {
+        match LogLevel::from_str(&level) {
+            Some(level) => filter.level = Some(level),
+            None => {
+                eprintln!("Error: unknown level {} (e.g. BLOCKED, WARN, ALLOWED)", level);
+                return None;
+            }
+        }
+    }
+    Some(filter)
+}
+
+/// `replay` subcommand: re-check logged inputs against the current config
+///
+/// Each tool call is evaluated with the config its project would load
+/// today; the entries whose decision changed are listed. Takes the `audit`
+/// filters, `--log` and `--json`.
+fn run_replay(sub_args: &[String], args: &Args) -> i32 {
+    let config = load_config(args, None);
+    let Some(filter) = audit_filter(sub_args) else {
+        return 1;
+    };
+    let Some(log_path) = audit_log_path(sub_args, &config) else {
+        eprintln!("Error: no audit log configured (use --log PATH)");
+        return 1;
+    };
+    let entries = match reader::read_entries(&log_path) {
+        Ok(entries) => entries,
+        Err(e) => {
+            eprintln!("Error: failed to read {}: {}", log_path.display(), e);
+            return 1;
+        }
+    };
+
+    let mut engines = EngineCache::default();
+    let report = replay::replay(filter.apply(&entries), |recorded| match recorded {
+        replay::Recorded::Tool(input) => {
+            let project_dir = input.cwd.as_deref().map(Path::new);
+            let config = load_config(
+                args,
+                project_dir.filter(|cwd| Config::find_project_config(cwd).is_some()),
+            );
+            engines.get(config).check(input)
+        }
+        replay::Recorded::Prompt { prompt, .. } => engines.get(config.clone()).check_prompt(prompt),
+    });
+
+    if sub_args.iter().any(|a| a == "--json") {
+        println!("{}", serde_json::to_string_pretty(&report).unwrap_or_default());
+    } else {
+        print!("{}", report.to_text());
+    }
+    0
+}
+
+/// `telemetry` subcommand: show, submit or reset the rule hit counters
+fn run_telemetry(args: &[String], config: &Config) -> i32 {
+    let Some(path) = config.telemetry_path() else {
+        eprintln!("Error: no telemetry path configured");
+        return 1;
+    };
+    match args.first().map(String::as_str) {
+        Some("show") => {
+            if !path.exists() {
+                eprintln!("No counters yet at {} (enable [telemetry] to start counting)", path.display());
+                return 1;
+            }
+            println!("{}", telemetry::Counters::load(&path).to_json());
+            0
+        }
+        Some("submit") => submit_telemetry(&path, config),
+        Some("reset") => match std::fs::remove_file(&path) {
+            Ok(()) => 0,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => 0,
+            Err(e) => {
+                eprintln!("Error: failed to remove {}: {}", path.display(), e);
+                1
+            }
+        },
+        _ => {
+            eprintln!("Usage: claude-guardrails telemetry <show | submit | reset>");
+            1
+        }
+    }
+}
+
+/// POST the counters to `[telemetry] endpoint`, then start a new period
+#[cfg(feature = "webhook")]
+fn submit_telemetry(path: &Path, config: &Config) -> i32 {
+    let Some(ref endpoint) = config.telemetry.endpoint else {
+        eprintln!("Error: set [telemetry] endpoint to submit counters");
+        return 1;
+    };
+    if let Err(e) = telemetry::submit(endpoint, &telemetry::Counters::load(path)) {
+        eprintln!("Error: failed to submit to {}: {}", endpoint, e);
+        return 1;
+    }
+    if let Err(e) = telemetry::Counters::default().save(path) {
+        eprintln!("Warning: submitted, but failed to reset {}: {}", path.display(), e);
+    }
+    0
+}
+
+#[cfg(not(feature = "webhook"))]
+fn submit_telemetry(_path: &Path, _config: &Config) -> i32 {
+    eprintln!("Error: this build has no HTTP client; rebuild with --features webhook to submit");
+    1
+}
+
+/// Count a decision in the telemetry file (and export it over OTLP) when
+/// `[telemetry]` is enabled
+fn record_telemetry(config: &Config, tool: &str, decision: &Decision, spans: &[SpanRecord]) {
+    if !config.telemetry.enabled {
         return;
     }
+    if let Some(path) = config.telemetry_path() {
+        if let Err(e) = telemetry::record(&path, decision) {
+            eprintln!("Warning: Failed to update telemetry counters: {}", e);
+        }
+    }
+    if let Some(ref endpoint) = config.telemetry.otlp_endpoint {
+        export_otlp(endpoint, config, tool, decision, spans);
+    }
+}
 
-    // Load configuration
-    let mut config = if let Some(ref path) = args.config_path {
-        Config::load_from(std::path::Path::new(path)).unwrap_or_else(|e| {
-            eprintln!("Warning: Failed to load config from {}: {}", path, e);
-            Config::default()
-        })
+#[cfg(feature = "otlp")]
+fn export_otlp(endpoint: &str, config: &Config, tool: &str, decision: &Decision, spans: &[SpanRecord]) {
+    let service = &config.telemetry.otlp_service_name;
+    if let Err(e) = telemetry::otlp::export(endpoint, service, tool, decision, spans) {
+        eprintln!("Warning: Failed to export telemetry over OTLP: {}", e);
+    }
+}
+
+#[cfg(not(feature = "otlp"))]
+fn export_otlp(_endpoint: &str, _config: &Config, _tool: &str, _decision: &Decision, _spans: &[SpanRecord]) {
+    eprintln!("Warning: telemetry.otlp_endpoint is set but this build lacks the otlp feature");
+}
+
+/// Describe what a sanitizing rewrite changed, for the hook message
+fn describe_rewrite(original: &ToolInput, sanitized: &ToolInput) -> String {
+    let (
+        ToolInput::Bash {
+            command: old_command,
+            timeout: old_timeout,
+            ..
+        },
+        ToolInput::Bash {
+            command, timeout, ..
+        },
+    ) = (original, sanitized)
+    else {
+        return String::new();
+    };
+
+    let mut changes = Vec::new();
+    if command != old_command {
+        match old_command.strip_prefix(command.as_str()) {
+            Some(dropped) => changes.push(format!("{} (dropped: {})", command, dropped.trim())),
+            None => changes.push(command.clone()),
+        }
+    }
+    if let (None, Some(ms)) = (old_timeout, timeout) {
+        changes.push(format!("timeout {}ms added", ms));
+    }
+    changes.join("; ")
+}
+
+/// `approve` subcommand: issue a one-time allow token
+fn run_approve(args: &[String], config: &Config) -> i32 {
+    // Tokens minted by the agent itself would defeat the point
+    if env::var_os(approvals::AGENT_ENV_VAR).is_some() {
+        eprintln!("Error: approve must be run by a human outside Claude Code");
+        return 1;
+    }
+
+    let Some(path) = config.approvals_path() else {
+        eprintln!("Error: no approvals_file configured under [overrides]");
+        return 1;
+    };
+    let Some(command) = flag_value(args, "--command").filter(|c| !c.trim().is_empty()) else {
+        eprintln!("Error: approve requires --command '<the blocked command>'");
+        return 1;
+    };
+
+    let ttl = match flag_value(args, "--ttl") {
+        Some(ttl) => match reader::parse_duration(&ttl)
+            .filter(|d| chrono::Utc::now().checked_add_signed(*d).is_some())
+        {
+            Some(ttl) => ttl,
+            None => {
+                eprintln!("Error: invalid --ttl value: {} (expected e.g. 15m, 1h)", ttl);
+                return 1;
+            }
+        },
+        None => chrono::Duration::minutes(approvals::DEFAULT_TTL_MINUTES),
+    };
+
+    let mut store = match approvals::ApprovalStore::load(&path) {
+        Ok(store) => store,
+        Err(e) => {
+            eprintln!("Error: failed to read {}: {}", path.display(), e);
+            return 1;
+        }
+    };
+    let approval = store.issue(&command, ttl, chrono::Utc::now()).clone();
+    if let Err(e) = store.save(&path) {
+        eprintln!("Error: failed to write {}: {}", path.display(), e);
+        return 1;
+    }
+
+    println!("{}", approval.token);
+    eprintln!(
+        "Append `# guardrails:allow {}` to `{}` (valid once for that command, until {})",
+        approval.token,
+        approval.command,
+        approval.expires.format("%H:%M UTC")
+    );
+    0
+}
+
+/// `rules` subcommand: inspect built-in rule provenance
+fn run_rules(args: &[String], config: &Config) -> i32 {
+    let manifest = Manifest::embedded();
+
+    match args.first().map(String::as_str) {
+        Some("diff") => {
+            let Some(from) = flag_value(args, "--from") else {
+                eprintln!("Error: rules diff requires --from VERSION");
+                return 1;
+            };
+            print!("{}", manifest.diff_from(&from).to_text());
+            0
+        }
+        Some("list") => {
+            let level = match flag_value(args, "--level") {
+                Some(level) => match SafetyLevel::from_str(&level) {
+                    Some(level) => Some(level),
+                    None => {
+                        eprintln!("Error: unknown level {} (critical, high, strict)", level);
+                        return 1;
+                    }
+                },
+                None => None,
+            };
+            let category = match flag_value(args, "--category") {
+                Some(name) => match Category::from_str(&name) {
+                    Some(category) => Some(category),
+                    None => {
+                        let names: Vec<&str> = Category::ALL.iter().map(|c| c.as_str()).collect();
+                        eprintln!("Error: unknown category {} ({})", name, names.join(", "));
+                        return 1;
+                    }
+                },
+                None => None,
+            };
+            let entries = catalog::list(level, category);
+            if args.iter().any(|a| a == "--json") {
+                println!("{}", catalog::to_json(&entries));
+            } else {
+                print!("{}", catalog::to_table(&entries));
+            }
+            0
+        }
+        Some("packs") => run_packs(&args[1..], config),
+        Some("manifest") => {
+            let version =
+                flag_value(args, "--version").unwrap_or_else(|| env!("CARGO_PKG_VERSION").to_string());
+            print!("{}", manifest.regenerate(&version).to_toml());
+            0
+        }
+        Some("lint") => {
+            let packs: Vec<std::path::PathBuf> = if args.len() > 1 {
+                args[1..].iter().map(std::path::PathBuf::from).collect()
+            } else {
+                config.token_pack_paths().into_iter().filter(|p| p.exists()).collect()
+            };
+
+            let mut issues = lint::lint_builtin();
+            issues.extend(lint::lint_token_pack(&TokenPack::builtin()));
+            for path in &packs {
+                match TokenPack::from_file(path) {
+                    Ok(pack) => issues.extend(lint::lint_token_pack(&pack)),
+                    Err(e) => {
+                        eprintln!("Error: {}: {}", path.display(), e);
+                        return 1;
+                    }
+                }
+            }
+
+            for issue in &issues {
+                println!("{}", issue);
+            }
+            if issues.is_empty() {
+                println!("All rule examples pass ({} token pack(s) checked)", packs.len() + 1);
+                0
+            } else {
+                1
+            }
+        }
+        Some("bench") => {
+            let Some(path) = args.get(1).filter(|a| !a.starts_with("--")) else {
+                eprintln!("Error: rules bench requires a corpus file (one command per line, - for stdin)");
+                return 1;
+            };
+            let text = if path == "-" {
+                let mut text = String::new();
+                io::stdin().read_to_string(&mut text).map(|_| text)
+            } else {
+                std::fs::read_to_string(path)
+            };
+            let corpus = match text {
+                Ok(text) => bench::parse_corpus(&text),
+                Err(e) => {
+                    eprintln!("Error: {}: {}", path, e);
+                    return 1;
+                }
+            };
+            let level = match flag_value(args, "--level") {
+                Some(level) => match SafetyLevel::from_str(&level) {
+                    Some(level) => level,
+                    None => {
+                        eprintln!("Error: unknown level {} (critical, high, strict)", level);
+                        return 1;
+                    }
+                },
+                None => config.safety_level_for("Bash"),
+            };
+            let number = |name: &str, default: usize| match flag_value(args, name) {
+                Some(n) => n.parse::<usize>().ok().filter(|&n| n > 0),
+                None => Some(default),
+            };
+            let (Some(sample), Some(top)) = (number("--sample", 1), number("--top", 20)) else {
+                eprintln!("Error: --sample and --top take a positive number");
+                return 1;
+            };
+
+            let report = bench::run(&corpus, config, level, sample);
+            if args.iter().any(|a| a == "--json") {
+                println!("{}", report.to_json());
+            } else {
+                print!("{}", report.to_text(top));
+            }
+            0
+        }
+        _ => {
+            eprintln!("Usage: claude-guardrails rules <list [--level L] [--category C] [--json] | diff --from VERSION | manifest [--version VERSION] | lint [PACK...] | bench CORPUS [--level L] [--sample N] [--top N] [--json] | packs <list | enable NAME | disable NAME>>");
+            1
+        }
+    }
+}
+
+/// `rules packs` subcommand: list the installed rule packs, or enable or
+/// disable one in the packs index
+fn run_packs(args: &[String], config: &Config) -> i32 {
+    let Some(dir) = config.packs_dir() else {
+        eprintln!("Error: no packs directory configured ([packs] dir)");
+        return 1;
+    };
+    let installed = match packs::installed(&dir) {
+        Ok(installed) => installed,
+        Err(e) => {
+            eprintln!("Error: failed to read {}: {}", dir.display(), e);
+            return 1;
+        }
+    };
+
+    let (enable, name) = match (args.first().map(String::as_str), args.get(1)) {
+        (Some("list"), None) => {
+            if installed.is_empty() {
+                println!("No rule packs installed in {}", dir.display());
+            }
+            print!("{}", packs::to_text(&installed));
+            return 0;
+        }
+        (Some("enable"), Some(name)) => (true, name),
+        (Some("disable"), Some(name)) => (false, name),
+        _ => {
+            eprintln!("Usage: claude-guardrails rules packs <list | enable NAME | disable NAME>");
+            return 1;
+        }
+    };
+
+    let mut index = match packs::PackIndex::load(&dir) {
+        Ok(index) => index,
+        Err(e) => {
+            eprintln!("Error: failed to read {}: {}", dir.join(packs::INDEX_FILE).display(), e);
+            return 1;
+        }
+    };
+    if enable {
+        match installed.iter().find(|p| &p.name == name).map(|p| &p.pack) {
+            None => {
+                eprintln!("Error: no pack {} in {}", name, dir.display());
+                return 1;
+            }
+            Some(Err(e)) => {
+                eprintln!("Error: pack {} is unreadable: {}", name, e);
+                return 1;
+            }
+            Some(Ok(pack)) if !pack.pack.is_compatible() => {
+                eprintln!(
+                    "Error: pack {} needs claude-guardrails {}",
+                    name,
+                    pack.pack.min_version.as_deref().unwrap_or_default()
+                );
+                return 1;
+            }
+            Some(Ok(_)) => {}
+        }
+        if !index.is_enabled(name) {
+            index.enabled.push(name.clone());
+        }
     } else {
-        Config::load()
+        index.enabled.retain(|n| n != name);
+    }
+    if let Err(e) = index.save(&dir) {
+        eprintln!("Error: failed to write {}: {}", dir.join(packs::INDEX_FILE).display(), e);
+        return 1;
+    }
+    eprintln!("{} {}", if enable { "Enabled" } else { "Disabled" }, name);
+    0
+}
+
+/// `policy` subcommand: fetch a signed policy file, or check the signatures
+/// of files on disk
+fn run_policy(args: &[String], config: &Config) -> i32 {
+    let mut keys = TrustedKeys::from_config(&config.signing);
+    if let Some(key) = flag_value(args, "--key") {
+        if let Err(e) = keys.add(&key) {
+            eprintln!("Error: invalid --key {}: {}", key, e);
+            return 1;
+        }
+    }
+    if keys.is_empty() {
+        eprintln!("Error: no trusted keys; set [signing] trusted_keys or pass --key");
+        return 1;
+    }
+    let mut operands = Vec::new();
+    let mut iter = args.iter().skip(1);
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--key" => {
+                iter.next();
+            }
+            arg if arg.starts_with("--key=") => {}
+            _ => operands.push(arg.as_str()),
+        }
+    }
+
+    match (args.first().map(String::as_str), operands.as_slice()) {
+        (Some("fetch"), [url, output]) => fetch_policy(url, Path::new(output), &keys, config),
+        (Some("verify"), files) if !files.is_empty() => {
+            let mut code = 0;
+            for file in files {
+                match keys.verify_file(Path::new(file)) {
+                    Ok(verification) => {
+                        println!("{}: {}", file, verification.describe());
+                        if !verification.is_verified() {
+                            code = 1;
+                        }
+                    }
+                    Err(e) => {
+                        eprintln!("Error: {}: {}", file, e);
+                        code = 1;
+                    }
+                }
+            }
+            code
+        }
+        _ => {
+            eprintln!("Usage: claude-guardrails policy <fetch URL OUTPUT | verify FILE...> [--key KEY]");
+            1
+        }
+    }
+}
+
+/// Download a policy file and its signature, and install both at `output`
+/// only if the signature verifies; either way the outcome is audited
+#[cfg(feature = "webhook")]
+fn fetch_policy(url: &str, output: &Path, keys: &TrustedKeys, config: &Config) -> i32 {
+    use claude_guardrails::signing;
+
+    let fetched = match signing::fetch(url, keys) {
+        Ok(fetched) => fetched,
+        Err(e) => {
+            eprintln!("Error: failed to fetch {}: {}", url, e);
+            return 1;
+        }
     };
+    let verified = fetched.verification.is_verified();
+    let mut installed = false;
+    if let (true, Some(signature)) = (verified, &fetched.signature) {
+        let signature_path = signing::signature_path(output);
+        let written = output
+            .parent()
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| std::fs::write(&signature_path, signature))
+            .and_then(|_| std::fs::write(output, &fetched.content));
+        match written {
+            Ok(()) => installed = true,
+            Err(e) => eprintln!("Error: failed to write {}: {}", output.display(), e),
+        }
+    }
 
-    // Override safety level if specified
-    if let Some(level) = args.safety_level {
+    let entry = AuditEntry::policy(url, output, &fetched.verification, installed);
+    if let Err(e) = AuditLogger::from_config(config).log(&entry) {
+        eprintln!("Warning: failed to audit the fetch: {}", e);
+    }
+    if !verified {
+        eprintln!("Error: refusing {}: {}", url, fetched.verification.describe());
+        return 1;
+    }
+    if !installed {
+        return 1;
+    }
+    eprintln!("Installed {} ({})", output.display(), fetched.verification.describe());
+    0
+}
+
+#[cfg(not(feature = "webhook"))]
+fn fetch_policy(_url: &str, _output: &Path, _keys: &TrustedKeys, _config: &Config) -> i32 {
+    eprintln!("Error: this build has no HTTP client; rebuild with --features webhook to fetch");
+    1
+}
+
+/// `healthz` subcommand: build the engine and report whether it enforces
+fn run_healthz(config: &Config) -> i32 {
+    let engine = SecurityEngine::new(config.clone());
+    let health = Health::of(&engine);
+    println!("{}", health.to_json());
+    if health.is_ready() {
+        0
+    } else {
+        1
+    }
+}
+
+/// `scan` subcommand: preflight a repository with the config that would
+/// apply inside it
+fn run_scan(sub_args: &[String], args: &Args) -> i32 {
+    let Some(dir) = sub_args.first().filter(|a| !a.starts_with("--")).map(Path::new) else {
+        eprintln!("Usage: claude-guardrails scan DIR [--content] [--exclude DIR,...] [--max-bytes N] [--json]");
+        return 1;
+    };
+    if !dir.is_dir() {
+        eprintln!("Error: {} is not a directory", dir.display());
+        return 1;
+    }
+    let mut options = scan::ScanOptions::default();
+    if let Some(n) = flag_value(sub_args, "--max-bytes") {
+        let Ok(n) = n.parse::<u64>() else {
+            eprintln!("Error: --max-bytes takes a number of bytes");
+            return 1;
+        };
+        options.max_bytes = n;
+    }
+    if let Some(exclude) = flag_value(sub_args, "--exclude") {
+        options.exclude = exclude.split(',').map(str::to_string).collect();
+    }
+
+    let mut config = load_config(args, Some(dir));
+    if sub_args.iter().any(|a| a == "--content") {
+        config.tokens.enabled = true;
+        config.content.entropy_scan = true;
+    }
+    let report = scan::scan(dir, &SecurityEngine::new(config), &options);
+    if sub_args.iter().any(|a| a == "--json") {
+        println!("{}", report.to_json());
+    } else {
+        print!("{}", report.to_text());
+    }
+    0
+}
+
+/// `statusline` subcommand: one line for Claude Code's status line
+///
+/// Reads the status line JSON from stdin for the session and its directory.
+/// Always exits 0 and prints something: a status line that errors shows
+/// nothing, which reads as if guardrails weren't installed.
+fn run_statusline(args: &Args) -> i32 {
+    let mut input_json = String::new();
+    let _ = io::stdin().read_to_string(&mut input_json);
+    let input = StatusLineInput::from_json(&input_json).unwrap_or_default();
+
+    let project_dir = input.cwd.as_deref().map(Path::new);
+    let config = load_config(
+        args,
+        project_dir.filter(|cwd| Config::find_project_config(cwd).is_some()),
+    );
+    let entries = config
+        .audit_path()
+        .filter(|_| input.session_id.is_some())
+        .and_then(|path| reader::read_entries(&path).ok());
+    let engine = SecurityEngine::new(config);
+    let status = StatusLine::build(&engine, entries.as_deref(), input.session_id.as_deref());
+    println!("{}", status.to_text());
+    0
+}
+
+/// `lint-script` subcommand: check shell scripts people commit with the
+/// policy the agent runs under
+///
+/// Uses the config a hook call from the current directory would see; exits
+/// 1 if any script has an error finding or can't be read.
+fn run_lint_script(sub_args: &[String], args: &Args) -> i32 {
+    let mut paths = Vec::new();
+    let mut i = 0;
+    while i < sub_args.len() {
+        match sub_args[i].as_str() {
+            "--level" => i += 1,
+            "--json" => {}
+            arg if arg.starts_with("--level=") => {}
+            path => paths.push(Path::new(path)),
+        }
+        i += 1;
+    }
+    if paths.is_empty() {
+        eprintln!("Usage: claude-guardrails lint-script FILE... [--level L] [--json]");
+        return 1;
+    }
+
+    let cwd = env::current_dir().ok();
+    let project_dir = cwd
+        .as_deref()
+        .filter(|cwd| Config::find_project_config(cwd).is_some());
+    let mut config = load_config(args, project_dir);
+    if let Some(level) = flag_value(sub_args, "--level") {
+        let Some(level) = SafetyLevel::from_str(&level) else {
+            eprintln!("Error: unknown level {} (critical, high, strict)", level);
+            return 1;
+        };
         config.general.safety_level = level;
+        config.tools.remove("Bash");
     }
 
-    // Set up dry-run mode via environment
-    if args.dry_run {
-        env::set_var("GUARDRAILS_WARN_ONLY", "1");
+    let report = lint_script::check_files(&paths, &SecurityEngine::new(config));
+    if sub_args.iter().any(|a| a == "--json") {
+        println!("{}", report.to_json());
+    } else {
+        print!("{}", report.to_text());
+    }
+    if report.failed() {
+        1
+    } else {
+        0
     }
+}
 
-    // Create security engine
-    let engine = SecurityEngine::new(config.clone());
+/// `explain` subcommand: show every rule and check a command runs into
+///
+/// Uses the config a hook call from the current directory would see,
+/// including its `.guardrails.toml`.
+fn run_explain(sub_args: &[String], args: &Args) -> i32 {
+    let json = sub_args.iter().any(|a| a == "--json");
+    let command = sub_args
+        .iter()
+        .filter(|a| a.as_str() != "--json")
+        .cloned()
+        .collect::<Vec<_>>()
+        .join(" ");
+    if command.trim().is_empty() {
+        eprintln!("Usage: claude-guardrails explain [--json] <command>");
+        return 1;
+    }
 
-    // Create audit logger
-    let audit_path = if config.general.audit_log {
-        config.audit_path()
+    let cwd = env::current_dir().ok();
+    let project_dir = cwd
+        .as_deref()
+        .filter(|cwd| Config::find_project_config(cwd).is_some());
+    let engine = SecurityEngine::new(load_config(args, project_dir));
+    let explanation = engine.explain(&command);
+    if json {
+        println!("{}", serde_json::to_string_pretty(&explanation).unwrap_or_default());
     } else {
-        None
+        print!("{}", explanation.to_text());
+    }
+    0
+}
+
+/// Load the config from the standard locations (or `--config`), with the
+/// project's `.guardrails.toml` merged when `cwd` has one
+fn load_config(args: &Args, cwd: Option<&Path>) -> Config {
+    let path = args.config_path.as_deref().map(Path::new);
+    let mut config = if args.no_home {
+        Config::load_without_home(path, cwd)
+    } else {
+        Config::load_layered(path, cwd)
     };
-    let mut logger = AuditLogger::new(audit_path.as_deref());
+    // Override safety level if specified
+    if let Some(level) = args.safety_level {
+        config.general.safety_level = level;
+    }
+    config
+}
 
-    // Read JSON from stdin
-    let stdin = io::stdin();
-    let mut input_json = String::new();
+/// UserPromptSubmit hook: warn Claude about prompt-injection heuristics
+///
+/// Advisory only: the prompt is never blocked, and an unparsable input is
+/// passed through rather than denied.
+fn prompt_submit(input_json: &str, config: &Config, engine: &SecurityEngine) -> HookOutput {
+    let input = match PromptInput::from_json(input_json) {
+        Ok(input) => input,
+        Err(e) => {
+            eprintln!("Warning: Failed to parse UserPromptSubmit input: {}", e);
+            return HookOutput::allow();
+        }
+    };
 
-    for line in stdin.lock().lines() {
-        match line {
-            Ok(line) => input_json.push_str(&line),
-            Err(_) => break,
+    let decision = engine.check_prompt(&input.prompt);
+    match decision.rule_id() {
+        Some(rule_id) => {
+            let mut logger = AuditLogger::from_config(config);
+            if let Err(e) = logger.log(&AuditEntry::prompt(&input, &decision)) {
+                eprintln!("Warning: Failed to write audit log: {}", e);
+            }
+            record_telemetry(config, "UserPromptSubmit", &decision, &[]);
+            HookOutput::user_prompt_submit(rule_id, decision.reason())
+                .with_message_format(&config.output.message_format, &decision)
         }
+        None => HookOutput::allow(),
     }
+}
+
+/// SessionStart hook: compile the session's rules up front and confirm
+/// that protection is on
+///
+/// In daemon mode this also warms the engine cache, so the session's first
+/// tool call doesn't pay for compilation.
+fn session_start(input_json: &str, args: &Args, engines: &mut EngineCache) -> HookOutput {
+    let input = SessionInput::from_json(input_json).unwrap_or_else(|e| {
+        eprintln!("Warning: Failed to parse SessionStart input: {}", e);
+        SessionInput::default()
+    });
+    let project_dir = input.cwd.as_deref().map(Path::new);
+    let config = load_config(
+        args,
+        project_dir.filter(|cwd| Config::find_project_config(cwd).is_some()),
+    );
+    let precheck = Precheck::run(engines.get(config));
+    HookOutput::session_start(&precheck.summary(), precheck.context())
+}
+
+/// Stop/SubagentStop hook: review the session's transcript for destructive
+/// work left half done and summarize it for the user
+fn stop(input_json: &str, args: &Args, engines: &mut EngineCache) -> HookOutput {
+    let input = match StopInput::from_json(input_json) {
+        Ok(input) => input,
+        Err(e) => {
+            eprintln!("Warning: Failed to parse Stop input: {}", e);
+            return HookOutput::allow();
+        }
+    };
+    let Some(path) = input.transcript_path.as_deref() else {
+        return HookOutput::allow();
+    };
+    let calls = match transcript::bash_calls(Path::new(path)) {
+        Ok(calls) => calls,
+        Err(e) => {
+            eprintln!("Warning: Failed to read transcript {}: {}", path, e);
+            return HookOutput::allow();
+        }
+    };
+
+    let project_dir = input.cwd.as_deref().map(Path::new);
+    let config = load_config(
+        args,
+        project_dir.filter(|cwd| Config::find_project_config(cwd).is_some()),
+    );
+    let engine = engines.get(config);
+    let findings = transcript::review(&calls, |command| engine.check_bash(command));
+    HookOutput::stop(transcript::summary(&findings))
+}
+
+/// Evaluate a hook input, or a batch of them (a JSON array of inputs),
+/// rendered in `version`'s shape
+///
+/// Batch items are checked independently, an item that fails to parse
+/// being denied on its own, but share the engine cache, so rules are
+/// compiled once per config. Outputs come back as an array in input order.
+fn evaluate_document(
+    input_json: &str,
+    args: &Args,
+    engines: &mut EngineCache,
+    version: HookSchemaVersion,
+) -> String {
+    let batch = input_json
+        .trim_start()
+        .starts_with('[')
+        .then(|| serde_json::from_str::<Vec<serde_json::Value>>(input_json).ok())
+        .flatten();
+    let Some(items) = batch else {
+        return evaluate(input_json, args, engines).to_json_versioned(version);
+    };
+    let outputs: Vec<String> = items
+        .iter()
+        .enumerate()
+        .map(|(i, item)| {
+            let output = if item.is_object() {
+                evaluate(&item.to_string(), args, engines)
+            } else {
+                // SECURITY: Fail closed on the item, not the whole batch
+                eprintln!("Error: Batch item {} is not a hook input object (denying)", i);
+                HookOutput::deny_with_rule(
+                    "parse-error",
+                    &format!("Failed to parse hook input: batch item {} is not an object", i),
+                )
+            };
+            output.to_json_versioned(version)
+        })
+        .collect();
+    format!("[{}]", outputs.join(","))
+}
 
+/// Evaluate one hook invocation, compiling (or reusing) the engine for its config
+fn evaluate(input_json: &str, args: &Args, engines: &mut EngineCache) -> HookOutput {
     // Handle empty input
     if input_json.trim().is_empty() {
         // No input = nothing to check, allow
-        let output = HookOutput::allow();
-        println!("{}", output.to_json());
-        return;
+        return HookOutput::allow();
+    }
+
+    // UserPromptSubmit carries a prompt instead of a tool call
+    if HookEvent::from_json(input_json) == HookEvent::UserPromptSubmit {
+        let config = load_config(args, None);
+        let engine = engines.get(config.clone());
+        return prompt_submit(input_json, &config, engine);
+    }
+
+    match HookEvent::from_json(input_json) {
+        HookEvent::SessionStart => return session_start(input_json, args, engines),
+        HookEvent::Stop | HookEvent::SubagentStop => return stop(input_json, args, engines),
+        _ => {}
     }
 
     // Parse input
-    let input = match HookInput::from_json(&input_json) {
+    let input = match HookInput::from_json(input_json) {
         Ok(input) => input,
         Err(e) => {
             // SECURITY: Fail closed on parse errors
             // Malformed input could be an evasion attempt
             eprintln!("Error: Failed to parse input (denying): {}", e);
-            let output = HookOutput::deny_with_rule(
+            return HookOutput::deny_with_rule(
                 "parse-error",
                 &format!("Failed to parse hook input: {}", e),
             );
-            println!("{}", output.to_json());
-            return;
         }
     };
 
+    // Merge the project's .guardrails.toml over the loaded config
+    let project_dir = input.cwd.as_deref().map(Path::new);
+    let config = load_config(
+        args,
+        project_dir.filter(|cwd| Config::find_project_config(cwd).is_some()),
+    );
+
+    // Without a home directory, `~` paths are dropped rather than guessed
+    if config.general.no_home {
+        for path in config.home_relative_paths() {
+            eprintln!("Warning: ignoring {} (--no-home: configure an explicit path)", path);
+        }
+    }
+
+    // Create (or reuse) the security engine
+    let engine = engines.get(config.clone());
+
+    // Create audit logger
+    let mut logger = AuditLogger::from_config(&config);
+
     // Check if disabled
     let disabled = engine.is_disabled();
 
-    // Run security check
-    let decision = engine.check(&input);
+    // PostToolUse: scan the tool's output and ask Claude to redact findings
+    if input.event() == HookEvent::PostToolUse {
+        let decision = engine.check_response(&input);
+        return match decision.rule_id() {
+            Some(rule_id) => {
+                let scanned = Some(provenance::Provenance::Rules);
+                if let Err(e) = logger.log_decision(&input, &decision, disabled, scanned) {
+                    eprintln!("Warning: Failed to write audit log: {}", e);
+                }
+                record_telemetry(&config, &input.tool_name, &decision, &[]);
+                let mut output = HookOutput::post_tool_use(rule_id, decision.reason());
+                output.system_message = Some(format_message(
+                    &config.output.message_format,
+                    rule_id,
+                    "Redact",
+                    decision.reason(),
+                ));
+                output
+            }
+            None => HookOutput::allow(),
+        };
+    }
+
+    // Run security check (recording its spans for OTLP export)
+    if config.telemetry.enabled && config.telemetry.otlp_endpoint.is_some() {
+        telemetry::trace::start();
+    }
+    let decision = telemetry::trace::span("check", || engine.check(&input));
+    let spans = telemetry::trace::finish();
+    // A human's allow-once token for this command is spent here, and only here
+    let mut decision = engine.redeem_approval(&input, decision);
+    let provenance = provenance::of(engine, &input, &decision);
+
+    // --compare-engines: log where the regex fallback would decide differently
+    if args.compare_engines {
+        if let ToolInput::Bash { ref command, .. } = input.tool_input {
+            let comparison = compare::compare(engine, command);
+            if comparison.disagrees() {
+                eprintln!("Warning: engines disagree: {}", comparison);
+            }
+        }
+    }
+
+    // Try a sanitized rewrite; only use it if the result passes every check
+    let rewritten = if config.rewrite.enabled && !disabled {
+        engine
+            .rewrite(&input)
+            .filter(|r| {
+                let ignored: Vec<&str> = r.neutralized.iter().map(String::as_str).collect();
+                engine.check_ignoring(&r.input, &ignored).is_allow()
+            })
+            .map(|r| r.input)
+    } else {
+        None
+    };
+    if let Some(ref sanitized) = rewritten {
+        decision = Decision::allow_from(AllowSource::Rewritten, format!("rewritten: {}", sanitized.summary()));
+    }
+
+    // Rules set to `remote-ask` block unless a teammate approves in time
+    if rewritten.is_none() && !disabled {
+        decision = remote_ask::resolve(&config, &input, decision);
+    }
 
     // Log the decision
-    if let Err(e) = logger.log_decision(&input, &decision, disabled) {
+    if let Err(e) = logger.log_decision(&input, &decision, disabled, Some(provenance)) {
         eprintln!("Warning: Failed to write audit log: {}", e);
     }
+    if !disabled {
+        record_telemetry(&config, &input.tool_name, &decision, &spans);
+    }
+
+    // Remember what an allowed command downloads, for download-execute
+    if let (Some(path), Some(session_id), ToolInput::Bash { command, .. }) =
+        (config.downloads_path(), input.session_id.as_deref(), &input.tool_input)
+    {
+        if decision.is_allow() && !disabled {
+            let cwd = input.cwd.as_deref().map(Path::new);
+            if let Err(e) = download::record(&path, session_id, command, &config.bash.wrappers, cwd, engine.now()) {
+                eprintln!("Warning: Failed to record downloads: {}", e);
+            }
+        }
+    }
+
+    // Take a recovery snapshot before the session's first mutating call
+    if config.snapshot.enabled && decision.is_allow() && snapshot::is_mutating(&input) {
+        if let Some(ref session_id) = input.session_id {
+            let cwd = input
+                .cwd
+                .as_ref()
+                .map(std::path::PathBuf::from)
+                .or_else(|| env::current_dir().ok());
+            if let Some(cwd) = cwd {
+                match snapshot::ensure_session_snapshot(&cwd, session_id) {
+                    Ok(Some((ref_name, commit))) => {
+                        let entry = AuditEntry::snapshot(&input, &ref_name, &commit);
+                        if let Err(e) = logger.log(&entry) {
+                            eprintln!("Warning: Failed to write audit log: {}", e);
+                        }
+                    }
+                    Ok(None) => {}
+                    Err(e) => eprintln!("Warning: Failed to create session snapshot: {}", e),
+                }
+            }
+        }
+    }
+
+    // Generate output (deferring to a stricter upstream hook, if any)
+    let mut output = match rewritten {
+        Some(sanitized) if !input.previous_decision.as_ref().is_some_and(|p| p.is_deny()) => {
+            HookOutput::allow_rewritten(
+                sanitized.tool_input.to_value(),
+                &describe_rewrite(&input.tool_input, &sanitized.tool_input),
+            )
+        }
+        _ => HookOutput::from_decision_chained(&decision, input.previous_decision.as_ref())
+            .with_message_format(&config.output.message_format, &decision),
+    };
+
+    // Suggest pre-approving hot, read-only commands that passed every check
+    if config.output.suggest_permissions && !disabled && decision.allow_source() == Some(&AllowSource::Checked) {
+        if let ToolInput::Bash { ref command, .. } = input.tool_input {
+            if let Some(permission) = hints::permission_for(command) {
+                output = output.with_permission_suggestion(&permission);
+            }
+        }
+    }
+
+    // Attach a retry hint so the agent can adapt instead of repeating the call
+    if config.output.hints && output.is_deny() {
+        if let Some(rule_id) = decision.rule_id() {
+            let suppressed = config.output.suppress_hints.iter().any(|r| r == rule_id);
+            if let (false, Some(try_instead), Some(subject)) =
+                (suppressed, hints::hint_for(rule_id), input.subject())
+            {
+                output = output.with_hint(&Hint {
+                    blocked: subject.to_string(),
+                    try_instead: try_instead.to_string(),
+                });
+            }
+        }
+    }
+
+    output
+}
+
+/// `--daemon`: serve hook and health requests over the Unix socket
+#[cfg(unix)]
+fn run_daemon(args: &Args) -> i32 {
+    let config = load_config(args, None);
+    let Some(path) = socket_path(args, &config) else {
+        eprintln!("Error: no daemon socket configured (use --socket PATH)");
+        return 1;
+    };
+    // Socket-activated by systemd, or bind it ourselves
+    let listener = match daemon::inherited_listener().map_or_else(|| daemon::bind(&path), Ok) {
+        Ok(listener) => listener,
+        Err(e) => {
+            eprintln!("Error: failed to listen on {}: {}", path.display(), e);
+            return 1;
+        }
+    };
+    eprintln!("claude-guardrails daemon listening on {}", path.display());
+
+    // The daemon's own switches, used for health checks
+    let own_flags = SessionFlags::from_env();
+    let mut engines = EngineCache::default();
+    let result = daemon::serve(&listener, |request| match request {
+        Request::Hook { mut flags, json } => {
+            flags.warn_only |= own_flags.warn_only;
+            engines.set_overrides(flags);
+            evaluate_document(&json, args, &mut engines, HookSchemaVersion::default())
+        }
+        Request::Http(line) => {
+            engines.set_overrides(own_flags);
+            let engine = engines.get(load_config(args, None));
+            health::respond(&line, engine).unwrap_or_default()
+        }
+    });
+    match result {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("Error: daemon stopped: {}", e);
+            1
+        }
+    }
+}
+
+/// `daemon` subcommand: `install-service` writes the service files for the
+/// daemon (or prints them with `--print`)
+fn run_daemon_command(sub_args: &[String], args: &Args, config: &Config) -> i32 {
+    if sub_args.first().map(String::as_str) != Some("install-service") {
+        eprintln!("Usage: claude-guardrails daemon install-service [--kind systemd|launchd] [--print]");
+        return 2;
+    }
+
+    let kind = match flag_value(sub_args, "--kind") {
+        Some(kind) => match ServiceKind::from_str(&kind) {
+            Some(kind) => kind,
+            None => {
+                eprintln!("Error: unknown --kind {} (expected systemd or launchd)", kind);
+                return 2;
+            }
+        },
+        None => ServiceKind::current(),
+    };
+    let Some(home) = config.resolve_path("~") else {
+        eprintln!("Error: install-service needs a home directory");
+        return 1;
+    };
+    let Some(socket) = socket_path(args, config) else {
+        eprintln!("Error: no daemon socket configured (use --socket PATH)");
+        return 1;
+    };
+    let binary = match env::current_exe() {
+        Ok(binary) => binary,
+        Err(e) => {
+            eprintln!("Error: can't locate the claude-guardrails binary: {}", e);
+            return 1;
+        }
+    };
+
+    // Carry the options this command was run with over to the daemon
+    let mut daemon_args = vec!["--daemon".to_string(), "--socket".to_string(), socket.display().to_string()];
+    if let Some(path) = &args.config_path {
+        let path = std::fs::canonicalize(path).unwrap_or_else(|_| path.into());
+        daemon_args.extend(["--config".to_string(), path.display().to_string()]);
+    }
+    if let Some(level) = args.safety_level {
+        daemon_args.push(format!("--safety-level={}", level.as_str()));
+    }
+    if args.no_home {
+        daemon_args.push("--no-home".to_string());
+    }
+
+    let service = ServiceFiles::render(kind, &binary, &daemon_args, &socket, &home);
+    if sub_args.iter().any(|a| a == "--print") {
+        for (path, content) in &service.files {
+            println!("# {}\n{}", path.display(), content);
+        }
+        return 0;
+    }
+
+    for (path, content) in &service.files {
+        let written = path
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| std::fs::write(path, content));
+        if let Err(e) = written {
+            eprintln!("Error: failed to write {}: {}", path.display(), e);
+            return 1;
+        }
+        eprintln!("Wrote {}", path.display());
+    }
+    eprintln!("Enable it with:\n    {}", service.enable);
+    eprintln!("Then use `claude-guardrails --client` as the hook command.");
+    0
+}
+
+/// Socket for `--daemon` / `--client`: `--socket`, then `[daemon] socket`
+fn socket_path(args: &Args, config: &Config) -> Option<std::path::PathBuf> {
+    match &args.socket {
+        Some(path) => config.resolve_path(path),
+        None => config.resolve_path(&config.daemon.socket),
+    }
+}
+
+/// `--client`: forward the hook input to the daemon; `None` if it is not
+/// reachable, in which case the caller checks in-process
+#[cfg(unix)]
+fn forward_to_daemon(args: &Args, input_json: &str) -> Option<String> {
+    let config = load_config(args, None);
+    let path = socket_path(args, &config)?;
+    match daemon::forward(&path, SessionFlags::from_env(), input_json) {
+        Ok(reply) => Some(reply.trim_end().to_string()),
+        Err(e) => {
+            eprintln!("Warning: daemon unavailable ({}), checking in-process", e);
+            None
+        }
+    }
+}
+
+fn main() {
+    let args = Args::parse();
+
+    // Handle help and version
+    if args.help {
+        print_help();
+        return;
+    }
+
+    if args.version {
+        print_version();
+        return;
+    }
+
+    // Dispatch subcommands
+    if let Some((name, sub_args)) = &args.subcommand {
+        let config = load_config(&args, None);
+        let code = match name.as_str() {
+            "digest" => run_digest(sub_args, &config),
+            "audit" => run_audit(sub_args, &config),
+            "telemetry" => run_telemetry(sub_args, &config),
+            "approve" => run_approve(sub_args, &config),
+            "rules" => run_rules(sub_args, &config),
+            "healthz" => run_healthz(&config),
+            "policy" => run_policy(sub_args, &config),
+            "scan" => run_scan(sub_args, &args),
+            "statusline" => run_statusline(&args),
+            "lint-script" => run_lint_script(sub_args, &args),
+            "daemon" => run_daemon_command(sub_args, &args, &config),
+            "explain" => run_explain(sub_args, &args),
+            "replay" => run_replay(sub_args, &args),
+            _ => unreachable!("unknown subcommand {}", name),
+        };
+        std::process::exit(code);
+    }
+
+    // Set up dry-run mode via environment
+    if args.dry_run {
+        env::set_var("GUARDRAILS_WARN_ONLY", "1");
+    }
+
+    #[cfg(unix)]
+    if args.daemon {
+        std::process::exit(run_daemon(&args));
+    }
+
+    // Read JSON from stdin
+    let stdin = io::stdin();
+    let mut input_json = String::new();
+
+    for line in stdin.lock().lines() {
+        match line {
+            Ok(line) => input_json.push_str(&line),
+            Err(_) => break,
+        }
+    }
+
+    // Client mode: let a warm daemon answer, if one is running
+    #[cfg(unix)]
+    let forwarded = if args.client {
+        forward_to_daemon(&args, &input_json)
+    } else {
+        None
+    };
+    #[cfg(not(unix))]
+    let forwarded: Option<String> = None;
 
-    // Generate output
-    let output = HookOutput::from_decision(&decision);
+    // The daemon answers in the current shape
+    let version = args.hook_schema_version;
+    let forwarded = forwarded.map(|json| match serde_json::from_str(&json) {
+        Ok(value) if version != HookSchemaVersion::default() => version.render(value).to_string(),
+        _ => json,
+    });
+    let json = forwarded.unwrap_or_else(|| {
+        evaluate_document(&input_json, &args, &mut EngineCache::default(), version)
+    });
 
     // Write to stdout
-    let json = output.to_json();
     let stdout = io::stdout();
     let mut handle = stdout.lock();
     let _ = writeln!(handle, "{}", json);
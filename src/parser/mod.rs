@@ -1,7 +1,15 @@
 //! Shell parsing utilities for claude-guardrails
 //!
-//! Provides shell tokenization, wrapper command detection, and AST-based analysis.
+//! Provides shell tokenization, wrapper command detection, AST-based analysis
+//! on a pinned, capability-checked grammar, a visitor API over the AST for
+//! downstream analyzers, zsh/fish rewriting, PowerShell normalization and
+//! resolving string-built (obfuscated) commands.
 
 pub mod ast;
+pub mod deobfuscate;
+pub mod dialect;
+pub mod grammar;
+pub mod powershell;
 pub mod shell;
+pub mod visit;
 pub mod wrapper;
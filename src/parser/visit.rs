@@ -0,0 +1,332 @@
+//! Visitor API over the parsed bash AST
+//!
+//! Exposes the shell constructs the built-in checks look at (commands,
+//! pipelines, redirects and substitutions, each with its byte span) so
+//! downstream analyzers can implement checks the core doesn't without
+//! carrying their own tree-sitter setup.
+//!
+//! ```
+//! use claude_guardrails::prelude::{ParsedScript, Redirect, Visitor};
+//!
+//! struct Writes(Vec<String>);
+//!
+//! impl Visitor for Writes {
+//!     fn visit_redirect(&mut self, redirect: &Redirect) {
+//!         if redirect.operator.starts_with('>') {
+//!             self.0.push(redirect.target.clone());
+//!         }
+//!     }
+//! }
+//!
+//! let script = ParsedScript::parse("sort < input.txt | tee build.log > summary.txt").unwrap();
+//! let mut writes = Writes(Vec::new());
+//! script.walk(&mut writes);
+//! assert_eq!(writes.0, vec!["summary.txt"]);
+//! ```
+
+use super::ast::{extract_command, normalize_word, substitution_body, NormalizedCommand, Span};
+use super::grammar;
+use tree_sitter::Node;
+
+/// A pipeline of two or more commands
+#[derive(Debug, Clone)]
+pub struct Pipeline {
+    /// The stages, left to right
+    pub commands: Vec<NormalizedCommand>,
+    /// Location of the whole pipeline
+    pub span: Span,
+}
+
+/// A redirect attached to a command
+#[derive(Debug, Clone)]
+pub struct Redirect {
+    /// The operator (`>`, `>>`, `<`, `&>`, `>&`, `<<`, `<<<`, ...)
+    pub operator: String,
+    /// Explicit file descriptor (`2` in `2>&1`)
+    pub descriptor: Option<String>,
+    /// Normalized target: a path, descriptor, heredoc delimiter or here-string
+    pub target: String,
+    /// Location of the redirect
+    pub span: Span,
+}
+
+/// Kind of substitution
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SubstitutionKind {
+    /// `$(...)` or backticks
+    Command,
+    /// `<(...)` or `>(...)`
+    Process,
+}
+
+/// A command or process substitution
+#[derive(Debug, Clone)]
+pub struct Substitution {
+    pub kind: SubstitutionKind,
+    /// The substituted command text, without the delimiters
+    pub body: String,
+    /// Location including the delimiters
+    pub span: Span,
+}
+
+/// One node of interest, in source order
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub enum ShellNode {
+    Command(NormalizedCommand),
+    Pipeline(Pipeline),
+    Redirect(Redirect),
+    Substitution(Substitution),
+}
+
+impl ShellNode {
+    /// Location of the node in the source
+    pub fn span(&self) -> Span {
+        match self {
+            ShellNode::Command(c) => c.span,
+            ShellNode::Pipeline(p) => p.span,
+            ShellNode::Redirect(r) => r.span,
+            ShellNode::Substitution(s) => s.span,
+        }
+    }
+}
+
+/// Callbacks for [`ParsedScript::walk`]; every method defaults to a no-op
+pub trait Visitor {
+    fn visit_command(&mut self, _command: &NormalizedCommand) {}
+    fn visit_pipeline(&mut self, _pipeline: &Pipeline) {}
+    fn visit_redirect(&mut self, _redirect: &Redirect) {}
+    fn visit_substitution(&mut self, _substitution: &Substitution) {}
+}
+
+/// A parsed command line
+#[derive(Debug, Clone)]
+pub struct ParsedScript {
+    nodes: Vec<ShellNode>,
+}
+
+impl ParsedScript {
+    /// Parse a command line; fails on input tree-sitter can't parse cleanly
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let mut parser = grammar::parser()?;
+        let tree = parser
+            .parse(source, None)
+            .ok_or_else(|| "Failed to parse command".to_string())?;
+        let root = tree.root_node();
+        if root.has_error() {
+            return Err("AST contains parse errors".to_string());
+        }
+
+        let mut nodes = Vec::new();
+        collect(&root, source, &mut nodes);
+        Ok(Self { nodes })
+    }
+
+    /// All nodes in source order (outer before inner)
+    pub fn nodes(&self) -> &[ShellNode] {
+        &self.nodes
+    }
+
+    /// Iterate over all nodes
+    pub fn iter(&self) -> impl Iterator<Item = &ShellNode> {
+        self.nodes.iter()
+    }
+
+    /// Every command, including those inside pipelines and substitutions
+    pub fn commands(&self) -> impl Iterator<Item = &NormalizedCommand> {
+        self.nodes.iter().filter_map(|n| match n {
+            ShellNode::Command(c) => Some(c),
+            _ => None,
+        })
+    }
+
+    /// Call the visitor for every node in source order
+    pub fn walk<V: Visitor + ?Sized>(&self, visitor: &mut V) {
+        for node in &self.nodes {
+            match node {
+                ShellNode::Command(c) => visitor.visit_command(c),
+                ShellNode::Pipeline(p) => visitor.visit_pipeline(p),
+                ShellNode::Redirect(r) => visitor.visit_redirect(r),
+                ShellNode::Substitution(s) => visitor.visit_substitution(s),
+            }
+        }
+    }
+}
+
+/// Pre-order traversal collecting nodes of interest
+fn collect(node: &Node, source: &str, out: &mut Vec<ShellNode>) {
+    match node.kind() {
+        "command" => {
+            if let Some(cmd) = extract_command(node, source) {
+                out.push(ShellNode::Command(cmd));
+            }
+        }
+        "pipeline" => {
+            let mut cursor = node.walk();
+            let commands = node
+                .children(&mut cursor)
+                .filter_map(|c| stage_command(&c, source))
+                .collect();
+            out.push(ShellNode::Pipeline(Pipeline {
+                commands,
+                span: Span::of(node),
+            }));
+        }
+        "file_redirect" | "heredoc_redirect" | "herestring_redirect" => {
+            if let Some(redirect) = extract_redirect(node, source) {
+                out.push(ShellNode::Redirect(redirect));
+            }
+        }
+        "command_substitution" | "process_substitution" => {
+            let text = node.utf8_text(source.as_bytes()).unwrap_or("");
+            let kind = if node.kind() == "process_substitution" {
+                SubstitutionKind::Process
+            } else {
+                SubstitutionKind::Command
+            };
+            out.push(ShellNode::Substitution(Substitution {
+                kind,
+                body: substitution_body(text),
+                span: Span::of(node),
+            }));
+        }
+        _ => {}
+    }
+
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect(&child, source, out);
+    }
+}
+
+/// The command run by one pipeline stage (looking through redirects)
+fn stage_command(node: &Node, source: &str) -> Option<NormalizedCommand> {
+    match node.kind() {
+        "command" => extract_command(node, source),
+        "redirected_statement" => {
+            let body = node.child_by_field_name("body")?;
+            stage_command(&body, source)
+        }
+        _ => None,
+    }
+}
+
+/// Build a redirect from a redirect node
+pub(crate) fn extract_redirect(node: &Node, source: &str) -> Option<Redirect> {
+    let mut cursor = node.walk();
+    let children: Vec<Node> = node.children(&mut cursor).collect();
+    let text = |n: &Node| n.utf8_text(source.as_bytes()).unwrap_or("").to_string();
+
+    let (operator, target) = match node.kind() {
+        "heredoc_redirect" => {
+            let start = children.iter().find(|c| c.kind() == "heredoc_start")?;
+            ("<<".to_string(), text(start))
+        }
+        "herestring_redirect" => {
+            let word = children.iter().rfind(|c| c.is_named())?;
+            ("<<<".to_string(), normalize_word(word, source))
+        }
+        _ => {
+            let operator = children.iter().find(|c| !c.is_named())?;
+            let target = node
+                .child_by_field_name("destination")
+                .map(|d| normalize_word(&d, source))
+                .unwrap_or_default();
+            (text(operator), target)
+        }
+    };
+
+    Some(Redirect {
+        operator,
+        descriptor: node.child_by_field_name("descriptor").map(|d| text(&d)),
+        target,
+        span: Span::of(node),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn redirects(source: &str) -> Vec<(String, Option<String>, String)> {
+        ParsedScript::parse(source)
+            .unwrap()
+            .iter()
+            .filter_map(|n| match n {
+                ShellNode::Redirect(r) => Some((r.operator.clone(), r.descriptor.clone(), r.target.clone())),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_commands_in_source_order_with_spans() {
+        let source = "echo $(whoami) && rm -rf ./build";
+        let script = ParsedScript::parse(source).unwrap();
+        let names: Vec<_> = script.commands().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["echo", "whoami", "rm"]);
+
+        let rm = script.commands().last().unwrap();
+        assert_eq!(rm.span.text(source), "rm -rf ./build");
+        assert_eq!(rm.arguments, vec!["-rf", "./build"]);
+    }
+
+    #[test]
+    fn test_pipelines_and_substitutions() {
+        let source = "diff <(sort a) `cat b` | grep x";
+        let script = ParsedScript::parse(source).unwrap();
+
+        let pipeline = script
+            .iter()
+            .find_map(|n| match n {
+                ShellNode::Pipeline(p) => Some(p),
+                _ => None,
+            })
+            .unwrap();
+        let stages: Vec<_> = pipeline.commands.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(stages, vec!["diff", "grep"]);
+        assert_eq!(pipeline.span.text(source), source);
+
+        let subs: Vec<_> = script
+            .iter()
+            .filter_map(|n| match n {
+                ShellNode::Substitution(s) => Some((s.kind, s.body.as_str())),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            subs,
+            vec![(SubstitutionKind::Process, "sort a"), (SubstitutionKind::Command, "cat b")]
+        );
+    }
+
+    #[test]
+    fn test_redirects() {
+        assert_eq!(
+            redirects("make > out.log 2>&1"),
+            vec![
+                (">".to_string(), None, "out.log".to_string()),
+                (">&".to_string(), Some("2".to_string()), "1".to_string()),
+            ]
+        );
+        assert_eq!(redirects("wc <<< 'a b'"), vec![("<<<".to_string(), None, "a b".to_string())]);
+        assert_eq!(redirects("cat <<EOF\nhi\nEOF"), vec![("<<".to_string(), None, "EOF".to_string())]);
+    }
+
+    #[test]
+    fn test_visitor_defaults_and_parse_errors() {
+        struct Count(usize);
+        impl Visitor for Count {
+            fn visit_command(&mut self, _command: &NormalizedCommand) {
+                self.0 += 1;
+            }
+        }
+
+        let mut count = Count(0);
+        ParsedScript::parse("a | b; c > d").unwrap().walk(&mut count);
+        assert_eq!(count.0, 3);
+
+        assert!(ParsedScript::parse("echo 'unterminated").is_err());
+    }
+}
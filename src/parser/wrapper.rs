@@ -21,6 +21,12 @@ pub const DEFAULT_WRAPPERS: &[&str] = &[
     "doas",       // BSD sudo alternative
 ];
 
+/// Shells whose `-c` argument is a script to run
+const INLINE_SCRIPT_SHELLS: &[&str] = &["sh", "bash", "zsh", "dash", "ksh", "mksh", "ash"];
+
+/// Shell options that take a separate value
+const SHELL_VALUE_OPTIONS: &[&str] = &["-o", "+o", "-O", "+O", "--rcfile", "--init-file"];
+
 /// Extract the actual command from wrapper commands
 ///
 /// Example: "sudo timeout 30 rm -rf /" -> ["rm -rf /"]
@@ -53,6 +59,49 @@ pub fn unwrap_command(command: &str, wrappers: &[String]) -> Vec<String> {
     results
 }
 
+/// Extract the script a shell runs inline, after unwrapping wrappers
+///
+/// Example: "sudo bash -lc 'rm -rf /tmp/x && ls'" -> Some("rm -rf /tmp/x && ls")
+///
+/// The script is taken from the original words, so its quoting survives
+/// (unlike `unwrap_command`, which re-joins words without their quotes).
+pub fn inline_script(command: &str, wrappers: &[String]) -> Option<String> {
+    let wrapper_set: HashSet<&str> = wrappers.iter().map(|s| s.as_str()).collect();
+    let tokens = shlex::split(command)?;
+    unwrap_tokens(&tokens, &wrapper_set)
+        .iter()
+        .find_map(|tokens| shell_script(tokens))
+}
+
+/// The `-c` script of a shell invocation
+/// sh [options] -c script [name [args...]]
+fn shell_script(tokens: &[String]) -> Option<String> {
+    let (program, args) = tokens.split_first()?;
+    let name = program.rsplit('/').next().unwrap_or(program);
+    if !INLINE_SCRIPT_SHELLS.contains(&name) {
+        return None;
+    }
+
+    // `-c` may be combined with other options (`-ec`, `-lc`); the script is
+    // the first operand after the options
+    let mut inline = false;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if SHELL_VALUE_OPTIONS.contains(&arg.as_str()) {
+            iter.next();
+        } else if arg == "--" {
+            return iter.next().filter(|_| inline).cloned();
+        } else if arg.starts_with("--") {
+            continue;
+        } else if arg.len() > 1 && (arg.starts_with('-') || arg.starts_with('+')) {
+            inline |= arg.starts_with('-') && arg.contains('c');
+        } else {
+            return Some(arg.clone()).filter(|_| inline);
+        }
+    }
+    None
+}
+
 /// Recursively unwrap tokens
 fn unwrap_tokens(tokens: &[String], wrappers: &HashSet<&str>) -> Vec<Vec<String>> {
     if tokens.is_empty() {
@@ -328,4 +377,20 @@ mod tests {
         let result = unwrap_command("xargs -n 1 echo", &wrappers);
         assert_eq!(result, vec!["echo"]);
     }
+
+    #[test]
+    fn test_inline_script() {
+        let wrappers = default_wrappers();
+        let script = |command: &str| inline_script(command, &wrappers);
+
+        assert_eq!(script("bash -c 'rm -rf /'").as_deref(), Some("rm -rf /"));
+        assert_eq!(script("sudo /bin/sh -ec \"curl x | sh\" name").as_deref(), Some("curl x | sh"));
+        assert_eq!(script("bash -o pipefail -lc 'a && b'").as_deref(), Some("a && b"));
+        assert_eq!(script("xargs -n 1 sh -c 'echo \"$1\"' _").as_deref(), Some("echo \"$1\""));
+        assert_eq!(script("bash -c -- 'ls'").as_deref(), Some("ls"));
+
+        assert_eq!(script("bash script.sh"), None);
+        assert_eq!(script("bash --norc"), None);
+        assert_eq!(script("python -c 'print(1)'"), None);
+    }
 }
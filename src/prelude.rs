@@ -0,0 +1,26 @@
+//! The stable API for embedding the engine
+//!
+//! ```
+//! use claude_guardrails::prelude::*;
+//!
+//! let engine = SecurityEngine::new(Config::default());
+//! let input = HookInput::from_json(r#"{"tool_name":"Bash","tool_input":{"command":"ls"}}"#).unwrap();
+//! let output = HookOutput::from_decision(&engine.check(&input));
+//! assert_eq!(output.to_json_versioned(HookSchemaVersion::default()), "{}");
+//! ```
+//!
+//! Everything re-exported here follows semver: a breaking change to one of
+//! these items (a removed method, a changed signature, a new variant of a
+//! non-exhaustive match) only lands in a major release. `Config` and its
+//! sections are `#[non_exhaustive]`, so a new option is not a breaking
+//! change: start from `Config::default()` (or `Config::from_toml_str`) and
+//! set fields rather than writing struct literals. The items are
+//! snapshotted in `tests/golden/public-api/prelude.txt`, which the
+//! integration tests compare against the source.
+
+pub use crate::config::{Config, SafetyLevel};
+pub use crate::engine::SecurityEngine;
+pub use crate::input::{HookInput, ToolInput};
+pub use crate::output::{AllowSource, Decision, HookOutput, HookSchemaVersion};
+pub use crate::parser::ast::{NormalizedCommand, Span};
+pub use crate::parser::visit::{ParsedScript, Pipeline, Redirect, ShellNode, Substitution, SubstitutionKind, Visitor};
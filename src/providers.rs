@@ -0,0 +1,115 @@
+//! Injectable clock and environment
+//!
+//! The engine and audit logger read the time and the `GUARDRAILS_*` kill
+//! switches through these traits instead of `Utc::now()` and `env::var`,
+//! so tests can pin the time (approval TTLs, download-log expiry, audit
+//! timestamps) and set variables without touching the process environment,
+//! which is shared by every test in the binary.
+//!
+//! ```
+//! use std::sync::Arc;
+//! use claude_guardrails::providers::{FixedClock, MapEnv};
+//! use claude_guardrails::{Config, SecurityEngine};
+//!
+//! let clock = Arc::new(FixedClock::new("2025-01-01T00:00:00Z".parse().unwrap()));
+//! let engine = SecurityEngine::new(Config::default())
+//!     .with_clock(clock.clone())
+//!     .with_environment(Arc::new(MapEnv::new().with("GUARDRAILS_WARN_ONLY", "1")));
+//! assert!(engine.is_warn_only());
+//! clock.advance(chrono::Duration::minutes(5));
+//! ```
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use chrono::{DateTime, Duration, Utc};
+
+/// Source of the current time
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// The system clock
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// A clock that only moves when told to
+#[derive(Debug)]
+pub struct FixedClock {
+    now: Mutex<DateTime<Utc>>,
+}
+
+impl FixedClock {
+    /// A clock stopped at `now`
+    pub fn new(now: DateTime<Utc>) -> Self {
+        Self { now: Mutex::new(now) }
+    }
+
+    /// Move the clock to `now`
+    pub fn set(&self, now: DateTime<Utc>) {
+        *self.now.lock().unwrap_or_else(|e| e.into_inner()) = now;
+    }
+
+    /// Move the clock forward by `duration`
+    pub fn advance(&self, duration: Duration) {
+        let mut now = self.now.lock().unwrap_or_else(|e| e.into_inner());
+        *now += duration;
+    }
+}
+
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.now.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Source of environment variables
+pub trait Environment: Send + Sync {
+    fn var(&self, key: &str) -> Option<String>;
+
+    /// Whether `key` is set (to anything, including an empty string)
+    fn is_set(&self, key: &str) -> bool {
+        self.var(key).is_some()
+    }
+}
+
+/// The process environment
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ProcessEnv;
+
+impl Environment for ProcessEnv {
+    fn var(&self, key: &str) -> Option<String> {
+        std::env::var(key).ok()
+    }
+}
+
+/// A fixed set of variables; everything else is unset
+#[derive(Debug, Clone, Default)]
+pub struct MapEnv {
+    vars: HashMap<String, String>,
+}
+
+impl MapEnv {
+    /// An empty environment
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builder-style: set `key` to `value`
+    pub fn with(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.vars.insert(key.into(), value.into());
+        self
+    }
+}
+
+impl Environment for MapEnv {
+    fn var(&self, key: &str) -> Option<String> {
+        self.vars.get(key).cloned()
+    }
+}
@@ -0,0 +1,304 @@
+//! Remote approval for rules set to `remote-ask`
+//!
+//! Supervised YOLO mode: instead of blocking outright, a denial by a rule
+//! set to `remote-ask` in `[rules.overrides]` is POSTed as JSON to
+//! `approval.endpoint` (a Slack bot, an internal tool), where a teammate
+//! approves or denies it. The endpoint answers with a verdict:
+//!
+//! ```json
+//! {"decision": "approve", "by": "alice"}
+//! {"decision": "deny", "by": "bob", "reason": "not on prod"}
+//! {"decision": "pending", "poll_url": "https://approvals.example.org/v1/requests/42"}
+//! ```
+//!
+//! A pending request is polled with GET (`poll_url`, or the endpoint with
+//! the request ID appended) every `poll_interval_ms` until a verdict comes
+//! back. Anything else — no verdict within `timeout_ms`, an HTTP error, an
+//! unreadable body, or a build without the `webhook` feature — denies the
+//! call. An approved call is let through as a warning, so the rule and the
+//! approver stay in the audit log.
+
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+use crate::config::{ApprovalConfig, Config, RuleAction};
+use crate::input::HookInput;
+use crate::output::Decision;
+
+/// What the approver is shown
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ApprovalRequest {
+    /// Unique per request; appended to the endpoint to poll it
+    pub id: String,
+    pub rule_id: String,
+    pub reason: String,
+    pub tool: String,
+    /// The tool input, summarized as in the audit log
+    pub input: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    pub timestamp: String,
+}
+
+impl ApprovalRequest {
+    /// The request for a denial of `input` by `rule_id`
+    pub fn new(input: &HookInput, rule_id: &str, reason: &str) -> Self {
+        let now = chrono::Utc::now();
+        Self {
+            id: format!("{:x}-{:x}", now.timestamp_micros(), std::process::id()),
+            rule_id: rule_id.to_string(),
+            reason: reason.to_string(),
+            tool: input.tool_name.clone(),
+            input: input.summary(),
+            cwd: input.cwd.clone(),
+            session_id: input.session_id.clone(),
+            timestamp: now.to_rfc3339(),
+        }
+    }
+}
+
+/// An endpoint's answer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    Approve { by: Option<String> },
+    Deny { by: Option<String>, reason: Option<String> },
+    /// Not decided yet; poll this URL
+    Pending { poll_url: String },
+}
+
+#[derive(Deserialize)]
+struct Answer {
+    decision: String,
+    by: Option<String>,
+    reason: Option<String>,
+    poll_url: Option<String>,
+}
+
+impl Verdict {
+    /// Parse a response body; `poll_url` is where a pending request is
+    /// polled unless the body names another URL
+    pub fn parse(body: &str, poll_url: &str) -> Result<Self, String> {
+        let answer: Answer = serde_json::from_str(body).map_err(|e| format!("unreadable verdict: {}", e))?;
+        match answer.decision.to_lowercase().as_str() {
+            "approve" | "approved" | "allow" => Ok(Verdict::Approve { by: answer.by }),
+            "deny" | "denied" | "reject" => Ok(Verdict::Deny { by: answer.by, reason: answer.reason }),
+            "pending" => Ok(Verdict::Pending {
+                poll_url: answer.poll_url.unwrap_or_else(|| poll_url.to_string()),
+            }),
+            other => Err(format!("unknown verdict \"{}\"", other)),
+        }
+    }
+}
+
+/// How requests reach the endpoint
+pub trait Transport {
+    /// POST `body` as JSON to `url`, returning the response body
+    fn post(&mut self, url: &str, body: &str, timeout: Duration) -> Result<String, String>;
+    /// GET `url`, returning the response body
+    fn get(&mut self, url: &str, timeout: Duration) -> Result<String, String>;
+}
+
+/// Ask the endpoint about `request` and wait for a verdict, returning the
+/// decision for the call
+pub fn ask(config: &ApprovalConfig, request: &ApprovalRequest, transport: &mut dyn Transport) -> Decision {
+    let denied = |detail: String| Decision::deny(&request.rule_id, format!("{} ({})", request.reason, detail));
+    let Some(endpoint) = config.endpoint.as_deref() else {
+        return denied("remote-ask without approval.endpoint".to_string());
+    };
+
+    let timeout = Duration::from_millis(config.timeout_ms);
+    let deadline = Instant::now() + timeout;
+    let default_poll_url = format!("{}/{}", endpoint.trim_end_matches('/'), request.id);
+    let body = serde_json::to_string(request).unwrap_or_default();
+    let mut answer = transport.post(endpoint, &body, timeout);
+
+    loop {
+        let verdict = match answer.and_then(|body| Verdict::parse(&body, &default_poll_url)) {
+            Ok(verdict) => verdict,
+            Err(e) => return denied(format!("remote approval failed: {}", e)),
+        };
+        let poll_url = match verdict {
+            Verdict::Approve { by } => {
+                let by = by.map(|by| format!(" by {}", by)).unwrap_or_default();
+                return Decision::warn(&request.rule_id, format!("{} (approved remotely{})", request.reason, by));
+            }
+            Verdict::Deny { by, reason } => {
+                let by = by.map(|by| format!(" by {}", by)).unwrap_or_default();
+                let reason = reason.map(|reason| format!(": {}", reason)).unwrap_or_default();
+                return denied(format!("denied remotely{}{}", by, reason));
+            }
+            Verdict::Pending { poll_url } => poll_url,
+        };
+
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return denied(format!("no remote approval within {}ms", config.timeout_ms));
+        }
+        std::thread::sleep(remaining.min(Duration::from_millis(config.poll_interval_ms)));
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return denied(format!("no remote approval within {}ms", config.timeout_ms));
+        }
+        answer = transport.get(&poll_url, remaining);
+    }
+}
+
+/// HTTP(S) transport
+#[cfg(feature = "webhook")]
+pub struct HttpTransport;
+
+#[cfg(feature = "webhook")]
+impl Transport for HttpTransport {
+    fn post(&mut self, url: &str, body: &str, timeout: Duration) -> Result<String, String> {
+        ureq::AgentBuilder::new()
+            .timeout(timeout)
+            .build()
+            .post(url)
+            .set("Content-Type", "application/json")
+            .send_string(body)
+            .map_err(|e| e.to_string())?
+            .into_string()
+            .map_err(|e| e.to_string())
+    }
+
+    fn get(&mut self, url: &str, timeout: Duration) -> Result<String, String> {
+        ureq::AgentBuilder::new()
+            .timeout(timeout)
+            .build()
+            .get(url)
+            .call()
+            .map_err(|e| e.to_string())?
+            .into_string()
+            .map_err(|e| e.to_string())
+    }
+}
+
+/// Without the `webhook` feature there is no HTTP client, so every
+/// `remote-ask` denial stands
+#[cfg(not(feature = "webhook"))]
+pub struct HttpTransport;
+
+#[cfg(not(feature = "webhook"))]
+impl Transport for HttpTransport {
+    fn post(&mut self, _url: &str, _body: &str, _timeout: Duration) -> Result<String, String> {
+        Err("built without the webhook feature".to_string())
+    }
+
+    fn get(&mut self, _url: &str, _timeout: Duration) -> Result<String, String> {
+        Err("built without the webhook feature".to_string())
+    }
+}
+
+/// Send a denial by a `remote-ask` rule for approval; other decisions are
+/// returned as they are
+pub fn resolve(config: &Config, input: &HookInput, decision: Decision) -> Decision {
+    let Decision::Deny { ref rule_id, ref reason } = decision else {
+        return decision;
+    };
+    if config.rule_action(rule_id) != Some(RuleAction::RemoteAsk) {
+        return decision;
+    }
+    let request = ApprovalRequest::new(input, rule_id, reason);
+    ask(&config.approval, &request, &mut HttpTransport)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Answers the POST and each GET in turn, recording the URLs asked
+    struct Scripted {
+        answers: Vec<Result<String, String>>,
+        urls: Vec<String>,
+    }
+
+    impl Scripted {
+        fn new(answers: &[&str]) -> Self {
+            Self { answers: answers.iter().rev().map(|a| Ok(a.to_string())).collect(), urls: Vec::new() }
+        }
+
+        fn next(&mut self, url: &str) -> Result<String, String> {
+            self.urls.push(url.to_string());
+            self.answers.pop().unwrap_or_else(|| Ok(r#"{"decision": "pending"}"#.to_string()))
+        }
+    }
+
+    impl Transport for Scripted {
+        fn post(&mut self, url: &str, _body: &str, _timeout: Duration) -> Result<String, String> {
+            self.next(url)
+        }
+
+        fn get(&mut self, url: &str, _timeout: Duration) -> Result<String, String> {
+            self.next(url)
+        }
+    }
+
+    fn config() -> ApprovalConfig {
+        ApprovalConfig {
+            endpoint: Some("https://approvals.test/v1/requests/".to_string()),
+            timeout_ms: 200,
+            poll_interval_ms: 1,
+        }
+    }
+
+    fn request() -> ApprovalRequest {
+        let input = HookInput::from_json(
+            r#"{"tool_name": "Bash", "tool_input": {"command": "terraform destroy"}, "cwd": "/srv/infra"}"#,
+        )
+        .unwrap();
+        ApprovalRequest::new(&input, "terraform-destroy", "Destroying Terraform-managed infrastructure")
+    }
+
+    #[test]
+    fn test_approved_after_polling() {
+        let request = request();
+        let mut transport = Scripted::new(&[r#"{"decision": "pending"}"#, r#"{"decision": "approve", "by": "alice"}"#]);
+        let decision = ask(&config(), &request, &mut transport);
+        assert!(matches!(decision, Decision::Warn { .. }));
+        assert_eq!(decision.rule_id(), Some("terraform-destroy"));
+        assert_eq!(decision.reason(), "Destroying Terraform-managed infrastructure (approved remotely by alice)");
+        assert_eq!(transport.urls[1], format!("https://approvals.test/v1/requests/{}", request.id));
+    }
+
+    #[test]
+    fn test_denied() {
+        let mut transport = Scripted::new(&[
+            r#"{"decision": "pending", "poll_url": "https://approvals.test/poll/7"}"#,
+            r#"{"decision": "deny", "by": "bob", "reason": "not on prod"}"#,
+        ]);
+        let decision = ask(&config(), &request(), &mut transport);
+        assert!(decision.is_deny());
+        assert!(decision.reason().ends_with("(denied remotely by bob: not on prod)"));
+        assert_eq!(transport.urls[1], "https://approvals.test/poll/7");
+    }
+
+    #[test]
+    fn test_denied_on_timeout_and_errors() {
+        let decision = ask(&config(), &request(), &mut Scripted::new(&[]));
+        assert!(decision.is_deny());
+        assert!(decision.reason().ends_with("(no remote approval within 200ms)"));
+
+        let decision = ask(&config(), &request(), &mut Scripted::new(&["<html>bad gateway</html>"]));
+        assert!(decision.is_deny());
+        assert!(decision.reason().contains("remote approval failed: unreadable verdict"));
+
+        let unset = ApprovalConfig { endpoint: None, ..config() };
+        assert!(ask(&unset, &request(), &mut Scripted::new(&[r#"{"decision": "approve"}"#])).is_deny());
+    }
+
+    #[test]
+    fn test_only_remote_ask_rules_are_sent() {
+        let input = HookInput::from_json(r#"{"tool_name": "Bash", "tool_input": {"command": "rm -rf /"}}"#).unwrap();
+        let mut config = Config::default();
+        let denial = Decision::deny("rm-root", "Deleting root");
+        assert_eq!(resolve(&config, &input, denial.clone()).reason(), "Deleting root");
+
+        // No endpoint: the denial stands, saying why
+        config.rules.overrides.insert("rm-root".to_string(), RuleAction::RemoteAsk);
+        let decision = resolve(&config, &input, denial);
+        assert!(decision.is_deny());
+        assert_eq!(decision.reason(), "Deleting root (remote-ask without approval.endpoint)");
+    }
+}
@@ -1,15 +1,25 @@
 //! Allowlist handling for bypassing security checks
 //!
-//! Supports user-defined patterns that should bypass security checks.
+//! Supports user-defined patterns that should bypass security checks. Several
+//! allowlist files (user, team-shared, project) can be merged; each entry
+//! remembers the file it came from so decisions can report it.
+//!
+//! Besides regex patterns, Bash entries can be structured: `command` plus
+//! optional `args_match`, checked against the AST-normalized commands rather
+//! than the raw string, so extra flags or chained commands don't slip through.
+//! A command line with a redirect, heredoc or substitution is never matched
+//! by them, so the redirect and file checks still see what it writes.
 
+use crate::parser::ast::{CommandAnalysis, NormalizedCommand};
 use regex::Regex;
 use serde::Deserialize;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 /// An allowlist entry
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Default, Deserialize)]
 pub struct AllowEntry {
-    /// Regex pattern to match
+    /// Regex pattern to match (unused for structured entries)
+    #[serde(default)]
     pub pattern: String,
 
     /// Human-readable reason for allowing
@@ -18,6 +28,16 @@ pub struct AllowEntry {
     /// Optional tool restriction (if not set, applies to all tools)
     #[serde(default)]
     pub tool: Option<String>,
+
+    /// Structured Bash entry: program name (`rm` also matches `/bin/rm` and
+    /// the other system directories in `SYSTEM_BIN_DIRS`) or exact path
+    #[serde(default)]
+    pub command: Option<String>,
+
+    /// Regexes each matching one argument in full; the argument count must
+    /// match too. Unset means any arguments.
+    #[serde(default)]
+    pub args_match: Option<Vec<String>>,
 }
 
 /// The allowlist configuration file structure
@@ -28,22 +48,74 @@ pub struct AllowlistConfig {
     pub allow: Vec<AllowEntry>,
 }
 
+/// A compiled allowlist entry
+struct CompiledEntry {
+    regex: Regex,
+    reason: String,
+    /// File the entry was loaded from, if any
+    source: Option<String>,
+}
+
+/// A compiled structured (program + arguments) entry
+struct CompiledCommand {
+    name: String,
+    args: Option<Vec<Regex>>,
+    reason: String,
+    source: Option<String>,
+}
+
+/// Directories a bare `command` name may also be run from by path
+const SYSTEM_BIN_DIRS: &[&str] = &["/bin", "/sbin", "/usr/bin", "/usr/sbin", "/usr/local/bin", "/opt/homebrew/bin"];
+
+impl CompiledCommand {
+    fn matches(&self, cmd: &NormalizedCommand) -> bool {
+        let name_matches = cmd.name == self.name
+            || (!self.name.contains('/')
+                && cmd
+                    .name
+                    .rsplit_once('/')
+                    .is_some_and(|(dir, name)| name == self.name && SYSTEM_BIN_DIRS.contains(&dir)));
+        // Nothing before the name: `LD_PRELOAD=x ls` runs `ls` in another
+        // environment (a quoted name is refused too, which is only stricter)
+        name_matches
+            && cmd.full_command.starts_with(cmd.name.as_str())
+            && !cmd.is_dynamic
+            && self.args.as_ref().is_none_or(|args| {
+                args.len() == cmd.arguments.len()
+                    && args.iter().zip(&cmd.arguments).all(|(re, arg)| re.is_match(arg))
+            })
+    }
+}
+
+/// An allowlist entry that matched an input
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AllowMatch<'a> {
+    pub reason: &'a str,
+    pub source: Option<&'a str>,
+}
+
 /// Compiled allowlist for efficient matching
 pub struct CompiledAllowlist {
     /// General patterns (apply to all tools)
-    general: Vec<(Regex, String)>,
+    general: Vec<CompiledEntry>,
 
     /// Bash-specific patterns
-    bash: Vec<(Regex, String)>,
+    bash: Vec<CompiledEntry>,
 
     /// Read-specific patterns
-    read: Vec<(Regex, String)>,
+    read: Vec<CompiledEntry>,
 
     /// Edit-specific patterns
-    edit: Vec<(Regex, String)>,
+    edit: Vec<CompiledEntry>,
 
     /// Write-specific patterns
-    write: Vec<(Regex, String)>,
+    write: Vec<CompiledEntry>,
+
+    /// WebFetch/WebSearch patterns
+    web: Vec<CompiledEntry>,
+
+    /// Structured Bash entries
+    commands: Vec<CompiledCommand>,
 }
 
 impl CompiledAllowlist {
@@ -55,6 +127,8 @@ impl CompiledAllowlist {
             read: Vec::new(),
             edit: Vec::new(),
             write: Vec::new(),
+            web: Vec::new(),
+            commands: Vec::new(),
         }
     }
 
@@ -62,22 +136,86 @@ impl CompiledAllowlist {
     pub fn from_file(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
         let content = std::fs::read_to_string(path)?;
         let config: AllowlistConfig = toml::from_str(&content)?;
-        Self::from_config(&config)
+        Self::compile(&config, Some(path.display().to_string()))
+    }
+
+    /// Parse and compile an allowlist from TOML text (`allow.toml` format)
+    pub fn from_toml_str(toml: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let config: AllowlistConfig = toml::from_str(toml)?;
+        Self::compile(&config, None)
+    }
+
+    /// Load and merge several allowlist files, in order
+    ///
+    /// Missing files are skipped; files that fail to parse are reported on
+    /// stderr and skipped so one bad file doesn't drop the others.
+    pub fn from_files(paths: &[PathBuf]) -> Self {
+        let mut allowlist = Self::empty();
+        for path in paths.iter().filter(|p| p.exists()) {
+            match Self::from_file(path) {
+                Ok(other) => allowlist.merge(other),
+                Err(e) => eprintln!("Warning: Ignoring allowlist {}: {}", path.display(), e),
+            }
+        }
+        allowlist
+    }
+
+    /// Append another allowlist's entries after this one's
+    pub fn merge(&mut self, other: CompiledAllowlist) {
+        self.general.extend(other.general);
+        self.bash.extend(other.bash);
+        self.read.extend(other.read);
+        self.edit.extend(other.edit);
+        self.write.extend(other.write);
+        self.web.extend(other.web);
+        self.commands.extend(other.commands);
     }
 
     /// Compile from config
     pub fn from_config(config: &AllowlistConfig) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::compile(config, None)
+    }
+
+    fn compile(
+        config: &AllowlistConfig,
+        source: Option<String>,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
         let mut allowlist = Self::empty();
 
         for entry in &config.allow {
-            let regex = Regex::new(&entry.pattern)?;
-            let item = (regex, entry.reason.clone());
+            if let Some(name) = &entry.command {
+                if !matches!(entry.tool.as_deref(), None | Some("Bash") | Some("bash")) {
+                    eprintln!("Warning: Structured allowlist entry for {} applies to Bash only", name);
+                }
+                let args = match &entry.args_match {
+                    Some(args) => Some(
+                        args.iter()
+                            .map(|a| Regex::new(&format!("^(?:{})$", a)))
+                            .collect::<Result<Vec<_>, _>>()?,
+                    ),
+                    None => None,
+                };
+                allowlist.commands.push(CompiledCommand {
+                    name: name.clone(),
+                    args,
+                    reason: entry.reason.clone(),
+                    source: source.clone(),
+                });
+                continue;
+            }
+
+            let item = CompiledEntry {
+                regex: Regex::new(&entry.pattern)?,
+                reason: entry.reason.clone(),
+                source: source.clone(),
+            };
 
             match entry.tool.as_deref() {
                 Some("Bash") | Some("bash") => allowlist.bash.push(item),
                 Some("Read") | Some("read") => allowlist.read.push(item),
-                Some("Edit") | Some("edit") => allowlist.edit.push(item),
+                Some("Edit") | Some("edit") | Some("MultiEdit") => allowlist.edit.push(item),
                 Some("Write") | Some("write") => allowlist.write.push(item),
+                Some("WebFetch") | Some("WebSearch") | Some("web") => allowlist.web.push(item),
                 None | Some("*") => allowlist.general.push(item),
                 Some(other) => {
                     eprintln!("Warning: Unknown tool type in allowlist: {}", other);
@@ -89,31 +227,69 @@ impl CompiledAllowlist {
         Ok(allowlist)
     }
 
+    /// Number of entries across all tools
+    pub fn len(&self) -> usize {
+        self.general.len()
+            + self.bash.len()
+            + self.read.len()
+            + self.edit.len()
+            + self.write.len()
+            + self.web.len()
+            + self.commands.len()
+    }
+
     /// Check if a command/path matches the allowlist for the given tool
     pub fn matches(&self, tool: &str, input: &str) -> Option<&str> {
+        self.find(tool, input).map(|m| m.reason)
+    }
+
+    /// Like `matches`, but also report which file supplied the entry
+    pub fn find(&self, tool: &str, input: &str) -> Option<AllowMatch<'_>> {
         // Check tool-specific patterns first
-        let tool_patterns: &[(Regex, String)] = match tool.to_lowercase().as_str() {
+        let tool_patterns: &[CompiledEntry] = match tool.to_lowercase().as_str() {
             "bash" => &self.bash,
             "read" => &self.read,
-            "edit" => &self.edit,
+            "edit" | "multiedit" => &self.edit,
             "write" => &self.write,
+            "webfetch" | "websearch" | "web" => &self.web,
             _ => &[],
         };
 
-        for (regex, reason) in tool_patterns {
-            if regex.is_match(input) {
-                return Some(reason);
-            }
-        }
-
         // Then check general patterns
-        for (regex, reason) in &self.general {
-            if regex.is_match(input) {
-                return Some(reason);
-            }
+        tool_patterns
+            .iter()
+            .chain(&self.general)
+            .find(|entry| entry.regex.is_match(input))
+            .map(|entry| AllowMatch {
+                reason: &entry.reason,
+                source: entry.source.as_deref(),
+            })
+    }
+
+    /// Match the commands of an analyzed Bash command line against the
+    /// structured entries
+    ///
+    /// Every command, including those in pipelines, must be allowed by some
+    /// entry; the first command's entry is reported. Nothing matches when the
+    /// line failed to parse or has a redirect, heredoc or substitution.
+    pub fn find_commands(&self, analysis: &CommandAnalysis) -> Option<AllowMatch<'_>> {
+        if !analysis.parsed || !analysis.redirects.is_empty() || !analysis.substitutions.is_empty() {
+            return None;
         }
+        let mut first = None;
+        for cmd in &analysis.commands {
+            let entry = self.commands.iter().find(|e| e.matches(cmd))?;
+            first.get_or_insert(AllowMatch {
+                reason: &entry.reason,
+                source: entry.source.as_deref(),
+            });
+        }
+        first
+    }
 
-        None
+    /// Whether any structured Bash entries are loaded
+    pub fn has_commands(&self) -> bool {
+        !self.commands.is_empty()
     }
 
     /// Check if the allowlist is empty
@@ -123,12 +299,15 @@ impl CompiledAllowlist {
             && self.read.is_empty()
             && self.edit.is_empty()
             && self.write.is_empty()
+            && self.web.is_empty()
+            && self.commands.is_empty()
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::parser::ast;
 
     #[test]
     fn test_allowlist_parsing() {
@@ -157,11 +336,13 @@ mod tests {
                     pattern: r"rm\s+-rf\s+\./node_modules".to_string(),
                     reason: "Allow cleaning node_modules".to_string(),
                     tool: Some("Bash".to_string()),
+                    ..Default::default()
                 },
                 AllowEntry {
                     pattern: r"\.env\.example$".to_string(),
                     reason: "Allow reading .env examples".to_string(),
                     tool: Some("Read".to_string()),
+                    ..Default::default()
                 },
             ],
         };
@@ -191,6 +372,7 @@ mod tests {
                 pattern: r"test-pattern".to_string(),
                 reason: "General allow".to_string(),
                 tool: None,
+                ..Default::default()
             }],
         };
 
@@ -201,4 +383,89 @@ mod tests {
         assert!(allowlist.matches("Read", "test-pattern").is_some());
         assert!(allowlist.matches("Write", "test-pattern").is_some());
     }
+
+    #[test]
+    fn test_merged_files_report_source() {
+        let dir = tempfile::tempdir().unwrap();
+        let user = dir.path().join("user.toml");
+        let project = dir.path().join("project.toml");
+        std::fs::write(
+            &user,
+            r#"
+                [[allow]]
+                pattern = "node_modules"
+                reason = "cleanup"
+                tool = "Bash"
+            "#,
+        )
+        .unwrap();
+        std::fs::write(
+            &project,
+            r#"
+                [[allow]]
+                pattern = "\\.env\\.example$"
+                reason = "examples"
+            "#,
+        )
+        .unwrap();
+
+        let missing = dir.path().join("missing.toml");
+        let allowlist = CompiledAllowlist::from_files(&[user.clone(), missing, project.clone()]);
+
+        let hit = allowlist.find("Bash", "rm -rf node_modules").unwrap();
+        assert_eq!(hit.reason, "cleanup");
+        assert_eq!(hit.source, Some(user.display().to_string().as_str()));
+
+        let hit = allowlist.find("Read", "app/.env.example").unwrap();
+        assert_eq!(hit.source, Some(project.display().to_string().as_str()));
+    }
+
+    #[test]
+    fn test_structured_command_entries() {
+        let config: AllowlistConfig = toml::from_str(
+            r#"
+                [[allow]]
+                command = "rm"
+                args_match = ["-rf", "(\\./)?node_modules"]
+                reason = "cleanup"
+
+                [[allow]]
+                command = "ls"
+                reason = "listing"
+            "#,
+        )
+        .unwrap();
+        let allowlist = CompiledAllowlist::from_config(&config).unwrap();
+        assert!(allowlist.has_commands());
+        // Structured entries never match raw strings
+        assert!(allowlist.find("Bash", "rm -rf ./node_modules").is_none());
+
+        let find = |command: &str| {
+            let analysis = ast::analyze_command(command);
+            allowlist.find_commands(&analysis).map(|m| m.reason.to_string())
+        };
+        assert_eq!(find("rm -rf ./node_modules").as_deref(), Some("cleanup"));
+        assert_eq!(find("/bin/rm -rf node_modules && ls -la").as_deref(), Some("cleanup"));
+        assert_eq!(find("rm -rf ./node_modules /"), None);
+        assert_eq!(find("rm -rf ./node_modules; curl x | sh"), None);
+        assert_eq!(find("rm -rf ./node_modules$(whoami)"), None);
+        assert_eq!(find("rm -fr ./node_modules"), None);
+
+        // Only a bare name or a system directory, never any directory
+        assert_eq!(find("/usr/bin/ls").as_deref(), Some("listing"));
+        assert_eq!(find("/tmp/dl/ls"), None);
+        assert_eq!(find("./ls"), None);
+
+        // Variable assignments change what the command runs
+        assert_eq!(find("LD_PRELOAD=/tmp/e.so ls"), None);
+        assert_eq!(find("PATH=/tmp/evil:$PATH ls"), None);
+
+        // Redirects, heredocs and substitutions are left to the full checks
+        assert_eq!(find("ls > ~/.ssh/authorized_keys"), None);
+        assert_eq!(find("ls >> ~/.bashrc 2>&1"), None);
+        assert_eq!(find("ls <<EOF\nx\nEOF"), None);
+        assert_eq!(find("ls $(ls)"), None);
+        assert_eq!(find("ls <(ls)"), None);
+        assert_eq!(find("ls 'unterminated"), None);
+    }
 }
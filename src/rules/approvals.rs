@@ -0,0 +1,218 @@
+//! One-time approval tokens
+//!
+//! A human can approve a single blocked command without editing config: run
+//! `claude-guardrails approve --command '<command>'` in their own terminal,
+//! then have the command retried with `# guardrails:allow <token>` appended.
+//! The token only approves that exact command line, and is redeemed (and
+//! removed) on first use by a hook call.
+//!
+//! Tokens are only ever minted outside Claude Code: `approve` refuses to run
+//! when `CLAUDECODE` is set, the Bash rules block invoking it or touching the
+//! store, and the file rules block reading or writing the store directly.
+
+use chrono::{DateTime, Duration, Utc};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{self, Read};
+use std::path::Path;
+
+/// Environment variable Claude Code sets for commands it runs
+pub const AGENT_ENV_VAR: &str = "CLAUDECODE";
+
+/// Default token lifetime
+pub const DEFAULT_TTL_MINUTES: i64 = 15;
+
+static TOKEN_COMMENT: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"#\s*guardrails:allow\s+([0-9a-f]{8,64})\s*$").unwrap());
+
+/// A pending approval
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Approval {
+    /// The token to append to the command
+    pub token: String,
+
+    /// The command line approved, without the token comment; tokens from
+    /// stores without one approve nothing
+    #[serde(default)]
+    pub command: String,
+
+    /// When the token was created
+    pub created: DateTime<Utc>,
+
+    /// When the token stops being honored
+    pub expires: DateTime<Utc>,
+}
+
+/// On-disk approval store
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ApprovalStore {
+    #[serde(default)]
+    pub approvals: Vec<Approval>,
+}
+
+/// Extract an allow-once token from a trailing `# guardrails:allow <token>` comment
+pub fn extract_token(command: &str) -> Option<&str> {
+    TOKEN_COMMENT
+        .captures(command)
+        .and_then(|caps| caps.get(1))
+        .map(|m| m.as_str())
+}
+
+/// `command` without its trailing token comment, as it is approved
+fn approved_form(command: &str) -> &str {
+    let end = TOKEN_COMMENT.find(command).map_or(command.len(), |m| m.start());
+    command[..end].trim()
+}
+
+/// Generate a random hex token
+fn generate_token() -> String {
+    let mut bytes = [0u8; 8];
+    let filled = fs::File::open("/dev/urandom")
+        .and_then(|mut f| f.read_exact(&mut bytes))
+        .is_ok();
+
+    if !filled {
+        // No /dev/urandom (Windows): fall back to a hash of time and pid
+        use std::hash::{Hash, Hasher};
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        Utc::now().timestamp_nanos_opt().hash(&mut hasher);
+        std::process::id().hash(&mut hasher);
+        bytes = hasher.finish().to_le_bytes();
+    }
+
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+impl ApprovalStore {
+    /// Load the store, treating a missing file as empty
+    pub fn load(path: &Path) -> io::Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(content) => serde_json::from_str(&content)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Save the store, creating parent directories as needed
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        fs::write(path, json)
+    }
+
+    /// Mint a new token approving `command`, valid for `ttl`, dropping
+    /// expired ones
+    pub fn issue(&mut self, command: &str, ttl: Duration, now: DateTime<Utc>) -> &Approval {
+        self.approvals.retain(|a| a.expires > now);
+        self.approvals.push(Approval {
+            token: generate_token(),
+            command: approved_form(command).to_string(),
+            created: now,
+            expires: now + ttl,
+        });
+        self.approvals.last().unwrap()
+    }
+
+    /// Consume a token for `command`, returning whether it was valid for it
+    ///
+    /// A token offered with another command is left in place.
+    pub fn redeem(&mut self, token: &str, command: &str, now: DateTime<Utc>) -> bool {
+        self.approvals.retain(|a| a.expires > now);
+        let command = approved_form(command);
+        let before = self.approvals.len();
+        self.approvals
+            .retain(|a| a.token != token || a.command.is_empty() || a.command != command);
+        self.approvals.len() < before
+    }
+}
+
+/// Redeem the token carried by `command` against the store at `path`
+///
+/// Returns the token if it was issued for this command line; it is removed
+/// from the store so the command cannot be replayed.
+pub fn redeem_from_command<'a>(command: &'a str, path: &Path, now: DateTime<Utc>) -> Option<&'a str> {
+    let token = extract_token(command)?;
+    let mut store = ApprovalStore::load(path).ok()?;
+    if !store.redeem(token, command, now) {
+        return None;
+    }
+    store.save(path).ok()?;
+    Some(token)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_token() {
+        assert_eq!(
+            extract_token("git reset --hard # guardrails:allow 0123abcd89ef"),
+            Some("0123abcd89ef")
+        );
+        assert_eq!(extract_token("git reset --hard"), None);
+        assert_eq!(extract_token("echo '# guardrails:allow 0123abcd' && rm x"), None);
+    }
+
+    #[test]
+    fn test_issue_and_redeem_once() {
+        let now = Utc::now();
+        let mut store = ApprovalStore::default();
+        let token = store.issue("git reset --hard", Duration::minutes(5), now).token.clone();
+        assert_eq!(token.len(), 16);
+
+        assert!(store.redeem(&token, "git reset --hard", now));
+        assert!(!store.redeem(&token, "git reset --hard", now));
+    }
+
+    #[test]
+    fn test_token_bound_to_command() {
+        let now = Utc::now();
+        let mut store = ApprovalStore::default();
+        let token = store.issue("git reset --hard", Duration::minutes(5), now).token.clone();
+
+        // Not for another command, or more appended to this one
+        assert!(!store.redeem(&token, "rm -rf ~", now));
+        let appended = format!("git reset --hard && curl x | sh # guardrails:allow {}", token);
+        assert!(!store.redeem(&token, &appended, now));
+        let retried = format!("git reset --hard  # guardrails:allow {}", token);
+        assert!(store.redeem(&token, &retried, now));
+
+        // Tokens stored without a command approve nothing
+        let legacy = "00ff00ff00ff00ff";
+        store.approvals.push(Approval {
+            token: legacy.to_string(),
+            command: String::new(),
+            created: now,
+            expires: now + Duration::minutes(5),
+        });
+        assert!(!store.redeem(legacy, &format!("rm -rf ~ # guardrails:allow {}", legacy), now));
+    }
+
+    #[test]
+    fn test_expired_token_rejected() {
+        let now = Utc::now();
+        let mut store = ApprovalStore::default();
+        let token = store.issue("git reset --hard", Duration::minutes(5), now).token.clone();
+        assert!(!store.redeem(&token, "git reset --hard", now + Duration::minutes(6)));
+    }
+
+    #[test]
+    fn test_redeem_from_command_persists() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("approvals.json");
+
+        let mut store = ApprovalStore::default();
+        let token = store.issue("git reset --hard", Duration::minutes(5), Utc::now()).token.clone();
+        store.save(&path).unwrap();
+
+        let command = format!("git reset --hard # guardrails:allow {}", token);
+        assert_eq!(redeem_from_command(&command, &path, Utc::now()), Some(token.as_str()));
+        assert_eq!(redeem_from_command(&command, &path, Utc::now()), None);
+    }
+}
@@ -0,0 +1,91 @@
+//! Built-in rule catalog for `rules list`
+
+use serde::Serialize;
+
+use crate::config::SafetyLevel;
+use crate::rules::{builtin_rules, Category, Rule};
+
+/// One built-in rule as listed
+#[derive(Debug, Clone, Serialize)]
+pub struct CatalogEntry {
+    pub id: &'static str,
+    /// Rule set ("bash", "file", "exfiltration", "network", "prompt", "sql")
+    pub set: &'static str,
+    pub level: &'static str,
+    pub category: &'static str,
+    pub pattern: &'static str,
+    pub reason: &'static str,
+}
+
+impl CatalogEntry {
+    fn new(set: &'static str, rule: &'static Rule) -> Self {
+        Self {
+            id: rule.id,
+            set,
+            level: rule.level.as_str(),
+            category: rule.category.as_str(),
+            pattern: rule.pattern,
+            reason: rule.reason,
+        }
+    }
+}
+
+/// Built-in rules defined at `level` (if given) in `category` (if given)
+pub fn list(level: Option<SafetyLevel>, category: Option<Category>) -> Vec<CatalogEntry> {
+    builtin_rules()
+        .filter(|(_, r)| level.is_none_or(|l| r.level == l))
+        .filter(|(_, r)| category.is_none_or(|c| r.category == c))
+        .map(|(set, r)| CatalogEntry::new(set, r))
+        .collect()
+}
+
+/// Render entries as an aligned table (patterns are not truncated)
+pub fn to_table(entries: &[CatalogEntry]) -> String {
+    let id_width = entries.iter().map(|e| e.id.len()).max().unwrap_or(0).max(2);
+    let category_width = entries.iter().map(|e| e.category.len()).max().unwrap_or(0).max(8);
+
+    let mut out = format!(
+        "{:<id_width$}  {:<8}  {:<category_width$}  {}\n",
+        "ID", "LEVEL", "CATEGORY", "REASON / PATTERN"
+    );
+    for e in entries {
+        out.push_str(&format!(
+            "{:<id_width$}  {:<8}  {:<category_width$}  {}\n",
+            e.id, e.level, e.category, e.reason
+        ));
+        out.push_str(&format!(
+            "{:<id_width$}  {:<8}  {:<category_width$}  {}\n",
+            "", "", "", e.pattern
+        ));
+    }
+    out
+}
+
+/// Render entries as a JSON array
+pub fn to_json(entries: &[CatalogEntry]) -> String {
+    serde_json::to_string_pretty(entries).unwrap_or_else(|_| "[]".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_list_filters() {
+        let all = list(None, None);
+        assert_eq!(all.len(), builtin_rules().count());
+
+        let high_exfil = list(Some(SafetyLevel::High), Some(Category::Exfiltration));
+        assert!(!high_exfil.is_empty());
+        assert!(high_exfil
+            .iter()
+            .all(|e| e.level == "high" && e.category == "exfiltration"));
+
+        let table = to_table(&list(None, Some(Category::Git)));
+        assert!(table.starts_with("ID"));
+        assert!(table.contains("git-reset-hard"));
+
+        let json: serde_json::Value = serde_json::from_str(&to_json(&high_exfil)).unwrap();
+        assert_eq!(json[0]["set"], "exfiltration");
+    }
+}
@@ -0,0 +1,176 @@
+//! Cloud provider CLI rules (aws, gcloud, az)
+//!
+//! With credentials in the environment, the provider CLIs reach production
+//! directly. Deleting a whole project or resource group without a prompt is
+//! Critical; terminating instances, emptying buckets, deleting databases
+//! and minting long-lived access keys are High; any other delete is Strict.
+//!
+//! Printing credentials is treated like `cat ~/.ssh/id_rsa`: reading the
+//! configured secret key, saving a session token to a file, or printing an
+//! access token puts a live credential into the transcript or the tree,
+//! so it is denied from High under the secrets category.
+//!
+//! Read-only calls (`describe-*`, `list`, `get`) are fine.
+
+use crate::config::SafetyLevel;
+use crate::rules::{Category, Rule};
+
+/// aws, gcloud and az patterns, at every level
+pub const CLOUD_RULES: &[Rule] = &[
+    Rule::new(
+        "gcloud-project-delete",
+        SafetyLevel::Critical,
+        r"\bgcloud\b[^;&|]*\sprojects\s+delete\b",
+        "Deleting a Google Cloud project (shuts down everything in it)",
+    )
+    .category(Category::Cloud)
+    .examples(&["gcloud projects delete my-prod", "gcloud --quiet projects delete acme-123"], &["gcloud projects list"]),
+    Rule::new(
+        "az-group-delete-yes",
+        SafetyLevel::Critical,
+        r"\baz\b[^;&|]*\sgroup\s+delete\b[^;&|]*\s(?:--yes|-y)\b",
+        "Deleting an Azure resource group without a prompt (deletes everything in it)",
+    )
+    .category(Category::Cloud)
+    .examples(&["az group delete --name prod-rg --yes", "az group delete -n rg -y --no-wait"], &["az group delete -n rg"]),
+    Rule::new(
+        "aws-ec2-terminate",
+        SafetyLevel::High,
+        r"\baws\b[^;&|]*\sec2\s+terminate-instances\b",
+        "Terminating EC2 instances",
+    )
+    .category(Category::Cloud)
+    .examples(
+        &["aws ec2 terminate-instances --instance-ids i-0abc", "aws --profile prod ec2 terminate-instances --instance-ids i-1"],
+        &["aws ec2 describe-instances", "aws ec2 stop-instances --instance-ids i-0abc"],
+    ),
+    Rule::new(
+        "aws-s3-rb-force",
+        SafetyLevel::High,
+        r"\baws\b[^;&|]*\ss3\s+rb\b[^;&|]*\s--force\b",
+        "Deleting an S3 bucket and every object in it",
+    )
+    .category(Category::Cloud)
+    .examples(&["aws s3 rb s3://prod-assets --force"], &["aws s3 rb s3://empty-bucket", "aws s3 ls"]),
+    Rule::new(
+        "aws-s3-rm-recursive",
+        SafetyLevel::High,
+        r"\baws\b[^;&|]*\ss3\s+rm\b[^;&|]*\s--recursive\b",
+        "Deleting every object under an S3 prefix",
+    )
+    .category(Category::Cloud)
+    .examples(&["aws s3 rm s3://bucket/ --recursive"], &["aws s3 rm s3://bucket/old.log", "aws s3 ls s3://bucket --recursive"]),
+    Rule::new(
+        "aws-rds-delete",
+        SafetyLevel::High,
+        r"\baws\b[^;&|]*\srds\s+delete-db-(?:instance|cluster)\b",
+        "Deleting an RDS database",
+    )
+    .category(Category::Cloud)
+    .examples(&["aws rds delete-db-instance --db-instance-identifier prod --skip-final-snapshot"], &["aws rds describe-db-instances"]),
+    Rule::new(
+        "aws-iam-create-access-key",
+        SafetyLevel::High,
+        r"\baws\b[^;&|]*\siam\s+create-access-key\b",
+        "Creating a long-lived IAM access key",
+    )
+    .category(Category::Cloud)
+    .examples(&["aws iam create-access-key --user-name deploy"], &["aws iam list-access-keys"]),
+    Rule::new(
+        "gcloud-instance-delete",
+        SafetyLevel::High,
+        r"\bgcloud\b[^;&|]*\s(?:compute\s+instances|sql\s+instances|container\s+clusters)\s+delete\b",
+        "Deleting Google Cloud VMs, Cloud SQL instances or GKE clusters",
+    )
+    .category(Category::Cloud)
+    .examples(
+        &["gcloud compute instances delete web-1 --zone us-east1-b", "gcloud sql instances delete prod-db", "gcloud container clusters delete prod"],
+        &["gcloud compute instances list", "gcloud sql instances describe prod-db"],
+    ),
+    Rule::new(
+        "az-group-delete",
+        SafetyLevel::High,
+        r"\baz\b[^;&|]*\sgroup\s+delete\b",
+        "Deleting an Azure resource group (deletes everything in it)",
+    )
+    .category(Category::Cloud)
+    .examples(&["az group delete -n prod-rg"], &["az group list", "az group show -n rg"]),
+    Rule::new(
+        "az-resource-delete",
+        SafetyLevel::High,
+        r"\baz\b[^;&|]*\s(?:vm|sql\s+(?:server|db)|storage\s+account|aks|cosmosdb)\s+delete\b",
+        "Deleting Azure VMs, databases, storage accounts or clusters",
+    )
+    .category(Category::Cloud)
+    .examples(&["az vm delete -g rg -n web-1 --yes", "az storage account delete -n prodstore"], &["az vm list", "az aks show -n prod"]),
+    Rule::new(
+        "aws-configure-get-secret",
+        SafetyLevel::High,
+        r"\baws\b[^;&|]*\sconfigure\s+(?:get\s+(?:[\w.-]*\.)?aws_(?:secret_access_key|session_token)\b|export-credentials\b)",
+        "Printing the configured AWS secret key",
+    )
+    .category(Category::Secrets)
+    .examples(
+        &["aws configure get aws_secret_access_key", "aws configure get profile.prod.aws_session_token", "aws configure export-credentials --format env"],
+        &["aws configure get region", "aws configure list"],
+    ),
+    Rule::new(
+        "aws-sts-token-to-file",
+        SafetyLevel::High,
+        r"\baws\b[^;&|]*\ssts\s+(?:get-session-token|get-federation-token|assume-role[\w-]*)\b[^;&|]*(?:\s>>?|[^\d\s&]>>?)\s*[^\s&]",
+        "Saving temporary AWS credentials to a file",
+    )
+    .category(Category::Secrets)
+    .examples(
+        &["aws sts get-session-token > creds.json", "aws sts assume-role --role-arn arn:aws:iam::1:role/x --role-session-name s >> /tmp/c"],
+        &["aws sts get-caller-identity > me.json", "aws sts get-session-token 2>/dev/null"],
+    ),
+    Rule::new(
+        "gcloud-print-access-token",
+        SafetyLevel::High,
+        r"\bgcloud\b[^;&|]*\sauth\s+(?:application-default\s+)?print-(?:access|identity)-token\b",
+        "Printing a Google Cloud access token",
+    )
+    .category(Category::Secrets)
+    .examples(
+        &["gcloud auth print-access-token", "gcloud auth application-default print-access-token", "TOKEN=$(gcloud auth print-identity-token)"],
+        &["gcloud auth list", "gcloud auth login"],
+    ),
+    Rule::new(
+        "az-get-access-token",
+        SafetyLevel::High,
+        r"\baz\b[^;&|]*\saccount\s+get-access-token\b",
+        "Printing an Azure access token",
+    )
+    .category(Category::Secrets)
+    .examples(&["az account get-access-token", "az account get-access-token --resource https://vault.azure.net"], &["az account show", "az account list"]),
+    Rule::new(
+        "aws-delete",
+        SafetyLevel::Strict,
+        r"\baws\b[^;&|]*\s(?:delete-[\w-]+|s3\s+(?:rb|rm))\b",
+        "Deleting AWS resources",
+    )
+    .category(Category::Cloud)
+    .examples(&["aws lambda delete-function --function-name api", "aws s3 rm s3://bucket/x"], &["aws s3 ls", "aws lambda list-functions"]),
+    Rule::new(
+        "gcloud-delete",
+        SafetyLevel::Strict,
+        r"\bgcloud\b[^;&|]*\sdelete\b",
+        "Deleting Google Cloud resources",
+    )
+    .category(Category::Cloud)
+    .examples(&["gcloud run services delete api", "gcloud storage rm gs://b/x && gcloud pubsub topics delete t"], &["gcloud run services list"]),
+    Rule::new(
+        "az-delete",
+        SafetyLevel::Strict,
+        r"\baz\b[^;&|]*\sdelete\b",
+        "Deleting Azure resources",
+    )
+    .category(Category::Cloud)
+    .examples(&["az webapp delete -n api -g rg"], &["az webapp list"]),
+];
+
+/// Cloud CLI rules active at `level`
+pub fn rules_for_level(level: SafetyLevel) -> impl Iterator<Item = &'static Rule> {
+    CLOUD_RULES.iter().filter(move |r| level.includes(r.level))
+}
@@ -0,0 +1,77 @@
+//! Repository hosting CLI rules (gh, glab)
+//!
+//! `gh` and `glab` act on the hosted repository rather than the clone:
+//! deleting a repo or release, or calling the REST API with DELETE, can't
+//! be undone with git. Changing CI secrets swaps the credentials pipelines
+//! run with, and `gh auth token` prints the token the CLI is logged in with.
+//!
+//! Reading (`gh pr view`, `glab mr list`, `gh api` GETs) is fine.
+
+use crate::config::SafetyLevel;
+use crate::rules::{Category, Rule};
+
+/// gh and glab patterns, at every level
+pub const FORGE_RULES: &[Rule] = &[
+    Rule::new(
+        "gh-repo-delete",
+        SafetyLevel::High,
+        r"\b(?:gh|glab)\s+(?:[^;&|]*\s)?repo\s+delete\b",
+        "Deleting a hosted repository (issues, PRs and releases go with it)",
+    )
+    .category(Category::Git)
+    .examples(&["gh repo delete acme/api --yes", "glab repo delete group/app"], &["gh repo view acme/api", "gh repo clone acme/api"]),
+    Rule::new(
+        "gh-release-delete",
+        SafetyLevel::High,
+        r"\b(?:gh|glab)\s+(?:[^;&|]*\s)?release\s+delete\b",
+        "Deleting a published release",
+    )
+    .category(Category::Git)
+    .examples(&["gh release delete v1.2.0 --cleanup-tag -y", "glab release delete v2.0"], &["gh release list", "gh release create v1.3.0"]),
+    Rule::new(
+        "gh-api-delete",
+        SafetyLevel::High,
+        r"\b(?:gh|glab)\s+api\b[^;&|]*\s(?:-X\s*|--method[=\s]+)['\x22]?(?i:delete)\b",
+        "Calling the hosting API with DELETE",
+    )
+    .category(Category::Git)
+    .examples(
+        &["gh api -X DELETE repos/acme/api/git/refs/heads/main", "gh api --method=DELETE /orgs/acme/hooks/1", "glab api -XDELETE projects/1"],
+        &["gh api repos/acme/api", "gh api -X POST repos/acme/api/issues -f title=x"],
+    ),
+    Rule::new(
+        "gh-secret-write",
+        SafetyLevel::High,
+        r"\bgh\s+(?:[^;&|]*\s)?(?:secret|variable)\s+(?:set|remove|delete)\b|\bglab\s+(?:[^;&|]*\s)?(?:variable|secret)\s+(?:set|update|delete)\b",
+        "Changing the secrets or variables CI runs with",
+    )
+    .category(Category::Secrets)
+    .examples(
+        &["gh secret set AWS_KEY < key.txt", "gh variable delete DEPLOY_ENV", "glab variable set TOKEN abc"],
+        &["gh secret list", "glab variable list"],
+    ),
+    Rule::new(
+        "gh-auth-token",
+        SafetyLevel::High,
+        r"\bgh\s+(?:[^;&|]*\s)?auth\s+token\b|\b(?:gh|glab)\s+(?:[^;&|]*\s)?auth\s+status\b[^;&|]*\s(?:-t|--show-token)\b",
+        "Printing the hosting CLI's access token",
+    )
+    .category(Category::Secrets)
+    .examples(
+        &["gh auth token", "export GH_TOKEN=$(gh auth token)", "glab auth status --show-token"],
+        &["gh auth status", "gh auth login --web"],
+    ),
+    Rule::new(
+        "gh-delete",
+        SafetyLevel::Strict,
+        r"\b(?:gh|glab)\s+[\w-]+\s+(?:delete|remove)\b",
+        "Deleting hosted issues, runs, caches or other repository data",
+    )
+    .category(Category::Git)
+    .examples(&["gh issue delete 42 --yes", "gh cache delete --all", "glab mr delete 7"], &["gh issue close 42", "gh pr create --body 'remove x'"]),
+];
+
+/// gh and glab rules active at `level`
+pub fn rules_for_level(level: SafetyLevel) -> impl Iterator<Item = &'static Rule> {
+    FORGE_RULES.iter().filter(move |r| level.includes(r.level))
+}
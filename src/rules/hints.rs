@@ -0,0 +1,171 @@
+//! Retry hints for denied operations
+//!
+//! Suggests a safer alternative the agent can try instead of the blocked
+//! operation. Rules where retrying is never appropriate (critical
+//! destruction, exfiltration) intentionally have no hint.
+//!
+//! The reverse also lives here: for a read-only command that passed every
+//! check, [`permission_for`] suggests a Claude Code permission rule that
+//! pre-approves exactly that command.
+
+/// Rule ID -> suggested alternative
+const HINTS: &[(&str, &str)] = &[
+    // Git
+    ("git-force-main", "git push --force-with-lease to a feature branch"),
+    ("git-force-main-alt", "git push --force-with-lease to a feature branch"),
+    ("git-force-any", "git push --force-with-lease"),
+    ("git-reset-hard", "git stash push -u && git reset --hard"),
+    ("git-clean-force", "git clean -n (dry run) and delete specific paths"),
+    ("git-discard-changes", "git stash push -u to keep the changes recoverable"),
+    // Permissions
+    ("chmod-777", "chmod 755 for directories or 644 for files"),
+    ("chmod-recursive-permissive", "chmod -R u+rwX,go+rX"),
+    // Remote code execution
+    ("curl-pipe-sh", "download to a file, inspect it, then run it explicitly"),
+    ("curl-pipe-bash", "download to a file, inspect it, then run it explicitly"),
+    ("curl-pipe-zsh", "download to a file, inspect it, then run it explicitly"),
+    ("curl-pipe-python", "download to a file, inspect it, then run it explicitly"),
+    ("pipe-to-shell", "write the script to a file, inspect it, then run it explicitly"),
+    ("pipe-to-interpreter", "write the script to a file, inspect it, then run it explicitly"),
+    ("download-execute", "download in one call and show the file for review before running it"),
+    ("kill-protected-process", "stop only the process you started, by the PID you got when starting it"),
+    ("egress-not-allowed", "use a host listed in [egress] allow, or ask the user to add this one"),
+    ("load-test-target", "run the load test against localhost or a host in bash.load_test_targets"),
+    // Dynamic execution
+    ("dynamic-command", "invoke the command by its literal name"),
+    ("variable-command", "invoke the command by its literal name"),
+    ("eval-variable", "run the command directly instead of through eval"),
+    ("eval-command-sub", "run the command directly instead of through eval"),
+    ("obfuscation-detected", "write the command out literally instead of assembling it"),
+    // Secrets
+    ("echo-secret-env", "test -n \"$VAR\" to check a secret is set without printing it"),
+    ("printenv-all", "printenv NAME for a specific non-secret variable"),
+    ("env-dump", "printenv NAME for a specific non-secret variable"),
+    ("cat-env-file", "read .env.example or ask the user for the value you need"),
+    ("env-file", "read .env.example or ask the user for the value you need"),
+    ("env-local", "read .env.example or ask the user for the value you need"),
+    ("protected-path-write", "ask the user to make the change to the protected file themselves"),
+    // Persistence
+    ("shell-init-bash", "put the setting in a project script or .envrc instead of a startup file"),
+    ("shell-init-zsh", "put the setting in a project script or .envrc instead of a startup file"),
+    ("crontab-file", "show the user the cron entry to install themselves"),
+    ("crontab-install", "show the user the cron entry to install themselves"),
+    ("crontab-edit", "show the user the cron entry to install themselves"),
+    ("launchd-plist", "show the user the plist and the launchctl command to run themselves"),
+    ("systemd-unit-file", "show the user the unit file and the systemctl command to run themselves"),
+    // Containers
+    ("docker-privileged", "drop --privileged and add only the needed --cap-add"),
+    ("docker-system-prune", "docker container prune or docker image prune (without -a)"),
+    ("docker-image-prune", "docker image prune (without -a)"),
+    // Infrastructure as code
+    ("terraform-destroy-auto-approve", "terraform plan -destroy and show the user the plan to apply themselves"),
+    ("pulumi-destroy-yes", "pulumi preview --diff and let the user confirm pulumi destroy themselves"),
+    ("cdk-destroy-force", "cdk diff and let the user confirm cdk destroy themselves"),
+    ("terraform-apply-auto-approve", "terraform plan -out tfplan and show the user the plan"),
+    // Cloud
+    ("aws-iam-create-access-key", "use short-lived credentials from aws sso login or aws sts assume-role"),
+    ("aws-s3-rm-recursive", "aws s3 rm --recursive --dryrun and show the user what would be deleted"),
+    ("aws-configure-get-secret", "aws sts get-caller-identity to check which credentials are active"),
+    ("gcloud-print-access-token", "gcloud auth list to check the active account without printing a token"),
+    ("az-get-access-token", "az account show to check the active account without printing a token"),
+    // Repository hosting
+    ("gh-auth-token", "gh auth status to check the login without printing the token"),
+    ("gh-secret-write", "show the user the gh secret set command to run themselves"),
+    ("gh-repo-delete", "gh repo archive, or show the user the delete command to run themselves"),
+    // Kubernetes
+    ("kubectl-delete-all", "delete the specific resources by name or label selector"),
+    ("kubectl-drain", "show the user the drain command to run themselves"),
+    ("helm-uninstall", "show the user the helm uninstall command to run themselves"),
+    ("kubectl-get-secrets", "kubectl get secret NAME -o jsonpath='{.data}' | jq 'keys' to list keys without values"),
+    // Cleanup
+    ("sudo-rm", "rm without sudo on files you own"),
+    ("rm-rf-star", "rm -rf on specific named paths"),
+    ("npm-cache-clean", "npm cache verify"),
+];
+
+/// Read-only commands worth pre-approving (matched on leading words)
+///
+/// Only commands that stay read-only whatever their arguments (`--output`
+/// aside); `git branch` (`-D`) and `cargo check` (build scripts) are not.
+const SAFE_COMMANDS: &[&str] = &["git status", "git diff", "git log", "git show", "ls", "pwd", "whoami"];
+
+/// Characters that make a command more than a single literal invocation
+const SHELL_SYNTAX: &[char] = &[
+    ';', '&', '|', '<', '>', '$', '`', '(', ')', '{', '}', '*', '?', '[', ']', '~', '\\', '\'', '"',
+    '\n', '#',
+];
+
+/// Get the retry suggestion for a rule, if one exists
+pub fn hint_for(rule_id: &str) -> Option<&'static str> {
+    HINTS
+        .iter()
+        .find(|(id, _)| *id == rule_id)
+        .map(|(_, hint)| *hint)
+}
+
+/// Permission rule pre-approving exactly `command`, if it is a plain
+/// invocation of a read-only command (no pipes, redirects, substitutions,
+/// globs or quoting)
+pub fn permission_for(command: &str) -> Option<String> {
+    let command = command.trim();
+    if command.is_empty() || command.contains(SHELL_SYNTAX) || command.contains("--output") {
+        return None;
+    }
+    let words: Vec<&str> = command.split_whitespace().collect();
+    let safe = SAFE_COMMANDS.iter().any(|safe| {
+        let prefix: Vec<&str> = safe.split(' ').collect();
+        words.starts_with(&prefix)
+    });
+    safe.then(|| format!("Bash({})", words.join(" ")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hint_for_force_push() {
+        assert!(hint_for("git-force-any").unwrap().contains("--force-with-lease"));
+    }
+
+    #[test]
+    fn test_no_hint_for_critical_rules() {
+        assert!(hint_for("rm-root").is_none());
+        assert!(hint_for("fork-bomb").is_none());
+    }
+
+    #[test]
+    fn test_permission_for_exact_safe_commands() {
+        assert_eq!(permission_for("git status").as_deref(), Some("Bash(git status)"));
+        assert_eq!(permission_for("  ls   -la ").as_deref(), Some("Bash(ls -la)"));
+        assert!(permission_for("git push").is_none());
+        assert!(permission_for("git statusx").is_none());
+        assert!(permission_for("ls; rm -rf x").is_none());
+        assert!(permission_for("git diff > out.patch").is_none());
+        assert!(permission_for("ls $(cat list)").is_none());
+        assert!(permission_for("git diff --output=/etc/x").is_none());
+    }
+
+    #[test]
+    fn test_hint_ids_are_known() {
+        let engine_checks = [
+            "pipe-to-shell",
+            "pipe-to-interpreter",
+            "dynamic-command",
+            "variable-command",
+            "download-execute",
+            "egress-not-allowed",
+            "kill-protected-process",
+            "load-test-target",
+            "protected-path-write",
+            "obfuscation-detected",
+        ];
+        for (id, _) in HINTS {
+            assert!(
+                crate::rules::find_rule(id).is_some() || engine_checks.contains(id),
+                "hint for unknown rule {}",
+                id
+            );
+        }
+    }
+}
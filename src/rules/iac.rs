@@ -0,0 +1,99 @@
+//! Infrastructure-as-code destroy and apply rules
+//!
+//! In an infrastructure repo one command tears down real resources.
+//! Destroying without the confirmation prompt (`terraform destroy
+//! -auto-approve`, `pulumi destroy --yes`, `cdk destroy --force`) skips the
+//! moment where the workspace and resource count are shown, so it is denied
+//! from the High level. At Strict, any destroy and any unreviewed apply is
+//! denied too. Planning (`terraform plan -destroy`, `pulumi preview`,
+//! `cdk diff`) is fine.
+//!
+//! Terraform patterns cover OpenTofu (`tofu`) and Terragrunt as well.
+
+use crate::config::SafetyLevel;
+use crate::rules::{Category, Rule};
+
+/// Terraform, Pulumi and CDK patterns, at every level
+pub const IAC_RULES: &[Rule] = &[
+    Rule::new(
+        "terraform-destroy-auto-approve",
+        SafetyLevel::High,
+        r"\b(?:terraform|tofu|terragrunt)\b[^;&|]*\s(?:destroy\b|apply\b[^;&|]*\s--?destroy\b)[^;&|]*\s--?auto-approve(?:=true)?(?:\s|$)|\b(?:terraform|tofu|terragrunt)\b[^;&|]*\sapply\b[^;&|]*\s--?auto-approve(?:=true)?\s[^;&|]*--?destroy\b",
+        "Destroying infrastructure without the confirmation prompt",
+    )
+    .category(Category::Infrastructure)
+    .examples(
+        &[
+            "terraform destroy -auto-approve",
+            "terraform -chdir=envs/prod apply -destroy -auto-approve",
+            "tofu apply -auto-approve -destroy",
+            "terragrunt run-all destroy --auto-approve",
+        ],
+        &["terraform destroy", "terraform plan -destroy", "terraform apply -auto-approve=false -destroy"],
+    ),
+    Rule::new(
+        "pulumi-destroy-yes",
+        SafetyLevel::High,
+        r"\bpulumi\b[^;&|]*\s(?:destroy|down)\b[^;&|]*\s(?:--yes|--skip-preview|-[a-z]*[yf][a-z]*)(?:=true)?(?:\s|$)",
+        "Destroying a Pulumi stack without the confirmation prompt",
+    )
+    .category(Category::Infrastructure)
+    .examples(
+        &["pulumi destroy --yes", "pulumi destroy -s prod -y", "pulumi down --skip-preview --yes"],
+        &["pulumi destroy", "pulumi preview", "pulumi up --yes"],
+    ),
+    Rule::new(
+        "cdk-destroy-force",
+        SafetyLevel::High,
+        r"\bcdk\b[^;&|]*\sdestroy\b[^;&|]*\s(?:--force|-f)(?:=true)?(?:\s|$)",
+        "Destroying CDK stacks without the confirmation prompt",
+    )
+    .category(Category::Infrastructure)
+    .examples(
+        &["cdk destroy --force", "npx cdk destroy --all -f"],
+        &["cdk destroy ApiStack", "cdk diff", "cdk deploy --force"],
+    ),
+    Rule::new(
+        "terraform-destroy",
+        SafetyLevel::Strict,
+        r"\b(?:terraform|tofu|terragrunt)\b[^;&|]*\s(?:destroy\b|apply\b[^;&|]*\s--?destroy\b)",
+        "Destroying Terraform-managed infrastructure",
+    )
+    .category(Category::Infrastructure)
+    .examples(
+        &["terraform destroy", "terraform destroy -target=aws_instance.web", "tofu apply -destroy"],
+        &["terraform plan -destroy", "terraform apply tfplan"],
+    ),
+    Rule::new(
+        "terraform-apply-auto-approve",
+        SafetyLevel::Strict,
+        r"\b(?:terraform|tofu|terragrunt)\b[^;&|]*\sapply\b[^;&|]*\s--?auto-approve(?:=true)?(?:\s|$)",
+        "Applying Terraform changes without reviewing the plan",
+    )
+    .category(Category::Infrastructure)
+    .examples(
+        &["terraform apply -auto-approve", "terragrunt apply --auto-approve -var env=prod"],
+        &["terraform apply", "terraform apply tfplan", "terraform plan -out tfplan"],
+    ),
+    Rule::new(
+        "pulumi-destroy",
+        SafetyLevel::Strict,
+        r"\bpulumi\b[^;&|]*\s(?:destroy|down)\b",
+        "Destroying a Pulumi stack's resources",
+    )
+    .category(Category::Infrastructure)
+    .examples(&["pulumi destroy", "pulumi down -s dev"], &["pulumi preview", "pulumi stack ls"]),
+    Rule::new(
+        "cdk-destroy",
+        SafetyLevel::Strict,
+        r"\bcdk\b[^;&|]*\sdestroy\b",
+        "Destroying CDK stacks",
+    )
+    .category(Category::Infrastructure)
+    .examples(&["cdk destroy ApiStack", "npx cdk destroy --all"], &["cdk synth", "cdk diff"]),
+];
+
+/// IaC rules active at `level`
+pub fn rules_for_level(level: SafetyLevel) -> impl Iterator<Item = &'static Rule> {
+    IAC_RULES.iter().filter(move |r| level.includes(r.level))
+}
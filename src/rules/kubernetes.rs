@@ -0,0 +1,90 @@
+//! Kubernetes and Helm command rules
+//!
+//! An agent working in a repo with a live kubeconfig can take down a
+//! cluster with one command. Deleting a namespace removes everything in it;
+//! `delete --all`, `drain` and `helm uninstall` take whole workloads offline;
+//! and `get secrets -o yaml` prints every secret's (only base64-encoded)
+//! values into the transcript.
+//!
+//! Reading cluster state (`get`, `describe`, `logs`) is fine, except for
+//! secret values.
+
+use crate::config::SafetyLevel;
+use crate::rules::{Category, Rule};
+
+/// kubectl and helm patterns, at every level
+pub const KUBERNETES_RULES: &[Rule] = &[
+    Rule::new(
+        "kubectl-delete-namespace",
+        SafetyLevel::Critical,
+        r"\bkubectl\b[^;&|]*\sdelete\s+(?:-\S+\s+)*(?:ns|namespaces?)(?:\s|/|$)",
+        "Deleting a Kubernetes namespace (removes everything in it)",
+    )
+    .category(Category::Kubernetes)
+    .examples(
+        &["kubectl delete ns prod", "kubectl --context prod delete namespace payments", "kubectl delete namespace/staging"],
+        &["kubectl get ns", "kubectl delete pod web-1 -n prod", "kubectl create namespace dev"],
+    ),
+    Rule::new(
+        "kubectl-delete-all",
+        SafetyLevel::High,
+        r"\bkubectl\b[^;&|]*\sdelete\s[^;&|]*\s(?:--all\b|-A\b)",
+        "Deleting every Kubernetes resource of a kind (--all)",
+    )
+    .category(Category::Kubernetes)
+    .examples(
+        &["kubectl delete pods --all -n prod", "kubectl delete deploy --all-namespaces -l app=web", "kubectl delete pvc -A --all"],
+        &["kubectl get pods --all-namespaces", "kubectl delete pod web-1"],
+    ),
+    Rule::new(
+        "kubectl-drain",
+        SafetyLevel::High,
+        r"\bkubectl\b[^;&|]*\sdrain\s",
+        "Draining a Kubernetes node (evicts all its pods)",
+    )
+    .category(Category::Kubernetes)
+    .examples(
+        &["kubectl drain node-1 --ignore-daemonsets", "kubectl --context prod drain ip-10-0-1-2 --force"],
+        &["kubectl get nodes", "kubectl uncordon node-1"],
+    ),
+    Rule::new(
+        "helm-uninstall",
+        SafetyLevel::High,
+        r"\bhelm\b[^;&|]*\s(?:uninstall|delete|del|un)\s",
+        "Uninstalling a Helm release (deletes its resources)",
+    )
+    .category(Category::Kubernetes)
+    .examples(
+        &["helm uninstall web -n prod", "helm --kube-context prod delete ingress-nginx", "helm un api"],
+        &["helm list -A", "helm upgrade --install web ./chart", "helm history web"],
+    ),
+    Rule::new(
+        "kubectl-get-secrets",
+        SafetyLevel::High,
+        r"\bkubectl\b[^;&|]*\sget\s+(?:[\w.-]+,)*secrets?\b[^;&|]*\s(?:-o|--output)[=\s]*(?:yaml|json|jsonpath|go-template|custom-columns)",
+        "Printing Kubernetes secret values",
+    )
+    .category(Category::Kubernetes)
+    .examples(
+        &[
+            "kubectl get secrets -o yaml",
+            "kubectl get secret db-creds -n prod -o jsonpath='{.data.password}'",
+            "kubectl get secret/api-key --output=json",
+            "kubectl get configmaps,secrets -A -o yaml",
+        ],
+        &["kubectl get secrets", "kubectl get pods -o yaml", "kubectl describe secret db-creds"],
+    ),
+    Rule::new(
+        "kubectl-delete",
+        SafetyLevel::Strict,
+        r"\bkubectl\b[^;&|]*\sdelete\s",
+        "Deleting Kubernetes resources",
+    )
+    .category(Category::Kubernetes)
+    .examples(&["kubectl delete pod web-1", "kubectl delete -f deploy.yaml"], &["kubectl get pods"]),
+];
+
+/// Kubernetes rules active at `level`
+pub fn rules_for_level(level: SafetyLevel) -> impl Iterator<Item = &'static Rule> {
+    KUBERNETES_RULES.iter().filter(move |r| level.includes(r.level))
+}
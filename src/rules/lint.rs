@@ -0,0 +1,101 @@
+//! Rule example checking
+//!
+//! Built-in rules and data-driven token formats carry `should_match` /
+//! `should_not_match` examples. `rules lint` runs them against each pattern so
+//! a regex change that stops catching (or starts over-catching) an example is
+//! reported before it ships; token packs are checked the same way on load.
+
+use crate::rules::builtin_rules;
+use crate::rules::tokens::TokenPack;
+use regex::Regex;
+
+/// A problem found in one rule
+#[derive(Debug, Clone, PartialEq)]
+pub struct LintIssue {
+    /// Rule ID (token formats are reported as `token-<id>`)
+    pub rule: String,
+
+    /// What is wrong
+    pub message: String,
+}
+
+impl std::fmt::Display for LintIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.rule, self.message)
+    }
+}
+
+/// Check one pattern against its examples
+pub fn check_examples<S: AsRef<str>>(
+    rule: &str,
+    pattern: &str,
+    should_match: &[S],
+    should_not_match: &[S],
+) -> Vec<LintIssue> {
+    let issue = |message: String| LintIssue {
+        rule: rule.to_string(),
+        message,
+    };
+
+    let regex = match Regex::new(pattern) {
+        Ok(regex) => regex,
+        Err(e) => return vec![issue(format!("invalid pattern: {}", e))],
+    };
+
+    let mut issues = Vec::new();
+    if should_match.is_empty() {
+        issues.push(issue("no should_match examples".to_string()));
+    }
+    for example in should_match.iter().map(AsRef::as_ref) {
+        if !regex.is_match(example) {
+            issues.push(issue(format!("does not match {:?}", example)));
+        }
+    }
+    for example in should_not_match.iter().map(AsRef::as_ref) {
+        if regex.is_match(example) {
+            issues.push(issue(format!("unexpectedly matches {:?}", example)));
+        }
+    }
+    issues
+}
+
+/// Check every built-in rule
+pub fn lint_builtin() -> Vec<LintIssue> {
+    builtin_rules()
+        .flat_map(|(_, r)| check_examples(r.id, r.pattern, r.should_match, r.should_not_match))
+        .collect()
+}
+
+/// Check every format in a token pack
+pub fn lint_token_pack(pack: &TokenPack) -> Vec<LintIssue> {
+    pack.token
+        .iter()
+        .flat_map(|t| check_examples(&t.rule_id(), &t.pattern, &t.should_match, &t.should_not_match))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_rules_pass_their_examples() {
+        let issues = lint_builtin();
+        assert!(issues.is_empty(), "{:#?}", issues);
+        assert!(lint_token_pack(&TokenPack::builtin()).is_empty());
+    }
+
+    #[test]
+    fn test_check_examples_reports_failures() {
+        let issues = check_examples("demo", r"\bfoo\b", &["foo bar", "food"], &["foo"]);
+        let messages: Vec<_> = issues.iter().map(|i| i.message.as_str()).collect();
+        assert_eq!(messages, vec![r#"does not match "food""#, r#"unexpectedly matches "foo""#]);
+
+        let none: &[&str] = &[];
+        assert_eq!(check_examples("demo", "(", none, none).len(), 1);
+        assert_eq!(
+            check_examples("demo", "foo", none, none)[0].message,
+            "no should_match examples"
+        );
+    }
+}
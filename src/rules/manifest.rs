@@ -0,0 +1,296 @@
+//! Rule provenance manifest
+//!
+//! `manifest.toml` records every built-in rule with a hash of its level and
+//! pattern and the crate version that introduced, last changed, or removed
+//! it. Admins upgrading the binary can diff two versions to review policy
+//! drift before rollout. A unit test keeps the manifest in sync with the
+//! rule tables; regenerate it with `claude-guardrails rules manifest`.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use super::{builtin_rules, Rule};
+
+/// The manifest shipped with this build
+const EMBEDDED_MANIFEST: &str = include_str!("manifest.toml");
+
+/// Provenance of one rule
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    pub id: String,
+    /// Rule set: "bash", "file", "exfiltration", "network", "prompt" or "sql"
+    pub kind: String,
+    /// FNV-1a hash of level and pattern
+    pub hash: String,
+    /// Version that introduced the rule
+    pub since: String,
+    /// Version that last changed the rule's level or pattern
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub changed: Option<String>,
+    /// Version that removed the rule
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub removed: Option<String>,
+}
+
+/// All manifest entries
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Manifest {
+    #[serde(default)]
+    pub rule: Vec<ManifestEntry>,
+}
+
+/// Rules added, changed and removed since a version
+#[derive(Debug, Default)]
+pub struct RulesDiff<'a> {
+    pub from: String,
+    pub added: Vec<&'a ManifestEntry>,
+    pub changed: Vec<&'a ManifestEntry>,
+    pub removed: Vec<&'a ManifestEntry>,
+}
+
+/// 64-bit FNV-1a
+pub(crate) fn fnv1a(data: &[u8]) -> u64 {
+    data.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Stable hash of the parts of a rule that affect policy
+pub fn rule_hash(rule: &Rule) -> String {
+    let input = format!("{}|{}", rule.level.as_str(), rule.pattern);
+    format!("{:016x}", fnv1a(input.as_bytes()))
+}
+
+/// Parse a dotted version ("0.3.0") for ordering; non-numeric parts count as 0
+fn version_key(version: &str) -> Vec<u64> {
+    version
+        .split(['.', '-'])
+        .map(|part| part.parse().unwrap_or(0))
+        .collect()
+}
+
+/// Whether `version` is strictly newer than `base`
+fn newer(version: &str, base: &str) -> bool {
+    version_key(version) > version_key(base)
+}
+
+impl Manifest {
+    /// Load the manifest embedded in this binary
+    pub fn embedded() -> Self {
+        toml::from_str(EMBEDDED_MANIFEST).unwrap_or_default()
+    }
+
+    /// Bring the manifest up to date with the current rule tables
+    ///
+    /// New rules get `since = version`, rules whose hash changed get
+    /// `changed = version`, and rules no longer built in get `removed = version`.
+    pub fn regenerate(&self, version: &str) -> Manifest {
+        let existing: HashMap<(&str, &str), &ManifestEntry> = self
+            .rule
+            .iter()
+            .map(|e| ((e.kind.as_str(), e.id.as_str()), e))
+            .collect();
+
+        let mut rules = Vec::new();
+        for (kind, rule) in builtin_rules() {
+            let hash = rule_hash(rule);
+            let entry = match existing.get(&(kind, rule.id)) {
+                Some(old) if old.hash == hash && old.removed.is_none() => (*old).clone(),
+                Some(old) if old.removed.is_none() => ManifestEntry {
+                    hash,
+                    changed: Some(version.to_string()),
+                    ..(*old).clone()
+                },
+                _ => ManifestEntry {
+                    id: rule.id.to_string(),
+                    kind: kind.to_string(),
+                    hash,
+                    since: version.to_string(),
+                    changed: None,
+                    removed: None,
+                },
+            };
+            rules.push(entry);
+        }
+
+        for old in &self.rule {
+            let still_built_in = rules.iter().any(|e| e.kind == old.kind && e.id == old.id);
+            if !still_built_in {
+                let mut entry = old.clone();
+                entry.removed.get_or_insert_with(|| version.to_string());
+                rules.push(entry);
+            }
+        }
+
+        Manifest { rule: rules }
+    }
+
+    /// Rules added, changed or removed after `from`
+    pub fn diff_from(&self, from: &str) -> RulesDiff<'_> {
+        let mut diff = RulesDiff {
+            from: from.to_string(),
+            ..Default::default()
+        };
+
+        for entry in &self.rule {
+            if newer(&entry.since, from) {
+                // Added and removed again in between: nothing to report
+                if entry.removed.is_none() {
+                    diff.added.push(entry);
+                }
+            } else if entry.removed.as_deref().is_some_and(|v| newer(v, from)) {
+                diff.removed.push(entry);
+            } else if entry.removed.is_none()
+                && entry.changed.as_deref().is_some_and(|v| newer(v, from))
+            {
+                diff.changed.push(entry);
+            }
+        }
+
+        diff
+    }
+
+    /// Serialize to TOML
+    pub fn to_toml(&self) -> String {
+        toml::to_string(self).unwrap_or_default()
+    }
+}
+
+impl RulesDiff<'_> {
+    /// Whether nothing changed
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.changed.is_empty() && self.removed.is_empty()
+    }
+
+    /// Render as a plain-text report
+    pub fn to_text(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "Built-in rule changes since {}", self.from);
+
+        if self.is_empty() {
+            let _ = writeln!(out, "\nNo changes.");
+            return out;
+        }
+
+        type Version = fn(&ManifestEntry) -> Option<&str>;
+        let sections: [(&str, &Vec<&ManifestEntry>, Version); 3] = [
+            ("Added", &self.added, |e| Some(e.since.as_str())),
+            ("Changed", &self.changed, |e| e.changed.as_deref()),
+            ("Removed", &self.removed, |e| e.removed.as_deref()),
+        ];
+        for (title, entries, version) in sections {
+            if entries.is_empty() {
+                continue;
+            }
+            let _ = writeln!(out, "\n{} ({}):", title, entries.len());
+            for entry in entries.iter() {
+                let level = super::find_rule(&entry.id)
+                    .map(|r| r.level.as_str())
+                    .unwrap_or("-");
+                let _ = writeln!(
+                    out,
+                    "  {:<8} {:<30} {:<13} {}",
+                    level,
+                    entry.id,
+                    entry.kind,
+                    version(entry).unwrap_or_default()
+                );
+            }
+        }
+
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: &str, since: &str, changed: Option<&str>, removed: Option<&str>) -> ManifestEntry {
+        ManifestEntry {
+            id: id.to_string(),
+            kind: "bash".to_string(),
+            hash: "0".to_string(),
+            since: since.to_string(),
+            changed: changed.map(String::from),
+            removed: removed.map(String::from),
+        }
+    }
+
+    #[test]
+    fn test_embedded_manifest_is_current() {
+        let manifest = Manifest::embedded();
+        assert!(!manifest.rule.is_empty());
+        let regenerated = manifest.regenerate("unreleased");
+        assert_eq!(
+            manifest, regenerated,
+            "src/rules/manifest.toml is stale; run `claude-guardrails rules manifest --version <next>`"
+        );
+    }
+
+    #[test]
+    fn test_rule_ids_unique_per_kind() {
+        let mut seen = std::collections::HashSet::new();
+        for (kind, rule) in builtin_rules() {
+            assert!(seen.insert((kind, rule.id)), "duplicate rule {}/{}", kind, rule.id);
+        }
+    }
+
+    #[test]
+    fn test_fnv1a() {
+        assert_eq!(fnv1a(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a(b"a"), 0xaf63_dc4c_8601_ec8c);
+    }
+
+    #[test]
+    fn test_version_ordering() {
+        assert!(newer("0.10.0", "0.9.0"));
+        assert!(newer("1.0.0", "0.3.0"));
+        assert!(!newer("0.3.0", "0.3.0"));
+    }
+
+    #[test]
+    fn test_diff_from() {
+        let manifest = Manifest {
+            rule: vec![
+                entry("old", "0.1.0", None, None),
+                entry("new", "0.3.0", None, None),
+                entry("tweaked", "0.1.0", Some("0.4.0"), None),
+                entry("gone", "0.1.0", None, Some("0.4.0")),
+                entry("transient", "0.3.0", None, Some("0.4.0")),
+            ],
+        };
+
+        let diff = manifest.diff_from("0.2.0");
+        let ids = |entries: &[&ManifestEntry]| entries.iter().map(|e| e.id.clone()).collect::<Vec<_>>();
+        assert_eq!(ids(&diff.added), vec!["new"]);
+        assert_eq!(ids(&diff.changed), vec!["tweaked"]);
+        assert_eq!(ids(&diff.removed), vec!["gone"]);
+
+        assert!(manifest.diff_from("0.4.0").is_empty());
+    }
+
+    #[test]
+    fn test_regenerate_marks_changes() {
+        let rule = builtin_rules().next().unwrap();
+        let manifest = Manifest {
+            rule: vec![
+                ManifestEntry {
+                    hash: "stale".to_string(),
+                    kind: rule.0.to_string(),
+                    ..entry(rule.1.id, "0.1.0", None, None)
+                },
+                entry("retired-rule", "0.1.0", None, None),
+            ],
+        };
+
+        let regenerated = manifest.regenerate("0.5.0");
+        let first = regenerated.rule.iter().find(|e| e.id == rule.1.id).unwrap();
+        assert_eq!(first.changed.as_deref(), Some("0.5.0"));
+        assert_eq!(first.hash, rule_hash(rule.1));
+
+        let retired = regenerated.rule.iter().find(|e| e.id == "retired-rule").unwrap();
+        assert_eq!(retired.removed.as_deref(), Some("0.5.0"));
+    }
+}
@@ -1,14 +1,145 @@
 //! Security rules for claude-guardrails
 //!
-//! Defines dangerous command patterns, secrets patterns, and exfiltration detection.
+//! Defines dangerous command patterns, secrets patterns, exfiltration detection,
+//! network (URL) rules, and prompt-injection heuristics.
 
 pub mod allowlist;
+pub mod approvals;
+pub mod catalog;
+pub mod cloud;
 pub mod dangerous;
+pub mod dangerous_windows;
 pub mod exfiltration;
+pub mod forge;
+pub mod hints;
+pub mod iac;
+pub mod kubernetes;
+pub mod lint;
+pub mod manifest;
+pub mod network;
+pub mod packs;
+pub mod persistence;
+pub mod prompt_injection;
+pub mod reverse_shell;
+pub mod rewrite;
 pub mod secrets;
+pub mod shell_init;
+pub mod sql;
+pub mod tokens;
 
 use crate::config::SafetyLevel;
 
+/// What kind of operation a rule guards against (`rules list --category`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Category {
+    /// Deleting large parts of the filesystem
+    Filesystem,
+    /// Raw disk and partition writes
+    Disk,
+    /// Fork bombs and killing processes wholesale
+    Process,
+    /// Tampering with guardrails itself
+    Guardrails,
+    /// Downloading and running code in one step
+    RemoteExec,
+    /// Reverse shells and remote access
+    RemoteAccess,
+    /// History-rewriting and work-discarding git operations
+    Git,
+    /// Permission changes and privilege escalation
+    Permissions,
+    /// Reading or printing secrets
+    Secrets,
+    /// Container escapes and cleanup
+    Container,
+    /// Cluster-wide deletes and secret reads through kubectl and helm
+    Kubernetes,
+    /// Destructive cloud provider CLI operations
+    Cloud,
+    /// Infrastructure-as-code destroys and unreviewed applies
+    Infrastructure,
+    /// Code hidden in interpreter strings and eval
+    CodeExecution,
+    /// Destructive database statements
+    Database,
+    /// Package manager state
+    PackageManager,
+    /// Shell history manipulation
+    History,
+    /// Sending data off the machine
+    Exfiltration,
+    /// Shell startup files and scheduled jobs that run code later
+    Persistence,
+    /// WebFetch/WebSearch URLs
+    Network,
+    /// Prompt-injection heuristics
+    PromptInjection,
+    /// Not categorized (custom rules)
+    Other,
+}
+
+impl Category {
+    /// Every category, in display order
+    pub const ALL: &'static [Category] = &[
+        Category::Filesystem,
+        Category::Disk,
+        Category::Process,
+        Category::Guardrails,
+        Category::RemoteExec,
+        Category::RemoteAccess,
+        Category::Git,
+        Category::Permissions,
+        Category::Secrets,
+        Category::Container,
+        Category::Kubernetes,
+        Category::Cloud,
+        Category::Infrastructure,
+        Category::CodeExecution,
+        Category::Database,
+        Category::PackageManager,
+        Category::History,
+        Category::Exfiltration,
+        Category::Persistence,
+        Category::Network,
+        Category::PromptInjection,
+        Category::Other,
+    ];
+
+    /// Name used on the command line and in JSON output
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Category::Filesystem => "filesystem",
+            Category::Disk => "disk",
+            Category::Process => "process",
+            Category::Guardrails => "guardrails",
+            Category::RemoteExec => "remote-exec",
+            Category::RemoteAccess => "remote-access",
+            Category::Git => "git",
+            Category::Permissions => "permissions",
+            Category::Secrets => "secrets",
+            Category::Container => "container",
+            Category::Kubernetes => "kubernetes",
+            Category::Cloud => "cloud",
+            Category::Infrastructure => "infrastructure",
+            Category::CodeExecution => "code-execution",
+            Category::Database => "database",
+            Category::PackageManager => "package-manager",
+            Category::History => "history",
+            Category::Exfiltration => "exfiltration",
+            Category::Persistence => "persistence",
+            Category::Network => "network",
+            Category::PromptInjection => "prompt-injection",
+            Category::Other => "other",
+        }
+    }
+
+    /// Parse a category name
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(s: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|c| c.as_str() == s)
+    }
+}
+
 /// A security rule definition
 #[derive(Debug, Clone)]
 pub struct Rule {
@@ -18,11 +149,20 @@ pub struct Rule {
     /// Safety level at which this rule is active
     pub level: SafetyLevel,
 
+    /// What kind of operation the rule guards against
+    pub category: Category,
+
     /// Regex pattern to match
     pub pattern: &'static str,
 
     /// Human-readable reason for blocking
     pub reason: &'static str,
+
+    /// Inputs the pattern must match (checked by `rules lint` and the tests)
+    pub should_match: &'static [&'static str],
+
+    /// Inputs the pattern must not match
+    pub should_not_match: &'static [&'static str],
 }
 
 impl Rule {
@@ -36,8 +176,115 @@ impl Rule {
         Self {
             id,
             level,
+            category: Category::Other,
             pattern,
             reason,
+            should_match: &[],
+            should_not_match: &[],
+        }
+    }
+
+    /// Set the rule's category
+    pub const fn category(self, category: Category) -> Self {
+        Self { category, ..self }
+    }
+
+    /// Attach regression examples to a rule
+    pub const fn examples(
+        self,
+        should_match: &'static [&'static str],
+        should_not_match: &'static [&'static str],
+    ) -> Self {
+        Self {
+            should_match,
+            should_not_match,
+            ..self
         }
     }
 }
+
+/// All built-in rules, tagged with the rule set they belong to
+/// ("bash", "file", "exfiltration", "shell-init", "network", "prompt" or "sql")
+pub fn builtin_rules() -> impl Iterator<Item = (&'static str, &'static Rule)> {
+    let bash = dangerous::CRITICAL_RULES
+        .iter()
+        .chain(dangerous::HIGH_RULES)
+        .chain(reverse_shell::REVERSE_SHELL_RULES)
+        .chain(persistence::PERSISTENCE_RULES)
+        .chain(dangerous::STRICT_RULES)
+        .chain(dangerous_windows::WINDOWS_RULES)
+        // Tool-family rules (kubectl, terraform, aws, gh) carry their own levels
+        .chain(kubernetes::KUBERNETES_RULES)
+        .chain(iac::IAC_RULES)
+        .chain(cloud::CLOUD_RULES)
+        .chain(forge::FORGE_RULES)
+        .map(|r| ("bash", r));
+    let file = secrets::CRITICAL_SECRET_PATTERNS
+        .iter()
+        .chain(secrets::HIGH_SECRET_PATTERNS)
+        .chain(secrets::STRICT_SECRET_PATTERNS)
+        .map(|r| ("file", r));
+    let exfil = exfiltration::EXFILTRATION_RULES
+        .iter()
+        .map(|r| ("exfiltration", r));
+    let shell_init = shell_init::SHELL_INIT_RULES
+        .iter()
+        .chain(persistence::PERSISTENCE_FILE_RULES)
+        .map(|r| ("shell-init", r));
+    let network = network::CRITICAL_NETWORK_RULES
+        .iter()
+        .chain(network::HIGH_NETWORK_RULES)
+        .chain(network::STRICT_NETWORK_RULES)
+        .map(|r| ("network", r));
+    let prompt = prompt_injection::CRITICAL_PROMPT_RULES
+        .iter()
+        .chain(prompt_injection::HIGH_PROMPT_RULES)
+        .chain(prompt_injection::STRICT_PROMPT_RULES)
+        .map(|r| ("prompt", r));
+    let sql = sql::HIGH_SQL_RULES
+        .iter()
+        .chain(sql::STRICT_SQL_RULES)
+        .map(|r| ("sql", r));
+    bash.chain(file).chain(exfil).chain(shell_init).chain(network).chain(prompt).chain(sql)
+}
+
+/// Look up a built-in rule by ID across all rule sets
+pub fn find_rule(id: &str) -> Option<&'static Rule> {
+    builtin_rules().map(|(_, r)| r).find(|r| r.id == id)
+}
+
+/// Severity of a rule ID for reporting
+///
+/// Engine-level checks without a `Rule` entry (dynamic-command, pipe-to-shell,
+/// env-hijacking, parse-error, ...) are always active and reported as high.
+pub fn severity_of(id: &str) -> SafetyLevel {
+    find_rule(id).map(|r| r.level).unwrap_or(SafetyLevel::High)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_rule() {
+        assert_eq!(find_rule("rm-root").map(|r| r.level), Some(SafetyLevel::Critical));
+        assert_eq!(find_rule("env-file").map(|r| r.id), Some("env-file"));
+        assert_eq!(find_rule("scp-env-out").map(|r| r.level), Some(SafetyLevel::High));
+        assert!(find_rule("does-not-exist").is_none());
+    }
+
+    #[test]
+    fn test_builtin_rules_are_categorized() {
+        for (_, rule) in builtin_rules() {
+            assert_ne!(rule.category, Category::Other, "{} has no category", rule.id);
+        }
+        assert_eq!(find_rule("git-reset-hard").map(|r| r.category), Some(Category::Git));
+        assert_eq!(Category::from_str("remote-exec"), Some(Category::RemoteExec));
+    }
+
+    #[test]
+    fn test_severity_of_engine_checks() {
+        assert_eq!(severity_of("dynamic-command"), SafetyLevel::High);
+        assert_eq!(severity_of("git-force-any"), SafetyLevel::Strict);
+    }
+}
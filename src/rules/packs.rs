@@ -0,0 +1,393 @@
+//! Installable rule packs
+//!
+//! Community rule packs live in their own directories under `[packs] dir`
+//! (`~/.claude/guardrails/packs` by default):
+//!
+//! ```text
+//! packs/
+//! ├── index.toml          # enabled = ["k8s-pack"]
+//! └── k8s-pack/
+//!     └── pack.toml
+//! ```
+//!
+//! `pack.toml` has a `[pack]` table (name, version, author, description,
+//! min_version) followed by `[[rule]]` Bash rules and `[[token]]` formats in
+//! the token-pack format. Dropping a pack in does nothing until it is
+//! enabled; `rules packs enable|disable` edits `index.toml`, so packs are
+//! toggled without touching the main config.
+//!
+//! Pack rules must come with `should_match` examples. A rule whose pattern
+//! doesn't compile, fails its examples or reuses a built-in rule ID is
+//! skipped with a warning, so a broken pack can't take the built-in rules
+//! down with it.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use super::lint::check_examples;
+use super::{find_rule, Category, Rule};
+use crate::config::SafetyLevel;
+use crate::signing::TrustedKeys;
+
+/// File listing the enabled packs, in the packs directory
+pub const INDEX_FILE: &str = "index.toml";
+
+/// A pack's definition file, in its directory
+pub const PACK_FILE: &str = "pack.toml";
+
+/// The `[pack]` table
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct PackInfo {
+    pub name: String,
+    #[serde(default)]
+    pub version: String,
+    #[serde(default)]
+    pub author: String,
+    #[serde(default)]
+    pub description: String,
+    /// Oldest claude-guardrails release the pack works with
+    #[serde(default)]
+    pub min_version: Option<String>,
+}
+
+impl PackInfo {
+    /// Whether this build is at least `min_version`
+    pub fn is_compatible(&self) -> bool {
+        self.min_version
+            .as_deref()
+            .is_none_or(|min| version_parts(env!("CARGO_PKG_VERSION")) >= version_parts(min))
+    }
+}
+
+/// `1.2.3` as `[1, 2, 3]` (pre-release suffixes ignored)
+fn version_parts(version: &str) -> Vec<u64> {
+    version
+        .split(['-', '+'])
+        .next()
+        .unwrap_or_default()
+        .split('.')
+        .map(|part| part.parse().unwrap_or(0))
+        .collect()
+}
+
+/// A Bash rule in a pack
+#[derive(Debug, Clone, Deserialize)]
+pub struct PackRule {
+    pub id: String,
+    pub level: SafetyLevel,
+    /// Category name as in `rules list --category` (default "other")
+    #[serde(default)]
+    pub category: Option<String>,
+    pub pattern: String,
+    pub reason: String,
+    #[serde(default)]
+    pub should_match: Vec<String>,
+    #[serde(default)]
+    pub should_not_match: Vec<String>,
+}
+
+/// A parsed `pack.toml`
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct RulePack {
+    pub pack: PackInfo,
+    #[serde(default)]
+    pub rule: Vec<PackRule>,
+    /// Token formats, counted here; they are loaded as a token pack
+    #[serde(default)]
+    pub token: Vec<toml::Value>,
+}
+
+impl RulePack {
+    /// Load a pack from its `pack.toml`
+    pub fn from_file(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let content = std::fs::read_to_string(path)?;
+        Ok(toml::from_str(&content)?)
+    }
+
+    /// The pack's rules that can be used, as `Rule`s; the rest are reported
+    pub fn rules(&self) -> Vec<&'static Rule> {
+        self.rule
+            .iter()
+            .filter(|rule| {
+                let problem = if find_rule(&rule.id).is_some() {
+                    Some("reuses a built-in rule ID".to_string())
+                } else {
+                    let issues =
+                        check_examples(&rule.id, &rule.pattern, &rule.should_match, &rule.should_not_match);
+                    issues.first().map(|issue| issue.message.clone())
+                };
+                if let Some(problem) = &problem {
+                    eprintln!("Warning: Skipping rule {} of pack {}: {}", rule.id, self.pack.name, problem);
+                }
+                problem.is_none()
+            })
+            .map(intern)
+            .collect()
+    }
+}
+
+/// Rules built from packs, kept for the life of the process
+///
+/// Rule sets hold `&'static Rule`s, so pack rules are leaked; identical
+/// rules are reused, so recompiling (a daemon reloading its config) doesn't
+/// leak again.
+static INTERNED: Mutex<Vec<&'static Rule>> = Mutex::new(Vec::new());
+
+fn intern(rule: &PackRule) -> &'static Rule {
+    let category = rule
+        .category
+        .as_deref()
+        .and_then(Category::from_str)
+        .unwrap_or(Category::Other);
+    let mut interned = INTERNED.lock().unwrap_or_else(|e| e.into_inner());
+    let existing = interned.iter().find(|r| {
+        r.id == rule.id
+            && r.level == rule.level
+            && r.category == category
+            && r.pattern == rule.pattern
+            && r.reason == rule.reason
+            && r.should_match.iter().eq(&rule.should_match)
+            && r.should_not_match.iter().eq(&rule.should_not_match)
+    });
+    if let Some(existing) = existing {
+        return existing;
+    }
+    let leak_all = |items: &[String]| -> &'static [&'static str] {
+        Vec::leak(items.iter().map(|s| &*String::leak(s.clone())).collect())
+    };
+    let leaked: &'static Rule = Box::leak(Box::new(
+        Rule::new(
+            String::leak(rule.id.clone()),
+            rule.level,
+            String::leak(rule.pattern.clone()),
+            String::leak(rule.reason.clone()),
+        )
+        .category(category)
+        .examples(leak_all(&rule.should_match), leak_all(&rule.should_not_match)),
+    ));
+    interned.push(leaked);
+    leaked
+}
+
+/// `index.toml`: which installed packs are enabled
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PackIndex {
+    #[serde(default)]
+    pub enabled: Vec<String>,
+}
+
+impl PackIndex {
+    /// Read the index in `dir` (empty if there is none)
+    pub fn load(dir: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        match std::fs::read_to_string(dir.join(INDEX_FILE)) {
+            Ok(content) => Ok(toml::from_str(&content)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Write the index to `dir`
+    pub fn save(&self, dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        std::fs::create_dir_all(dir)?;
+        std::fs::write(dir.join(INDEX_FILE), toml::to_string(self)?)?;
+        Ok(())
+    }
+
+    pub fn is_enabled(&self, name: &str) -> bool {
+        self.enabled.iter().any(|n| n == name)
+    }
+}
+
+/// An installed pack, as found in the packs directory
+#[derive(Debug)]
+pub struct InstalledPack {
+    /// Directory name
+    pub name: String,
+    /// Its `pack.toml`
+    pub path: PathBuf,
+    /// The parsed pack, or why it couldn't be read
+    pub pack: Result<RulePack, String>,
+    pub enabled: bool,
+}
+
+/// Every pack directory in `dir`, by name
+pub fn installed(dir: &Path) -> Result<Vec<InstalledPack>, Box<dyn std::error::Error>> {
+    let index = PackIndex::load(dir)?;
+    let mut packs = Vec::new();
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(packs),
+        Err(e) => return Err(e.into()),
+    };
+    for entry in entries.flatten() {
+        let path = entry.path().join(PACK_FILE);
+        if !path.is_file() {
+            continue;
+        }
+        let name = entry.file_name().to_string_lossy().into_owned();
+        packs.push(InstalledPack {
+            enabled: index.is_enabled(&name),
+            pack: RulePack::from_file(&path).map_err(|e| e.to_string()),
+            name,
+            path,
+        });
+    }
+    packs.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(packs)
+}
+
+/// The enabled packs in `dir` that this build can load
+///
+/// Packs that are missing, unreadable, unsigned (when `keys` are trusted)
+/// or need a newer release are skipped with a warning.
+pub fn load_enabled(dir: &Path, keys: &TrustedKeys) -> Vec<(PathBuf, RulePack)> {
+    let index = match PackIndex::load(dir) {
+        Ok(index) => index,
+        Err(e) => {
+            eprintln!("Warning: Ignoring {}: {}", dir.join(INDEX_FILE).display(), e);
+            return Vec::new();
+        }
+    };
+    let mut packs = Vec::new();
+    for name in &index.enabled {
+        let path = dir.join(name).join(PACK_FILE);
+        if !path.is_file() {
+            eprintln!("Warning: Enabled pack {} is not installed in {}", name, dir.display());
+            continue;
+        }
+        if !keys.accepts(&path) {
+            continue;
+        }
+        match RulePack::from_file(&path) {
+            Ok(pack) if !pack.pack.is_compatible() => eprintln!(
+                "Warning: Skipping pack {}: needs claude-guardrails {}",
+                name,
+                pack.pack.min_version.as_deref().unwrap_or_default()
+            ),
+            Ok(pack) => packs.push((path, pack)),
+            Err(e) => eprintln!("Warning: Ignoring pack {}: {}", path.display(), e),
+        }
+    }
+    packs
+}
+
+/// Text table for `rules packs list`
+pub fn to_text(packs: &[InstalledPack]) -> String {
+    let mut out = String::new();
+    for installed in packs {
+        let state = if installed.enabled { "enabled" } else { "disabled" };
+        let line = match &installed.pack {
+            Ok(pack) => {
+                let info = &pack.pack;
+                let mut line = format!(
+                    "{:<20}  {:<8}  {:<8}  {} rules, {} tokens",
+                    installed.name,
+                    info.version,
+                    state,
+                    pack.rule.len(),
+                    pack.token.len()
+                );
+                if !info.author.is_empty() {
+                    line.push_str(&format!("  by {}", info.author));
+                }
+                if !info.is_compatible() {
+                    line.push_str(&format!(
+                        "  (needs {})",
+                        info.min_version.as_deref().unwrap_or_default()
+                    ));
+                }
+                if !info.description.is_empty() {
+                    line.push_str(&format!("\n{:<22}{}", "", info.description));
+                }
+                line
+            }
+            Err(e) => format!("{:<20}  {:<8}  {:<8}  unreadable: {}", installed.name, "-", state, e),
+        };
+        out.push_str(&line);
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const K8S_PACK: &str = r#"
+[pack]
+name = "k8s-pack"
+version = "0.1.0"
+author = "someone"
+min_version = "0.1.0"
+
+[[rule]]
+id = "pack-kubectl-delete-ns"
+level = "high"
+category = "container"
+pattern = '\bkubectl\s+delete\s+(ns|namespace)\b'
+reason = "Deleting a Kubernetes namespace"
+should_match = ["kubectl delete ns prod"]
+should_not_match = ["kubectl get ns"]
+
+[[rule]]
+id = "pack-broken"
+level = "high"
+pattern = "kubectl("
+reason = "Broken"
+should_match = ["kubectl("]
+
+[[rule]]
+id = "rm-root"
+level = "critical"
+pattern = "rm"
+reason = "Shadowing a built-in"
+should_match = ["rm"]
+"#;
+
+    fn install(dir: &Path, name: &str, content: &str) {
+        std::fs::create_dir_all(dir.join(name)).unwrap();
+        std::fs::write(dir.join(name).join(PACK_FILE), content).unwrap();
+    }
+
+    #[test]
+    fn test_pack_rules_validated() {
+        let pack: RulePack = toml::from_str(K8S_PACK).unwrap();
+        let rules = pack.rules();
+        let ids: Vec<&str> = rules.iter().map(|r| r.id).collect();
+        assert_eq!(ids, ["pack-kubectl-delete-ns"]);
+        assert_eq!(rules[0].category, Category::Container);
+        // Loading again reuses the same rule
+        assert!(std::ptr::eq(rules[0], pack.rules()[0]));
+    }
+
+    #[test]
+    fn test_min_version() {
+        let info = |min: &str| PackInfo { min_version: Some(min.to_string()), ..Default::default() };
+        assert!(info("0.0.9").is_compatible());
+        assert!(info(env!("CARGO_PKG_VERSION")).is_compatible());
+        assert!(!info("99.0.0").is_compatible());
+        assert!(PackInfo::default().is_compatible());
+    }
+
+    #[test]
+    fn test_only_enabled_packs_load() {
+        let dir = tempfile::tempdir().unwrap();
+        install(dir.path(), "k8s-pack", K8S_PACK);
+        install(dir.path(), "future-pack", "[pack]\nname = \"future-pack\"\nmin_version = \"99.0\"\n");
+        assert!(load_enabled(dir.path(), &TrustedKeys::default()).is_empty());
+
+        let index = PackIndex { enabled: vec!["k8s-pack".into(), "future-pack".into(), "missing".into()] };
+        index.save(dir.path()).unwrap();
+        let loaded = load_enabled(dir.path(), &TrustedKeys::default());
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].1.pack.name, "k8s-pack");
+
+        let listed = installed(dir.path()).unwrap();
+        let names: Vec<&str> = listed.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["future-pack", "k8s-pack"]);
+        let text = to_text(&listed);
+        assert!(text.contains("(needs 99.0)"));
+        assert!(text.contains("3 rules, 0 tokens  by someone"));
+    }
+}
@@ -0,0 +1,424 @@
+//! Rewrite rules for sanitizing commands
+//!
+//! Instead of blocking a risky command outright, some commands have a safe
+//! equivalent that preserves the intent (force push with lease, a dry run of
+//! a destructive clean). Each rewrite transforms a single command segment and
+//! returns `None` when it does not apply.
+
+use once_cell::sync::Lazy;
+use regex::{Regex, RegexSet};
+
+use crate::providers::Environment;
+
+/// A command rewrite
+pub struct Rewrite {
+    /// Unique identifier (used in config to skip rewrites)
+    pub id: &'static str,
+
+    /// Rule IDs this rewrite neutralizes
+    pub rule_ids: &'static [&'static str],
+
+    /// Human-readable description of the change
+    pub description: &'static str,
+
+    /// Transform a command segment, or `None` if the rewrite does not apply
+    pub apply: fn(&str) -> Option<String>,
+}
+
+/// All built-in rewrites, applied in order
+pub const REWRITES: &[Rewrite] = &[
+    Rewrite {
+        id: "force-with-lease",
+        rule_ids: &["git-force-any", "git-force-main", "git-force-main-alt"],
+        description: "Replace git push --force with --force-with-lease",
+        apply: force_with_lease,
+    },
+    Rewrite {
+        id: "git-clean-dry-run",
+        rule_ids: &["git-clean-force"],
+        description: "Run git clean as --dry-run instead of -f",
+        apply: git_clean_dry_run,
+    },
+];
+
+static GIT_PUSH: Lazy<Regex> = Lazy::new(|| Regex::new(r"\bgit\s+push\b[^;&|\n]*").unwrap());
+static GIT_CLEAN: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\bgit\s+clean\b[^;&|\n]*").unwrap());
+static GIT_CLEAN_CMD: Lazy<Regex> = Lazy::new(|| Regex::new(r"\bgit\s+clean\b").unwrap());
+
+/// Commands that may run for a long time or hang on the network
+const LONG_RUNNING_PATTERNS: &[&str] = &[
+    r"\b(curl|wget|ssh|scp|rsync|sftp|ftp|nc|ncat|telnet|ping)\b",
+    r"\bgit\s+(clone|fetch|pull|push)\b",
+    r"\b(npm|pnpm|yarn|bun)\s+(install|i|ci|add|update)\b",
+    r"\b(pip3?|uv|poetry)\s+(install|sync|add)\b",
+    r"\bcargo\s+(build|install|test|update)\b",
+    r"\bdocker\s+(build|pull|push|run)\b",
+    r"\b(apt|apt-get|brew|dnf|yum)\s+(install|upgrade|update)\b",
+    r"\b(sleep|watch|make)\b",
+    r"\btail\s+(-\w*f|--follow)",
+];
+
+static LONG_RUNNING: Lazy<RegexSet> = Lazy::new(|| RegexSet::new(LONG_RUNNING_PATTERNS).unwrap());
+
+/// Whether a command matches a long-running or network pattern
+pub fn is_long_running(command: &str) -> bool {
+    LONG_RUNNING.is_match(command)
+}
+
+/// Map space-separated tokens of every `re` match through `f`
+///
+/// Splitting on single spaces (rather than whitespace runs) keeps the
+/// original spacing intact when the tokens are joined back together.
+fn map_tokens(command: &str, re: &Regex, f: impl Fn(&str) -> Option<String>) -> Option<String> {
+    let mut changed = false;
+    let result = re.replace_all(command, |caps: &regex::Captures| {
+        caps[0]
+            .split(' ')
+            .filter_map(|token| match f(token) {
+                Some(new) => {
+                    changed = true;
+                    (!new.is_empty()).then_some(new)
+                }
+                None => Some(token.to_string()),
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    });
+    changed.then(|| result.into_owned())
+}
+
+fn force_with_lease(command: &str) -> Option<String> {
+    map_tokens(command, &GIT_PUSH, |token| {
+        matches!(token, "-f" | "--force").then(|| "--force-with-lease".to_string())
+    })
+}
+
+fn git_clean_dry_run(command: &str) -> Option<String> {
+    let unforced = map_tokens(command, &GIT_CLEAN, |token| match token {
+        "--force" => Some(String::new()),
+        t if t.starts_with('-') && !t.starts_with("--") && t.contains('f') => {
+            let rest = t.replace('f', "");
+            Some(if rest == "-" { String::new() } else { rest })
+        }
+        _ => None,
+    })?;
+    Some(GIT_CLEAN_CMD.replace_all(&unforced, "$0 --dry-run").into_owned())
+}
+
+/// Trash CLIs to look for on PATH, with the command line to use
+const TRASH_COMMANDS: &[(&str, &str)] = &[
+    ("trash-put", "trash-put"),
+    ("gio", "gio trash"),
+    ("trash", "trash"),
+];
+
+static RM_RECURSIVE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(^|[;&|(]\s*|\bsudo\s+)rm((?:\s+--?[A-Za-z-]+)+)\s+").unwrap()
+});
+
+/// Find a trash CLI on `environment`'s PATH
+pub fn detect_trash_command(environment: &dyn Environment) -> Option<&'static str> {
+    let path = environment.var("PATH")?;
+    TRASH_COMMANDS
+        .iter()
+        .find(|(bin, _)| std::env::split_paths(&path).any(|dir| dir.join(bin).is_file()))
+        .map(|(_, command)| *command)
+}
+
+/// Rewrite recursive `rm` invocations to move targets to the trash instead
+pub fn rm_to_trash(command: &str, trash: &str) -> Option<String> {
+    let mut changed = false;
+    let result = RM_RECURSIVE.replace_all(command, |caps: &regex::Captures| {
+        let flags = &caps[2];
+        let recursive = flags.split_whitespace().any(|f| {
+            f == "--recursive" || (!f.starts_with("--") && (f.contains('r') || f.contains('R')))
+        });
+        if recursive {
+            changed = true;
+            format!("{}{} ", &caps[1], trash)
+        } else {
+            caps[0].to_string()
+        }
+    });
+    changed.then(|| result.into_owned())
+}
+
+/// Command prepended to destructive git operations to keep a backup
+pub const STASH_PREFIX: &str = "git stash push -u -m \"guardrails-backup\" && ";
+
+/// Commands that change which directory or repository git acts on
+static CHANGES_REPO: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(^|[\s;&|(])(cd|pushd|popd)(\s|;|$)|\s-C\s|--git-dir|--work-tree").unwrap()
+});
+
+/// Insert a stash right before the first statement matching any of
+/// `rule_ids`
+///
+/// Returns the new command and the rule IDs it matched. Those rules still
+/// match the rewritten command, but uncommitted work is now recoverable with
+/// `git stash pop`. The stash runs where the matching statement does
+/// (`cd inner && git stash ... && git reset --hard`); when that can't be
+/// guaranteed (the statement follows `||` or runs in the background, or a
+/// `cd`, `pushd`, `git -C` or `--git-dir` comes at or after it) the command
+/// is left alone, and stays denied.
+pub fn stash_before(command: &str, rule_ids: &[String]) -> Option<(String, Vec<String>)> {
+    if command.contains(STASH_PREFIX) {
+        return None;
+    }
+
+    let rules: Vec<(&String, Regex)> = rule_ids
+        .iter()
+        .filter_map(|id| Some((id, Regex::new(super::find_rule(id)?.pattern).ok()?)))
+        .collect();
+    let matched: Vec<String> = rules
+        .iter()
+        .filter(|(_, re)| re.is_match(command))
+        .map(|(id, _)| id.to_string())
+        .collect();
+    if matched.is_empty() {
+        return None;
+    }
+
+    let statement = statements(command)
+        .into_iter()
+        .find(|s| rules.iter().any(|(_, re)| re.is_match(&command[s.start..s.end])))?;
+    if !statement.after_and || CHANGES_REPO.is_match(&command[statement.start..]) {
+        return None;
+    }
+    let (before, after) = command.split_at(statement.start);
+    Some((format!("{}{}{}", before, STASH_PREFIX, after), matched))
+}
+
+/// A leaf statement of a command line (lists split at `&&`, `||`, `;`)
+struct Statement {
+    start: usize,
+    end: usize,
+    /// First in the line, or after `;`, a newline or `&&`: a stash
+    /// inserted before it runs exactly when it does
+    after_and: bool,
+}
+
+/// The leaf statements of `command`, in order; empty if it doesn't parse
+fn statements(command: &str) -> Vec<Statement> {
+    let tree = crate::parser::grammar::parser().ok().and_then(|mut parser| parser.parse(command, None));
+    let Some(tree) = tree.filter(|tree| !tree.root_node().has_error()) else {
+        return Vec::new();
+    };
+
+    fn leaves(node: tree_sitter::Node, after_and: bool, out: &mut Vec<Statement>) {
+        if node.kind() != "list" {
+            out.push(Statement { start: node.start_byte(), end: node.end_byte(), after_and });
+            return;
+        }
+        let mut after_and = after_and;
+        let mut cursor = node.walk();
+        for child in node.children(&mut cursor) {
+            match child.kind() {
+                "&&" => after_and = true,
+                "||" => after_and = false,
+                _ if child.is_named() => leaves(child, after_and, out),
+                _ => {}
+            }
+        }
+    }
+
+    let root = tree.root_node();
+    let mut out: Vec<Statement> = Vec::new();
+    let mut cursor = root.walk();
+    for child in root.children(&mut cursor) {
+        if child.kind() == "&" {
+            // Sent to the background: the stash wouldn't finish first
+            if let Some(last) = out.last_mut() {
+                last.after_and = false;
+            }
+        } else if child.is_named() && child.kind() != "comment" {
+            leaves(child, true, &mut out);
+        }
+    }
+    out
+}
+
+/// Prefixes of a compound command that end at a top-level `&&`, `||`, `;`
+/// or newline, longest first
+///
+/// `npm test && rm -rf /` yields `npm test`. A statement sent to the
+/// background with `&` is never a cut point (dropping the `&` would change
+/// how it runs), and a command that doesn't parse cleanly yields nothing.
+pub fn compound_prefixes(command: &str) -> Vec<&str> {
+    let tree = crate::parser::grammar::parser().ok().and_then(|mut parser| parser.parse(command, None));
+    let Some(tree) = tree.filter(|tree| !tree.root_node().has_error()) else {
+        return Vec::new();
+    };
+
+    fn statement_ends(node: tree_sitter::Node, ends: &mut Vec<usize>) {
+        if node.kind() != "list" {
+            ends.push(node.end_byte());
+            return;
+        }
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            statement_ends(child, ends);
+        }
+    }
+
+    let root = tree.root_node();
+    let mut ends = Vec::new();
+    let mut cursor = root.walk();
+    for child in root.children(&mut cursor) {
+        if child.kind() == "&" {
+            ends.pop();
+        } else if child.is_named() && child.kind() != "comment" {
+            statement_ends(child, &mut ends);
+        }
+    }
+
+    let last = ends.iter().copied().max().unwrap_or(0);
+    ends.into_iter()
+        .rev()
+        .filter(|&end| end < last)
+        .filter_map(|end| command.get(..end))
+        .map(str::trim_end)
+        .filter(|prefix| !prefix.is_empty())
+        .collect()
+}
+
+/// Apply every rewrite not listed in `skip`
+///
+/// Returns the rewritten command and the IDs of the rewrites that changed it,
+/// or `None` if the command was left untouched.
+pub fn apply(command: &str, skip: &[String]) -> Option<(String, Vec<&'static str>)> {
+    let mut current = command.to_string();
+    let mut applied = Vec::new();
+
+    for rewrite in REWRITES {
+        if skip.iter().any(|s| s == rewrite.id) {
+            continue;
+        }
+        if let Some(next) = (rewrite.apply)(&current) {
+            current = next;
+            applied.push(rewrite.id);
+        }
+    }
+
+    (!applied.is_empty()).then_some((current, applied))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compound_prefixes() {
+        assert_eq!(compound_prefixes("npm test && rm -rf /"), ["npm test"]);
+        assert_eq!(
+            compound_prefixes("cd app; make && make install || echo failed"),
+            ["cd app; make && make install", "cd app; make", "cd app"]
+        );
+        assert_eq!(compound_prefixes("a | b && c"), ["a | b"]);
+        assert_eq!(compound_prefixes("if true; then rm -rf /; fi"), Vec::<&str>::new());
+        assert_eq!(compound_prefixes("sleep 5 & rm -rf /"), Vec::<&str>::new());
+        assert_eq!(compound_prefixes("make\nrm -rf build"), ["make"]);
+        assert_eq!(compound_prefixes("echo 'x && y"), Vec::<&str>::new());
+        assert!(compound_prefixes("ls").is_empty());
+    }
+
+    #[test]
+    fn test_force_with_lease() {
+        assert_eq!(
+            force_with_lease("git push --force origin feature").as_deref(),
+            Some("git push --force-with-lease origin feature")
+        );
+        assert_eq!(
+            force_with_lease("git push -f && git push -f upstream").as_deref(),
+            Some("git push --force-with-lease && git push --force-with-lease upstream")
+        );
+        assert!(force_with_lease("git push --force-with-lease").is_none());
+        assert!(force_with_lease("git push origin main").is_none());
+    }
+
+    #[test]
+    fn test_git_clean_dry_run() {
+        assert_eq!(
+            git_clean_dry_run("git clean -fd").as_deref(),
+            Some("git clean --dry-run -d")
+        );
+        assert_eq!(
+            git_clean_dry_run("git clean -f").as_deref(),
+            Some("git clean --dry-run")
+        );
+        assert_eq!(
+            git_clean_dry_run("cd repo && git clean --force -x").as_deref(),
+            Some("cd repo && git clean --dry-run -x")
+        );
+        assert!(git_clean_dry_run("git clean -n").is_none());
+    }
+
+    #[test]
+    fn test_apply_respects_skip() {
+        let (rewritten, applied) = apply("git push -f", &[]).unwrap();
+        assert_eq!(rewritten, "git push --force-with-lease");
+        assert_eq!(applied, vec!["force-with-lease"]);
+
+        assert!(apply("git push -f", &["force-with-lease".to_string()]).is_none());
+        assert!(apply("ls -la", &[]).is_none());
+    }
+
+    #[test]
+    fn test_rm_to_trash() {
+        assert_eq!(
+            rm_to_trash("rm -rf build dist", "trash-put").as_deref(),
+            Some("trash-put build dist")
+        );
+        assert_eq!(
+            rm_to_trash("cd app && rm -r -f node_modules", "gio trash").as_deref(),
+            Some("cd app && gio trash node_modules")
+        );
+        assert!(rm_to_trash("rm file.txt", "trash-put").is_none());
+        assert!(rm_to_trash("rm -f file.txt", "trash-put").is_none());
+        assert!(rm_to_trash("npm run rm -rf", "trash-put").is_none());
+    }
+
+    #[test]
+    fn test_stash_before() {
+        let rules = vec!["git-reset-hard".to_string(), "git-discard-changes".to_string()];
+        let (command, matched) = stash_before("git checkout -- .", &rules).unwrap();
+        assert_eq!(command, format!("{}git checkout -- .", STASH_PREFIX));
+        assert_eq!(matched, vec!["git-discard-changes"]);
+
+        assert!(stash_before("git status", &rules).is_none());
+        assert!(stash_before("git reset --hard", &[]).is_none());
+        assert!(stash_before(&command, &rules).is_none());
+
+        // The stash goes right before the statement, after any `cd`
+        let (command, _) = stash_before("cd inner && git reset --hard", &rules).unwrap();
+        assert_eq!(command, format!("cd inner && {}git reset --hard", STASH_PREFIX));
+        let (command, _) = stash_before("make clean; git checkout -- .", &rules).unwrap();
+        assert_eq!(command, format!("make clean; {}git checkout -- .", STASH_PREFIX));
+
+        // ... and isn't inserted where it wouldn't protect that repository
+        assert!(stash_before("git reset --hard && cd ../other && git reset --hard", &rules).is_none());
+        assert!(stash_before("(cd inner && git reset --hard)", &rules).is_none());
+        assert!(stash_before("git -C ../other reset --hard", &rules).is_none());
+        assert!(stash_before("git pull || git reset --hard", &rules).is_none());
+        assert!(stash_before("git reset --hard &", &rules).is_none());
+    }
+
+    #[test]
+    fn test_is_long_running() {
+        assert!(is_long_running("curl https://example.com -o out"));
+        assert!(is_long_running("cd web && npm install"));
+        assert!(is_long_running("tail -f log.txt"));
+        assert!(!is_long_running("ls -la"));
+        assert!(!is_long_running("git status"));
+    }
+
+    #[test]
+    fn test_rewrite_rule_ids_are_known() {
+        for rewrite in REWRITES {
+            for id in rewrite.rule_ids {
+                assert!(crate::rules::find_rule(id).is_some(), "unknown rule {}", id);
+            }
+        }
+    }
+}
@@ -3,7 +3,7 @@
 //! Defines patterns for files that commonly contain secrets.
 
 use crate::config::SafetyLevel;
-use crate::rules::Rule;
+use crate::rules::{Category, Rule};
 
 /// Critical secrets - direct credential files
 pub const CRITICAL_SECRET_PATTERNS: &[Rule] = &[
@@ -12,54 +12,83 @@ pub const CRITICAL_SECRET_PATTERNS: &[Rule] = &[
         SafetyLevel::Critical,
         r"\.env$",
         "Environment file may contain secrets",
-    ),
+    )
+    .category(Category::Secrets)
+    .examples(&["/repo/.env"], &["/repo/.env.example"]),
     Rule::new(
         "env-local",
         SafetyLevel::Critical,
         r"\.env\.local$",
         "Local environment file may contain secrets",
-    ),
+    )
+    .category(Category::Secrets)
+    .examples(&["/repo/.env.local"], &["/repo/.env.local.example"]),
     Rule::new(
         "env-production",
         SafetyLevel::Critical,
         r"\.env\.production$",
         "Production environment file contains secrets",
-    ),
+    )
+    .category(Category::Secrets)
+    .examples(&["/repo/.env.production"], &["/repo/.env.production.sample"]),
     Rule::new(
         "ssh-private-key",
         SafetyLevel::Critical,
         r"\.ssh/id_(rsa|ed25519|ecdsa|dsa)$",
         "SSH private key file",
-    ),
+    )
+    .category(Category::Secrets)
+    .examples(&["/home/u/.ssh/id_ed25519"], &["/home/u/.ssh/id_ed25519.pub"]),
     Rule::new(
         "aws-credentials",
         SafetyLevel::Critical,
         r"\.aws/credentials$",
         "AWS credentials file",
-    ),
+    )
+    .category(Category::Secrets)
+    .examples(&["/home/u/.aws/credentials"], &["/home/u/.aws/config"]),
     Rule::new(
         "kube-config",
         SafetyLevel::Critical,
         r"\.kube/config$",
         "Kubernetes config with credentials",
-    ),
+    )
+    .category(Category::Secrets)
+    .examples(&["/home/u/.kube/config"], &["/home/u/.kube/cache"]),
     Rule::new(
         "pem-file",
         SafetyLevel::Critical,
         r"\.pem$",
         "PEM certificate/key file",
-    ),
+    )
+    .category(Category::Secrets)
+    .examples(&["/etc/ssl/private/server.pem"], &["/repo/docs/pem-format.md"]),
     Rule::new(
         "p12-file",
         SafetyLevel::Critical,
         r"\.p12$",
         "PKCS#12 certificate file",
-    ),
+    )
+    .category(Category::Secrets)
+    .examples(&["/repo/certs/client.p12"], &["/repo/certs/README.md"]),
     Rule::new(
         "key-file",
         SafetyLevel::Critical,
         r"\.key$",
         "Private key file",
+    )
+    .category(Category::Secrets)
+    .examples(&["/repo/certs/server.key"], &["/repo/src/keyboard.rs"]),
+    Rule::new(
+        "guardrails-approval-file",
+        SafetyLevel::Critical,
+        r"guardrails/approvals\.json$",
+        "Guardrails approval store",
+    )
+    .category(Category::Secrets)
+    .examples(
+        &["/home/u/.claude/guardrails/approvals.json"],
+        &["/home/u/.claude/guardrails/audit.jsonl"],
     ),
 ];
 
@@ -70,73 +99,100 @@ pub const HIGH_SECRET_PATTERNS: &[Rule] = &[
         SafetyLevel::High,
         r"credentials\.json$",
         "Credentials configuration file",
-    ),
+    )
+    .category(Category::Secrets)
+    .examples(&["/repo/credentials.json"], &["/repo/credentials.json.example"]),
     Rule::new(
         "secrets-file",
         SafetyLevel::High,
         r"secrets?\.(json|ya?ml|toml)$",
         "Secrets configuration file",
-    ),
+    )
+    .category(Category::Secrets)
+    .examples(&["/repo/config/secrets.yaml", "/repo/secret.toml"], &["/repo/secrets.md"]),
     Rule::new(
         "docker-config",
         SafetyLevel::High,
         r"\.docker/config\.json$",
         "Docker registry credentials",
-    ),
+    )
+    .category(Category::Secrets)
+    .examples(&["/home/u/.docker/config.json"], &["/repo/docker/config.json"]),
     Rule::new(
         "netrc",
         SafetyLevel::High,
         r"\.netrc$",
         "Network credentials file",
-    ),
+    )
+    .category(Category::Secrets)
+    .examples(&["/home/u/.netrc"], &["/repo/netrc.md"]),
     Rule::new(
         "npmrc",
         SafetyLevel::High,
         r"\.npmrc$",
         "npm authentication tokens",
-    ),
+    )
+    .category(Category::Secrets)
+    .examples(&["/home/u/.npmrc"], &["/repo/.npmrc.example"]),
     Rule::new(
         "pypirc",
         SafetyLevel::High,
         r"\.pypirc$",
         "PyPI authentication file",
-    ),
+    )
+    .category(Category::Secrets)
+    .examples(&["/home/u/.pypirc"], &["/repo/pypirc.txt"]),
     Rule::new(
         "pgpass",
         SafetyLevel::High,
         r"\.pgpass$",
         "PostgreSQL password file",
-    ),
+    )
+    .category(Category::Secrets)
+    .examples(&["/home/u/.pgpass"], &["/repo/pgpass.md"]),
     Rule::new(
         "my-cnf",
         SafetyLevel::High,
         r"\.my\.cnf$",
         "MySQL credentials file",
-    ),
+    )
+    .category(Category::Secrets)
+    .examples(&["/home/u/.my.cnf"], &["/etc/mysql/my.cnf"]),
     Rule::new(
         "gcp-credentials",
         SafetyLevel::High,
         r"gcloud/credentials\.db$",
         "GCP credentials database",
+    )
+    .category(Category::Secrets)
+    .examples(
+        &["/home/u/.config/gcloud/credentials.db"],
+        &["/home/u/.config/gcloud/active_config"],
     ),
     Rule::new(
         "azure-profile",
         SafetyLevel::High,
         r"\.azure/accessTokens\.json$",
         "Azure access tokens",
-    ),
+    )
+    .category(Category::Secrets)
+    .examples(&["/home/u/.azure/accessTokens.json"], &["/home/u/.azure/config"]),
     Rule::new(
         "github-token",
         SafetyLevel::High,
         r"\.github/token$",
         "GitHub token file",
-    ),
+    )
+    .category(Category::Secrets)
+    .examples(&["/home/u/.github/token"], &["/repo/.github/workflows/ci.yml"]),
     Rule::new(
         "gnupg-keyring",
         SafetyLevel::High,
         r"\.gnupg/(secring|private-keys)",
         "GPG private keyring",
-    ),
+    )
+    .category(Category::Secrets)
+    .examples(&["/home/u/.gnupg/private-keys-v1.d/ABC.key"], &["/home/u/.gnupg/pubring.kbx"]),
 ];
 
 /// Strict level secrets - files that might contain secrets
@@ -146,25 +202,33 @@ pub const STRICT_SECRET_PATTERNS: &[Rule] = &[
         SafetyLevel::Strict,
         r"(config|settings)\.(json|ya?ml|toml)$",
         "Configuration file may contain credentials",
-    ),
+    )
+    .category(Category::Secrets)
+    .examples(&["/repo/config.json", "/repo/app/settings.yaml"], &["/repo/src/config.rs"]),
     Rule::new(
         "htpasswd",
         SafetyLevel::Strict,
         r"\.htpasswd$",
         "Apache password file",
-    ),
+    )
+    .category(Category::Secrets)
+    .examples(&["/etc/nginx/.htpasswd"], &["/etc/nginx/nginx.conf"]),
     Rule::new(
         "shadow",
         SafetyLevel::Strict,
         r"/etc/shadow$",
         "System password hashes",
-    ),
+    )
+    .category(Category::Secrets)
+    .examples(&["/etc/shadow"], &["/etc/shadow.bak"]),
     Rule::new(
         "passwd",
         SafetyLevel::Strict,
         r"/etc/passwd$",
         "System user database",
-    ),
+    )
+    .category(Category::Secrets)
+    .examples(&["/etc/passwd"], &["/etc/passwd-"]),
 ];
 
 /// Get all secret patterns up to and including the specified safety level
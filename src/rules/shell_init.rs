@@ -0,0 +1,57 @@
+//! Shell startup and scheduled-job files
+//!
+//! Anything appended to `~/.bashrc` or dropped into `/etc/cron.d` runs
+//! later, in every new shell or on a schedule, long after the command that
+//! wrote it was checked. These patterns are matched against the files a Bash
+//! command writes (redirect targets and `tee` operands) and the paths given
+//! to Write/Edit; reading the files is fine.
+//!
+//! All rules are active from the High level.
+
+use crate::config::SafetyLevel;
+use crate::rules::{Category, Rule};
+
+/// Startup and scheduled-job file patterns (High level)
+pub const SHELL_INIT_RULES: &[Rule] = &[
+    Rule::new(
+        "shell-init-bash",
+        SafetyLevel::High,
+        r"(?:^|/)\.(?:bashrc|bash_profile|bash_login|bash_logout|profile)$",
+        "Writing a bash startup file (runs in every new shell)",
+    )
+    .category(Category::Persistence)
+    .examples(&["~/.bashrc", "/home/dev/.profile"], &["~/.bashrc.bak", "docs/profile"]),
+    Rule::new(
+        "shell-init-zsh",
+        SafetyLevel::High,
+        r"(?:^|/)\.(?:zshrc|zshenv|zprofile|zlogin|zlogout)$",
+        "Writing a zsh startup file (runs in every new shell)",
+    )
+    .category(Category::Persistence)
+    .examples(&["~/.zshrc", "$HOME/.zshenv"], &["~/.zsh_history", "zshrc.example"]),
+    Rule::new(
+        "shell-init-fish",
+        SafetyLevel::High,
+        r"(?:^|/)\.config/fish/(?:config\.fish|conf\.d/[^/]+)$",
+        "Writing a fish startup file (runs in every new shell)",
+    )
+    .category(Category::Persistence)
+    .examples(&["~/.config/fish/config.fish", "~/.config/fish/conf.d/env.fish"], &["~/.config/fish/fish_variables"]),
+    Rule::new(
+        "shell-init-system",
+        SafetyLevel::High,
+        r"^/etc/(?:profile|profile\.d/[^/]+|bash\.bashrc|bashrc|zshrc|zsh/[^/]+|zprofile|zshenv|environment)$",
+        "Writing a system-wide shell startup file",
+    )
+    .category(Category::Persistence)
+    .examples(&["/etc/profile", "/etc/profile.d/proxy.sh", "/etc/environment"], &["/etc/profiles.txt"]),
+    Rule::new(
+        "crontab-file",
+        SafetyLevel::High,
+        r"^(?:/etc/(?:crontab|anacrontab|cron\.(?:d|hourly|daily|weekly|monthly)/[^/]+)|/var/spool/cron/.+)$",
+        "Writing a cron job file (runs on a schedule)",
+    )
+    .category(Category::Persistence)
+    .examples(&["/etc/crontab", "/etc/cron.d/backup", "/var/spool/cron/crontabs/root"], &["./crontab.txt"]),
+];
+
@@ -0,0 +1,99 @@
+//! Database statement rules
+//!
+//! Matched against the SQL, shell script or command a database client is
+//! handed inline (`psql -c`, `mysql -e`, `sqlite3 db "..."`, `mongosh
+//! --eval`, `redis-cli flushall`), as extracted by `engine::dbclient`,
+//! rather than against the whole command line. Patterns are
+//! case-insensitive, as SQL is.
+//!
+//! The `drop-database` and `truncate-table` Bash rules match the raw
+//! command text anywhere (a commit message, a grep pattern), so they stay
+//! at Strict; a statement known to be executed is denied from High.
+
+use crate::config::SafetyLevel;
+use crate::rules::{Category, Rule};
+
+/// Statements that destroy a database, table or keyspace's data (High level)
+pub const HIGH_SQL_RULES: &[Rule] = &[
+    Rule::new(
+        "sql-drop",
+        SafetyLevel::High,
+        r"(?i)\bdrop\s+(?:database|schema|table|keyspace)\b",
+        "Dropping a database, schema or table",
+    )
+    .category(Category::Database)
+    .examples(
+        &["drop table users", "DROP DATABASE IF EXISTS app;", "select 1; drop schema public cascade"],
+        &["select * from drops", "create table users (id int)"],
+    ),
+    Rule::new(
+        "sql-truncate",
+        SafetyLevel::High,
+        r"(?i)\btruncate\s+(?:table\s+)?[\w.\x22`\[]",
+        "Truncating a table",
+    )
+    .category(Category::Database)
+    .examples(&["truncate users", "TRUNCATE TABLE audit_log RESTART IDENTITY"], &["select truncate(1.5)"]),
+    Rule::new(
+        "sql-delete-all",
+        SafetyLevel::High,
+        r"(?i)\bdelete\s+from\s+[\w.\x22`\[\]]+\s*(?:;|$)",
+        "Deleting every row of a table (no WHERE clause)",
+    )
+    .category(Category::Database)
+    .examples(&["delete from users", "DELETE FROM public.sessions;"], &["delete from users where id = 4"]),
+    Rule::new(
+        "mongo-drop",
+        SafetyLevel::High,
+        r"\bdropDatabase\s*\(|\.drop\s*\(\s*\)|\.(?:deleteMany|remove)\s*\(\s*\{\s*\}\s*\)",
+        "Dropping a MongoDB database or collection, or deleting every document",
+    )
+    .category(Category::Database)
+    .examples(
+        &["db.dropDatabase()", "db.users.drop()", "db.sessions.deleteMany({})"],
+        &["db.users.find({})", "db.sessions.deleteMany({ expired: true })"],
+    ),
+    Rule::new(
+        "redis-flush",
+        SafetyLevel::High,
+        r"(?i)(?:^|;)\s*flush(?:all|db)\b",
+        "Flushing every key in Redis",
+    )
+    .category(Category::Database)
+    .examples(&["FLUSHALL", "flushdb async"], &["get flushall", "keys session:*"]),
+];
+
+/// Statements that remove data or schema (Strict level)
+pub const STRICT_SQL_RULES: &[Rule] = &[
+    Rule::new(
+        "sql-delete",
+        SafetyLevel::Strict,
+        r"(?i)\bdelete\s+from\b",
+        "Deleting rows",
+    )
+    .category(Category::Database)
+    .examples(&["delete from users where id = 4"], &["select * from users"]),
+    Rule::new(
+        "sql-alter-drop",
+        SafetyLevel::Strict,
+        r"(?i)\balter\s+table\b[^;]*\bdrop\b",
+        "Dropping a column or constraint",
+    )
+    .category(Category::Database)
+    .examples(&["ALTER TABLE users DROP COLUMN email"], &["alter table users add column age int"]),
+];
+
+/// Get all SQL rules up to and including the specified safety level
+pub fn get_sql_rules_for_level(level: SafetyLevel) -> Vec<&'static Rule> {
+    let mut rules = Vec::new();
+
+    if level.includes(SafetyLevel::High) {
+        rules.extend(HIGH_SQL_RULES.iter());
+    }
+
+    if level.includes(SafetyLevel::Strict) {
+        rules.extend(STRICT_SQL_RULES.iter());
+    }
+
+    rules
+}
@@ -0,0 +1,216 @@
+//! Service definitions for the daemon
+//!
+//! `claude-guardrails daemon install-service` writes a systemd user unit
+//! pair (socket-activated: systemd owns the socket and starts the daemon on
+//! the first hook call) or, on macOS, a launchd agent that keeps the daemon
+//! running. launchd socket activation needs `launch_activate_socket`, which
+//! isn't available without a C binding, so the agent uses `KeepAlive`
+//! instead.
+
+use std::path::{Path, PathBuf};
+
+/// Unit name shared by the systemd socket and service
+const SYSTEMD_UNIT: &str = "claude-guardrails";
+
+/// launchd job label
+const LAUNCHD_LABEL: &str = "com.claude-guardrails.daemon";
+
+/// Which service manager to target
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ServiceKind {
+    Systemd,
+    Launchd,
+}
+
+impl ServiceKind {
+    /// The service manager of the running platform
+    pub fn current() -> Self {
+        if cfg!(target_os = "macos") {
+            ServiceKind::Launchd
+        } else {
+            ServiceKind::Systemd
+        }
+    }
+
+    /// Parse from a `--kind` value
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "systemd" => Some(ServiceKind::Systemd),
+            "launchd" => Some(ServiceKind::Launchd),
+            _ => None,
+        }
+    }
+}
+
+/// Files to install and the command that enables them
+#[derive(Debug, Clone)]
+pub struct ServiceFiles {
+    /// (path, content) pairs
+    pub files: Vec<(PathBuf, String)>,
+    /// Shell command to run after writing the files
+    pub enable: String,
+}
+
+impl ServiceFiles {
+    /// Render the service files for `kind`
+    ///
+    /// `daemon_args` are the arguments the daemon runs with (`--daemon`,
+    /// `--socket`, and any `--config`/`--no-home` to carry over).
+    pub fn render(kind: ServiceKind, binary: &Path, daemon_args: &[String], socket: &Path, home: &Path) -> Self {
+        match kind {
+            ServiceKind::Systemd => Self::systemd(binary, daemon_args, socket, home),
+            ServiceKind::Launchd => Self::launchd(binary, daemon_args, home),
+        }
+    }
+
+    fn systemd(binary: &Path, daemon_args: &[String], socket: &Path, home: &Path) -> Self {
+        let dir = home.join(".config/systemd/user");
+        let exec = std::iter::once(binary.display().to_string())
+            .chain(daemon_args.iter().cloned())
+            .map(|arg| systemd_quote(&arg))
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        let socket_unit = format!(
+            "[Unit]\n\
+             Description=claude-guardrails daemon socket\n\
+             \n\
+             [Socket]\n\
+             ListenStream={}\n\
+             SocketMode=0600\n\
+             DirectoryMode=0700\n\
+             \n\
+             [Install]\n\
+             WantedBy=sockets.target\n",
+            socket.display()
+        );
+        let service_unit = format!(
+            "[Unit]\n\
+             Description=claude-guardrails daemon (warm rule engine)\n\
+             Requires={unit}.socket\n\
+             After={unit}.socket\n\
+             \n\
+             [Service]\n\
+             ExecStart={exec}\n\
+             Restart=on-failure\n\
+             \n\
+             [Install]\n\
+             Also={unit}.socket\n",
+            unit = SYSTEMD_UNIT,
+            exec = exec
+        );
+
+        Self {
+            files: vec![
+                (dir.join(format!("{}.socket", SYSTEMD_UNIT)), socket_unit),
+                (dir.join(format!("{}.service", SYSTEMD_UNIT)), service_unit),
+            ],
+            enable: format!(
+                "systemctl --user daemon-reload && systemctl --user enable --now {}.socket",
+                SYSTEMD_UNIT
+            ),
+        }
+    }
+
+    fn launchd(binary: &Path, daemon_args: &[String], home: &Path) -> Self {
+        let path = home.join(format!("Library/LaunchAgents/{}.plist", LAUNCHD_LABEL));
+        let log = home.join(".claude/guardrails/daemon.log");
+        let arguments: String = std::iter::once(binary.display().to_string())
+            .chain(daemon_args.iter().cloned())
+            .map(|arg| format!("        <string>{}</string>\n", xml_escape(&arg)))
+            .collect();
+
+        let plist = format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>Label</key>
+    <string>{label}</string>
+    <key>ProgramArguments</key>
+    <array>
+{arguments}    </array>
+    <key>RunAtLoad</key>
+    <true/>
+    <key>KeepAlive</key>
+    <true/>
+    <key>StandardErrorPath</key>
+    <string>{log}</string>
+</dict>
+</plist>
+"#,
+            label = LAUNCHD_LABEL,
+            arguments = arguments,
+            log = xml_escape(&log.display().to_string())
+        );
+
+        Self {
+            enable: format!("launchctl load -w {}", path.display()),
+            files: vec![(path, plist)],
+        }
+    }
+}
+
+/// Quote an ExecStart argument if it contains whitespace or quotes
+fn systemd_quote(arg: &str) -> String {
+    if arg.contains(|c: char| c.is_whitespace() || c == '"' || c == '\\') {
+        format!("\"{}\"", arg.replace('\\', "\\\\").replace('"', "\\\""))
+    } else {
+        arg.to_string()
+    }
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args() -> Vec<String> {
+        ["--daemon", "--socket", "/home/u/.claude/guardrails/daemon.sock"]
+            .map(String::from)
+            .to_vec()
+    }
+
+    #[test]
+    fn test_systemd_units() {
+        let files = ServiceFiles::render(
+            ServiceKind::Systemd,
+            Path::new("/home/u/bin/claude-guardrails"),
+            &args(),
+            Path::new("/home/u/.claude/guardrails/daemon.sock"),
+            Path::new("/home/u"),
+        );
+        let (socket_path, socket) = &files.files[0];
+        assert_eq!(socket_path, Path::new("/home/u/.config/systemd/user/claude-guardrails.socket"));
+        assert!(socket.contains("ListenStream=/home/u/.claude/guardrails/daemon.sock\n"));
+        assert!(socket.contains("SocketMode=0600"));
+
+        let (_, service) = &files.files[1];
+        assert!(service.contains(
+            "ExecStart=/home/u/bin/claude-guardrails --daemon --socket /home/u/.claude/guardrails/daemon.sock\n"
+        ));
+        assert!(files.enable.contains("enable --now claude-guardrails.socket"));
+        assert_eq!(systemd_quote("/a b/c"), "\"/a b/c\"");
+    }
+
+    #[test]
+    fn test_launchd_plist() {
+        let files = ServiceFiles::render(
+            ServiceKind::Launchd,
+            Path::new("/Users/u/bin/claude-guardrails"),
+            &args(),
+            Path::new("/Users/u/.claude/guardrails/daemon.sock"),
+            Path::new("/Users/u"),
+        );
+        assert_eq!(files.files.len(), 1);
+        let (path, plist) = &files.files[0];
+        assert_eq!(path, Path::new("/Users/u/Library/LaunchAgents/com.claude-guardrails.daemon.plist"));
+        assert!(plist.contains("<string>/Users/u/bin/claude-guardrails</string>\n        <string>--daemon</string>"));
+        assert!(plist.contains("<key>KeepAlive</key>"));
+        assert!(files.enable.starts_with("launchctl load -w "));
+    }
+}
@@ -0,0 +1,254 @@
+//! Signature checks for distributed policy files (minisign)
+//!
+//! Token packs, and policy files fetched with `policy fetch`, can weaken
+//! protection: a pack entry with an existing id replaces the built-in
+//! format, and a config file can turn rules off. When `[signing]
+//! trusted_keys` lists minisign public keys, such files must come with a
+//! detached signature (`<file>.minisig`) made by one of them: token packs
+//! without one are skipped, and `policy fetch` refuses to install them.
+//! Every fetch is recorded in the audit log with its verification status.
+//!
+//! Sign with `minisign -Sm pack.toml` and distribute `pack.toml.minisig`
+//! next to the file.
+
+use std::path::{Path, PathBuf};
+
+use minisign_verify::{Error, PublicKey, Signature};
+use serde::Serialize;
+
+use crate::config::SigningConfig;
+use crate::parser::powershell;
+
+/// Extension of detached signatures
+pub const SIGNATURE_EXTENSION: &str = "minisig";
+
+/// Where the detached signature of `path` is expected
+pub fn signature_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".");
+    name.push(SIGNATURE_EXTENSION);
+    PathBuf::from(name)
+}
+
+/// Result of checking a file's signature
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "status", rename_all = "lowercase")]
+pub enum Verification {
+    /// Signed by the trusted key with this ID
+    Verified { key_id: String },
+    /// No signature found
+    Unsigned,
+    /// A signature that doesn't verify against any trusted key
+    Invalid { error: String },
+}
+
+impl Verification {
+    pub fn is_verified(&self) -> bool {
+        matches!(self, Verification::Verified { .. })
+    }
+
+    /// Status name ("verified", "unsigned", "invalid")
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Verification::Verified { .. } => "verified",
+            Verification::Unsigned => "unsigned",
+            Verification::Invalid { .. } => "invalid",
+        }
+    }
+
+    /// One-line description, e.g. `signature verified (key E7620F1842B4E81F)`
+    pub fn describe(&self) -> String {
+        match self {
+            Verification::Verified { key_id } => format!("signature verified (key {})", key_id),
+            Verification::Unsigned => "no signature".to_string(),
+            Verification::Invalid { error } => format!("bad signature: {}", error),
+        }
+    }
+}
+
+/// Minisign public keys trusted to sign policy files
+#[derive(Debug, Clone, Default)]
+pub struct TrustedKeys {
+    keys: Vec<(String, PublicKey)>,
+}
+
+impl TrustedKeys {
+    /// The keys of `[signing] trusted_keys`; keys that can't be read are
+    /// skipped with a warning
+    pub fn from_config(config: &SigningConfig) -> Self {
+        let mut keys = Self::default();
+        for key in &config.trusted_keys {
+            if let Err(e) = keys.add(key) {
+                eprintln!("Warning: Ignoring trusted key {}: {}", key, e);
+            }
+        }
+        keys
+    }
+
+    /// Trust a key: the base64 key line (`RW...`) or the path of a minisign
+    /// `.pub` file
+    pub fn add(&mut self, key: &str) -> Result<(), String> {
+        let line = if key.starts_with("RW") {
+            key.to_string()
+        } else {
+            let path = crate::config::Config::expand_path(key);
+            let content = std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
+            content
+                .lines()
+                .map(str::trim)
+                .find(|l| !l.is_empty() && !l.starts_with("untrusted comment:"))
+                .ok_or("no key in file")?
+                .to_string()
+        };
+        let public_key = PublicKey::from_base64(&line).map_err(|e| e.to_string())?;
+        self.keys.push((key_id(&line).unwrap_or_default(), public_key));
+        Ok(())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Check `content` against a detached signature (the `.minisig` text)
+    pub fn verify(&self, content: &[u8], signature: Option<&str>) -> Verification {
+        let Some(signature) = signature else {
+            return Verification::Unsigned;
+        };
+        let signature = match Signature::decode(signature) {
+            Ok(signature) => signature,
+            Err(e) => return Verification::Invalid { error: e.to_string() },
+        };
+        let mut error = "signed by a key that isn't trusted".to_string();
+        for (key_id, key) in &self.keys {
+            match key.verify(content, &signature, false) {
+                Ok(()) => return Verification::Verified { key_id: key_id.clone() },
+                Err(Error::UnexpectedKeyId) => {}
+                Err(e) => error = e.to_string(),
+            }
+        }
+        Verification::Invalid { error }
+    }
+
+    /// Check the file at `path` against `<path>.minisig`
+    pub fn verify_file(&self, path: &Path) -> std::io::Result<Verification> {
+        let content = std::fs::read(path)?;
+        let signature = std::fs::read_to_string(signature_path(path)).ok();
+        Ok(self.verify(&content, signature.as_deref()))
+    }
+
+    /// Whether the file at `path` may be loaded: always when no keys are
+    /// trusted, else only with a valid signature (a warning is printed
+    /// otherwise)
+    pub fn accepts(&self, path: &Path) -> bool {
+        if self.is_empty() {
+            return true;
+        }
+        match self.verify_file(path) {
+            Ok(verification) if verification.is_verified() => true,
+            Ok(verification) => {
+                eprintln!("Warning: Ignoring {}: {}", path.display(), verification.describe());
+                false
+            }
+            Err(e) => {
+                eprintln!("Warning: Ignoring {}: {}", path.display(), e);
+                false
+            }
+        }
+    }
+}
+
+/// Key ID as minisign prints it (`E7620F1842B4E81F`)
+fn key_id(line: &str) -> Option<String> {
+    let bytes = powershell::base64_decode(line)?;
+    let id: [u8; 8] = bytes.get(2..10)?.try_into().ok()?;
+    Some(format!("{:016X}", u64::from_le_bytes(id)))
+}
+
+/// A policy file downloaded with its signature
+#[derive(Debug, Clone)]
+pub struct Fetched {
+    pub content: Vec<u8>,
+    /// The `.minisig` text, if the server had one
+    pub signature: Option<String>,
+    pub verification: Verification,
+}
+
+/// Download `url` and `<url>.minisig` and check them against `keys`
+#[cfg(feature = "webhook")]
+pub fn fetch(url: &str, keys: &TrustedKeys) -> std::io::Result<Fetched> {
+    use std::io::Read;
+
+    let agent = ureq::AgentBuilder::new()
+        .timeout(std::time::Duration::from_secs(30))
+        .build();
+    let get = |url: &str| -> std::io::Result<Vec<u8>> {
+        let response = agent.get(url).call().map_err(|e| std::io::Error::other(e.to_string()))?;
+        let mut body = Vec::new();
+        response.into_reader().take(MAX_POLICY_BYTES).read_to_end(&mut body)?;
+        Ok(body)
+    };
+    let content = get(url)?;
+    let signature = get(&format!("{}.{}", url, SIGNATURE_EXTENSION))
+        .ok()
+        .and_then(|s| String::from_utf8(s).ok());
+    let verification = keys.verify(&content, signature.as_deref());
+    Ok(Fetched { content, signature, verification })
+}
+
+/// Largest policy file `fetch` reads
+#[cfg(feature = "webhook")]
+const MAX_POLICY_BYTES: u64 = 4 * 1024 * 1024;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Test vector from minisign-verify: a prehashed signature of b"test"
+    const KEY: &str = "RWQf6LRCGA9i53mlYecO4IzT51TGPpvWucNSCh1CBM0QTaLn73Y7GFO3";
+    const SIGNATURE: &str = "untrusted comment: signature from minisign secret key
+RUQf6LRCGA9i559r3g7V1qNyJDApGip8MfqcadIgT9CuhV3EMhHoN1mGTkUidF/z7SrlQgXdy8ofjb7bNJJylDOocrCo8KLzZwo=
+trusted comment: timestamp:1556193335\tfile:test
+y/rUw2y8/hOUYjZU71eHp/Wo1KZ40fGy2VJEDl34XMJM+TX48Ss/17u3IvIfbVR1FkZZSNCisQbuQY+bHwhEBg==";
+
+    fn keys() -> TrustedKeys {
+        TrustedKeys::from_config(&SigningConfig { trusted_keys: vec![KEY.to_string()] })
+    }
+
+    #[test]
+    fn test_verify() {
+        let keys = keys();
+        let verified = Verification::Verified { key_id: "E7620F1842B4E81F".to_string() };
+        assert_eq!(keys.verify(b"test", Some(SIGNATURE)), verified);
+        assert_eq!(keys.verify(b"test", None), Verification::Unsigned);
+        assert_eq!(keys.verify(b"Test", Some(SIGNATURE)).as_str(), "invalid");
+        assert_eq!(keys.verify(b"test", Some("garbage")).as_str(), "invalid");
+
+        let other = TrustedKeys::from_config(&SigningConfig {
+            trusted_keys: vec!["RWQAAAAAAAAAAHmlYecO4IzT51TGPpvWucNSCh1CBM0QTaLn73Y7GFO3".to_string()],
+        });
+        let Verification::Invalid { error } = other.verify(b"test", Some(SIGNATURE)) else {
+            panic!("verified with the wrong key");
+        };
+        assert!(error.contains("isn't trusted"));
+    }
+
+    #[test]
+    fn test_accepts_signed_files_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("pack.toml");
+        std::fs::write(&path, "test").unwrap();
+        assert!(TrustedKeys::default().accepts(&path), "no keys, no checks");
+        assert!(!keys().accepts(&path));
+
+        std::fs::write(signature_path(&path), SIGNATURE).unwrap();
+        assert!(keys().accepts(&path));
+        std::fs::write(&path, "tampered").unwrap();
+        assert!(!keys().accepts(&path));
+
+        let pub_file = dir.path().join("policy.pub");
+        std::fs::write(&pub_file, format!("untrusted comment: minisign public key\n{}\n", KEY)).unwrap();
+        let mut from_file = TrustedKeys::default();
+        from_file.add(pub_file.to_str().unwrap()).unwrap();
+        assert!(from_file.verify(b"test", Some(SIGNATURE)).is_verified());
+    }
+}
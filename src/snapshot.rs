@@ -0,0 +1,158 @@
+//! Per-session git snapshots
+//!
+//! Before the first mutating command of a session, record the current state
+//! of the working tree under `refs/guardrails/session-<id>` so anything the
+//! session breaks can be recovered with `git checkout` or `git stash apply`.
+//! The ref itself marks the session as snapshotted, so no state is kept here.
+
+use once_cell::sync::Lazy;
+use regex::RegexSet;
+use std::io;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use crate::input::{HookInput, ToolInput};
+
+/// Prefix for snapshot refs
+pub const REF_PREFIX: &str = "refs/guardrails/session-";
+
+/// Bash commands that modify files or repository state
+const MUTATING_PATTERNS: &[&str] = &[
+    r"\b(rm|rmdir|mv|cp|ln|truncate|shred|unlink|chmod|chown|touch|mkdir|install)\b",
+    r"\bsed\s+(-\w*i|--in-place)",
+    r"\bgit\s+(reset|checkout|switch|restore|clean|rebase|merge|cherry-pick|revert|commit|stash|pull|am|apply|rm|mv)\b",
+    r"\b(patch|tee|dd)\b",
+    r"[^<>&0-9]>{1,2}\s*[^&\s]",
+    r"\b(npm|pnpm|yarn|cargo|pip3?)\s+(install|add|remove|uninstall|update|fmt|fix)\b",
+];
+
+static MUTATING: Lazy<RegexSet> = Lazy::new(|| RegexSet::new(MUTATING_PATTERNS).unwrap());
+
+/// Whether a tool call may modify the working tree
+pub fn is_mutating(input: &HookInput) -> bool {
+    match &input.tool_input {
+        ToolInput::Bash { command, .. } => MUTATING.is_match(command),
+        ToolInput::Edit { .. } | ToolInput::MultiEdit { .. } | ToolInput::Write { .. } => true,
+        ToolInput::Read { .. }
+        | ToolInput::WebFetch { .. }
+        | ToolInput::WebSearch { .. }
+        | ToolInput::Unknown { .. } => false,
+    }
+}
+
+/// Ref name for a session, with characters git won't accept replaced
+pub fn session_ref(session_id: &str) -> String {
+    let safe: String = session_id
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    format!("{}{}", REF_PREFIX, safe)
+}
+
+/// Run git in `cwd`, returning trimmed stdout on success
+fn git(cwd: &Path, args: &[&str]) -> io::Result<Option<String>> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(cwd)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()?;
+
+    if !output.status.success() {
+        return Ok(None);
+    }
+    Ok(Some(String::from_utf8_lossy(&output.stdout).trim().to_string()))
+}
+
+/// Snapshot the repository at `cwd` for a session, once
+///
+/// Returns the ref name and commit if a snapshot was created, `None` if `cwd`
+/// is not a git work tree, has no commits yet, or the session already has a
+/// snapshot. Uncommitted tracked changes are captured via `git stash create`,
+/// which builds a stash commit without touching the working tree or the
+/// stash list; untracked files are not included.
+pub fn ensure_session_snapshot(cwd: &Path, session_id: &str) -> io::Result<Option<(String, String)>> {
+    if git(cwd, &["rev-parse", "--is-inside-work-tree"])?.as_deref() != Some("true") {
+        return Ok(None);
+    }
+
+    let ref_name = session_ref(session_id);
+    if git(cwd, &["rev-parse", "--verify", "--quiet", &ref_name])?.is_some() {
+        return Ok(None);
+    }
+
+    let commit = match git(cwd, &["stash", "create", "guardrails session snapshot"])? {
+        Some(stash) if !stash.is_empty() => stash,
+        _ => match git(cwd, &["rev-parse", "--verify", "--quiet", "HEAD"])? {
+            Some(head) => head,
+            None => return Ok(None),
+        },
+    };
+
+    if git(cwd, &["update-ref", &ref_name, &commit])?.is_none() {
+        return Err(io::Error::other(format!("git update-ref {} failed", ref_name)));
+    }
+
+    Ok(Some((ref_name, commit)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bash(command: &str) -> HookInput {
+        let json = serde_json::json!({"tool_name": "Bash", "tool_input": {"command": command}});
+        HookInput::from_json(&json.to_string()).unwrap()
+    }
+
+    #[test]
+    fn test_is_mutating() {
+        assert!(is_mutating(&bash("rm -rf build")));
+        assert!(is_mutating(&bash("echo hi > notes.txt")));
+        assert!(is_mutating(&bash("git reset --hard")));
+        assert!(is_mutating(&bash("sed -i 's/a/b/' file")));
+        assert!(!is_mutating(&bash("ls -la")));
+        assert!(!is_mutating(&bash("git status")));
+        assert!(!is_mutating(&bash("cargo test 2>&1")));
+    }
+
+    #[test]
+    fn test_session_ref() {
+        assert_eq!(session_ref("abc-123"), "refs/guardrails/session-abc-123");
+        assert_eq!(session_ref("a/b:c"), "refs/guardrails/session-a_b_c");
+    }
+
+    #[test]
+    fn test_snapshot_once_per_session() {
+        let dir = tempfile::tempdir().unwrap();
+        let cwd = dir.path();
+        let run = |args: &[&str]| {
+            Command::new("git")
+                .args(args)
+                .current_dir(cwd)
+                .output()
+                .unwrap()
+        };
+        run(&["init", "-q"]);
+        run(&["-c", "user.name=t", "-c", "user.email=t@t", "commit", "-q", "--allow-empty", "-m", "init"]);
+        std::fs::write(cwd.join("file.txt"), "hello").unwrap();
+        run(&["add", "file.txt"]);
+
+        let (ref_name, commit) = ensure_session_snapshot(cwd, "s1").unwrap().unwrap();
+        assert_eq!(ref_name, "refs/guardrails/session-s1");
+        assert!(!commit.is_empty());
+
+        // The staged change is still in place and the stash list is untouched
+        assert!(cwd.join("file.txt").exists());
+        assert!(run(&["stash", "list"]).stdout.is_empty());
+
+        // Second call for the same session is a no-op
+        assert!(ensure_session_snapshot(cwd, "s1").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_snapshot_outside_repo() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(ensure_session_snapshot(dir.path(), "s1").unwrap().is_none());
+    }
+}
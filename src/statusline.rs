@@ -0,0 +1,117 @@
+//! Status line for Claude Code
+//!
+//! `claude-guardrails statusline` is meant to be Claude Code's `statusLine`
+//! command: Claude Code pipes it the session's JSON and shows what it
+//! prints under the prompt. The line says whether guardrails is enforcing,
+//! at which level, and how many calls it has denied this session (counted
+//! from the audit log), e.g. `guardrails high | 2 denied`. A warn-only,
+//! disabled or degraded engine is spelled out, so a session that isn't
+//! protected is never mistaken for one that is.
+
+use crate::audit::{AuditEntry, LogLevel};
+use crate::engine::SecurityEngine;
+use crate::health::Health;
+
+/// What the status line reports
+#[derive(Debug, Clone)]
+pub struct StatusLine {
+    /// Engine status, as in [`Health::status`]
+    pub status: &'static str,
+    pub safety_level: &'static str,
+    /// Calls denied this session (`None` without a session or audit log)
+    pub denied: Option<usize>,
+    /// Calls warned about this session (what warn-only mode reports
+    /// instead of denials)
+    pub warned: Option<usize>,
+}
+
+impl StatusLine {
+    /// Status of `engine`, with the decisions logged for `session_id`
+    /// among `entries`
+    pub fn build(engine: &SecurityEngine, entries: Option<&[AuditEntry]>, session_id: Option<&str>) -> Self {
+        let health = Health::of(engine);
+        let count = |level: LogLevel| {
+            let session_id = session_id?;
+            let entries = entries?;
+            Some(
+                entries
+                    .iter()
+                    .filter(|e| e.level == level && e.session_id.as_deref() == Some(session_id))
+                    .count(),
+            )
+        };
+        Self {
+            status: health.status,
+            safety_level: health.safety_level,
+            denied: count(LogLevel::Blocked),
+            warned: count(LogLevel::Warn),
+        }
+    }
+
+    /// One line, e.g. `guardrails high | 2 denied` or
+    /// `guardrails high WARN-ONLY | 3 warned`
+    pub fn to_text(&self) -> String {
+        let mut line = match self.status {
+            "disabled" => return "guardrails OFF".to_string(),
+            "warn-only" => format!("guardrails {} WARN-ONLY", self.safety_level),
+            "degraded" => format!("guardrails {} DEGRADED", self.safety_level),
+            _ => format!("guardrails {}", self.safety_level),
+        };
+        if let Some(denied) = self.denied.filter(|&n| n > 0) {
+            line.push_str(&format!(" | {} denied", denied));
+        }
+        if let Some(warned) = self.warned.filter(|&n| n > 0) {
+            line.push_str(&format!(" | {} warned", warned));
+        }
+        line
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::engine::Overrides;
+    use crate::input::HookInput;
+
+    fn entry(command: &str, session_id: &str, engine: &SecurityEngine) -> AuditEntry {
+        let json = format!(
+            r#"{{"tool_name":"Bash","tool_input":{{"command":"{}"}},"session_id":"{}"}}"#,
+            command, session_id
+        );
+        let input = HookInput::from_json(&json).unwrap();
+        AuditEntry::new(&input, &engine.check(&input), false)
+    }
+
+    #[test]
+    fn test_counts_this_session() {
+        let engine = SecurityEngine::new(Config::default()).with_overrides(Overrides::default());
+        let entries = vec![
+            entry("rm -rf /", "s1", &engine),
+            entry("ls", "s1", &engine),
+            entry("rm -rf /", "s2", &engine),
+            entry("rm -rf ~", "s1", &engine),
+        ];
+        let status = StatusLine::build(&engine, Some(&entries), Some("s1"));
+        assert_eq!(status.denied, Some(2));
+        assert_eq!(status.to_text(), "guardrails high | 2 denied");
+
+        // Nothing to count without a session
+        let status = StatusLine::build(&engine, Some(&entries), None);
+        assert_eq!(status.denied, None);
+        assert_eq!(status.to_text(), "guardrails high");
+    }
+
+    #[test]
+    fn test_overrides_spelled_out() {
+        let warn_only = Overrides { warn_only: true, ..Default::default() };
+        let engine = SecurityEngine::new(Config::default()).with_overrides(warn_only);
+        let entries = vec![entry("rm -rf /", "s1", &engine)];
+        let status = StatusLine::build(&engine, Some(&entries), Some("s1"));
+        assert_eq!(status.to_text(), "guardrails high WARN-ONLY | 1 warned");
+
+        let disabled = Overrides { disabled: true, ..Default::default() };
+        let engine = SecurityEngine::new(Config::default()).with_overrides(disabled);
+        assert_eq!(StatusLine::build(&engine, None, Some("s1")).to_text(), "guardrails OFF");
+    }
+}
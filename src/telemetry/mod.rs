@@ -0,0 +1,143 @@
+//! Opt-in, anonymous rule hit counters
+//!
+//! With `[telemetry] enabled = true`, every hook decision bumps a counter in
+//! a local JSON file: how often each rule blocked, warned, asked, or was
+//! overridden with an allow-once token (the best signal of a false positive
+//! guardrails has). Only rule IDs and counts are stored, never command text,
+//! paths or session IDs. `claude-guardrails telemetry show` prints the file;
+//! `telemetry submit` POSTs it to the configured org endpoint (requires the
+//! `webhook` feature) and starts a new counting period. Decisions and the
+//! time spent checking them can also be exported over OTLP (`otlp`).
+
+pub mod otlp;
+pub mod trace;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io;
+use std::path::Path;
+
+use crate::output::{AllowSource, Decision};
+
+/// Counts for one rule
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RuleCounts {
+    pub blocked: u64,
+    pub warned: u64,
+    pub asked: u64,
+    /// Blocks overridden with an allow-once token
+    pub approved: u64,
+}
+
+/// The counters file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Counters {
+    /// guardrails version that last wrote the file
+    pub version: String,
+    /// Start of the counting period
+    pub since: DateTime<Utc>,
+    /// Decisions recorded, including plain allows
+    pub decisions: u64,
+    pub rules: BTreeMap<String, RuleCounts>,
+}
+
+impl Default for Counters {
+    fn default() -> Self {
+        Self {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            since: Utc::now(),
+            decisions: 0,
+            rules: BTreeMap::new(),
+        }
+    }
+}
+
+impl Counters {
+    /// Load the counters, starting fresh if the file is missing or unreadable
+    pub fn load(path: &Path) -> Self {
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default()
+    }
+
+    /// Count a decision
+    pub fn record(&mut self, decision: &Decision) {
+        self.decisions += 1;
+        let (rule_id, counter): (&str, fn(&mut RuleCounts) -> &mut u64) = match decision {
+            Decision::Deny { rule_id, .. } => (rule_id, |c| &mut c.blocked),
+            Decision::Warn { rule_id, .. } => (rule_id, |c| &mut c.warned),
+            Decision::Ask { rule_id, .. } => (rule_id, |c| &mut c.asked),
+            Decision::Allow { source: AllowSource::Approved { rule_id }, .. } => (rule_id, |c| &mut c.approved),
+            Decision::Allow { .. } => return,
+        };
+        *counter(self.rules.entry(rule_id.to_string()).or_default()) += 1;
+    }
+
+    /// Write the counters atomically (concurrent hooks may lose an
+    /// increment, but never corrupt the file)
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let tmp = path.with_extension(format!("tmp.{}", std::process::id()));
+        std::fs::write(&tmp, self.to_json())?;
+        std::fs::rename(&tmp, path)
+    }
+
+    /// Serialize to a JSON string
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_else(|_| "{}".to_string())
+    }
+}
+
+/// Count a decision in the counters file at `path`
+pub fn record(path: &Path, decision: &Decision) -> io::Result<()> {
+    let mut counters = Counters::load(path);
+    counters.version = env!("CARGO_PKG_VERSION").to_string();
+    counters.record(decision);
+    counters.save(path)
+}
+
+/// POST the counters to `endpoint` as JSON
+#[cfg(feature = "webhook")]
+pub fn submit(endpoint: &str, counters: &Counters) -> io::Result<()> {
+    ureq::AgentBuilder::new()
+        .timeout(std::time::Duration::from_secs(10))
+        .build()
+        .post(endpoint)
+        .set("Content-Type", "application/json")
+        .send_string(&counters.to_json())
+        .map(|_| ())
+        .map_err(|e| io::Error::other(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_counts_rules_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("telemetry.json");
+
+        record(&path, &Decision::deny("rm-root", "Attempting to delete root filesystem")).unwrap();
+        record(&path, &Decision::allow("passed all checks")).unwrap();
+        let approved = AllowSource::Approved { rule_id: "git-force-any".to_string() };
+        record(&path, &Decision::allow_from(approved, "approved once: token abc123 (git-force-any)")).unwrap();
+        // Only the typed origin counts, not a reason that reads like one
+        record(&path, &Decision::allow("approved once: token abc123 (rm-root)")).unwrap();
+
+        let counters = Counters::load(&path);
+        assert_eq!(counters.decisions, 4);
+        assert_eq!(counters.rules["rm-root"].blocked, 1);
+        assert_eq!(counters.rules["rm-root"].approved, 0);
+        assert_eq!(counters.rules["git-force-any"].approved, 1);
+
+        let json = std::fs::read_to_string(&path).unwrap();
+        assert!(!json.contains("root filesystem"));
+        assert!(!json.contains("abc123"));
+    }
+}
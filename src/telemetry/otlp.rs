@@ -0,0 +1,221 @@
+//! OpenTelemetry export (OTLP over HTTP, JSON encoding)
+//!
+//! With `[telemetry] enabled = true` and `otlp_endpoint` set, every hook
+//! decision is sent to an OpenTelemetry collector as a delta of the
+//! `guardrails.decisions` counter (attributes: level, rule ID, tool), and
+//! the spans recorded while checking it (`check`, `parse`, `ast-analysis`,
+//! `rule-match`, and sampled per-rule `rule-cost` spans, see `trace`) as one
+//! trace. Like the counters file, nothing
+//! identifies the command: no command text, paths or session IDs. Sending
+//! needs the `otlp` cargo feature.
+
+use serde_json::{json, Value};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::trace::SpanRecord;
+use crate::output::Decision;
+
+/// Instrumentation scope name
+const SCOPE: &str = "claude-guardrails";
+
+/// Name of the decision counter
+pub const DECISIONS_METRIC: &str = "guardrails.decisions";
+
+/// OTLP `AggregationTemporality` for deltas
+const TEMPORALITY_DELTA: u8 = 1;
+
+/// OTLP `SpanKind` for in-process work
+const SPAN_KIND_INTERNAL: u8 = 1;
+
+fn nanos(time: SystemTime) -> String {
+    time.duration_since(UNIX_EPOCH).map(|d| d.as_nanos()).unwrap_or(0).to_string()
+}
+
+fn attribute(key: &str, value: &str) -> Value {
+    json!({ "key": key, "value": { "stringValue": value } })
+}
+
+fn resource(service_name: &str) -> Value {
+    json!({
+        "attributes": [
+            attribute("service.name", service_name),
+            attribute("service.version", env!("CARGO_PKG_VERSION")),
+        ]
+    })
+}
+
+fn scope() -> Value {
+    json!({ "name": SCOPE, "version": env!("CARGO_PKG_VERSION") })
+}
+
+/// Level name and rule ID of a decision ("allowed", "blocked", ...)
+fn outcome(decision: &Decision) -> (&'static str, Option<&str>) {
+    match decision {
+        Decision::Allow { .. } => ("allowed", None),
+        Decision::Deny { rule_id, .. } => ("blocked", Some(rule_id)),
+        Decision::Warn { rule_id, .. } => ("warn", Some(rule_id)),
+        Decision::Ask { rule_id, .. } => ("ask", Some(rule_id)),
+    }
+}
+
+/// `/v1/metrics` body counting one decision
+pub fn metrics_payload(service_name: &str, tool: &str, decision: &Decision, at: SystemTime) -> Value {
+    let (level, rule_id) = outcome(decision);
+    let mut attributes = vec![attribute("guardrails.level", level), attribute("guardrails.tool", tool)];
+    if let Some(rule_id) = rule_id {
+        attributes.push(attribute("guardrails.rule_id", rule_id));
+    }
+    json!({
+        "resourceMetrics": [{
+            "resource": resource(service_name),
+            "scopeMetrics": [{
+                "scope": scope(),
+                "metrics": [{
+                    "name": DECISIONS_METRIC,
+                    "description": "Hook decisions by level, rule and tool",
+                    "unit": "1",
+                    "sum": {
+                        "aggregationTemporality": TEMPORALITY_DELTA,
+                        "isMonotonic": true,
+                        "dataPoints": [{
+                            "attributes": attributes,
+                            "startTimeUnixNano": nanos(at),
+                            "timeUnixNano": nanos(at),
+                            "asInt": "1",
+                        }],
+                    },
+                }],
+            }],
+        }],
+    })
+}
+
+/// `/v1/traces` body with the recorded spans as one trace, or `None` if
+/// nothing was recorded
+pub fn traces_payload(service_name: &str, tool: &str, decision: &Decision, spans: &[SpanRecord]) -> Option<Value> {
+    if spans.is_empty() {
+        return None;
+    }
+    let trace_id = format!("{:016x}{:016x}", random_id(), random_id());
+    let span_ids: Vec<String> = spans.iter().map(|_| format!("{:016x}", random_id())).collect();
+    let (level, rule_id) = outcome(decision);
+    let spans: Vec<Value> = spans
+        .iter()
+        .enumerate()
+        .map(|(i, span)| {
+            let mut attributes = Vec::new();
+            if span.parent.is_none() {
+                attributes.push(attribute("guardrails.tool", tool));
+                attributes.push(attribute("guardrails.level", level));
+                if let Some(rule_id) = rule_id {
+                    attributes.push(attribute("guardrails.rule_id", rule_id));
+                }
+            }
+            if let Some(rule_id) = span.rule_id {
+                attributes.push(attribute("guardrails.rule_id", rule_id));
+            }
+            json!({
+                "traceId": trace_id,
+                "spanId": span_ids[i],
+                "parentSpanId": span.parent.map(|p| span_ids[p].clone()).unwrap_or_default(),
+                "name": span.name,
+                "kind": SPAN_KIND_INTERNAL,
+                "startTimeUnixNano": nanos(span.start),
+                "endTimeUnixNano": nanos(span.end),
+                "attributes": attributes,
+            })
+        })
+        .collect();
+    Some(json!({
+        "resourceSpans": [{
+            "resource": resource(service_name),
+            "scopeSpans": [{ "scope": scope(), "spans": spans }],
+        }],
+    }))
+}
+
+/// A non-zero ID, unique enough for trace and span IDs
+fn random_id() -> u64 {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let mut hasher = DefaultHasher::new();
+    SystemTime::now().hash(&mut hasher);
+    std::process::id().hash(&mut hasher);
+    COUNTER.fetch_add(1, Ordering::Relaxed).hash(&mut hasher);
+    hasher.finish().max(1)
+}
+
+/// POST a decision's metrics (and trace, if spans were recorded) to the
+/// collector at `endpoint` (`http://localhost:4318`)
+#[cfg(feature = "otlp")]
+pub fn export(
+    endpoint: &str,
+    service_name: &str,
+    tool: &str,
+    decision: &Decision,
+    spans: &[SpanRecord],
+) -> std::io::Result<()> {
+    let agent = ureq::AgentBuilder::new()
+        .timeout(std::time::Duration::from_secs(1))
+        .build();
+    let post = |path: &str, body: &Value| {
+        agent
+            .post(&format!("{}{}", endpoint.trim_end_matches('/'), path))
+            .set("Content-Type", "application/json")
+            .send_string(&body.to_string())
+            .map(|_| ())
+            .map_err(|e| std::io::Error::other(e.to_string()))
+    };
+    post("/v1/metrics", &metrics_payload(service_name, tool, decision, SystemTime::now()))?;
+    match traces_payload(service_name, tool, decision, spans) {
+        Some(traces) => post("/v1/traces", &traces),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_metrics_payload() {
+        let decision = Decision::deny("rm-root", "Attempting to delete root filesystem");
+        let payload = metrics_payload("guardrails-dev", "Bash", &decision, UNIX_EPOCH + Duration::from_secs(1));
+        let metric = &payload["resourceMetrics"][0]["scopeMetrics"][0]["metrics"][0];
+        assert_eq!(metric["name"], DECISIONS_METRIC);
+        let point = &metric["sum"]["dataPoints"][0];
+        assert_eq!(point["timeUnixNano"], "1000000000");
+        assert_eq!(point["asInt"], "1");
+        let attributes = point["attributes"].to_string();
+        assert!(attributes.contains(r#"{"key":"guardrails.rule_id","value":{"stringValue":"rm-root"}}"#));
+        assert!(!payload.to_string().contains("root filesystem"));
+    }
+
+    #[test]
+    fn test_traces_payload_links_parents() {
+        let at = UNIX_EPOCH + Duration::from_secs(5);
+        let span = |name, parent| SpanRecord {
+            name,
+            start: at,
+            end: at + Duration::from_millis(2),
+            parent,
+            rule_id: None,
+        };
+        let cost = SpanRecord { rule_id: Some("rm-root"), ..span("rule-cost", Some(0)) };
+        let spans = [span("check", None), span("parse", Some(0)), cost];
+        let payload = traces_payload("svc", "Bash", &Decision::allow("ok"), &spans).unwrap();
+
+        let spans = &payload["resourceSpans"][0]["scopeSpans"][0]["spans"];
+        assert_eq!(spans[0]["traceId"], spans[1]["traceId"]);
+        assert_eq!(spans[0]["traceId"].as_str().unwrap().len(), 32);
+        assert_eq!(spans[1]["parentSpanId"], spans[0]["spanId"]);
+        assert_eq!(spans[0]["parentSpanId"], "");
+        assert_eq!(spans[1]["endTimeUnixNano"], "5002000000");
+        assert_eq!(spans[1]["attributes"], serde_json::json!([]));
+        assert_eq!(spans[2]["attributes"][0]["value"]["stringValue"], "rm-root");
+        assert!(traces_payload("svc", "Bash", &Decision::allow("ok"), &[]).is_none());
+    }
+}
@@ -0,0 +1,149 @@
+//! Span recording for OTLP trace export
+//!
+//! Instrumented code wraps its phases in `span("parse", || ...)`. Nothing
+//! is recorded unless `start` was called on the current thread, so with
+//! tracing off each span costs one thread-local check.
+//!
+//! A sampled share of rule-set matches also times every rule on its own and
+//! records the slowest as `rule-cost` spans carrying the rule ID (see
+//! [`rule_cost`]), attributing matching time to individual patterns.
+
+use std::cell::RefCell;
+use std::collections::hash_map::RandomState;
+use std::hash::BuildHasher;
+use std::time::{Duration, SystemTime};
+
+/// Name of the spans [`rule_cost`] records
+pub const RULE_COST_SPAN: &str = "rule-cost";
+
+/// A finished span
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpanRecord {
+    pub name: &'static str,
+    pub start: SystemTime,
+    pub end: SystemTime,
+    /// Index of the enclosing span in the recorded list
+    pub parent: Option<usize>,
+    /// Rule the time is attributed to (`rule-cost` spans)
+    pub rule_id: Option<&'static str>,
+}
+
+#[derive(Default)]
+struct Recorder {
+    spans: Vec<SpanRecord>,
+    /// Indexes of the spans currently open
+    open: Vec<usize>,
+}
+
+thread_local! {
+    static RECORDER: RefCell<Option<Recorder>> = const { RefCell::new(None) };
+}
+
+/// Start recording spans on this thread (discarding any not yet taken)
+pub fn start() {
+    RECORDER.with(|r| *r.borrow_mut() = Some(Recorder::default()));
+}
+
+/// Stop recording and return the spans, in the order they were opened
+pub fn finish() -> Vec<SpanRecord> {
+    RECORDER.with(|r| r.borrow_mut().take().map(|r| r.spans).unwrap_or_default())
+}
+
+/// Whether spans are being recorded on this thread
+pub fn is_recording() -> bool {
+    RECORDER.with(|r| r.borrow().is_some())
+}
+
+/// Whether to take a sample, with probability `rate` (0 never, 1 always)
+pub fn sampled(rate: f64) -> bool {
+    if rate <= 0.0 {
+        return false;
+    }
+    (RandomState::new().hash_one(SystemTime::now()) as f64 / u64::MAX as f64) < rate
+}
+
+/// Record `elapsed` matching time for `rule_id`, from `start`, as a finished
+/// `rule-cost` span nested in the span open around it
+pub fn rule_cost(rule_id: &'static str, start: SystemTime, elapsed: Duration) {
+    RECORDER.with(|r| {
+        if let Some(recorder) = r.borrow_mut().as_mut() {
+            recorder.spans.push(SpanRecord {
+                name: RULE_COST_SPAN,
+                start,
+                end: start + elapsed,
+                parent: recorder.open.last().copied(),
+                rule_id: Some(rule_id),
+            });
+        }
+    });
+}
+
+/// Run `f` as a span named `name` (nested in the span open around it)
+pub fn span<T>(name: &'static str, f: impl FnOnce() -> T) -> T {
+    let index = RECORDER.with(|r| {
+        let mut recorder = r.borrow_mut();
+        let recorder = recorder.as_mut()?;
+        let now = SystemTime::now();
+        recorder.spans.push(SpanRecord {
+            name,
+            start: now,
+            end: now,
+            parent: recorder.open.last().copied(),
+            rule_id: None,
+        });
+        let index = recorder.spans.len() - 1;
+        recorder.open.push(index);
+        Some(index)
+    });
+    let result = f();
+    if let Some(index) = index {
+        RECORDER.with(|r| {
+            if let Some(recorder) = r.borrow_mut().as_mut() {
+                recorder.open.retain(|&i| i != index);
+                if let Some(span) = recorder.spans.get_mut(index) {
+                    span.end = SystemTime::now();
+                }
+            }
+        });
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spans_nest_and_are_off_by_default() {
+        assert_eq!(span("ignored", || 1), 1);
+        assert!(finish().is_empty());
+
+        start();
+        span("check", || {
+            span("parse", || ());
+            span("rule-match", || ());
+        });
+        let spans = finish();
+        let names: Vec<_> = spans.iter().map(|s| (s.name, s.parent)).collect();
+        assert_eq!(names, [("check", None), ("parse", Some(0)), ("rule-match", Some(0))]);
+        assert!(spans.iter().all(|s| s.end >= s.start));
+        assert!(finish().is_empty());
+    }
+
+    #[test]
+    fn test_rule_cost_spans() {
+        let now = SystemTime::now();
+        rule_cost("ignored", now, Duration::from_micros(3));
+        assert!(!is_recording());
+
+        start();
+        span("rule-match", || rule_cost("rm-root", now, Duration::from_micros(3)));
+        let spans = finish();
+        assert_eq!(spans[1].name, RULE_COST_SPAN);
+        assert_eq!((spans[1].parent, spans[1].rule_id), (Some(0), Some("rm-root")));
+        assert_eq!(spans[1].end.duration_since(spans[1].start).unwrap(), Duration::from_micros(3));
+
+        assert!(!sampled(0.0));
+        assert!(sampled(1.0));
+    }
+}
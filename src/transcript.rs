@@ -0,0 +1,294 @@
+//! Session transcript review for the Stop and SubagentStop hooks
+//!
+//! When Claude stops, the session's transcript (JSONL, one message per
+//! line) is read back for signs that destructive work was left half done:
+//! a command the guardrails kept denying while Claude retried it, a git
+//! rebase/merge/cherry-pick that failed and was never continued or
+//! aborted, and a `guardrails-backup` stash (from the stash rewrite) that
+//! was never restored. Findings are summarized for the user; the stop
+//! itself isn't blocked.
+
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::path::Path;
+
+use crate::output::Decision;
+
+/// Git operations that stop midway on a conflict
+const RESUMABLE_GIT: &[&str] = &["rebase", "merge", "cherry-pick", "revert", "am"];
+
+/// Commands shown in the summary are cut to this many characters
+const MAX_COMMAND_CHARS: usize = 80;
+
+/// A Bash tool call and how it ended
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BashCall {
+    pub command: String,
+    /// The tool result was an error (the command failed or was denied)
+    pub failed: bool,
+}
+
+/// Something left unfinished
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Finding {
+    /// A rule denied the same kind of command more than once
+    Retried {
+        rule_id: String,
+        command: String,
+        attempts: usize,
+    },
+    /// A git operation failed and was never continued or aborted
+    Unfinished { command: String },
+    /// A `guardrails-backup` stash was pushed and never popped or applied
+    BackupStash,
+}
+
+impl Finding {
+    fn describe(&self) -> String {
+        match self {
+            Finding::Retried { rule_id, command, attempts } => {
+                format!("`{}` was blocked {} times ({})", shorten(command), attempts, rule_id)
+            }
+            Finding::Unfinished { command } => {
+                format!("`{}` failed and was never continued or aborted", shorten(command))
+            }
+            Finding::BackupStash => {
+                "a guardrails-backup stash was never restored (see `git stash list`)".to_string()
+            }
+        }
+    }
+}
+
+fn shorten(command: &str) -> String {
+    let line = command.lines().next().unwrap_or_default();
+    if line.chars().count() > MAX_COMMAND_CHARS || command.contains('\n') {
+        format!("{}...", line.chars().take(MAX_COMMAND_CHARS).collect::<String>())
+    } else {
+        line.to_string()
+    }
+}
+
+/// Bash calls in a transcript, in order
+///
+/// Lines that don't parse and tool calls without a command are skipped.
+pub fn bash_calls(path: &Path) -> io::Result<Vec<BashCall>> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut calls = Vec::new();
+    let mut by_id: HashMap<String, usize> = HashMap::new();
+    for line in reader.lines().map_while(Result::ok) {
+        let Ok(entry) = serde_json::from_str::<Value>(&line) else {
+            continue;
+        };
+        let Some(content) = entry.pointer("/message/content").and_then(Value::as_array) else {
+            continue;
+        };
+        for block in content {
+            match block["type"].as_str() {
+                Some("tool_use") if block["name"] == "Bash" => {
+                    if let Some(command) = block["input"]["command"].as_str() {
+                        if let Some(id) = block["id"].as_str() {
+                            by_id.insert(id.to_string(), calls.len());
+                        }
+                        calls.push(BashCall { command: command.to_string(), failed: false });
+                    }
+                }
+                Some("tool_result") => {
+                    let index = block["tool_use_id"].as_str().and_then(|id| by_id.get(id));
+                    if let Some(&index) = index {
+                        calls[index].failed = block["is_error"].as_bool().unwrap_or(false);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    Ok(calls)
+}
+
+/// `git SUBCOMMAND` of a command, skipping `git -C dir` style options
+fn git_subcommand(command: &str) -> Option<(String, Vec<String>)> {
+    let words = shlex::split(command)?;
+    let git = words.iter().position(|w| w == "git" || w.ends_with("/git"))?;
+    let mut rest = words[git + 1..].iter();
+    while let Some(word) = rest.next() {
+        if matches!(word.as_str(), "-C" | "-c") {
+            rest.next();
+        } else if !word.starts_with('-') {
+            return Some((word.clone(), rest.cloned().collect()));
+        }
+    }
+    None
+}
+
+/// `--continue`, `--abort`, ...: resuming or ending an operation
+fn resolves(args: &[String]) -> bool {
+    args.iter().any(|a| matches!(a.as_str(), "--continue" | "--abort" | "--quit" | "--skip"))
+}
+
+/// Review a session's Bash calls, with `check` deciding what the
+/// guardrails deny
+pub fn review(calls: &[BashCall], check: impl Fn(&str) -> Decision) -> Vec<Finding> {
+    let mut findings = Vec::new();
+
+    // Denied calls, grouped by rule in order of first denial
+    let mut blocked = vec![false; calls.len()];
+    let mut denied: Vec<(String, String, usize)> = Vec::new();
+    for (i, call) in calls.iter().enumerate().filter(|(_, c)| c.failed) {
+        if let Decision::Deny { rule_id, .. } = check(&call.command) {
+            blocked[i] = true;
+            match denied.iter_mut().find(|(id, _, _)| *id == rule_id) {
+                Some((_, _, attempts)) => *attempts += 1,
+                None => denied.push((rule_id, call.command.clone(), 1)),
+            }
+        }
+    }
+    findings.extend(
+        denied
+            .into_iter()
+            .filter(|(_, _, attempts)| *attempts > 1)
+            .map(|(rule_id, command, attempts)| Finding::Retried { rule_id, command, attempts }),
+    );
+
+    // Every call of a compound command line counts; `&&` chains are split
+    // so `git stash push ... && git reset --hard` is seen as both
+    let steps: Vec<(usize, &str)> = calls
+        .iter()
+        .enumerate()
+        .flat_map(|(i, c)| c.command.split("&&").map(move |part| (i, part.trim())))
+        .collect();
+    let resolved_after = |index: usize, op: &str| {
+        steps.iter().any(|(i, step)| {
+            *i > index && git_subcommand(step).is_some_and(|(sub, args)| sub == op && resolves(&args))
+        })
+    };
+    for (index, step) in &steps {
+        let Some((sub, args)) = git_subcommand(step) else {
+            continue;
+        };
+        // A denied command never ran, so it left nothing behind
+        if calls[*index].failed
+            && !blocked[*index]
+            && RESUMABLE_GIT.contains(&sub.as_str())
+            && !resolves(&args)
+            && !resolved_after(*index, &sub)
+        {
+            findings.push(Finding::Unfinished { command: calls[*index].command.clone() });
+        }
+    }
+
+    let stashed = steps.iter().rposition(|(i, step)| !calls[*i].failed && step.contains("guardrails-backup"));
+    if let Some(stashed) = stashed {
+        let restored = steps[stashed + 1..].iter().any(|(_, step)| {
+            git_subcommand(step).is_some_and(|(sub, args)| {
+                sub == "stash" && args.first().is_some_and(|a| matches!(a.as_str(), "pop" | "apply" | "drop"))
+            })
+        });
+        if !restored {
+            findings.push(Finding::BackupStash);
+        }
+    }
+    findings.dedup();
+    findings
+}
+
+/// One-line summary for the user, or `None` without findings
+pub fn summary(findings: &[Finding]) -> Option<String> {
+    if findings.is_empty() {
+        return None;
+    }
+    let parts: Vec<String> = findings.iter().map(Finding::describe).collect();
+    Some(format!("[guardrails] Unfinished work this session: {}", parts.join("; ")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn tool_use(id: &str, command: &str) -> String {
+        serde_json::json!({
+            "type": "assistant",
+            "message": {"role": "assistant", "content": [
+                {"type": "tool_use", "id": id, "name": "Bash", "input": {"command": command}}
+            ]}
+        })
+        .to_string()
+    }
+
+    fn tool_result(id: &str, is_error: bool) -> String {
+        serde_json::json!({
+            "type": "user",
+            "message": {"role": "user", "content": [
+                {"type": "tool_result", "tool_use_id": id, "is_error": is_error, "content": "..."}
+            ]}
+        })
+        .to_string()
+    }
+
+    fn check(command: &str) -> Decision {
+        if command.contains("--force ") {
+            Decision::deny("git-force-push-main", "Force push to main")
+        } else {
+            Decision::allow("ok")
+        }
+    }
+
+    #[test]
+    fn test_bash_calls_from_transcript() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session.jsonl");
+        let mut file = File::create(&path).unwrap();
+        for line in [
+            r#"{"type":"user","message":{"role":"user","content":"push it"}}"#.to_string(),
+            tool_use("t1", "git push --force origin main"),
+            tool_result("t1", true),
+            "not json".to_string(),
+            tool_use("t2", "git status"),
+            tool_result("t2", false),
+        ] {
+            writeln!(file, "{}", line).unwrap();
+        }
+
+        let calls = bash_calls(&path).unwrap();
+        assert_eq!(
+            calls,
+            [
+                BashCall { command: "git push --force origin main".to_string(), failed: true },
+                BashCall { command: "git status".to_string(), failed: false },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_review_findings() {
+        let call = |command: &str, failed| BashCall { command: command.to_string(), failed };
+        let calls = [
+            call("git push --force origin main", true),
+            call("git push --force origin HEAD:main", true),
+            call("git rebase main", true),
+            call("git stash push -u -m \"guardrails-backup\" && git reset --hard", false),
+            call("git merge feature", true),
+            call("git merge --abort", false),
+        ];
+        let findings = review(&calls, check);
+        assert_eq!(
+            findings,
+            [
+                Finding::Retried {
+                    rule_id: "git-force-push-main".to_string(),
+                    command: "git push --force origin main".to_string(),
+                    attempts: 2,
+                },
+                Finding::Unfinished { command: "git rebase main".to_string() },
+                Finding::BackupStash,
+            ]
+        );
+        let summary = summary(&findings).unwrap();
+        assert!(summary.contains("`git push --force origin main` was blocked 2 times (git-force-push-main)"));
+
+        let calls = [call("git rebase main", true), call("git rebase --continue", false), call("git stash pop", false)];
+        assert!(review(&calls, check).is_empty());
+    }
+}
@@ -0,0 +1,213 @@
+//! Public API snapshot
+//!
+//! `tests/golden/public-api/prelude.txt` holds every item the prelude
+//! re-exports, read from the source: derives, public fields and variants,
+//! trait methods, public methods and trait impls, without docs or bodies. A change to it is
+//! a change to the stable API: review the diff (removing or changing
+//! anything needs a major release; additions need a minor one), then
+//! regenerate with `GUARDRAILS_UPDATE_GOLDEN=1 cargo test --test integration api`.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use syn::{Attribute, Fields, ImplItem, Item, UseTree, Visibility};
+
+fn src_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("src")
+}
+
+fn parse(path: &Path) -> syn::File {
+    let text = fs::read_to_string(path).unwrap_or_else(|e| panic!("{}: {}", path.display(), e));
+    syn::parse_file(&text).unwrap_or_else(|e| panic!("{}: {}", path.display(), e))
+}
+
+/// Module path and name of each `pub use crate::...` in the prelude
+fn prelude_items() -> Vec<(Vec<String>, String)> {
+    fn walk(tree: &UseTree, path: &mut Vec<String>, items: &mut Vec<(Vec<String>, String)>) {
+        match tree {
+            UseTree::Path(p) => {
+                path.push(p.ident.to_string());
+                walk(&p.tree, path, items);
+                path.pop();
+            }
+            UseTree::Name(n) => items.push((path[1..].to_vec(), n.ident.to_string())),
+            UseTree::Group(g) => g.items.iter().for_each(|t| walk(t, path, items)),
+            UseTree::Rename(_) | UseTree::Glob(_) => panic!("the prelude re-exports items by name"),
+        }
+    }
+    let mut items = Vec::new();
+    for item in parse(&src_dir().join("prelude.rs")).items {
+        if let Item::Use(u) = item {
+            walk(&u.tree, &mut Vec::new(), &mut items);
+        }
+    }
+    items
+}
+
+/// Source file of a module path (`["engine"]` -> `src/engine/mod.rs`)
+fn module_file(module: &[String]) -> PathBuf {
+    let base = module.iter().fold(src_dir(), |dir, m| dir.join(m));
+    let file = base.with_extension("rs");
+    if file.exists() {
+        file
+    } else {
+        base.join("mod.rs")
+    }
+}
+
+/// Every library source file, sorted
+fn library_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    let mut entries: Vec<PathBuf> = fs::read_dir(dir).unwrap().flatten().map(|e| e.path()).collect();
+    entries.sort();
+    for path in entries {
+        if path.is_dir() {
+            library_files(&path, files);
+        } else if path.extension().is_some_and(|e| e == "rs") && !path.ends_with("src/main.rs") {
+            files.push(path);
+        }
+    }
+}
+
+fn is_pub(vis: &Visibility) -> bool {
+    matches!(vis, Visibility::Public(_))
+}
+
+/// Keep only the attributes that are part of the API
+fn api_attrs(attrs: &mut Vec<Attribute>) {
+    attrs.retain(|a| a.path().is_ident("derive") || a.path().is_ident("non_exhaustive"));
+}
+
+fn is_test_only(attrs: &[Attribute]) -> bool {
+    attrs.iter().any(|a| a.path().is_ident("cfg") && quote_string(a).contains("test"))
+}
+
+fn quote_string(attr: &Attribute) -> String {
+    match &attr.meta {
+        syn::Meta::List(list) => list.tokens.to_string(),
+        _ => String::new(),
+    }
+}
+
+/// Drop private fields (`all_public` for enum variants, whose fields are)
+fn api_fields(fields: &mut Fields, all_public: bool) {
+    if let Fields::Named(named) = fields.clone() {
+        if !all_public {
+            *fields = Fields::Named(syn::FieldsNamed {
+                named: named.named.into_iter().filter(|f| is_pub(&f.vis)).collect(),
+                ..named
+            });
+        }
+    }
+    for field in fields.iter_mut() {
+        api_attrs(&mut field.attrs);
+    }
+}
+
+/// The definition of `name` in `file`, reduced to its API
+fn definition(file: &syn::File, name: &str) -> Option<Item> {
+    file.items.iter().find_map(|item| {
+        let mut item = item.clone();
+        match &mut item {
+            Item::Struct(s) if s.ident == name => {
+                api_attrs(&mut s.attrs);
+                api_fields(&mut s.fields, false);
+            }
+            Item::Enum(e) if e.ident == name => {
+                api_attrs(&mut e.attrs);
+                for variant in e.variants.iter_mut() {
+                    api_attrs(&mut variant.attrs);
+                    api_fields(&mut variant.fields, true);
+                }
+            }
+            Item::Trait(t) if t.ident == name => {
+                api_attrs(&mut t.attrs);
+                for trait_item in t.items.iter_mut() {
+                    if let syn::TraitItem::Fn(f) = trait_item {
+                        f.attrs.clear();
+                        f.default = f.default.as_ref().map(|_| syn::parse_quote!({}));
+                    }
+                }
+            }
+            _ => return None,
+        }
+        Some(item)
+    })
+}
+
+/// The impl blocks of `name` across the library, reduced to their API
+fn impls(files: &[syn::File], name: &str) -> Vec<Item> {
+    let mut found = Vec::new();
+    for item in files.iter().flat_map(|f| &f.items) {
+        let Item::Impl(block) = item else { continue };
+        let syn::Type::Path(self_ty) = &*block.self_ty else { continue };
+        if self_ty.path.segments.last().is_none_or(|s| s.ident != name) || is_test_only(&block.attrs) {
+            continue;
+        }
+        let mut block = block.clone();
+        block.attrs.clear();
+        if block.trait_.is_some() {
+            block.items.clear();
+        } else {
+            block.items.retain(|i| match i {
+                ImplItem::Fn(f) => is_pub(&f.vis),
+                ImplItem::Const(c) => is_pub(&c.vis),
+                _ => false,
+            });
+            for impl_item in block.items.iter_mut() {
+                match impl_item {
+                    ImplItem::Fn(f) => {
+                        f.attrs.clear();
+                        f.block = syn::parse_quote!({});
+                    }
+                    ImplItem::Const(c) => {
+                        c.attrs.clear();
+                        c.expr = syn::parse_quote!(_);
+                    }
+                    _ => {}
+                }
+            }
+            if block.items.is_empty() {
+                continue;
+            }
+        }
+        found.push(Item::Impl(block));
+    }
+    found
+}
+
+fn snapshot() -> String {
+    let mut paths = Vec::new();
+    library_files(&src_dir(), &mut paths);
+    let files: Vec<syn::File> = paths.iter().map(|p| parse(p)).collect();
+
+    let mut out = String::new();
+    for (module, name) in prelude_items() {
+        let definition = definition(&parse(&module_file(&module)), &name)
+            .unwrap_or_else(|| panic!("{} not found in crate::{}", name, module.join("::")));
+        let mut items = vec![definition];
+        items.extend(impls(&files, &name));
+        out.push_str(&format!("// crate::{}::{}\n", module.join("::"), name));
+        out.push_str(&prettyplease::unparse(&syn::File { shebang: None, attrs: Vec::new(), items }));
+        out.push('\n');
+    }
+    out
+}
+
+#[test]
+fn test_public_api_matches_snapshot() {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/golden/public-api/prelude.txt");
+    let actual = snapshot();
+    if std::env::var_os("GUARDRAILS_UPDATE_GOLDEN").is_some() {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, &actual).unwrap();
+        return;
+    }
+    let expected = fs::read_to_string(&path).unwrap_or_else(|e| panic!("{}: {}", path.display(), e));
+    let changed = actual.lines().zip(expected.lines()).position(|(a, e)| a != e);
+    assert!(
+        actual == expected,
+        "the public API changed (first difference at line {}); review it, then regenerate {}",
+        changed.map_or(expected.lines().count().min(actual.lines().count()), |i| i) + 1,
+        path.display()
+    );
+}
@@ -0,0 +1,224 @@
+//! End-to-end tests: the compiled binary over stdin/stdout
+
+use std::fs;
+
+use claude_guardrails::harness::{bash_input, tool_input, Harness};
+use claude_guardrails::SafetyLevel;
+use serde_json::json;
+use tempfile::TempDir;
+
+/// The hook binary with `HOME` in a fresh directory
+fn hook() -> (Harness, TempDir) {
+    let home = TempDir::new().unwrap();
+    let harness = Harness::new(env!("CARGO_BIN_EXE_claude-guardrails")).home(home.path());
+    (harness, home)
+}
+
+// ============================================================================
+// Decisions
+// ============================================================================
+
+#[test]
+fn test_dangerous_command_denied() {
+    let (hook, _home) = hook();
+    let run = hook.run(&bash_input("rm -rf /")).unwrap();
+
+    assert!(run.status.success(), "the hook reports denials in JSON, not the exit code");
+    assert_eq!(run.stdout.lines().count(), 1);
+    assert_eq!(run.decision().as_deref(), Some("deny"));
+    assert!(run.message().unwrap().contains("rm-root"));
+    assert_eq!(run.output().unwrap()["hookSpecificOutput"]["hookEventName"], "PreToolUse");
+}
+
+#[test]
+fn test_safe_command_allowed() {
+    let (hook, _home) = hook();
+    let run = hook.run(&bash_input("ls -la && git status")).unwrap();
+
+    assert!(run.status.success());
+    assert_eq!(run.output().unwrap(), json!({}));
+    assert_eq!(run.decision(), None);
+}
+
+#[test]
+fn test_file_tools_checked() {
+    let (hook, _home) = hook();
+    let read = hook.run(&tool_input("Read", json!({ "file_path": "/home/user/.ssh/id_rsa" }))).unwrap();
+    assert_eq!(read.decision().as_deref(), Some("deny"));
+
+    let write = tool_input("Write", json!({ "file_path": "src/main.rs", "content": "fn main() {}" }));
+    assert_eq!(hook.run(&write).unwrap().decision(), None);
+}
+
+// ============================================================================
+// Environment overrides
+// ============================================================================
+
+#[test]
+fn test_warn_only_env_reports_without_blocking() {
+    let (hook, _home) = hook();
+    let run = hook.env("GUARDRAILS_WARN_ONLY", "1").run(&bash_input("rm -rf /")).unwrap();
+
+    assert_eq!(run.decision(), None);
+    assert_eq!(run.warnings(), ["rm-root"]);
+    assert!(run.message().unwrap().contains("Warning (rm-root)"));
+}
+
+#[test]
+fn test_disabled_env_allows() {
+    let (hook, _home) = hook();
+    let run = hook.env("GUARDRAILS_DISABLED", "1").run(&bash_input("rm -rf /")).unwrap();
+
+    assert_eq!(run.output().unwrap(), json!({}));
+}
+
+#[test]
+fn test_override_inside_command_blocked() {
+    let (hook, _home) = hook();
+    let run = hook.run(&bash_input("GUARDRAILS_DISABLED=1 rm -rf /tmp/x")).unwrap();
+
+    assert_eq!(run.decision().as_deref(), Some("deny"));
+}
+
+// ============================================================================
+// Config and flags
+// ============================================================================
+
+#[test]
+fn test_dry_run_flag_warns() {
+    let (hook, _home) = hook();
+    let run = hook.arg("--dry-run").run(&bash_input("rm -rf /")).unwrap();
+
+    assert_eq!(run.decision(), None);
+    assert_eq!(run.warnings(), ["rm-root"]);
+}
+
+#[test]
+fn test_safety_level_flag() {
+    let (hook, _home) = hook();
+    let force_push = bash_input("git push --force origin feature");
+
+    assert_eq!(hook.run(&force_push).unwrap().decision(), None);
+    let strict = hook.clone().safety_level(SafetyLevel::Strict).run(&force_push).unwrap();
+    assert_eq!(strict.decision().as_deref(), Some("deny"));
+
+    let critical = hook.safety_level(SafetyLevel::Critical);
+    assert_eq!(critical.run(&bash_input("git push --force origin main")).unwrap().decision(), None);
+    assert_eq!(critical.run(&bash_input("rm -rf /")).unwrap().decision().as_deref(), Some("deny"));
+}
+
+#[test]
+fn test_config_flag() {
+    let (hook, home) = hook();
+    let path = home.path().join("custom.toml");
+    fs::write(&path, "[general]\nsafety_level = \"critical\"\n").unwrap();
+
+    let run = hook.config(&path).run(&bash_input("git push --force origin main")).unwrap();
+    assert_eq!(run.decision(), None);
+}
+
+#[test]
+fn test_user_config_and_no_home() {
+    let (hook, home) = hook();
+    let dir = home.path().join(".claude/guardrails");
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("config.toml"), "[general]\nsafety_level = \"strict\"\n").unwrap();
+    let force_push = bash_input("git push --force origin feature");
+
+    assert_eq!(hook.run(&force_push).unwrap().decision().as_deref(), Some("deny"));
+    assert_eq!(hook.no_home().run(&force_push).unwrap().decision(), None);
+}
+
+#[test]
+fn test_project_config_from_input_cwd() {
+    let (hook, home) = hook();
+    let project = home.path().join("project");
+    fs::create_dir_all(&project).unwrap();
+    let input = |level: &str, command: &str| {
+        fs::write(project.join(".guardrails.toml"), format!("[general]\nsafety_level = \"{}\"\n", level)).unwrap();
+        json!({ "tool_name": "Bash", "tool_input": { "command": command }, "cwd": project }).to_string()
+    };
+
+    // A project can raise the level...
+    let run = hook.run(&input("strict", "git push --force origin feature")).unwrap();
+    assert_eq!(run.decision().as_deref(), Some("deny"));
+    // ... but not lower it
+    let run = hook.run(&input("critical", "git push --force origin main")).unwrap();
+    assert_eq!(run.decision().as_deref(), Some("deny"));
+}
+
+// ============================================================================
+// Malformed input
+// ============================================================================
+
+#[test]
+fn test_malformed_json_fails_closed() {
+    let (hook, _home) = hook();
+    for input in ["garbage", "{\"tool_name\":", "{\"tool_input\":{\"command\":\"ls\"}}"] {
+        let run = hook.run(input).unwrap();
+        assert!(run.status.success());
+        assert_eq!(run.decision().as_deref(), Some("deny"), "input: {}", input);
+        assert!(run.message().unwrap().contains("parse-error"));
+        assert!(run.stderr.contains("Failed to parse input"));
+    }
+}
+
+#[test]
+fn test_batch_of_inputs() {
+    let (hook, _home) = hook();
+    let batch = json!([
+        {"tool_name": "Bash", "tool_input": {"command": "rm -rf /"}},
+        {"tool_name": "Bash", "tool_input": {"command": "ls"}},
+        {"tool_input": {"command": "ls"}},
+    ]);
+    let run = hook.run(&batch.to_string()).unwrap();
+    assert!(run.status.success());
+
+    let outputs = run.output().unwrap();
+    let outputs = outputs.as_array().expect("one output per input");
+    assert_eq!(outputs.len(), 3);
+    assert_eq!(outputs[0]["hookSpecificOutput"]["permissionDecision"], "deny");
+    assert_eq!(outputs[1], json!({}));
+    assert_eq!(outputs[2]["hookSpecificOutput"]["permissionDecision"], "deny");
+    assert!(outputs[2]["systemMessage"].as_str().unwrap().contains("parse-error"));
+}
+
+#[test]
+fn test_batch_item_not_an_object_denied_alone() {
+    let (hook, _home) = hook();
+    let batch = json!([
+        {"tool_name": "Bash", "tool_input": {"command": "ls"}},
+        5,
+        {"tool_name": "Bash", "tool_input": {"command": "rm -rf /"}},
+    ]);
+    let run = hook.run(&batch.to_string()).unwrap();
+    assert!(run.status.success());
+
+    let outputs = run.output().unwrap();
+    let outputs = outputs.as_array().expect("one output per input");
+    assert_eq!(outputs.len(), 3);
+    assert_eq!(outputs[0], json!({}));
+    assert_eq!(outputs[1]["hookSpecificOutput"]["permissionDecision"], "deny");
+    assert!(outputs[1]["systemMessage"].as_str().unwrap().contains("parse-error"));
+    assert_eq!(outputs[2]["hookSpecificOutput"]["permissionDecision"], "deny");
+    assert!(outputs[2]["systemMessage"].as_str().unwrap().contains("rm-root"));
+
+    let outputs = hook.run("[1, 2, 3]").unwrap().output().unwrap();
+    assert!(outputs.as_array().unwrap().iter().all(|o| o["hookSpecificOutput"]["permissionDecision"] == "deny"));
+}
+
+#[test]
+fn test_empty_input_allowed() {
+    let (hook, _home) = hook();
+    for input in ["", "\n", "   "] {
+        assert_eq!(hook.run(input).unwrap().output().unwrap(), json!({}));
+    }
+}
+
+#[test]
+fn test_multiline_input_joined() {
+    let (hook, _home) = hook();
+    let run = hook.run("{\"tool_name\": \"Bash\",\n \"tool_input\": {\"command\": \"rm -rf /\"}}\n").unwrap();
+
+    assert_eq!(run.decision().as_deref(), Some("deny"));
+}
@@ -0,0 +1,82 @@
+//! Golden-file tests for the hook output schema
+//!
+//! `tests/golden/hook-output/v<N>/<case>.json` holds the exact output for
+//! each decision kind in each supported hook schema version. A change that
+//! alters one is a change to the hook contract: review the diff, then
+//! regenerate with `GUARDRAILS_UPDATE_GOLDEN=1 cargo test --test integration golden`.
+
+use std::fs;
+use std::path::PathBuf;
+
+use claude_guardrails::harness::{bash_input, Harness};
+use claude_guardrails::output::{Decision, Hint, HookOutput, HookSchemaVersion};
+use serde_json::{json, Value};
+
+/// One output per decision kind
+fn cases() -> Vec<(&'static str, HookOutput)> {
+    vec![
+        ("allow", HookOutput::allow()),
+        ("deny", HookOutput::from_decision(&Decision::deny("rm-root", "Attempting to delete root filesystem"))),
+        (
+            "deny-hint",
+            HookOutput::deny_with_rule("git-force-main", "Force pushing to main/master branch").with_hint(&Hint {
+                blocked: "git push --force origin main".to_string(),
+                try_instead: "git push --force-with-lease origin main".to_string(),
+            }),
+        ),
+        ("warn", HookOutput::from_decision(&Decision::warn("git-reset-hard", "Hard reset loses uncommitted changes"))),
+        ("ask", HookOutput::from_decision(&Decision::ask("git-force-main", "Force pushing to main/master branch"))),
+        (
+            "rewrite",
+            HookOutput::allow_rewritten(
+                json!({ "command": "git push --force-with-lease origin feature" }),
+                "--force replaced with --force-with-lease",
+            ),
+        ),
+    ]
+}
+
+fn golden_path(version: HookSchemaVersion, case: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden/hook-output")
+        .join(format!("v{}", version.as_str()))
+        .join(format!("{}.json", case))
+}
+
+fn read_golden(version: HookSchemaVersion, case: &str) -> Value {
+    let path = golden_path(version, case);
+    let text = fs::read_to_string(&path).unwrap_or_else(|e| panic!("{}: {}", path.display(), e));
+    serde_json::from_str(&text).unwrap()
+}
+
+#[test]
+fn test_hook_output_matches_golden_files() {
+    let update = std::env::var_os("GUARDRAILS_UPDATE_GOLDEN").is_some();
+    for &version in HookSchemaVersion::ALL {
+        for (case, output) in cases() {
+            let actual: Value = serde_json::from_str(&output.to_json_versioned(version)).unwrap();
+            let path = golden_path(version, case);
+            if update {
+                fs::create_dir_all(path.parent().unwrap()).unwrap();
+                fs::write(&path, serde_json::to_string_pretty(&actual).unwrap() + "\n").unwrap();
+                continue;
+            }
+            assert_eq!(actual, read_golden(version, case), "v{} {}", version.as_str(), case);
+        }
+    }
+}
+
+#[test]
+fn test_binary_honors_hook_schema_version() {
+    let home = tempfile::TempDir::new().unwrap();
+    let hook = Harness::new(env!("CARGO_BIN_EXE_claude-guardrails")).home(home.path());
+    let input = bash_input("rm -rf /");
+
+    for &version in HookSchemaVersion::ALL {
+        let run = hook.clone().arg(format!("--hook-schema-version={}", version.as_str())).run(&input).unwrap();
+        assert_eq!(run.output().unwrap(), read_golden(version, "deny"));
+    }
+    let unknown = hook.arg("--hook-schema-version").arg("99").run(&input).unwrap();
+    assert_eq!(unknown.output().unwrap(), read_golden(HookSchemaVersion::default(), "deny"));
+    assert!(unknown.stderr.contains("unknown hook schema version 99"));
+}
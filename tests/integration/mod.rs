@@ -1,5 +1,9 @@
 //! Integration tests for claude-guardrails
 
+mod api_tests;
 mod bash_tests;
+mod compare_tests;
+mod e2e_tests;
 mod file_tests;
+mod golden_tests;
 mod override_tests;
@@ -1,24 +1,23 @@
 //! Integration tests for override mechanisms
 
-use std::env;
-use claude_guardrails::{Config, HookInput, SecurityEngine};
+use std::sync::Arc;
 
-fn check_with_env(command: &str, env_var: &str, env_val: &str) -> bool {
-    // Set the environment variable
-    env::set_var(env_var, env_val);
+use claude_guardrails::providers::MapEnv;
+use claude_guardrails::{Config, Decision, HookInput, SecurityEngine};
 
+/// Check a command with the given variables as the engine's environment
+///
+/// The variables are injected rather than set on the process, which every
+/// test in this binary shares.
+fn check_with_env(command: &str, env_var: &str, env_val: &str) -> Decision {
     let json = format!(
         r#"{{"tool_name":"Bash","tool_input":{{"command":"{}"}}}}"#,
         command.replace('\\', "\\\\").replace('"', "\\\"")
     );
     let input = HookInput::from_json(&json).unwrap();
-    let engine = SecurityEngine::new(Config::default());
-    let decision = engine.check(&input);
-
-    // Clean up environment
-    env::remove_var(env_var);
-
-    decision.is_allow()
+    let environment = MapEnv::new().with(env_var, env_val);
+    let engine = SecurityEngine::new(Config::default()).with_environment(Arc::new(environment));
+    engine.check(&input)
 }
 
 // ============================================================================
@@ -30,12 +29,12 @@ fn test_disabled_allows_dangerous_command() {
     // Without GUARDRAILS_DISABLED, rm -rf / is blocked
     let json = r#"{"tool_name":"Bash","tool_input":{"command":"rm -rf /"}}"#;
     let input = HookInput::from_json(json).unwrap();
-    let engine = SecurityEngine::new(Config::default());
+    let engine = SecurityEngine::new(Config::default()).with_environment(Arc::new(MapEnv::new()));
     let decision = engine.check(&input);
     assert!(decision.is_deny());
 
     // With GUARDRAILS_DISABLED, it's allowed
-    assert!(check_with_env("rm -rf /", "GUARDRAILS_DISABLED", "1"));
+    assert!(check_with_env("rm -rf /", "GUARDRAILS_DISABLED", "1").is_allow());
 }
 
 // ============================================================================
@@ -44,17 +43,11 @@ fn test_disabled_allows_dangerous_command() {
 
 #[test]
 fn test_warn_only_converts_deny_to_warn() {
-    env::set_var("GUARDRAILS_WARN_ONLY", "1");
-
-    let json = r#"{"tool_name":"Bash","tool_input":{"command":"rm -rf /"}}"#;
-    let input = HookInput::from_json(json).unwrap();
-    let engine = SecurityEngine::new(Config::default());
-    let decision = engine.check(&input);
-
-    env::remove_var("GUARDRAILS_WARN_ONLY");
+    let decision = check_with_env("rm -rf /", "GUARDRAILS_WARN_ONLY", "1");
 
     // Should be a warn (which counts as allow)
-    assert!(decision.is_allow() || matches!(decision, claude_guardrails::Decision::Warn { .. }));
+    assert!(matches!(decision, Decision::Warn { .. }));
+    assert_eq!(decision.rule_id(), Some("rm-root"));
 }
 
 // ============================================================================
@@ -79,8 +72,7 @@ fn test_safety_level_critical() {
     let json = r#"{"tool_name":"Bash","tool_input":{"command":"git push -f origin main"}}"#;
     let input = HookInput::from_json(json).unwrap();
     let decision = engine.check(&input);
-    // This might still be blocked by pipe-to-shell or other checks,
-    // so we just verify the safety level is applied
+    assert!(decision.is_allow());
     assert_eq!(engine.safety_level(), SafetyLevel::Critical);
 }
 
@@ -109,6 +101,7 @@ fn test_allowlist_matching() {
                 pattern: r"rm\s+-rf\s+\./node_modules".to_string(),
                 reason: "Allow cleaning node_modules".to_string(),
                 tool: Some("Bash".to_string()),
+                ..Default::default()
             },
         ],
     };